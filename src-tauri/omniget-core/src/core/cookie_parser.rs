@@ -15,7 +15,17 @@ fn cookie_domain_matches(
 
 pub fn load_extension_cookies_for_domain(domain: &str) -> Option<Arc<reqwest::cookie::Jar>> {
     let cookie_path = crate::core::ytdlp::ext_cookie_path_if_fresh()?;
-    let content = std::fs::read_to_string(&cookie_path).ok()?;
+    let jar = load_cookie_file_for_domain(&cookie_path, domain)?;
+    tracing::debug!("[cookies] loaded extension cookies for {}", domain);
+    Some(jar)
+}
+
+/// Cookies for `domain` from a Netscape-format cookie file.
+pub fn load_cookie_file_for_domain(
+    cookie_path: &std::path::Path,
+    domain: &str,
+) -> Option<Arc<reqwest::cookie::Jar>> {
+    let content = std::fs::read_to_string(cookie_path).ok()?;
 
     let jar = reqwest::cookie::Jar::default();
     let mut count = 0usize;
@@ -70,9 +80,10 @@ pub fn load_extension_cookies_for_domain(domain: &str) -> Option<Arc<reqwest::co
     }
 
     tracing::debug!(
-        "[cookies] loaded {} extension cookies for {}",
+        "[cookies] loaded {} cookies for {} from {}",
         count,
-        domain
+        domain,
+        cookie_path.display()
     );
     Some(Arc::new(jar))
 }
@@ -1,25 +1,47 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, OnceLock};
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
 use futures::StreamExt;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::sync::{mpsc, Semaphore};
 use tokio_util::sync::CancellationToken;
 
+use crate::core::errors::{find_download_error, DownloadError};
 use crate::core::http_fetcher::{
     get_global_max_concurrent_segments, HttpFetcher, HttpFetcherConfig,
 };
+use crate::models::media::{MediaInfo, MediaType, VideoQuality};
 use crate::models::progress::ProgressUpdate;
 
 const CHUNK_TIMEOUT: Duration = Duration::from_secs(45);
-const MAX_RETRIES: u32 = 3;
 const CHUNK_SIZE: u64 = 10 * 1024 * 1024;
 const CHUNK_THRESHOLD: u64 = 10 * 1024 * 1024;
 const MAX_PARALLEL: usize = 12;
 const MAX_PER_HOST: usize = 16;
+const SIZE_PROBE_TIMEOUT: Duration = Duration::from_secs(8);
+/// Files at least this large go through aria2c when it's available; below
+/// that, starting a process costs more than the extra connections gain.
+const ARIA2C_THRESHOLD: u64 = 32 * 1024 * 1024;
+const ARIA2C_MAX_CONNECTIONS: usize = 16;
+/// Pause before asking again for a media URL the CDN refused.
+const REFUSAL_RETRY_DELAY: Duration = Duration::from_millis(1500);
+/// Media CDNs and the site whose pages embed their files, for retrying a
+/// refused request as if it came from that site.
+const CDN_SITE_ORIGINS: &[(&str, &str)] = &[
+    ("cdninstagram.com", "https://www.instagram.com"),
+    ("fbcdn.net", "https://www.instagram.com"),
+    ("twimg.com", "https://x.com"),
+    ("pinimg.com", "https://www.pinterest.com"),
+    ("tiktokcdn.com", "https://www.tiktok.com"),
+    ("tiktokcdn-us.com", "https://www.tiktok.com"),
+    ("redd.it", "https://www.reddit.com"),
+];
 
 fn host_semaphores() -> &'static tokio::sync::Mutex<HashMap<String, Arc<Semaphore>>> {
     static MAP: OnceLock<tokio::sync::Mutex<HashMap<String, Arc<Semaphore>>>> = OnceLock::new();
@@ -37,6 +59,95 @@ pub async fn get_host_semaphore(url: &str) -> Arc<Semaphore> {
         .clone()
 }
 
+/// Global speed cap in bytes per second shared by every native transfer; 0 is unlimited.
+static GLOBAL_SPEED_LIMIT_BPS: AtomicU64 = AtomicU64::new(0);
+
+struct SpeedBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+fn speed_bucket() -> &'static std::sync::Mutex<SpeedBucket> {
+    static BUCKET: OnceLock<std::sync::Mutex<SpeedBucket>> = OnceLock::new();
+    BUCKET.get_or_init(|| {
+        std::sync::Mutex::new(SpeedBucket {
+            tokens: 0.0,
+            refilled_at: Instant::now(),
+        })
+    })
+}
+
+pub fn set_global_speed_limit_kbps(kbps: Option<u64>) {
+    let bps = kbps.unwrap_or(0).saturating_mul(1024);
+    GLOBAL_SPEED_LIMIT_BPS.store(bps, Ordering::Relaxed);
+}
+
+/// Attempts a transfer gets before `download_direct` gives up.
+static MAX_RETRIES: AtomicU32 = AtomicU32::new(3);
+/// Deadline for one whole `download_direct` call in seconds; 0 is none.
+static DOWNLOAD_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(0);
+
+pub fn set_global_max_retries(retries: u32) {
+    MAX_RETRIES.store(retries.max(1), Ordering::Relaxed);
+}
+
+pub fn set_global_download_timeout_mins(mins: u64) {
+    DOWNLOAD_TIMEOUT_SECS.store(mins.saturating_mul(60), Ordering::Relaxed);
+}
+
+/// Whether large ranged downloads may be handed to aria2c.
+static USE_ARIA2C: AtomicBool = AtomicBool::new(true);
+
+pub fn set_global_use_aria2c(enabled: bool) {
+    USE_ARIA2C.store(enabled, Ordering::Relaxed);
+}
+
+pub fn get_global_speed_limit_kbps() -> Option<u64> {
+    match GLOBAL_SPEED_LIMIT_BPS.load(Ordering::Relaxed) {
+        0 => None,
+        bps => Some((bps / 1024).max(1)),
+    }
+}
+
+impl SpeedBucket {
+    /// Charges `bytes` at `rate` bytes/s and returns how long the caller has
+    /// to wait. The bucket holds at most one second of burst and may go into
+    /// debt, so concurrent callers queue up behind each other.
+    fn charge(&mut self, rate: f64, bytes: usize, now: Instant) -> Duration {
+        let refill = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64()
+            * rate;
+        self.refilled_at = now;
+        if rate <= 0.0 {
+            self.tokens = 0.0;
+            return Duration::ZERO;
+        }
+        self.tokens = (self.tokens + refill).min(rate) - bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
+/// Waits until `bytes` fit under the global speed cap. The limit is read on
+/// every call, so changing it applies to downloads already in flight.
+pub async fn throttle(bytes: usize) {
+    let rate = GLOBAL_SPEED_LIMIT_BPS.load(Ordering::Relaxed);
+    if rate == 0 {
+        return;
+    }
+    let wait = speed_bucket()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .charge(rate as f64, bytes, Instant::now());
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
 struct ProbeResult {
     content_length: Option<u64>,
     accept_ranges: bool,
@@ -60,9 +171,31 @@ pub async fn download_direct_with_headers(
     headers: Option<reqwest::header::HeaderMap>,
     cancel: Option<&CancellationToken>,
 ) -> anyhow::Result<u64> {
+    let transfer = download_with_retries(client, url, output, &progress_tx, headers, cancel);
+    match DOWNLOAD_TIMEOUT_SECS.load(Ordering::Relaxed) {
+        0 => transfer.await,
+        secs => match tokio::time::timeout(Duration::from_secs(secs), transfer).await {
+            Ok(result) => result,
+            Err(_) => {
+                let _ = std::fs::remove_file(part_path_for(output));
+                Err(anyhow!("Download timed out after {} minutes", secs / 60))
+            }
+        },
+    }
+}
+
+async fn download_with_retries(
+    client: &reqwest::Client,
+    url: &str,
+    output: &Path,
+    progress_tx: &mpsc::Sender<ProgressUpdate>,
+    headers: Option<reqwest::header::HeaderMap>,
+    cancel: Option<&CancellationToken>,
+) -> anyhow::Result<u64> {
+    let max_retries = MAX_RETRIES.load(Ordering::Relaxed);
     let mut last_err = None;
 
-    for attempt in 0..MAX_RETRIES {
+    for attempt in 0..max_retries {
         if let Some(token) = cancel {
             if token.is_cancelled() {
                 return Err(anyhow!("Download cancelled"));
@@ -75,17 +208,30 @@ pub async fn download_direct_with_headers(
             tokio::time::sleep(Duration::from_millis(base + jitter)).await;
         }
 
-        match download_attempt(client, url, output, &progress_tx, headers.clone(), cancel).await {
+        match download_attempt(client, url, output, progress_tx, headers.clone(), cancel).await {
             Ok(bytes) => return Ok(bytes),
             Err(e) => {
                 if is_fatal_error(&e) {
-                    let _ = std::fs::remove_file(&part_path_for(output));
+                    let e = if is_refused(&e) {
+                        match retry_refused(client, url, output, progress_tx, &headers, cancel)
+                            .await
+                        {
+                            Ok(bytes) => return Ok(bytes),
+                            Err(retry_err) => retry_err,
+                        }
+                    } else {
+                        e
+                    };
+                    let _ = std::fs::remove_file(part_path_for(output));
                     return Err(e);
                 }
+                if matches!(find_download_error(&e), Some(DownloadError::RateLimited)) {
+                    crate::core::stats::session().record_rate_limited();
+                }
                 tracing::warn!(
                     "[direct] attempt {}/{} failed: {}",
                     attempt + 1,
-                    MAX_RETRIES,
+                    max_retries,
                     e
                 );
                 last_err = Some(e);
@@ -93,14 +239,97 @@ pub async fn download_direct_with_headers(
         }
     }
 
-    let _ = std::fs::remove_file(&part_path_for(output));
-    Err(last_err.unwrap_or_else(|| anyhow!("Download failed after {} attempts", MAX_RETRIES)))
+    let _ = std::fs::remove_file(part_path_for(output));
+    Err(last_err.unwrap_or_else(|| anyhow!("Download failed after {} attempts", max_retries)))
+}
+
+/// CDNs sometimes answer the first hit on a media URL with 403 or 410 and
+/// serve it on the next one, or once the request carries the embedding
+/// site's `Referer`/`Origin`. Tries both before the download fails.
+async fn retry_refused(
+    client: &reqwest::Client,
+    url: &str,
+    output: &Path,
+    progress_tx: &mpsc::Sender<ProgressUpdate>,
+    headers: &Option<reqwest::header::HeaderMap>,
+    cancel: Option<&CancellationToken>,
+) -> anyhow::Result<u64> {
+    tokio::time::sleep(REFUSAL_RETRY_DELAY).await;
+    if cancel.is_some_and(|t| t.is_cancelled()) {
+        return Err(anyhow!("Download cancelled"));
+    }
+    tracing::warn!("[direct] {} was refused, retrying", url);
+    let err =
+        match download_attempt(client, url, output, progress_tx, headers.clone(), cancel).await {
+            Err(e) if is_refused(&e) => e,
+            other => return other,
+        };
+
+    let Some(site) = site_headers(url, headers.clone()) else {
+        return Err(err);
+    };
+    tracing::warn!(
+        "[direct] {} refused again, retrying with the site's referer",
+        url
+    );
+    download_attempt(client, url, output, progress_tx, Some(site), cancel).await
+}
+
+/// `HTTP <status> downloading <url>`, carrying `DownloadError::RateLimited`
+/// for a 429 so callers can tell it apart without reading the message.
+fn status_error(status: reqwest::StatusCode, url: &str) -> anyhow::Error {
+    let message = format!("HTTP {} downloading {}", status, url);
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        anyhow::Error::new(DownloadError::RateLimited).context(message)
+    } else {
+        anyhow!(message)
+    }
+}
+
+fn is_refused(err: &anyhow::Error) -> bool {
+    let msg = err.to_string();
+    msg.contains("HTTP 403") || msg.contains("HTTP 410")
+}
+
+/// The site a media URL is embedded on: a known CDN's site, or else the
+/// URL's own origin.
+fn site_origin_for(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    let known = CDN_SITE_ORIGINS.iter().find(|(cdn, _)| {
+        host == *cdn
+            || host
+                .strip_suffix(cdn)
+                .is_some_and(|rest| rest.ends_with('.'))
+    });
+    Some(match known {
+        Some((_, site)) => site.to_string(),
+        None => format!("{}://{}", parsed.scheme(), host),
+    })
+}
+
+/// `headers` with `Referer` and `Origin` pointing at the site of `url`.
+/// `None` when they already did, since the retry would be identical.
+fn site_headers(
+    url: &str,
+    headers: Option<reqwest::header::HeaderMap>,
+) -> Option<reqwest::header::HeaderMap> {
+    let origin = site_origin_for(url)?;
+    let referer = format!("{}/", origin);
+    let mut headers = headers.unwrap_or_default();
+    if headers
+        .get(reqwest::header::REFERER)
+        .is_some_and(|v| v.as_bytes() == referer.as_bytes())
+    {
+        return None;
+    }
+    headers.insert(reqwest::header::REFERER, referer.parse().ok()?);
+    headers.insert(reqwest::header::ORIGIN, origin.parse().ok()?);
+    Some(headers)
 }
 
 fn part_path_for(output: &Path) -> PathBuf {
-    let mut part = output.as_os_str().to_owned();
-    part.push(".part");
-    PathBuf::from(part)
+    crate::core::temp_dir::part_path_for(output)
 }
 
 fn is_fatal_error(err: &anyhow::Error) -> bool {
@@ -151,6 +380,51 @@ async fn probe_url(
     }
 }
 
+/// Sums the `Content-Length` of `urls` with concurrent HEAD requests.
+/// `None` unless every URL reports a length.
+pub async fn probe_total_size(
+    client: &reqwest::Client,
+    urls: &[&str],
+    headers: Option<&reqwest::header::HeaderMap>,
+) -> Option<u64> {
+    if urls.is_empty() {
+        return None;
+    }
+    let probes = futures::future::join_all(urls.iter().map(|url| probe_url(client, url, headers)));
+    let results = tokio::time::timeout(SIZE_PROBE_TIMEOUT, probes)
+        .await
+        .ok()?;
+    results.iter().map(|r| r.content_length).sum()
+}
+
+fn is_directly_sizeable(quality: &VideoQuality) -> bool {
+    (quality.url.starts_with("https://") || quality.url.starts_with("http://"))
+        && !quality.url.contains(".m3u8")
+        && !quality.format.starts_with("ytdlp")
+}
+
+/// Fills `info.file_size_bytes` before the download starts: every item for
+/// carousels, the default (first) quality otherwise. Left untouched when the
+/// media goes through yt-dlp or HLS, or a server doesn't report a length.
+pub async fn fill_file_size(
+    client: &reqwest::Client,
+    info: &mut MediaInfo,
+    headers: Option<&reqwest::header::HeaderMap>,
+) {
+    if info.file_size_bytes.is_some() {
+        return;
+    }
+    let targets: Vec<&VideoQuality> = match info.media_type {
+        MediaType::Carousel => info.available_qualities.iter().collect(),
+        _ => info.available_qualities.iter().take(1).collect(),
+    };
+    if targets.is_empty() || !targets.iter().all(|q| is_directly_sizeable(q)) {
+        return;
+    }
+    let urls: Vec<&str> = targets.iter().map(|q| q.url.as_str()).collect();
+    info.file_size_bytes = probe_total_size(client, &urls, headers).await;
+}
+
 async fn download_attempt(
     client: &reqwest::Client,
     url: &str,
@@ -168,8 +442,43 @@ async fn download_attempt(
 
     let use_chunked =
         probe.accept_ranges && probe.content_length.is_some_and(|s| s > CHUNK_THRESHOLD);
+    let wants_aria2c = USE_ARIA2C.load(Ordering::Relaxed)
+        && probe.accept_ranges
+        && probe.content_length.is_some_and(|s| s >= ARIA2C_THRESHOLD);
+    let aria2c = if wants_aria2c {
+        crate::core::dependencies::ensure_aria2c().await
+    } else {
+        None
+    };
 
-    if use_chunked {
+    let mut fetched_by_aria2c = false;
+    if let Some(aria2c) = aria2c {
+        match run_aria2c(
+            &aria2c,
+            url,
+            &part_path,
+            probe.content_length,
+            progress_tx,
+            headers.as_ref(),
+            cancel,
+        )
+        .await
+        {
+            Ok(()) => fetched_by_aria2c = true,
+            Err(e) if cancel.is_some_and(CancellationToken::is_cancelled) => {
+                remove_aria2c_leftovers(&part_path);
+                return Err(e);
+            }
+            Err(e) => {
+                remove_aria2c_leftovers(&part_path);
+                tracing::warn!("[direct] aria2c failed, falling back to reqwest: {}", e);
+            }
+        }
+    }
+
+    // After aria2c the part file is complete; the size check below still
+    // applies.
+    if !fetched_by_aria2c && use_chunked {
         match run_http_fetcher(client, url, output, progress_tx, headers.clone(), cancel).await {
             Ok(size) => return Ok(size),
             Err(fetch_err) => {
@@ -194,7 +503,7 @@ async fn download_attempt(
                 .await?;
             }
         }
-    } else {
+    } else if !fetched_by_aria2c {
         let existing = match std::fs::metadata(&part_path) {
             Ok(m) if m.len() > 0 && probe.accept_ranges => m.len(),
             _ => 0,
@@ -224,13 +533,193 @@ async fn download_attempt(
         }
     }
 
-    std::fs::rename(&part_path, output)?;
+    crate::core::temp_dir::move_file(&part_path, output).await?;
     let _ = progress_tx.send(ProgressUpdate::percent(100.0)).await;
 
     let size = std::fs::metadata(output)?.len();
     Ok(size)
 }
 
+/// aria2c transfers running right now, which split the global speed cap.
+static ACTIVE_ARIA2C: AtomicUsize = AtomicUsize::new(0);
+
+/// One running aria2c transfer: holds its share of the speed cap and gives
+/// it back when dropped.
+struct Aria2cRun;
+
+impl Aria2cRun {
+    /// Registers the transfer and returns it with the number running,
+    /// this one included.
+    fn start() -> (Self, usize) {
+        let active = ACTIVE_ARIA2C.fetch_add(1, Ordering::Relaxed) + 1;
+        (Self, active)
+    }
+}
+
+impl Drop for Aria2cRun {
+    fn drop(&mut self) {
+        ACTIVE_ARIA2C.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// aria2c `--input-file` entry for one download: the URL, then its options
+/// indented below it. The URL, proxy credentials, cookies and auth headers
+/// travel this way, over stdin, so they never show up on a command line
+/// or in a file other processes could read.
+fn aria2c_input(
+    url: &str,
+    dir: &Path,
+    name: &std::ffi::OsStr,
+    proxy: Option<&str>,
+    headers: Option<&reqwest::header::HeaderMap>,
+) -> String {
+    let mut input = format!("{}\n", url.trim());
+    input.push_str(&format!(" dir={}\n", dir.display()));
+    input.push_str(&format!(" out={}\n", name.to_string_lossy()));
+    if let Some(proxy) = proxy {
+        input.push_str(&format!(" all-proxy={}\n", proxy));
+    }
+    for (name, value) in headers.into_iter().flatten() {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        if *name == reqwest::header::USER_AGENT {
+            input.push_str(&format!(" user-agent={}\n", value));
+        } else {
+            input.push_str(&format!(" header={}: {}\n", name, value));
+        }
+    }
+    input
+}
+
+/// Fetches `url` into `part_path` with aria2c over several connections and
+/// forwards its progress summary. The proxy and the global speed cap carry
+/// over. aria2c only limits its own process, so the cap is split between
+/// the aria2c transfers running when this one starts; ones already running
+/// keep the share they started with.
+async fn run_aria2c(
+    aria2c: &Path,
+    url: &str,
+    part_path: &Path,
+    total: Option<u64>,
+    progress_tx: &mpsc::Sender<ProgressUpdate>,
+    headers: Option<&reqwest::header::HeaderMap>,
+    cancel: Option<&CancellationToken>,
+) -> anyhow::Result<()> {
+    let dir = part_path
+        .parent()
+        .ok_or_else(|| anyhow!("Output has no parent directory"))?;
+    let name = part_path
+        .file_name()
+        .ok_or_else(|| anyhow!("Output has no file name"))?;
+    let conns = get_global_max_concurrent_segments()
+        .unwrap_or(MAX_PARALLEL)
+        .clamp(1, ARIA2C_MAX_CONNECTIONS);
+
+    let (_run_guard, active) = Aria2cRun::start();
+
+    let mut args = vec![
+        format!("--max-connection-per-server={}", conns),
+        format!("--split={}", conns),
+        "--min-split-size=1M".to_string(),
+        "--file-allocation=none".to_string(),
+        "--allow-overwrite=true".to_string(),
+        "--auto-file-renaming=false".to_string(),
+        "--summary-interval=1".to_string(),
+        "--console-log-level=warn".to_string(),
+        "--download-result=hide".to_string(),
+    ];
+    if let Some(kbps) = get_global_speed_limit_kbps() {
+        let share = (kbps / active as u64).max(1);
+        args.push(format!("--max-overall-download-limit={}K", share));
+    }
+    args.push("--input-file=-".to_string());
+    let proxy = crate::core::http_client::proxy_url();
+    let input = aria2c_input(url, dir, name, proxy.as_deref(), headers);
+
+    let mut child = crate::core::process::command(aria2c)
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow!("Failed to start aria2c: {}", e))?;
+    let mut stdin = child.stdin.take().ok_or_else(|| anyhow!("No stdin"))?;
+    stdin
+        .write_all(input.as_bytes())
+        .await
+        .map_err(|e| anyhow!("Failed to pass options to aria2c: {}", e))?;
+    // aria2c starts once the list ends.
+    drop(stdin);
+    let stdout = child.stdout.take().ok_or_else(|| anyhow!("No stdout"))?;
+
+    let mut last_message = String::new();
+    let run = async {
+        // The summary is redrawn with `\r` on a terminal and printed line by
+        // line otherwise.
+        let mut segments = BufReader::new(stdout).split(b'\r');
+        while let Ok(Some(segment)) = segments.next_segment().await {
+            for line in String::from_utf8_lossy(&segment).lines() {
+                if let Some(mut update) = parse_aria2c_summary(line) {
+                    update.total_bytes = total.or(update.total_bytes);
+                    let _ = progress_tx.send(update).await;
+                } else if !line.trim().is_empty() {
+                    last_message = line.trim().to_string();
+                }
+            }
+        }
+        child.wait().await
+    };
+    let status = match cancel {
+        Some(token) => tokio::select! {
+            status = run => status?,
+            _ = token.cancelled() => return Err(anyhow!("Download cancelled")),
+        },
+        None => run.await?,
+    };
+
+    if !status.success() {
+        return Err(anyhow!("aria2c exited with {}: {}", status, last_message));
+    }
+    Ok(())
+}
+
+/// Removes the part file and aria2c's `.aria2` control file after a failed
+/// attempt, so the fallback starts from scratch.
+fn remove_aria2c_leftovers(part_path: &Path) {
+    let _ = std::fs::remove_file(part_path);
+    let mut control = part_path.as_os_str().to_owned();
+    control.push(".aria2");
+    let _ = std::fs::remove_file(PathBuf::from(control));
+}
+
+/// Reads aria2c's progress summary, e.g.
+/// `[#1ce85c 35MiB/68MiB(50%) CN:8 DL:1.5MiB ETA:21s]`.
+fn parse_aria2c_summary(line: &str) -> Option<ProgressUpdate> {
+    let body = line.trim().strip_prefix("[#")?.strip_suffix(']')?;
+    let mut fields = body.split_whitespace().skip(1);
+    let (sizes, percent) = fields.next()?.split_once('(')?;
+    let percent = percent.strip_suffix("%)")?.parse::<f64>().ok()?;
+    let (downloaded, total) = sizes.split_once('/')?;
+    let mut speed = None;
+    let mut eta = None;
+    for field in fields {
+        if let Some(value) = field.strip_prefix("DL:") {
+            speed = crate::core::ytdlp::parse_size_token(value);
+        } else if let Some(value) = field.strip_prefix("ETA:") {
+            eta = crate::core::ytdlp::parse_timestamp(value).map(|s| s as u64);
+        }
+    }
+    Some(ProgressUpdate::rich(
+        percent,
+        crate::core::ytdlp::parse_size_token(downloaded).map(|b| b as u64),
+        crate::core::ytdlp::parse_size_token(total).map(|b| b as u64),
+        speed,
+        eta,
+    ))
+}
+
 async fn run_http_fetcher(
     client: &reqwest::Client,
     url: &str,
@@ -296,10 +785,10 @@ async fn download_single_stream(
             let _ = std::fs::remove_file(part_path);
             return Err(anyhow!("Range not satisfiable, restarting"));
         } else if !response.status().is_success() {
-            return Err(anyhow!("HTTP {} downloading {}", response.status(), url));
+            return Err(status_error(response.status(), url));
         }
     } else if !response.status().is_success() {
-        return Err(anyhow!("HTTP {} downloading {}", response.status(), url));
+        return Err(status_error(response.status(), url));
     }
 
     if let Some(ct) = response.headers().get("content-type") {
@@ -342,6 +831,7 @@ async fn download_single_stream(
                 file.write_all(&chunk)
                     .map_err(|e| anyhow!("Write error (disk full?): {}", e))?;
                 downloaded += chunk.len() as u64;
+                throttle(chunk.len()).await;
 
                 if last_emit.elapsed() >= std::time::Duration::from_millis(250) {
                     let dt = speed_anchor_time.elapsed().as_secs_f64();
@@ -412,6 +902,20 @@ mod tests {
         assert_eq!(part, PathBuf::from("video.mp4.part"));
     }
 
+    #[test]
+    fn status_error_marks_rate_limits() {
+        let limited = status_error(reqwest::StatusCode::TOO_MANY_REQUESTS, "https://a/b");
+        assert!(matches!(
+            find_download_error(&limited),
+            Some(DownloadError::RateLimited)
+        ));
+        assert!(limited.to_string().starts_with("HTTP 429"));
+
+        let missing = status_error(reqwest::StatusCode::NOT_FOUND, "https://a/b");
+        assert!(find_download_error(&missing).is_none());
+        assert!(is_fatal_error(&missing));
+    }
+
     #[test]
     fn part_path_no_extension() {
         let output = Path::new("video");
@@ -446,6 +950,38 @@ mod tests {
         assert!(is_fatal_error(&anyhow!("HTTP 404 downloading url")));
     }
 
+    #[test]
+    fn refusals_are_403_and_410() {
+        assert!(is_refused(&anyhow!("HTTP 403 Forbidden downloading url")));
+        assert!(is_refused(&anyhow!("HTTP 410 Gone downloading url")));
+        assert!(!is_refused(&anyhow!("HTTP 404 Not Found downloading url")));
+    }
+
+    #[test]
+    fn refused_cdn_urls_retry_as_their_site() {
+        assert_eq!(
+            site_origin_for("https://scontent-gru2-1.cdninstagram.com/v/t51/a.jpg").as_deref(),
+            Some("https://www.instagram.com")
+        );
+        assert_eq!(
+            site_origin_for("https://video.twimg.com/ext_tw_video/1/a.mp4").as_deref(),
+            Some("https://x.com")
+        );
+        assert_eq!(
+            site_origin_for("https://media.example.org/a.mp4").as_deref(),
+            Some("https://media.example.org")
+        );
+        assert_eq!(
+            site_origin_for("https://notfbcdn.net/a.mp4").as_deref(),
+            Some("https://notfbcdn.net")
+        );
+
+        let headers = site_headers("https://pbs.twimg.com/media/a.jpg", None).unwrap();
+        assert_eq!(headers[reqwest::header::REFERER], "https://x.com/");
+        assert_eq!(headers[reqwest::header::ORIGIN], "https://x.com");
+        assert!(site_headers("https://pbs.twimg.com/media/a.jpg", Some(headers)).is_none());
+    }
+
     #[test]
     fn is_fatal_html_response() {
         assert!(is_fatal_error(&anyhow!(
@@ -498,4 +1034,88 @@ mod tests {
     fn threshold_gte_chunk_size() {
         assert!(CHUNK_THRESHOLD >= CHUNK_SIZE);
     }
+
+    #[test]
+    fn speed_bucket_allows_one_second_burst_then_waits() {
+        let start = Instant::now();
+        let mut bucket = SpeedBucket {
+            tokens: 0.0,
+            refilled_at: start,
+        };
+        let later = start + Duration::from_secs(5);
+        assert_eq!(bucket.charge(1000.0, 1000, later), Duration::ZERO);
+        assert_eq!(
+            bucket.charge(1000.0, 500, later),
+            Duration::from_millis(500)
+        );
+        assert_eq!(bucket.charge(1000.0, 500, later), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn speed_bucket_unlimited_clears_debt() {
+        let now = Instant::now();
+        let mut bucket = SpeedBucket {
+            tokens: -4000.0,
+            refilled_at: now,
+        };
+        assert_eq!(bucket.charge(0.0, 1 << 20, now), Duration::ZERO);
+        assert_eq!(bucket.tokens, 0.0);
+    }
+
+    #[test]
+    fn aria2c_gets_secrets_through_its_input() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::COOKIE, "sid=secret".parse().unwrap());
+        headers.insert(reqwest::header::USER_AGENT, "UA/1".parse().unwrap());
+        let input = aria2c_input(
+            "https://cdn.example.com/f.bin?token=t",
+            Path::new("/downloads"),
+            std::ffi::OsStr::new("f.bin.part"),
+            Some("http://user:pw@proxy:8080"),
+            Some(&headers),
+        );
+        let mut lines = input.lines();
+        assert_eq!(lines.next(), Some("https://cdn.example.com/f.bin?token=t"));
+        let options: Vec<&str> = lines.collect();
+        assert!(options.iter().all(|l| l.starts_with(' ')));
+        assert!(options.contains(&" dir=/downloads"));
+        assert!(options.contains(&" out=f.bin.part"));
+        assert!(options.contains(&" all-proxy=http://user:pw@proxy:8080"));
+        assert!(options.contains(&" header=cookie: sid=secret"));
+        assert!(options.contains(&" user-agent=UA/1"));
+    }
+
+    #[test]
+    fn parses_aria2c_summary() {
+        let update =
+            parse_aria2c_summary("[#1ce85c 35MiB/68MiB(51%) CN:8 DL:1.5MiB ETA:1m21s]").unwrap();
+        assert_eq!(update.percent, 51.0);
+        assert_eq!(update.downloaded_bytes, Some(35 * 1024 * 1024));
+        assert_eq!(update.total_bytes, Some(68 * 1024 * 1024));
+        assert_eq!(update.speed_bps, Some(1.5 * 1024.0 * 1024.0));
+        assert_eq!(update.eta_seconds, Some(81));
+
+        let starting = parse_aria2c_summary("[#1ce85c 0B/68MiB(0%) CN:1 DL:0B]").unwrap();
+        assert_eq!(starting.eta_seconds, None);
+        assert!(parse_aria2c_summary("[#1ce85c 0B/0B CN:1 DL:0B]").is_none());
+        assert!(parse_aria2c_summary("*** Download Progress Summary ***").is_none());
+    }
+
+    #[test]
+    fn only_direct_media_urls_are_sizeable() {
+        let sizeable = |url: &str, format: &str| {
+            is_directly_sizeable(&VideoQuality {
+                label: "original".to_string(),
+                width: 0,
+                height: 0,
+                url: url.to_string(),
+                format: format.to_string(),
+                ..Default::default()
+            })
+        };
+        assert!(sizeable("https://cdn.example/v.mp4", "mp4"));
+        assert!(!sizeable("https://cdn.example/v.m3u8", "mp4"));
+        assert!(!sizeable("https://vimeo.com/1", "ytdlp"));
+        assert!(!sizeable("blob:abc", "mp4"));
+    }
 }
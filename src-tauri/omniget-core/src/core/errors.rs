@@ -1,4 +1,128 @@
-pub fn classify_download_error(error: &str) -> (&str, &str) {
+use thiserror::Error;
+
+/// Failure reasons downloaders can report without relying on message text.
+///
+/// Downloaders still return `anyhow::Result`; the queue recovers the variant
+/// with `downcast_ref` to decide on retries and pick a hint.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DownloadError {
+    #[error("This content is private")]
+    Private,
+    #[error("Content not found or has been deleted")]
+    NotFound,
+    #[error("Age-restricted content")]
+    AgeRestricted,
+    #[error("Rate limited by the platform")]
+    RateLimited,
+    #[error("Session token expired")]
+    TokenExpired,
+    /// Only visible to a logged-in account (NSFW, quarantined, members-only)
+    /// and no cookies were available for the site.
+    #[error("Log in to view this content")]
+    LoginRequired,
+    /// Blocked in the user's region. `country_hint` is what the platform
+    /// said about where the content is available, when it said anything.
+    #[error("Not available in your region{}", available_in(country_hint))]
+    GeoBlocked { country_hint: Option<String> },
+    /// The output volume can't hold the download plus a safety margin.
+    #[error(
+        "Not enough disk space: needs {} MB, {} MB free",
+        needed / MIB,
+        available / MIB
+    )]
+    InsufficientSpace { needed: u64, available: u64 },
+    /// Larger than the user's maximum file size.
+    #[error("File is {} MB, over the {} MB limit", size / MIB, limit_mb)]
+    TooLarge { size: u64, limit_mb: u64 },
+    /// The media is a stream that is still on air.
+    #[error("This is an ongoing live stream")]
+    LiveStream,
+    #[error("{0}")]
+    Unsupported(String),
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+const MIB: u64 = 1024 * 1024;
+
+fn available_in(country_hint: &Option<String>) -> String {
+    country_hint
+        .as_deref()
+        .map(|c| format!(" (available in {})", c))
+        .unwrap_or_default()
+}
+
+impl DownloadError {
+    /// Maps a non-success HTTP status from a platform API.
+    pub fn from_status(service: &str, status: reqwest::StatusCode) -> Self {
+        match status.as_u16() {
+            401 | 403 => DownloadError::Private,
+            404 | 410 => DownloadError::NotFound,
+            451 => DownloadError::GeoBlocked { country_hint: None },
+            429 => DownloadError::RateLimited,
+            _ => DownloadError::Network(format!("{} returned HTTP {}", service, status)),
+        }
+    }
+
+    /// Same categories `classify_download_error` produces for plain messages.
+    pub fn category(&self) -> &'static str {
+        match self {
+            DownloadError::Private | DownloadError::AgeRestricted => "restricted",
+            DownloadError::NotFound => "not_found",
+            DownloadError::RateLimited => "rate_limited",
+            DownloadError::TokenExpired | DownloadError::LoginRequired => "auth_required",
+            DownloadError::GeoBlocked { .. } => "geo_blocked",
+            DownloadError::InsufficientSpace { .. } => "insufficient_space",
+            DownloadError::TooLarge { .. } => "too_large",
+            DownloadError::LiveStream => "live_stream",
+            DownloadError::Unsupported(_) => "unsupported",
+            DownloadError::Network(_) => "network",
+            DownloadError::Other(_) => "unknown",
+        }
+    }
+}
+
+/// Looks for a `DownloadError` anywhere in the chain before falling back to
+/// matching on the message text.
+pub fn classify_error(error: &anyhow::Error) -> &'static str {
+    find_download_error(error)
+        .map(DownloadError::category)
+        .unwrap_or_else(|| classify_message(&error.to_string()))
+}
+
+/// The first `DownloadError` in the chain of `error`.
+pub fn find_download_error(error: &anyhow::Error) -> Option<&DownloadError> {
+    error
+        .chain()
+        .find_map(|e| e.downcast_ref::<DownloadError>())
+}
+
+/// Whether a message reads like a region block ("not available in your
+/// country", "geo restricted", ...).
+pub fn is_geo_block_message(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("geo restrict")
+        || lower.contains("geo-restrict")
+        || (lower.contains("geo") && lower.contains("block"))
+        || lower.contains("in your country")
+        || lower.contains("from your location")
+        || lower.contains("in your region")
+}
+
+/// English hint for a category, if there is one. See `core::i18n` for
+/// other languages.
+pub fn category_hint(category: &str) -> Option<&'static str> {
+    crate::core::i18n::error_message(crate::core::i18n::DEFAULT_LOCALE, category)
+}
+
+pub fn classify_download_error(error: &str) -> (&'static str, &str) {
+    let category = classify_message(error);
+    (category, category_hint(category).unwrap_or(error))
+}
+
+fn classify_message(error: &str) -> &'static str {
     let lower = error.to_lowercase();
 
     if lower.contains("cookie")
@@ -7,7 +131,11 @@ pub fn classify_download_error(error: &str) -> (&str, &str) {
         || lower.contains("authentication")
         || lower.contains("403")
     {
-        return ("auth_required", "This content requires login. Install the browser extension and visit the site while logged in.");
+        return "auth_required";
+    }
+
+    if is_geo_block_message(error) {
+        return "geo_blocked";
     }
 
     if lower.contains("captcha")
@@ -16,21 +144,15 @@ pub fn classify_download_error(error: &str) -> (&str, &str) {
         || lower.contains("429")
         || lower.contains("too many")
     {
-        return (
-            "rate_limited",
-            "Too many requests. Try again in a few minutes.",
-        );
+        return "rate_limited";
     }
 
     if lower.contains("private") || lower.contains("restricted") || lower.contains("age") {
-        return ("restricted", "This content is private or age-restricted.");
+        return "restricted";
     }
 
     if lower.contains("downloaded file") && lower.contains("not found") {
-        return (
-            "file_missing",
-            "Downloaded file could not be located in the output folder.",
-        );
+        return "file_missing";
     }
 
     if lower.contains("not found")
@@ -38,29 +160,103 @@ pub fn classify_download_error(error: &str) -> (&str, &str) {
         || lower.contains("unavailable")
         || lower.contains("deleted")
     {
-        return ("not_found", "Content not found or has been deleted.");
+        return "not_found";
+    }
+
+    if lower.contains("timed out")
+        || lower.contains("timeout")
+        || lower.contains("connection reset")
+        || lower.contains("connection refused")
+        || lower.contains("connection closed")
+    {
+        return "network";
     }
 
     if lower.contains("ffmpeg") || lower.contains("mux") || lower.contains("merge") {
-        return (
-            "ffmpeg_needed",
-            "FFmpeg is required for this download. Install it from Settings.",
-        );
+        return "ffmpeg_needed";
     }
 
     if lower.contains("yt-dlp") || lower.contains("ytdlp") || lower.contains("no downloader") {
-        return (
-            "ytdlp_needed",
-            "yt-dlp is required. Install it from Settings.",
-        );
+        return "ytdlp_needed";
     }
 
     if lower.contains("nsig") || lower.contains("signature") || lower.contains("cipher") {
-        return (
-            "ytdlp_outdated",
-            "yt-dlp needs updating. Restart the app to auto-update.",
+        return "ytdlp_outdated";
+    }
+
+    "unknown"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structured_errors_win_over_message_sniffing() {
+        // "Session token expired" would otherwise read as unknown.
+        let err = anyhow::Error::new(DownloadError::TokenExpired);
+        assert_eq!(classify_error(&err), "auth_required");
+
+        let wrapped = anyhow::Error::new(DownloadError::RateLimited).context("fetching post");
+        assert_eq!(classify_error(&wrapped), "rate_limited");
+
+        let plain = anyhow::anyhow!("HTTP Error 404: Not Found");
+        assert_eq!(classify_error(&plain), "not_found");
+
+        let timeout = anyhow::anyhow!("error sending request: operation timed out");
+        assert_eq!(classify_error(&timeout), "network");
+    }
+
+    #[test]
+    fn from_status_maps_common_codes() {
+        use reqwest::StatusCode;
+        assert_eq!(
+            DownloadError::from_status("Reddit", StatusCode::NOT_FOUND),
+            DownloadError::NotFound
+        );
+        assert_eq!(
+            DownloadError::from_status("Reddit", StatusCode::TOO_MANY_REQUESTS),
+            DownloadError::RateLimited
+        );
+        assert_eq!(
+            DownloadError::from_status("Reddit", StatusCode::BAD_GATEWAY).category(),
+            "network"
+        );
+    }
+
+    #[test]
+    fn unknown_messages_keep_their_text_as_hint() {
+        assert_eq!(
+            classify_download_error("something odd"),
+            ("unknown", "something odd")
         );
+        assert_eq!(classify_download_error("HTTP 429").0, "rate_limited");
     }
 
-    ("unknown", error)
+    #[test]
+    fn region_blocks_get_their_own_category() {
+        let err = anyhow::Error::new(DownloadError::GeoBlocked {
+            country_hint: Some("Japan".into()),
+        });
+        assert_eq!(classify_error(&err), "geo_blocked");
+        assert_eq!(
+            err.to_string(),
+            "Not available in your region (available in Japan)"
+        );
+        assert_eq!(
+            classify_download_error(
+                "The uploader has not made this video available in your country"
+            )
+            .0,
+            "geo_blocked"
+        );
+        assert_eq!(
+            DownloadError::from_status(
+                "Bluesky",
+                reqwest::StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS
+            )
+            .category(),
+            "geo_blocked"
+        );
+    }
 }
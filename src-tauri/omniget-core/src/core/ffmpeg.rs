@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
@@ -6,6 +6,9 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
+use crate::core::hwaccel::{self, EncoderBackend};
+use crate::core::log_hook;
+use crate::models::media::{DownloadNote, DownloadPhase};
 use crate::models::progress::ProgressUpdate;
 
 static FFMPEG_AVAILABLE_CACHE: std::sync::RwLock<Option<bool>> = std::sync::RwLock::new(None);
@@ -31,10 +34,14 @@ pub fn reset_ffmpeg_available_cache() {
     }
 }
 
+/// Merges `video` and `audio` into `output`. ffmpeg writes to a staging
+/// file first, so an interrupted merge never leaves a file at `output`.
 pub async fn mux_video_audio(video: &Path, audio: &Path, output: &Path) -> anyhow::Result<()> {
+    log_hook::report_phase(DownloadPhase::Muxing);
     if let Some(parent) = output.parent() {
         std::fs::create_dir_all(parent)?;
     }
+    let staging = crate::core::temp_dir::staging_for(output);
 
     let status = crate::core::process::command("ffmpeg")
         .args([
@@ -45,19 +52,223 @@ pub async fn mux_video_audio(video: &Path, audio: &Path, output: &Path) -> anyho
             &audio.to_string_lossy(),
             "-c",
             "copy",
-            &output.to_string_lossy(),
+            &staging.to_string_lossy(),
         ])
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
         .status()
         .await
+        .map_err(|e| anyhow!("Failed to run ffmpeg: {}", e));
+
+    let finished = match status {
+        Ok(status) if !status.success() => Err(anyhow!("ffmpeg returned code {}", status)),
+        Ok(_) if !std::fs::metadata(&staging).is_ok_and(|m| m.len() > 0) => {
+            Err(anyhow!("ffmpeg produced an empty file"))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = finished {
+        let _ = std::fs::remove_file(&staging);
+        return Err(e);
+    }
+
+    crate::core::temp_dir::move_file(&staging, output)
+        .await
+        .map_err(|e| anyhow!("Failed to move merged file into place: {}", e))
+}
+
+/// Drops every audio track from `file` in place, copying the rest as is.
+/// Returns the new size.
+pub async fn strip_audio(file: &Path) -> anyhow::Result<u64> {
+    remux_in_place(file, &["-an"]).await
+}
+
+/// Copies every stream of `file` into a fresh container and puts it back
+/// in place, which rebuilds the index (and the MP4 `moov` atom). Fixes many
+/// files that won't seek or play after a cut-off mux or HLS assembly.
+/// Returns the new size.
+pub async fn repair_media(file: &Path) -> anyhow::Result<u64> {
+    let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let faststart: &[&str] = if matches!(ext.to_lowercase().as_str(), "mp4" | "m4a" | "mov") {
+        &["-movflags", "+faststart"]
+    } else {
+        &[]
+    };
+    remux_in_place(file, faststart).await
+}
+
+async fn remux_in_place(file: &Path, extra_args: &[&str]) -> anyhow::Result<u64> {
+    let temp_output = crate::core::temp_dir::staging_for(file);
+    let input = file.to_string_lossy().to_string();
+    let staging = temp_output.to_string_lossy().to_string();
+    let mut args = vec!["-y", "-i", &input, "-map", "0", "-c", "copy"];
+    args.extend_from_slice(extra_args);
+    args.push(&staging);
+
+    let output = crate::core::process::command("ffmpeg")
+        .args(&args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .await
         .map_err(|e| anyhow!("Failed to run ffmpeg: {}", e))?;
 
-    if !status.success() {
-        return Err(anyhow!("ffmpeg returned code {}", status));
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp_output);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("ffmpeg remux failed: {}", stderr));
+    }
+
+    if let Err(e) = crate::core::temp_dir::move_file(&temp_output, file).await {
+        let _ = std::fs::remove_file(&temp_output);
+        return Err(anyhow!("Failed to replace file: {}", e));
     }
 
-    Ok(())
+    Ok(std::fs::metadata(file)?.len())
+}
+
+/// Applies `download_mode: "mute"` to a video a native downloader saved
+/// with its audio. Without ffmpeg the file is kept as downloaded and the
+/// note says so; `bytes` is the size to report in that case.
+pub async fn mute_downloaded(
+    file: &Path,
+    bytes: u64,
+) -> anyhow::Result<(u64, Option<DownloadNote>)> {
+    if !is_ffmpeg_available().await {
+        tracing::warn!("[ffmpeg] mute requested but FFmpeg is not installed — keeping audio");
+        return Ok((bytes, Some(DownloadNote::FfmpegMissingKeptAudio)));
+    }
+    log_hook::report_phase(DownloadPhase::Muxing);
+    Ok((strip_audio(file).await?, None))
+}
+
+/// Applies `download_mode: "audio"` to a video a native downloader saved:
+/// extracts its audio with the first preset for `audio_format`'s codec and
+/// drops the video unless originals are kept. Without ffmpeg the video is
+/// kept and the note says so; `bytes` is the size to report in that case.
+pub async fn audio_from_downloaded(
+    file: &Path,
+    bytes: u64,
+    audio_format: Option<&str>,
+    cancel_token: CancellationToken,
+) -> anyhow::Result<(PathBuf, u64, Option<DownloadNote>)> {
+    if !is_ffmpeg_available().await {
+        tracing::warn!("[ffmpeg] audio requested but FFmpeg is not installed — keeping the video");
+        return Ok((
+            file.to_path_buf(),
+            bytes,
+            Some(DownloadNote::FfmpegMissingKeptVideo),
+        ));
+    }
+    let codec = AudioCodec::from_format(audio_format);
+    let output = file.with_extension(codec.extension());
+    if output == file {
+        return Ok((file.to_path_buf(), bytes, None));
+    }
+    let preset = AUDIO_PRESETS
+        .iter()
+        .find(|p| p.codec == codec)
+        .ok_or_else(|| anyhow!("No audio preset for {:?}", codec))?;
+
+    log_hook::report_phase(DownloadPhase::Converting);
+    let (tx, _rx) = mpsc::channel(8);
+    let result = convert_to_audio(
+        file,
+        &output,
+        codec,
+        preset.bitrate_kbps,
+        preset.mode,
+        cancel_token,
+        tx,
+    )
+    .await?;
+    if !result.success {
+        let _ = std::fs::remove_file(&output);
+        return Err(anyhow!(
+            "Audio extraction failed: {}",
+            result.error.unwrap_or_default()
+        ));
+    }
+    if !crate::core::ytdlp::convert_keep_original_setting() {
+        let _ = std::fs::remove_file(file);
+    }
+    Ok((output, result.file_size_bytes, None))
+}
+
+/// Error lines kept in a `MediaHealth`; a broken file can print thousands.
+const MAX_HEALTH_ERRORS: usize = 20;
+
+/// What `verify_media` found.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MediaHealth {
+    pub ok: bool,
+    /// First lines ffmpeg printed at the `error` level.
+    pub errors: Vec<String>,
+}
+
+const VERIFIABLE_EXTENSIONS: &[&str] = &[
+    "mp4", "m4v", "mkv", "webm", "mov", "ts", "flv", "avi", "mp3", "m4a", "aac", "ogg", "opus",
+    "flac", "wav",
+];
+
+/// Whether `path` looks like audio or video that `verify_media` can check.
+pub fn is_verifiable(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    VERIFIABLE_EXTENSIONS.contains(&ext.as_str())
+}
+
+/// Decodes all of `file` without writing anything and reports the errors
+/// ffmpeg ran into. Every frame is decoded, so long videos take a while;
+/// `cancel_token` kills ffmpeg and returns an error.
+pub async fn verify_media(
+    file: &Path,
+    cancel_token: &CancellationToken,
+) -> anyhow::Result<MediaHealth> {
+    let child = crate::core::process::command("ffmpeg")
+        .args([
+            "-nostdin",
+            "-v",
+            "error",
+            "-i",
+            &file.to_string_lossy(),
+            "-f",
+            "null",
+            "-",
+        ])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run ffmpeg: {}", e))?;
+    let output = tokio::select! {
+        output = child.wait_with_output() => {
+            output.map_err(|e| anyhow!("ffmpeg process failed: {}", e))?
+        }
+        _ = cancel_token.cancelled() => return Err(anyhow!("Verification cancelled")),
+    };
+    Ok(health_from_output(
+        output.status.success(),
+        &String::from_utf8_lossy(&output.stderr),
+    ))
+}
+
+fn health_from_output(success: bool, stderr: &str) -> MediaHealth {
+    let errors: Vec<String> = stderr
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .take(MAX_HEALTH_ERRORS)
+        .map(String::from)
+        .collect();
+    MediaHealth {
+        ok: success && errors.is_empty(),
+        errors,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,6 +287,19 @@ pub struct ConversionOptions {
     pub additional_input_args: Option<Vec<String>>,
     pub additional_output_args: Option<Vec<String>>,
     pub preset: Option<String>,
+    /// GPU vs CPU choice when `video_codec` is H.264 or HEVC.
+    #[serde(default)]
+    pub encoder: EncoderBackend,
+    /// Position among the input's audio streams (`0:a:N`), not the global
+    /// stream index.
+    #[serde(default)]
+    pub audio_track_index: Option<u32>,
+    /// Position among the input's subtitle streams (`0:s:N`).
+    #[serde(default)]
+    pub subtitle_track_index: Option<u32>,
+    /// Render `subtitle_track_index` into the picture instead of muxing it.
+    #[serde(default)]
+    pub burn_subtitles: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +310,8 @@ pub struct MediaProbeInfo {
     pub file_size_bytes: u64,
     pub bit_rate: u64,
     pub streams: Vec<StreamInfo>,
+    #[serde(default)]
+    pub chapters: Vec<ChapterInfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,6 +327,24 @@ pub struct StreamInfo {
     pub sample_rate: Option<u32>,
     pub channels: Option<u32>,
     pub duration_seconds: Option<f64>,
+    /// e.g. `stereo` or `5.1(side)`.
+    #[serde(default)]
+    pub channel_layout: Option<String>,
+    /// ISO 639 tag from the stream's `language` tag, when set.
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Track players pick by default among streams of its type.
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChapterInfo {
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub title: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,6 +365,7 @@ pub async fn probe(path: &Path) -> anyhow::Result<MediaProbeInfo> {
             "json",
             "-show_format",
             "-show_streams",
+            "-show_chapters",
             &path.to_string_lossy(),
         ])
         .stdout(std::process::Stdio::piped())
@@ -136,7 +381,10 @@ pub async fn probe(path: &Path) -> anyhow::Result<MediaProbeInfo> {
 
     let json: serde_json::Value = serde_json::from_slice(&output.stdout)
         .map_err(|e| anyhow!("Failed to parse ffprobe JSON: {}", e))?;
+    parse_probe_json(&json)
+}
 
+fn parse_probe_json(json: &serde_json::Value) -> anyhow::Result<MediaProbeInfo> {
     let format = json
         .get("format")
         .ok_or_else(|| anyhow!("Missing 'format' field"))?;
@@ -177,6 +425,12 @@ pub async fn probe(path: &Path) -> anyhow::Result<MediaProbeInfo> {
         .map(|arr| arr.iter().map(parse_stream_info).collect())
         .unwrap_or_default();
 
+    let chapters = json
+        .get("chapters")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(parse_chapter_info).collect())
+        .unwrap_or_default();
+
     Ok(MediaProbeInfo {
         duration_seconds,
         format_name,
@@ -184,9 +438,34 @@ pub async fn probe(path: &Path) -> anyhow::Result<MediaProbeInfo> {
         file_size_bytes,
         bit_rate,
         streams,
+        chapters,
     })
 }
 
+fn parse_chapter_info(c: &serde_json::Value) -> Option<ChapterInfo> {
+    let seconds = |key: &str| {
+        c.get(key)
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.parse::<f64>().ok())
+    };
+    Some(ChapterInfo {
+        start_seconds: seconds("start_time")?,
+        end_seconds: seconds("end_time")?,
+        title: tag_value(c, "title"),
+    })
+}
+
+/// A stream or chapter tag. Matroska writes some tags upper-case.
+fn tag_value(v: &serde_json::Value, key: &str) -> Option<String> {
+    let tags = v.get("tags")?.as_object()?;
+    tags.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .and_then(|(_, v)| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
 fn parse_stream_info(s: &serde_json::Value) -> StreamInfo {
     let index = s.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
 
@@ -233,6 +512,17 @@ fn parse_stream_info(s: &serde_json::Value) -> StreamInfo {
         .and_then(|v| v.as_str())
         .and_then(|s| s.parse::<f64>().ok());
 
+    let channel_layout = s
+        .get("channel_layout")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+
+    let is_default = s
+        .get("disposition")
+        .and_then(|d| d.get("default"))
+        .and_then(|v| v.as_u64())
+        == Some(1);
+
     StreamInfo {
         index,
         codec_type,
@@ -245,6 +535,10 @@ fn parse_stream_info(s: &serde_json::Value) -> StreamInfo {
         sample_rate,
         channels,
         duration_seconds,
+        channel_layout,
+        language: tag_value(s, "language").filter(|l| l != "und"),
+        title: tag_value(s, "title"),
+        is_default,
     }
 }
 
@@ -265,10 +559,123 @@ pub async fn get_duration_us(path: &Path) -> anyhow::Result<u64> {
     Ok((info.duration_seconds * 1_000_000.0) as u64)
 }
 
+/// Filter graph that samples `columns * rows` frames at the middle of equal
+/// slices of the video and tiles them into one image `width` pixels wide.
+/// The first frame is picked by seeking, see [`contact_sheet_seek`].
+pub fn contact_sheet_filter(duration_seconds: f64, columns: u32, rows: u32, width: u32) -> String {
+    let frames = columns * rows;
+    let tile_width = (width / columns).max(2) & !1;
+    format!(
+        "fps={}/{:.3},scale={}:-2,tile={}x{}",
+        frames, duration_seconds, tile_width, columns, rows
+    )
+}
+
+/// Offset of the first sampled frame: half a slice into the video, so the
+/// sheet skips the usual black opening frame.
+pub fn contact_sheet_seek(duration_seconds: f64, columns: u32, rows: u32) -> f64 {
+    duration_seconds / f64::from(columns * rows) / 2.0
+}
+
+/// Writes a `columns` x `rows` grid of evenly spaced frames from `input` to
+/// `output` as a single JPEG.
+pub async fn generate_contact_sheet(
+    input: &Path,
+    output: &Path,
+    columns: u32,
+    rows: u32,
+    width: u32,
+) -> anyhow::Result<()> {
+    if columns == 0 || rows == 0 {
+        return Err(anyhow!("grid must have at least one column and row"));
+    }
+    let info = probe(input).await?;
+    if info.duration_seconds <= 0.0 {
+        return Err(anyhow!("could not determine video duration"));
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let seek = contact_sheet_seek(info.duration_seconds, columns, rows);
+    let filter = contact_sheet_filter(info.duration_seconds, columns, rows, width);
+    let ran = crate::core::process::command("ffmpeg")
+        .args([
+            "-y",
+            "-ss",
+            &format!("{:.3}", seek),
+            "-i",
+            &input.to_string_lossy(),
+            "-vf",
+            &filter,
+            "-frames:v",
+            "1",
+            "-q:v",
+            "3",
+            &output.to_string_lossy(),
+        ])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run ffmpeg: {}", e))?;
+
+    if !ran.status.success() {
+        let stderr = String::from_utf8_lossy(&ran.stderr);
+        let last = stderr.lines().last().unwrap_or_default();
+        return Err(anyhow!("ffmpeg returned code {}: {}", ran.status, last));
+    }
+
+    Ok(())
+}
+
+/// Runs a conversion, swapping H.264/HEVC for a detected GPU encoder when
+/// `opts.encoder` allows it. If the hardware run fails, ffmpeg is run again
+/// with the software encoder before giving up.
 pub async fn convert(
     opts: &ConversionOptions,
     cancel_token: CancellationToken,
     progress_tx: mpsc::Sender<ProgressUpdate>,
+) -> anyhow::Result<ConversionResult> {
+    let Some(codec) = opts.video_codec.as_deref() else {
+        return run_conversion(opts, cancel_token, progress_tx).await;
+    };
+    let hardware = if opts.encoder == EncoderBackend::Software {
+        None
+    } else {
+        let info = hwaccel::detect_hwaccel().await;
+        hwaccel::hardware_encoder(codec, opts.encoder, &info.encoders)
+    };
+    let (Some(hardware), Some(software)) = (hardware, hwaccel::software_encoder(codec)) else {
+        return run_conversion(opts, cancel_token, progress_tx).await;
+    };
+
+    let mut hw_opts = opts.clone();
+    hw_opts.video_codec = Some(hardware.clone());
+    if hardware.ends_with("videotoolbox") {
+        // VideoToolbox has no x264-style presets.
+        hw_opts.preset = None;
+    }
+    let result = run_conversion(&hw_opts, cancel_token.clone(), progress_tx.clone()).await?;
+    if result.success || cancel_token.is_cancelled() {
+        return Ok(result);
+    }
+
+    tracing::warn!(
+        "[convert] {} failed ({}), retrying with {}",
+        hardware,
+        result.error.as_deref().unwrap_or("unknown error"),
+        software
+    );
+    let mut sw_opts = opts.clone();
+    sw_opts.video_codec = Some(software.to_string());
+    run_conversion(&sw_opts, cancel_token, progress_tx).await
+}
+
+async fn run_conversion(
+    opts: &ConversionOptions,
+    cancel_token: CancellationToken,
+    progress_tx: mpsc::Sender<ProgressUpdate>,
 ) -> anyhow::Result<ConversionResult> {
     let input_path = Path::new(&opts.input_path);
     let output_path = Path::new(&opts.output_path);
@@ -277,7 +684,13 @@ pub async fn convert(
         std::fs::create_dir_all(parent)?;
     }
 
-    let total_duration_us = get_duration_us(input_path).await.unwrap_or(0);
+    let probed = probe(input_path).await.ok();
+    let input_duration_us = probed
+        .as_ref()
+        .map(|info| (info.duration_seconds * 1_000_000.0) as u64)
+        .unwrap_or(0);
+    let total_duration_us = output_duration_us(opts, input_duration_us);
+    let track_args = track_selection_args(opts, probed.as_ref().map(|info| &info.streams[..]))?;
 
     let mut args: Vec<String> = vec!["-y".to_string()];
 
@@ -292,9 +705,19 @@ pub async fn convert(
     args.extend(["-i".to_string(), opts.input_path.clone()]);
 
     if let Some(ref end) = opts.trim_end {
-        args.extend(["-to".to_string(), end.clone()]);
+        // `-ss` is an input option, so output timestamps restart at zero and
+        // an absolute end has to be turned into a length.
+        let start = opts.trim_start.as_deref().and_then(parse_timestamp);
+        match (start, parse_timestamp(end)) {
+            (Some(start), Some(end)) => {
+                args.extend(["-t".to_string(), format!("{:.3}", (end - start).max(0.0))]);
+            }
+            _ => args.extend(["-to".to_string(), end.clone()]),
+        }
     }
 
+    args.extend(track_args);
+
     if let Some(ref codec) = opts.video_codec {
         args.extend(["-c:v".to_string(), codec.clone()]);
     }
@@ -355,15 +778,13 @@ pub async fn convert(
     let cancel = cancel_token.clone();
     let progress = progress_tx.clone();
     let line_reader = tokio::spawn(async move {
+        let mut state = FfmpegProgress::default();
         while let Ok(Some(line)) = lines.next_line().await {
             if cancel.is_cancelled() {
                 break;
             }
-            if let Some(us) = parse_out_time_us(&line) {
-                if total_duration_us > 0 {
-                    let pct = (us as f64 / total_duration_us as f64 * 100.0).min(100.0);
-                    let _ = progress.send(ProgressUpdate::percent(pct)).await;
-                }
+            if state.feed(&line) {
+                let _ = progress.send(state.update(total_duration_us)).await;
             }
         }
     });
@@ -422,6 +843,665 @@ pub async fn convert(
     }
 }
 
+/// `-map`, `-vf subtitles` and subtitle codec arguments for the track choices
+/// in `opts`. `streams` is the probed input, or `None` when probing failed
+/// and the indices are passed through unchecked. A subtitle track the input
+/// doesn't have is dropped with a warning; a missing audio track is an error.
+pub fn track_selection_args(
+    opts: &ConversionOptions,
+    streams: Option<&[StreamInfo]>,
+) -> anyhow::Result<Vec<String>> {
+    let count = |kind: &str| {
+        streams.map(|streams| streams.iter().filter(|s| s.codec_type == kind).count() as u32)
+    };
+    let output_ext = Path::new(&opts.output_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let audio_only = matches!(
+        output_ext.as_str(),
+        "mp3" | "m4a" | "aac" | "ogg" | "opus" | "flac" | "wav" | "wma"
+    );
+
+    if let (Some(index), Some(available)) = (opts.audio_track_index, count("audio")) {
+        if index >= available {
+            return Err(anyhow!(
+                "audio track {} requested but the file has {}",
+                index + 1,
+                available
+            ));
+        }
+    }
+
+    let subtitle = opts.subtitle_track_index.filter(|&index| {
+        if audio_only {
+            tracing::warn!(
+                "[convert] {} output can't carry subtitles, ignoring track {}",
+                output_ext,
+                index + 1
+            );
+            return false;
+        }
+        match count("subtitle") {
+            Some(available) if index >= available => {
+                tracing::warn!(
+                    "[convert] subtitle track {} requested but {} has {}, ignoring",
+                    index + 1,
+                    opts.input_path,
+                    available
+                );
+                false
+            }
+            _ => true,
+        }
+    });
+    let burn = subtitle.filter(|_| opts.burn_subtitles);
+    if burn.is_some() && opts.video_codec.as_deref() == Some("copy") {
+        return Err(anyhow!(
+            "burning in subtitles needs the video to be re-encoded"
+        ));
+    }
+    let soft = subtitle.filter(|_| !opts.burn_subtitles);
+
+    let mut args = Vec::new();
+    if opts.audio_track_index.is_some() || soft.is_some() {
+        // Any `-map` turns off ffmpeg's automatic stream picking, so the
+        // video and audio have to be mapped alongside the chosen tracks.
+        if !audio_only {
+            args.extend(["-map".to_string(), "0:v:0?".to_string()]);
+        }
+        let audio = match opts.audio_track_index {
+            Some(index) => format!("0:a:{}", index),
+            None => "0:a:0?".to_string(),
+        };
+        args.extend(["-map".to_string(), audio]);
+    }
+    if let Some(index) = soft {
+        let codec = match output_ext.as_str() {
+            "mp4" | "m4v" | "mov" => "mov_text",
+            "webm" => "webvtt",
+            _ => "copy",
+        };
+        args.extend([
+            "-map".to_string(),
+            format!("0:s:{}", index),
+            "-c:s".to_string(),
+            codec.to_string(),
+        ]);
+    }
+    if let Some(index) = burn {
+        let subtitles = format!(
+            "subtitles={}:si={}",
+            escape_filter_path(&opts.input_path),
+            index
+        );
+        // The filter reads the subtitles from the start of the file while
+        // `-ss` restarts the video at zero, so shift the frames back to
+        // their place in the file for it and then to zero again.
+        let start = opts
+            .trim_start
+            .as_deref()
+            .and_then(parse_timestamp)
+            .filter(|&start| start > 0.0);
+        let filter = match start {
+            Some(start) => format!(
+                "setpts=PTS+{:.3}/TB,{},setpts=PTS-STARTPTS",
+                start, subtitles
+            ),
+            None => subtitles,
+        };
+        args.extend(["-vf".to_string(), filter, "-sn".to_string()]);
+    }
+    Ok(args)
+}
+
+/// Escapes a path for use as a filter option inside a filtergraph: once for
+/// the option value, then again for the graph.
+fn escape_filter_path(path: &str) -> String {
+    fn escape(s: &str, special: &[char]) -> String {
+        let mut out = String::with_capacity(s.len());
+        for c in s.chars() {
+            if special.contains(&c) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out
+    }
+    let path = if cfg!(windows) {
+        path.replace('\\', "/")
+    } else {
+        path.to_string()
+    };
+    let option = escape(&path, &['\\', '\'', ':']);
+    escape(&option, &['\\', '\'', '[', ']', ',', ';'])
+}
+
+/// Length of the output once `trim_start`/`trim_end` are applied, so
+/// progress is measured against the kept segment rather than the input.
+fn output_duration_us(opts: &ConversionOptions, input_duration_us: u64) -> u64 {
+    if input_duration_us == 0 {
+        return 0;
+    }
+    let input = input_duration_us as f64 / 1_000_000.0;
+    let start = opts
+        .trim_start
+        .as_deref()
+        .and_then(parse_timestamp)
+        .unwrap_or(0.0);
+    let end = opts
+        .trim_end
+        .as_deref()
+        .and_then(parse_timestamp)
+        .unwrap_or(input)
+        .min(input);
+    ((end - start).max(0.0) * 1_000_000.0) as u64
+}
+
+/// Parses `ss[.ms]`, `mm:ss[.ms]` or `hh:mm:ss[.ms]` into seconds.
+pub fn parse_timestamp(s: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    let parts: Vec<&str> = s.trim().split(':').collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+    for part in parts {
+        let value: f64 = part.parse().ok()?;
+        if !value.is_finite() || value < 0.0 {
+            return None;
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    Some(seconds)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TrimRange {
+    pub start: f64,
+    pub end: f64,
+    /// The requested range reached past the end of the file and was cut
+    /// back to fit.
+    pub clamped: bool,
+}
+
+/// Fits `start..end` inside `0..duration`. A missing `end` means the end
+/// of the file. Fails when nothing would be left to keep.
+pub fn clamp_trim_range(start: f64, end: Option<f64>, duration: f64) -> anyhow::Result<TrimRange> {
+    let requested_end = end.unwrap_or(duration);
+    let start_clamped = start.clamp(0.0, duration);
+    let end_clamped = requested_end.clamp(0.0, duration);
+    if end_clamped <= start_clamped {
+        return Err(anyhow!(
+            "trim range {:.3}-{:.3} is empty for a {:.3}s file",
+            start,
+            requested_end,
+            duration
+        ));
+    }
+    Ok(TrimRange {
+        start: start_clamped,
+        end: end_clamped,
+        clamped: start_clamped != start || end_clamped != requested_end,
+    })
+}
+
+/// Conversion that keeps `range` of `input`. Stream copy is fast but cuts
+/// on the keyframe before `start`; `reencode` lets ffmpeg pick the
+/// container's default encoders and cuts on the exact frame.
+pub fn trim_options(
+    input: &Path,
+    output: &Path,
+    range: TrimRange,
+    reencode: bool,
+) -> ConversionOptions {
+    let (codec, extra) = if reencode {
+        (None, None)
+    } else {
+        (
+            Some("copy".to_string()),
+            Some(vec![
+                "-avoid_negative_ts".to_string(),
+                "make_zero".to_string(),
+            ]),
+        )
+    };
+    ConversionOptions {
+        input_path: input.to_string_lossy().into_owned(),
+        output_path: output.to_string_lossy().into_owned(),
+        video_codec: codec.clone(),
+        audio_codec: codec,
+        resolution: None,
+        video_bitrate: None,
+        audio_bitrate: None,
+        sample_rate: None,
+        fps: None,
+        trim_start: Some(format!("{:.3}", range.start)),
+        trim_end: Some(format!("{:.3}", range.end)),
+        additional_input_args: None,
+        additional_output_args: extra,
+        preset: None,
+        encoder: EncoderBackend::Software,
+        audio_track_index: None,
+        subtitle_track_index: None,
+        burn_subtitles: false,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCodec {
+    Mp3,
+    Opus,
+    M4a,
+    Flac,
+}
+
+impl AudioCodec {
+    pub fn encoder(self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "libmp3lame",
+            AudioCodec::Opus => "libopus",
+            AudioCodec::M4a => "aac",
+            AudioCodec::Flac => "flac",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "mp3",
+            AudioCodec::Opus => "opus",
+            AudioCodec::M4a => "m4a",
+            AudioCodec::Flac => "flac",
+        }
+    }
+
+    /// The codec for an `audio_format` setting. Formats without an encoder
+    /// here fall back to M4A, the audio-mode default.
+    pub fn from_format(format: Option<&str>) -> Self {
+        match format.map(|f| f.trim().to_ascii_lowercase()).as_deref() {
+            Some("mp3") => AudioCodec::Mp3,
+            Some("opus") => AudioCodec::Opus,
+            Some("flac") => AudioCodec::Flac,
+            _ => AudioCodec::M4a,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BitrateMode {
+    #[default]
+    Cbr,
+    Vbr,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioPreset {
+    pub id: &'static str,
+    pub codec: AudioCodec,
+    pub bitrate_kbps: u32,
+    pub mode: BitrateMode,
+}
+
+/// One-click "rip audio" choices shown in the UI.
+pub const AUDIO_PRESETS: &[AudioPreset] = &[
+    AudioPreset {
+        id: "mp3_320",
+        codec: AudioCodec::Mp3,
+        bitrate_kbps: 320,
+        mode: BitrateMode::Cbr,
+    },
+    AudioPreset {
+        id: "mp3_v0",
+        codec: AudioCodec::Mp3,
+        bitrate_kbps: 245,
+        mode: BitrateMode::Vbr,
+    },
+    AudioPreset {
+        id: "opus_192",
+        codec: AudioCodec::Opus,
+        bitrate_kbps: 192,
+        mode: BitrateMode::Vbr,
+    },
+    AudioPreset {
+        id: "m4a_256",
+        codec: AudioCodec::M4a,
+        bitrate_kbps: 256,
+        mode: BitrateMode::Cbr,
+    },
+    AudioPreset {
+        id: "flac",
+        codec: AudioCodec::Flac,
+        bitrate_kbps: 0,
+        mode: BitrateMode::Vbr,
+    },
+];
+
+/// Best LAME `-q:a` level (0 is highest) whose typical bitrate fits in `kbps`.
+fn lame_vbr_quality(kbps: u32) -> u32 {
+    const LEVELS: [u32; 9] = [245, 225, 190, 175, 165, 130, 115, 100, 85];
+    LEVELS
+        .iter()
+        .position(|&level| kbps >= level)
+        .unwrap_or(LEVELS.len()) as u32
+}
+
+/// Options for `convert` that drop the video and encode the audio track.
+/// FLAC is lossless and ignores the bitrate; AAC always uses it as an
+/// average since ffmpeg's encoder has no usable VBR mode.
+pub fn audio_conversion_options(
+    input: &Path,
+    output: &Path,
+    codec: AudioCodec,
+    bitrate_kbps: u32,
+    mode: BitrateMode,
+) -> ConversionOptions {
+    let mut audio_bitrate = None;
+    let mut output_args = vec!["-vn".to_string()];
+    match codec {
+        AudioCodec::Mp3 if mode == BitrateMode::Vbr => {
+            output_args.extend([
+                "-q:a".to_string(),
+                lame_vbr_quality(bitrate_kbps).to_string(),
+            ]);
+        }
+        AudioCodec::Opus => {
+            audio_bitrate = Some(format!("{}k", bitrate_kbps));
+            let vbr = if mode == BitrateMode::Vbr {
+                "on"
+            } else {
+                "off"
+            };
+            output_args.extend(["-vbr".to_string(), vbr.to_string()]);
+        }
+        AudioCodec::Flac => {}
+        AudioCodec::Mp3 | AudioCodec::M4a => {
+            audio_bitrate = Some(format!("{}k", bitrate_kbps));
+        }
+    }
+
+    ConversionOptions {
+        input_path: input.to_string_lossy().into_owned(),
+        output_path: output.to_string_lossy().into_owned(),
+        video_codec: None,
+        audio_codec: Some(codec.encoder().to_string()),
+        resolution: None,
+        video_bitrate: None,
+        audio_bitrate,
+        sample_rate: None,
+        fps: None,
+        trim_start: None,
+        trim_end: None,
+        additional_input_args: None,
+        additional_output_args: Some(output_args),
+        preset: None,
+        encoder: EncoderBackend::Software,
+        audio_track_index: None,
+        subtitle_track_index: None,
+        burn_subtitles: false,
+    }
+}
+
+/// Extracts the audio of `input` into `output`, reporting progress the same
+/// way as `convert`.
+pub async fn convert_to_audio(
+    input: &Path,
+    output: &Path,
+    codec: AudioCodec,
+    bitrate_kbps: u32,
+    mode: BitrateMode,
+    cancel_token: CancellationToken,
+    progress_tx: mpsc::Sender<ProgressUpdate>,
+) -> anyhow::Result<ConversionResult> {
+    let opts = audio_conversion_options(input, output, codec, bitrate_kbps, mode);
+    convert(&opts, cancel_token, progress_tx).await
+}
+
+/// Filter graph for a palette GIF: `palettegen` first reads the whole
+/// scaled clip to build a 256-colour palette for it, then `paletteuse`
+/// maps every frame onto that palette. `stats_mode=diff` and rectangle
+/// diffing favour the parts that move, which keeps the file small.
+pub fn gif_filter(fps: u32, width: u32) -> String {
+    format!(
+        "fps={},scale={}:-1:flags=lanczos,split[a][b];[a]palettegen=stats_mode=diff[p];[b][p]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle",
+        fps, width
+    )
+}
+
+/// Conversion of a clip to an optimized GIF `width` pixels wide. GIFs have
+/// no audio, so it is dropped.
+pub fn gif_options(
+    input: &Path,
+    output: &Path,
+    fps: u32,
+    width: u32,
+    looping: bool,
+) -> ConversionOptions {
+    let loop_count = if looping { "0" } else { "-1" };
+    ConversionOptions {
+        input_path: input.to_string_lossy().into_owned(),
+        output_path: output.to_string_lossy().into_owned(),
+        video_codec: None,
+        audio_codec: None,
+        resolution: None,
+        video_bitrate: None,
+        audio_bitrate: None,
+        sample_rate: None,
+        fps: None,
+        trim_start: None,
+        trim_end: None,
+        additional_input_args: None,
+        additional_output_args: Some(vec![
+            "-filter_complex".to_string(),
+            gif_filter(fps, width),
+            "-loop".to_string(),
+            loop_count.to_string(),
+            "-an".to_string(),
+        ]),
+        preset: None,
+        encoder: EncoderBackend::Software,
+        audio_track_index: None,
+        subtitle_track_index: None,
+        burn_subtitles: false,
+    }
+}
+
+/// Conversion of a GIF (or a GIF-like clip) to a silent H.264 MP4 that
+/// browsers and phones can play. The frame size is rounded down to even
+/// numbers because yuv420p can't hold odd ones.
+pub fn gif_to_mp4_options(input: &Path, output: &Path) -> ConversionOptions {
+    ConversionOptions {
+        input_path: input.to_string_lossy().into_owned(),
+        output_path: output.to_string_lossy().into_owned(),
+        video_codec: Some("libx264".to_string()),
+        audio_codec: None,
+        resolution: None,
+        video_bitrate: None,
+        audio_bitrate: None,
+        sample_rate: None,
+        fps: None,
+        trim_start: None,
+        trim_end: None,
+        additional_input_args: None,
+        additional_output_args: Some(vec![
+            "-vf".to_string(),
+            "scale=trunc(iw/2)*2:trunc(ih/2)*2".to_string(),
+            "-pix_fmt".to_string(),
+            "yuv420p".to_string(),
+            "-movflags".to_string(),
+            "+faststart".to_string(),
+            "-an".to_string(),
+        ]),
+        preset: None,
+        encoder: EncoderBackend::Auto,
+        audio_track_index: None,
+        subtitle_track_index: None,
+        burn_subtitles: false,
+    }
+}
+
+/// Hardware encoders commonly allow only a couple of sessions at once.
+const MAX_HW_ENCODE_SESSIONS: usize = 2;
+
+/// Whether `opts` asks for nothing but the container the input already
+/// has, so running ffmpeg would only produce a copy.
+pub fn is_noop_conversion(opts: &ConversionOptions) -> bool {
+    let extension = |path: &str| {
+        Path::new(path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+    };
+    let untouched = |codec: &Option<String>| codec.as_deref().is_none_or(|c| c == "copy");
+
+    extension(&opts.input_path).is_some()
+        && extension(&opts.input_path) == extension(&opts.output_path)
+        && untouched(&opts.video_codec)
+        && untouched(&opts.audio_codec)
+        && opts.resolution.is_none()
+        && opts.video_bitrate.is_none()
+        && opts.audio_bitrate.is_none()
+        && opts.sample_rate.is_none()
+        && opts.fps.is_none()
+        && opts.trim_start.is_none()
+        && opts.trim_end.is_none()
+        && opts.additional_input_args.is_none()
+        && opts.additional_output_args.is_none()
+        && opts.audio_track_index.is_none()
+        && opts.subtitle_track_index.is_none()
+}
+
+#[derive(Debug, Clone)]
+pub enum BatchEvent {
+    /// `percent` is the job's own progress (-1 when unknown); `overall` is
+    /// the mean across the batch, counting finished jobs as 100.
+    Progress {
+        index: usize,
+        percent: f64,
+        overall: f64,
+    },
+    Finished {
+        index: usize,
+        result: ConversionResult,
+    },
+}
+
+fn overall_percent(percents: &[f64]) -> f64 {
+    if percents.is_empty() {
+        return 100.0;
+    }
+    percents.iter().map(|p| p.max(0.0)).sum::<f64>() / percents.len() as f64
+}
+
+/// Converts `jobs` with at most `max_concurrent` ffmpeg processes, or
+/// fewer when GPU encoders are in play. Jobs that would not change the
+/// file are reported as finished without running ffmpeg. Returns results
+/// in job order.
+pub async fn convert_batch(
+    jobs: Vec<ConversionOptions>,
+    max_concurrent: usize,
+    cancel_token: CancellationToken,
+    events: mpsc::Sender<BatchEvent>,
+) -> Vec<ConversionResult> {
+    use futures::StreamExt;
+
+    let mut limit = max_concurrent.max(1);
+    let hw = hwaccel::detect_hwaccel().await;
+    let uses_hardware = jobs.iter().any(|job| {
+        job.encoder != EncoderBackend::Software
+            && job
+                .video_codec
+                .as_deref()
+                .and_then(|codec| hwaccel::hardware_encoder(codec, job.encoder, &hw.encoders))
+                .is_some()
+    });
+    if uses_hardware {
+        limit = limit.min(MAX_HW_ENCODE_SESSIONS);
+    }
+
+    let percents = std::sync::Arc::new(std::sync::Mutex::new(vec![0.0; jobs.len()]));
+    let mut results: Vec<(usize, ConversionResult)> =
+        futures::stream::iter(jobs.into_iter().enumerate())
+            .map(|(index, job)| {
+                let cancel = cancel_token.clone();
+                let events = events.clone();
+                let percents = percents.clone();
+                async move {
+                    let result = run_batch_job(&job, index, &cancel, &events, &percents).await;
+                    if let Ok(mut p) = percents.lock() {
+                        p[index] = 100.0;
+                    }
+                    let _ = events
+                        .send(BatchEvent::Finished {
+                            index,
+                            result: result.clone(),
+                        })
+                        .await;
+                    (index, result)
+                }
+            })
+            .buffer_unordered(limit)
+            .collect()
+            .await;
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+async fn run_batch_job(
+    job: &ConversionOptions,
+    index: usize,
+    cancel_token: &CancellationToken,
+    events: &mpsc::Sender<BatchEvent>,
+    percents: &std::sync::Mutex<Vec<f64>>,
+) -> ConversionResult {
+    let failed = |error: String| ConversionResult {
+        success: false,
+        output_path: job.output_path.clone(),
+        file_size_bytes: 0,
+        duration_seconds: 0.0,
+        error: Some(error),
+    };
+
+    if cancel_token.is_cancelled() {
+        return failed("Conversion cancelled".to_string());
+    }
+    if is_noop_conversion(job) {
+        return ConversionResult {
+            success: true,
+            output_path: job.input_path.clone(),
+            file_size_bytes: std::fs::metadata(&job.input_path)
+                .map(|m| m.len())
+                .unwrap_or(0),
+            duration_seconds: 0.0,
+            error: None,
+        };
+    }
+
+    let (tx, mut rx) = mpsc::channel::<ProgressUpdate>(16);
+    let conversion = convert(job, cancel_token.child_token(), tx);
+    let forward = async {
+        while let Some(update) = rx.recv().await {
+            let overall = match percents.lock() {
+                Ok(mut p) => {
+                    p[index] = update.percent;
+                    overall_percent(&p)
+                }
+                Err(_) => continue,
+            };
+            let _ = events
+                .send(BatchEvent::Progress {
+                    index,
+                    percent: update.percent,
+                    overall,
+                })
+                .await;
+        }
+    };
+    let (result, _) = tokio::join!(conversion, forward);
+    result.unwrap_or_else(|e| failed(e.to_string()))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct MetadataEmbed {
     pub title: Option<String>,
@@ -446,7 +1526,7 @@ pub async fn embed_metadata(
 
     let temp_dir = file.parent().unwrap_or(Path::new("."));
     let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
-    let temp_output = temp_dir.join(format!(".omniget_meta_{}.{}", uuid::Uuid::new_v4(), ext));
+    let temp_output = crate::core::temp_dir::staging_for(file);
 
     let is_audio_only = matches!(
         ext.to_lowercase().as_str(),
@@ -539,7 +1619,7 @@ pub async fn embed_metadata(
 
     let mut rename_ok = false;
     for attempt in 0..3 {
-        match std::fs::rename(&temp_output, file) {
+        match crate::core::temp_dir::move_file(&temp_output, file).await {
             Ok(()) => {
                 rename_ok = true;
                 break;
@@ -627,13 +1707,371 @@ async fn download_thumbnail(
     Ok(thumb_path)
 }
 
-fn parse_out_time_us(line: &str) -> Option<u64> {
-    let line = line.trim();
-    if let Some(val) = line.strip_prefix("out_time_us=") {
-        return val.trim().parse::<u64>().ok();
+/// Accumulates the key=value blocks ffmpeg writes with `-progress`.
+#[derive(Debug, Default)]
+struct FfmpegProgress {
+    out_time_us: u64,
+    total_size: Option<u64>,
+}
+
+impl FfmpegProgress {
+    /// Takes one line; returns true when a block is complete and a progress
+    /// update should go out.
+    fn feed(&mut self, line: &str) -> bool {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            return false;
+        };
+        let value = value.trim();
+        match key {
+            // Despite the name, ffmpeg reports `out_time_ms` in microseconds.
+            "out_time_us" | "out_time_ms" => {
+                if let Ok(us) = value.parse() {
+                    self.out_time_us = us;
+                }
+            }
+            "total_size" => self.total_size = value.parse().ok(),
+            "progress" => return true,
+            _ => {}
+        }
+        false
+    }
+
+    /// Percent of `total_duration_us` reached, or -1 (indeterminate, like
+    /// downloads) when the input duration is unknown.
+    fn update(&self, total_duration_us: u64) -> ProgressUpdate {
+        let percent = if total_duration_us > 0 {
+            (self.out_time_us as f64 / total_duration_us as f64 * 100.0).min(100.0)
+        } else {
+            -1.0
+        };
+        ProgressUpdate {
+            percent,
+            downloaded_bytes: self.total_size,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn health_needs_clean_exit_and_no_errors() {
+        assert!(health_from_output(true, "\n").ok);
+        let health = health_from_output(
+            true,
+            "[h264 @ 0x1] error while decoding MB 12 7\n[h264 @ 0x1] concealing 30 errors\n",
+        );
+        assert!(!health.ok);
+        assert_eq!(health.errors.len(), 2);
+        assert!(!health_from_output(false, "").ok);
+        let noisy = "bad frame\n".repeat(100);
+        assert_eq!(
+            health_from_output(true, &noisy).errors.len(),
+            MAX_HEALTH_ERRORS
+        );
+    }
+
+    #[test]
+    fn probe_json_reads_tracks_and_chapters() {
+        let json = serde_json::json!({
+            "format": { "duration": "600.5", "format_name": "matroska,webm", "size": "1000" },
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264",
+                  "width": 1920, "height": 1080, "r_frame_rate": "30000/1001",
+                  "disposition": { "default": 1 } },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac",
+                  "channels": 6, "channel_layout": "5.1(side)",
+                  "tags": { "LANGUAGE": "eng", "title": "Surround" },
+                  "disposition": { "default": 1 } },
+                { "index": 2, "codec_type": "audio", "codec_name": "opus",
+                  "tags": { "language": "und" }, "disposition": { "default": 0 } }
+            ],
+            "chapters": [
+                { "start_time": "0.000000", "end_time": "120.000000", "tags": { "title": "Intro" } },
+                { "start_time": "120.000000", "end_time": "600.500000" }
+            ]
+        });
+        let probe = parse_probe_json(&json).unwrap();
+        assert_eq!(probe.streams.len(), 3);
+        let surround = &probe.streams[1];
+        assert_eq!(surround.language.as_deref(), Some("eng"));
+        assert_eq!(surround.channel_layout.as_deref(), Some("5.1(side)"));
+        assert_eq!(surround.title.as_deref(), Some("Surround"));
+        assert!(surround.is_default);
+        assert_eq!(probe.streams[2].language, None);
+        assert!(!probe.streams[2].is_default);
+        assert_eq!(probe.chapters.len(), 2);
+        assert_eq!(probe.chapters[0].title.as_deref(), Some("Intro"));
+        assert_eq!(probe.chapters[1].end_seconds, 600.5);
+        assert_eq!(probe.chapters[1].title, None);
+    }
+
+    fn job(input: &str, output: &str) -> ConversionOptions {
+        ConversionOptions {
+            input_path: input.to_string(),
+            output_path: output.to_string(),
+            video_codec: None,
+            audio_codec: None,
+            resolution: None,
+            video_bitrate: None,
+            audio_bitrate: None,
+            sample_rate: None,
+            fps: None,
+            trim_start: None,
+            trim_end: None,
+            additional_input_args: None,
+            additional_output_args: None,
+            preset: None,
+            encoder: EncoderBackend::Auto,
+            audio_track_index: None,
+            subtitle_track_index: None,
+            burn_subtitles: false,
+        }
+    }
+
+    #[test]
+    fn same_format_without_changes_is_skipped() {
+        assert!(is_noop_conversion(&job(
+            "a/lesson.MP4",
+            "b/lesson_converted.mp4"
+        )));
+        assert!(!is_noop_conversion(&job("lesson.mkv", "lesson.mp4")));
+
+        let mut reencode = job("lesson.mp4", "lesson_converted.mp4");
+        reencode.video_codec = Some("libx265".to_string());
+        assert!(!is_noop_conversion(&reencode));
+
+        let mut remux = job("lesson.mp4", "lesson_converted.mp4");
+        remux.video_codec = Some("copy".to_string());
+        assert!(is_noop_conversion(&remux));
+
+        remux.audio_track_index = Some(1);
+        assert!(!is_noop_conversion(&remux));
+    }
+
+    fn streams(kinds: &[&str]) -> Vec<StreamInfo> {
+        kinds
+            .iter()
+            .enumerate()
+            .map(|(i, kind)| StreamInfo {
+                index: i as u32,
+                codec_type: kind.to_string(),
+                codec_name: String::new(),
+                codec_long_name: String::new(),
+                width: None,
+                height: None,
+                fps: None,
+                bit_rate: None,
+                sample_rate: None,
+                channels: None,
+                duration_seconds: None,
+                channel_layout: None,
+                language: None,
+                title: None,
+                is_default: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn track_selection_maps_chosen_streams() {
+        let input = streams(&["video", "audio", "audio", "subtitle"]);
+
+        let mut opts = job("movie.mkv", "movie.mp4");
+        assert!(track_selection_args(&opts, Some(&input))
+            .unwrap()
+            .is_empty());
+
+        opts.audio_track_index = Some(1);
+        opts.subtitle_track_index = Some(0);
+        assert_eq!(
+            track_selection_args(&opts, Some(&input)).unwrap(),
+            ["-map", "0:v:0?", "-map", "0:a:1", "-map", "0:s:0", "-c:s", "mov_text"]
+        );
+
+        opts.audio_track_index = Some(2);
+        assert!(track_selection_args(&opts, Some(&input)).is_err());
+        assert!(track_selection_args(&opts, None).is_ok());
+    }
+
+    #[test]
+    fn missing_subtitle_track_is_ignored() {
+        let input = streams(&["video", "audio"]);
+        let mut opts = job("movie.mkv", "movie.mkv");
+        opts.subtitle_track_index = Some(0);
+        opts.burn_subtitles = true;
+        assert!(track_selection_args(&opts, Some(&input))
+            .unwrap()
+            .is_empty());
+
+        let mut audio = job("movie.mkv", "movie.mp3");
+        audio.subtitle_track_index = Some(0);
+        assert!(
+            track_selection_args(&audio, Some(&streams(&["audio", "subtitle"])))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn burned_subtitles_use_filter_and_need_reencode() {
+        let input = streams(&["video", "audio", "subtitle", "subtitle"]);
+        let mut opts = job("/films/it's [1]:x.mkv", "out.mp4");
+        opts.subtitle_track_index = Some(1);
+        opts.burn_subtitles = true;
+        assert_eq!(
+            track_selection_args(&opts, Some(&input)).unwrap(),
+            ["-vf", r"subtitles=/films/it\\\'s \[1\]\\:x.mkv:si=1", "-sn"]
+        );
+
+        opts.trim_start = Some("01:30".to_string());
+        assert_eq!(
+            track_selection_args(&opts, Some(&input)).unwrap()[1],
+            r"setpts=PTS+90.000/TB,subtitles=/films/it\\\'s \[1\]\\:x.mkv:si=1,setpts=PTS-STARTPTS"
+        );
+
+        opts.video_codec = Some("copy".to_string());
+        assert!(track_selection_args(&opts, Some(&input)).is_err());
+    }
+
+    #[test]
+    fn gif_options_build_palette_graph() {
+        let opts = gif_options(Path::new("clip.mp4"), Path::new("clip.gif"), 15, 480, false);
+        let args = opts.additional_output_args.unwrap();
+        assert_eq!(args[0], "-filter_complex");
+        assert!(args[1].starts_with("fps=15,scale=480:-1:flags=lanczos,split[a][b];"));
+        assert!(args[1].contains("[a]palettegen"));
+        assert!(args[1].contains("[b][p]paletteuse"));
+        assert_eq!(&args[2..], ["-loop", "-1", "-an"]);
+
+        let mp4 = gif_to_mp4_options(Path::new("clip.gif"), Path::new("clip.mp4"));
+        assert_eq!(mp4.video_codec.as_deref(), Some("libx264"));
+        assert!(mp4
+            .additional_output_args
+            .unwrap()
+            .windows(2)
+            .any(|w| w == ["-pix_fmt", "yuv420p"]));
+    }
+
+    #[test]
+    fn overall_percent_ignores_unknown_progress() {
+        assert_eq!(overall_percent(&[100.0, -1.0, 50.0, 50.0]), 50.0);
+        assert_eq!(overall_percent(&[]), 100.0);
     }
-    if let Some(val) = line.strip_prefix("out_time_ms=") {
-        return val.trim().parse::<u64>().ok().map(|ms| ms * 1000);
+
+    #[test]
+    fn progress_blocks_report_percent_and_size() {
+        let mut state = FfmpegProgress::default();
+        for line in ["frame=120", "total_size=524288", "out_time_ms=5000000"] {
+            assert!(!state.feed(line));
+        }
+        assert!(state.feed("progress=continue"));
+
+        let update = state.update(20_000_000);
+        assert_eq!(update.percent, 25.0);
+        assert_eq!(update.downloaded_bytes, Some(524_288));
+
+        assert_eq!(state.update(0).percent, -1.0);
+    }
+
+    #[test]
+    fn audio_format_setting_picks_the_codec() {
+        assert_eq!(AudioCodec::from_format(Some("MP3")), AudioCodec::Mp3);
+        assert_eq!(AudioCodec::from_format(Some("opus")), AudioCodec::Opus);
+        assert_eq!(AudioCodec::from_format(Some("wav")), AudioCodec::M4a);
+        assert_eq!(AudioCodec::from_format(None), AudioCodec::M4a);
+    }
+
+    #[test]
+    fn mp3_vbr_maps_bitrate_to_lame_quality() {
+        assert_eq!(lame_vbr_quality(320), 0);
+        assert_eq!(lame_vbr_quality(245), 0);
+        assert_eq!(lame_vbr_quality(192), 2);
+        assert_eq!(lame_vbr_quality(64), 9);
+
+        let opts = audio_conversion_options(
+            Path::new("in.mp4"),
+            Path::new("out.mp3"),
+            AudioCodec::Mp3,
+            245,
+            BitrateMode::Vbr,
+        );
+        assert_eq!(opts.audio_codec.as_deref(), Some("libmp3lame"));
+        assert_eq!(opts.audio_bitrate, None);
+        assert_eq!(
+            opts.additional_output_args,
+            Some(vec!["-vn".to_string(), "-q:a".to_string(), "0".to_string()])
+        );
+    }
+
+    #[test]
+    fn cbr_and_lossless_audio_options() {
+        let opus = audio_conversion_options(
+            Path::new("in.mkv"),
+            Path::new("out.opus"),
+            AudioCodec::Opus,
+            192,
+            BitrateMode::Cbr,
+        );
+        assert_eq!(opus.audio_bitrate.as_deref(), Some("192k"));
+        assert_eq!(
+            opus.additional_output_args,
+            Some(vec![
+                "-vn".to_string(),
+                "-vbr".to_string(),
+                "off".to_string()
+            ])
+        );
+
+        let flac = audio_conversion_options(
+            Path::new("in.mkv"),
+            Path::new("out.flac"),
+            AudioCodec::Flac,
+            320,
+            BitrateMode::Cbr,
+        );
+        assert_eq!(flac.audio_bitrate, None);
+        assert_eq!(flac.additional_output_args, Some(vec!["-vn".to_string()]));
+    }
+
+    #[test]
+    fn contact_sheet_samples_slice_midpoints() {
+        assert_eq!(
+            contact_sheet_filter(120.0, 4, 3, 1280),
+            "fps=12/120.000,scale=320:-2,tile=4x3"
+        );
+        assert_eq!(contact_sheet_seek(120.0, 4, 3), 5.0);
+        // Tile width stays even so yuv420 scaling works.
+        assert!(contact_sheet_filter(60.0, 3, 3, 1000).contains("scale=332:-2"));
+    }
+
+    #[test]
+    fn parses_clock_timestamps() {
+        assert_eq!(parse_timestamp("90"), Some(90.0));
+        assert_eq!(parse_timestamp("01:30.5"), Some(90.5));
+        assert_eq!(parse_timestamp("1:00:00"), Some(3600.0));
+        assert_eq!(parse_timestamp("1:2:3:4"), None);
+        assert_eq!(parse_timestamp("-5"), None);
+        assert_eq!(parse_timestamp("abc"), None);
+    }
+
+    #[test]
+    fn trim_range_is_clamped_to_duration() {
+        let range = clamp_trim_range(10.0, Some(500.0), 120.0).unwrap();
+        assert_eq!((range.start, range.end, range.clamped), (10.0, 120.0, true));
+        let range = clamp_trim_range(10.0, None, 120.0).unwrap();
+        assert!(!range.clamped);
+        assert!(clamp_trim_range(130.0, Some(140.0), 120.0).is_err());
+    }
+
+    #[test]
+    fn progress_total_covers_only_the_trimmed_segment() {
+        let range = clamp_trim_range(30.0, Some(90.0), 120.0).unwrap();
+        let opts = trim_options(Path::new("in.mp4"), Path::new("out.mp4"), range, false);
+        assert_eq!(opts.video_codec.as_deref(), Some("copy"));
+        assert_eq!(output_duration_us(&opts, 120_000_000), 60_000_000);
+        assert_eq!(output_duration_us(&opts, 0), 0);
     }
-    None
 }
@@ -195,10 +195,6 @@ pub fn preset(action: &str, start: Option<&str>, end: Option<&str>) -> Result<Pr
             ],
             out_ext: "mp4",
         }),
-        "to_gif" => Ok(Preset {
-            args: vec![s("-vf"), s("fps=12,scale=480:-1:flags=lanczos"), s("-an")],
-            out_ext: "gif",
-        }),
         "trim" => {
             let st = start.unwrap_or("").trim();
             let en = end.unwrap_or("").trim();
@@ -404,7 +400,7 @@ mod tests {
 
     #[test]
     fn presets_pass_validation() {
-        for action in ["extract_audio", "mute", "to_mp4", "to_gif"] {
+        for action in ["extract_audio", "mute", "to_mp4"] {
             let p = preset(action, None, None).unwrap();
             assert!(
                 validate_transform_args(&p.args).is_ok(),
@@ -1,8 +1,18 @@
 use regex::Regex;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 use unicode_normalization::UnicodeNormalization;
 
+use crate::models::media::{MediaInfo, OverwritePolicy};
+
 static WS_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+static FIELD_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"%\((\w+)\)s").unwrap());
+static NAME_FIELD_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"%\((\w+)\)(?:\.(\d+))?[sd]").unwrap());
+static EMPTY_BRACKETS_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\s*\]|\(\s*\)").unwrap());
+static PLAYLIST_FIELD_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"%\((playlist_index|playlist_count)\)0?(\d*)[ds]").unwrap());
 
 pub fn sanitize_path_component(name: &str) -> String {
     let name: String = name.nfc().collect();
@@ -32,10 +42,202 @@ pub fn sanitize_path_component(name: &str) -> String {
     result.trim().to_string()
 }
 
+pub fn is_nonempty_file(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.len() > 0)
+        .unwrap_or(false)
+}
+
+/// Appends ` (1)`, ` (2)`, ... to the file stem until the path is free.
+pub fn next_free_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path.extension().map(|e| e.to_string_lossy().to_string());
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
+    (1u32..)
+        .map(|n| {
+            let name = match &ext {
+                Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", stem, n),
+            };
+            parent.join(name)
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Resolves where a download should be written under `policy`.
+/// `None` means a non-empty file is already there and the download should be skipped.
+pub fn apply_overwrite_policy(path: &Path, policy: OverwritePolicy) -> Option<PathBuf> {
+    if !is_nonempty_file(path) {
+        return Some(path.to_path_buf());
+    }
+    match policy {
+        OverwritePolicy::Skip => None,
+        OverwritePolicy::Overwrite => Some(path.to_path_buf()),
+        OverwritePolicy::Rename => Some(next_free_path(path)),
+    }
+}
+
+/// Inserts `_<label>` before the extension of a file name or a yt-dlp
+/// output template, so each file of a multi-quality download is distinct.
+pub fn with_quality_suffix(name: &str, label: &str) -> String {
+    let label = sanitize_path_component(label);
+    if let Some(stem) = name.strip_suffix(".%(ext)s") {
+        return format!("{}_{}.%(ext)s", stem, label);
+    }
+    let file_start = name.rfind(['/', '\\']).map_or(0, |i| i + 1);
+    match name[file_start..].rfind('.').filter(|i| *i > 0) {
+        Some(dot) => {
+            let dot = file_start + dot;
+            format!("{}_{}{}", &name[..dot], label, &name[dot..])
+        }
+        None => format!("{}_{}", name, label),
+    }
+}
+
+fn subfolder_field(info: &MediaInfo, field: &str) -> Option<String> {
+    let value = match field {
+        "platform" => info.platform.clone(),
+        "author" | "uploader" => info.author.clone(),
+        "title" => info.title.clone(),
+        _ => return None,
+    };
+    Some(value).filter(|v| !v.trim().is_empty())
+}
+
+/// Expands a subfolder template like `%(platform)s/%(author)s/` into a
+/// relative path. The template is split on separators before substitution
+/// and each component is sanitized on its own, so a field value containing
+/// slashes can't add extra levels. Unknown or empty fields become `NA`.
+pub fn render_subfolder_template(template: &str, info: &MediaInfo) -> PathBuf {
+    template
+        .split(['/', '\\'])
+        .map(|part| {
+            FIELD_RE.replace_all(part, |caps: &regex::Captures| {
+                subfolder_field(info, &caps[1]).unwrap_or_else(|| "NA".to_string())
+            })
+        })
+        .map(|part| sanitize_path_component(&part))
+        .filter(|part| !part.is_empty() && part != "." && part != "..")
+        .collect()
+}
+
+fn name_field(info: &MediaInfo, field: &str, template: &str) -> String {
+    let value = match field {
+        // Native titles are often `twitter_<id>` already; don't repeat it.
+        "id" => info
+            .id
+            .clone()
+            .filter(|id| !(template.contains("%(title)") && info.title.contains(id.as_str()))),
+        "title" => Some(info.title.clone()),
+        "author" | "uploader" => Some(info.author.clone()),
+        "platform" => Some(info.platform.clone()),
+        _ => None,
+    };
+    value.unwrap_or_default()
+}
+
+/// Renders a yt-dlp style output template such as
+/// `%(title).200s [%(id)s].%(ext)s` for native downloaders. Fields the
+/// native side doesn't know come out empty and so do brackets left around
+/// them; an empty result falls back to the title.
+pub fn render_filename(template: &str, info: &MediaInfo, ext: &str) -> String {
+    let stem_template = template.strip_suffix(".%(ext)s").unwrap_or(template);
+    let stem = NAME_FIELD_RE.replace_all(stem_template, |caps: &regex::Captures| {
+        let value = name_field(info, &caps[1], stem_template);
+        match caps.get(2).and_then(|n| n.as_str().parse::<usize>().ok()) {
+            Some(max) => value.chars().take(max).collect(),
+            None => value,
+        }
+    });
+    let stem = EMPTY_BRACKETS_RE.replace_all(&stem, "");
+    let mut stem = sanitize_path_component(&stem);
+    if stem.is_empty() {
+        stem = sanitize_path_component(&info.title);
+    }
+    if stem.is_empty() {
+        stem = info.id.clone().unwrap_or_else(|| "download".to_string());
+    }
+    format!("{}.{}", stem, ext)
+}
+
+/// Whether a yt-dlp output template numbers playlist items.
+pub fn uses_playlist_index(template: &str) -> bool {
+    template.contains("%(playlist_index)")
+}
+
+/// `index` zero-padded to the digits of `count`, or `min_width` if wider,
+/// so numbered files sort in playlist order.
+pub fn playlist_number(index: u32, count: u32, min_width: usize) -> String {
+    let width = count.max(index).to_string().len().max(min_width);
+    format!("{:0width$}", index, width = width)
+}
+
+/// Fills `%(playlist_index)02d`-style and `%(playlist_count)s` fields for
+/// an item that was queued on its own after a collection was expanded,
+/// where yt-dlp itself would only see a single video.
+pub fn expand_playlist_fields(template: &str, index: u32, count: u32) -> String {
+    PLAYLIST_FIELD_RE
+        .replace_all(template, |caps: &regex::Captures| {
+            let width = caps[2].parse().unwrap_or(0);
+            match &caps[1] {
+                "playlist_index" => playlist_number(index, count, width),
+                _ => count.to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Joins the expanded subfolder template onto `base` and creates it.
+/// Carousel and playlist items share one `MediaInfo`, so they land together.
+pub fn prepare_output_dir(
+    base: &Path,
+    template: Option<&str>,
+    info: &MediaInfo,
+) -> std::io::Result<PathBuf> {
+    let dir = match template.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(template) => base.join(render_subfolder_template(template, info)),
+        None => base.to_path_buf(),
+    };
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn playlist_fields_pad_to_the_collection_size() {
+        assert_eq!(
+            expand_playlist_fields("%(playlist_index)02d - %(title)s.%(ext)s", 7, 120),
+            "007 - %(title)s.%(ext)s"
+        );
+        assert_eq!(
+            expand_playlist_fields("%(playlist_index)s of %(playlist_count)s", 3, 9),
+            "3 of 9"
+        );
+        assert_eq!(playlist_number(4, 12, 2), "04");
+        assert!(uses_playlist_index("%(playlist_index)03d %(title)s"));
+        assert!(!uses_playlist_index("%(title)s [%(id)s].%(ext)s"));
+    }
+
+    #[test]
+    fn quality_suffix_goes_before_extension() {
+        assert_eq!(
+            with_quality_suffix("%(title)s [%(id)s].%(ext)s", "1080p"),
+            "%(title)s [%(id)s]_1080p.%(ext)s"
+        );
+        assert_eq!(with_quality_suffix("clip.mp4", "720p"), "clip_720p.mp4");
+        assert_eq!(with_quality_suffix("a.b/clip", "720p"), "a.b/clip_720p");
+    }
+
     #[test]
     fn sanitize_basic_forbidden_chars() {
         assert_eq!(sanitize_path_component("a:b?c"), "a꞉b？c");
@@ -106,4 +308,96 @@ mod tests {
         assert!(result.starts_with("omniget-"));
         assert!(result.ends_with("[id]"));
     }
+
+    #[test]
+    fn overwrite_policy_on_existing_file() {
+        let dir = std::env::temp_dir().join(format!("omniget-fname-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("clip.mp4");
+        std::fs::write(&target, b"data").unwrap();
+
+        assert_eq!(apply_overwrite_policy(&target, OverwritePolicy::Skip), None);
+        assert_eq!(
+            apply_overwrite_policy(&target, OverwritePolicy::Overwrite),
+            Some(target.clone())
+        );
+        assert_eq!(
+            apply_overwrite_policy(&target, OverwritePolicy::Rename),
+            Some(dir.join("clip (1).mp4"))
+        );
+
+        std::fs::write(dir.join("clip (1).mp4"), b"data").unwrap();
+        assert_eq!(next_free_path(&target), dir.join("clip (2).mp4"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn overwrite_policy_ignores_empty_files() {
+        let dir = std::env::temp_dir().join(format!("omniget-fname-empty-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("empty.bin");
+        std::fs::write(&target, b"").unwrap();
+
+        assert_eq!(
+            apply_overwrite_policy(&target, OverwritePolicy::Skip),
+            Some(target.clone())
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn sample_info(author: &str) -> MediaInfo {
+        MediaInfo {
+            title: "Clip".to_string(),
+            author: author.to_string(),
+            platform: "tiktok".to_string(),
+            duration_seconds: None,
+            thumbnail_url: None,
+            available_qualities: Vec::new(),
+            media_type: crate::models::media::MediaType::Video,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn subfolder_template_expands_fields() {
+        assert_eq!(
+            render_subfolder_template("%(platform)s/%(author)s/", &sample_info("someone")),
+            PathBuf::from("tiktok").join("someone")
+        );
+    }
+
+    #[test]
+    fn subfolder_template_sanitizes_each_component() {
+        assert_eq!(
+            render_subfolder_template("%(platform)s/%(author)s", &sample_info("AC/DC")),
+            PathBuf::from("tiktok").join("AC⧸DC")
+        );
+        assert_eq!(
+            render_subfolder_template("../%(author)s/%(unknown)s", &sample_info("")),
+            PathBuf::from("NA").join("NA")
+        );
+    }
+
+    #[test]
+    fn native_filenames_follow_the_style_template() {
+        let mut info = sample_info("someone");
+        info.id = Some("7301".to_string());
+        let with_id = "%(title).200s [%(id)s].%(ext)s";
+        assert_eq!(render_filename(with_id, &info, "mp4"), "Clip [7301].mp4");
+        assert_eq!(render_filename("%(id)s.%(ext)s", &info, "jpg"), "7301.jpg");
+        assert_eq!(
+            render_filename("%(title).2s.%(ext)s", &info, "mp4"),
+            "Cl.mp4"
+        );
+
+        info.id = None;
+        assert_eq!(render_filename(with_id, &info, "mp4"), "Clip.mp4");
+        assert_eq!(render_filename("%(id)s.%(ext)s", &info, "mp4"), "Clip.mp4");
+
+        info.title = "twitter_7301".to_string();
+        info.id = Some("7301".to_string());
+        assert_eq!(render_filename(with_id, &info, "jpg"), "twitter_7301.jpg");
+    }
 }
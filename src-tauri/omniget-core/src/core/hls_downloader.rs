@@ -2,17 +2,96 @@ use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::stream::{self, StreamExt};
 use m3u8_rs::{parse_master_playlist, parse_media_playlist, MasterPlaylist, VariantStream};
 use reqwest::Client;
 use tokio::sync::mpsc::{self, UnboundedSender};
-use tokio::sync::Semaphore;
+use tokio::sync::{Semaphore, SemaphorePermit};
 use tokio_util::sync::CancellationToken;
 
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
 
+/// Minimum time between two concurrency reductions, so one burst of 429s
+/// from in-flight segments only counts once.
+const BACKOFF_COOLDOWN: Duration = Duration::from_secs(3);
+
+/// Segment concurrency for one playlist that halves whenever the server
+/// starts answering 429/503, and stays reduced for the rest of the playlist.
+struct AdaptiveConcurrency {
+    semaphore: Semaphore,
+    limit: AtomicUsize,
+    pending_shrink: AtomicUsize,
+    last_backoff: std::sync::Mutex<Option<Instant>>,
+}
+
+struct AdaptivePermit<'a> {
+    permit: Option<SemaphorePermit<'a>>,
+    owner: &'a AdaptiveConcurrency,
+}
+
+impl Drop for AdaptivePermit<'_> {
+    fn drop(&mut self) {
+        let Some(permit) = self.permit.take() else {
+            return;
+        };
+        let shrink =
+            self.owner
+                .pending_shrink
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+        if shrink.is_ok() {
+            permit.forget();
+        }
+    }
+}
+
+impl AdaptiveConcurrency {
+    fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            semaphore: Semaphore::new(limit),
+            limit: AtomicUsize::new(limit),
+            pending_shrink: AtomicUsize::new(0),
+            last_backoff: std::sync::Mutex::new(None),
+        }
+    }
+
+    async fn acquire(&self) -> AdaptivePermit<'_> {
+        AdaptivePermit {
+            permit: self.semaphore.acquire().await.ok(),
+            owner: self,
+        }
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    /// Halves the limit (never below one). Permits are retired as running
+    /// segments finish rather than interrupting them.
+    fn back_off(&self) {
+        let mut last = self.last_backoff.lock().unwrap_or_else(|e| e.into_inner());
+        if last.is_some_and(|at| at.elapsed() < BACKOFF_COOLDOWN) {
+            return;
+        }
+        let current = self.limit();
+        let reduced = (current / 2).max(1);
+        if reduced == current {
+            return;
+        }
+        *last = Some(Instant::now());
+        self.limit.store(reduced, Ordering::Relaxed);
+        self.pending_shrink
+            .fetch_add(current - reduced, Ordering::Relaxed);
+        tracing::warn!(
+            "[hls] server is throttling, reducing segment concurrency {} -> {}",
+            current,
+            reduced
+        );
+    }
+}
+
 pub struct HlsDownloadResult {
     pub path: PathBuf,
     pub file_size: u64,
@@ -207,11 +286,7 @@ impl HlsDownloader {
             .await?;
 
         let output = PathBuf::from(output_path);
-        let part_path = {
-            let mut p = output.as_os_str().to_owned();
-            p.push(".part");
-            PathBuf::from(p)
-        };
+        let part_path = crate::core::temp_dir::part_path_for(&output);
         if let Some(parent) = output.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -231,7 +306,7 @@ impl HlsDownloader {
             .await
         });
 
-        let semaphore = Arc::new(Semaphore::new(max_concurrent as usize));
+        let concurrency = AdaptiveConcurrency::new(max_concurrent as usize);
         let completed = Arc::new(AtomicUsize::new(0));
         let fail_token = cancel_token.child_token();
         let errors: Arc<tokio::sync::Mutex<HashMap<String, u32>>> =
@@ -248,7 +323,7 @@ impl HlsDownloader {
         let errors_ref = &errors;
         let completed_ref = &completed;
         let fail_ref = &fail_token;
-        let sem_ref = &semaphore;
+        let concurrency_ref = &concurrency;
         let user_agent = self.effective_user_agent().to_string();
         let user_agent_ref = &user_agent;
 
@@ -258,7 +333,7 @@ impl HlsDownloader {
                 let seg_tx = seg_tx.clone();
                 let referer = referer.to_string();
                 async move {
-                    let _permit = sem_ref.acquire().await.unwrap();
+                    let _permit = concurrency_ref.acquire().await;
                     if fail_ref.is_cancelled() {
                         return;
                     }
@@ -269,6 +344,7 @@ impl HlsDownloader {
                         user_agent_ref,
                         max_retries,
                         fail_ref,
+                        concurrency_ref,
                     )
                     .await
                     {
@@ -323,7 +399,7 @@ impl HlsDownloader {
 
         writer_result?;
 
-        std::fs::rename(&part_path, &output)?;
+        crate::core::temp_dir::move_file(&part_path, &output).await?;
 
         let file_size = std::fs::metadata(&output)?.len();
 
@@ -511,6 +587,7 @@ async fn download_segment_with_retry(
     user_agent: &str,
     max_retries: u32,
     cancel: &CancellationToken,
+    concurrency: &AdaptiveConcurrency,
 ) -> anyhow::Result<Vec<u8>> {
     let mut last_err = None;
     for attempt in 0..max_retries {
@@ -529,6 +606,9 @@ async fn download_segment_with_retry(
             let status = resp.status();
             if !status.is_success() {
                 let code = status.as_u16();
+                if code == 429 || code == 503 {
+                    concurrency.back_off();
+                }
                 if (400..500).contains(&code) && code != 429 && code != 408 {
                     return Err(anyhow::anyhow!("HTTP {} (fatal) downloading segment", code));
                 }
@@ -543,7 +623,10 @@ async fn download_segment_with_retry(
         .await;
 
         match result {
-            Ok(Ok(data)) => return Ok(data),
+            Ok(Ok(data)) => {
+                crate::core::direct_downloader::throttle(data.len()).await;
+                return Ok(data);
+            }
             Ok(Err(e)) => {
                 if e.to_string().contains("(fatal)") {
                     return Err(e);
@@ -845,4 +928,24 @@ mod tests {
         let result = compute_iv(&enc, 0, 0);
         assert_eq!(result, [0u8; 16]);
     }
+
+    #[tokio::test]
+    async fn adaptive_concurrency_retires_permits_after_backoff() {
+        let concurrency = AdaptiveConcurrency::new(8);
+        let held: Vec<_> = futures::future::join_all((0..8).map(|_| concurrency.acquire())).await;
+        concurrency.back_off();
+        concurrency.back_off();
+        assert_eq!(concurrency.limit(), 4);
+
+        drop(held);
+        assert_eq!(concurrency.semaphore.available_permits(), 4);
+    }
+
+    #[test]
+    fn adaptive_concurrency_never_drops_below_one() {
+        let concurrency = AdaptiveConcurrency::new(1);
+        concurrency.back_off();
+        assert_eq!(concurrency.limit(), 1);
+        assert_eq!(concurrency.semaphore.available_permits(), 1);
+    }
 }
@@ -256,7 +256,7 @@ impl HttpFetcher {
 
         match result {
             Ok(()) => {
-                tokio::fs::rename(&part_path, &self.output_path)
+                crate::core::temp_dir::move_file(&part_path, &self.output_path)
                     .await
                     .map_err(|e| anyhow!("rename .part to final failed: {}", e))?;
                 if self.config.use_sidecar_resume {
@@ -338,6 +338,7 @@ impl HttpFetcher {
                 Ok(Some(Ok(chunk))) => {
                     file.write_all(&chunk).await?;
                     downloaded += chunk.len() as u64;
+                    crate::core::direct_downloader::throttle(chunk.len()).await;
                     if last_emit.elapsed() >= Duration::from_millis(250) {
                         let elapsed = anchor_time.elapsed().as_secs_f64();
                         if elapsed >= 0.3 {
@@ -392,7 +393,15 @@ impl HttpFetcher {
 
         file.flush().await?;
         drop(file);
-        tokio::fs::rename(part_path, &self.output_path).await?;
+        if let Some(expected) = total.filter(|t| *t > 0 && *t != downloaded) {
+            let _ = tokio::fs::remove_file(part_path).await;
+            return Err(anyhow!(
+                "Size mismatch: expected {} bytes, got {}",
+                expected,
+                downloaded
+            ));
+        }
+        crate::core::temp_dir::move_file(part_path, &self.output_path).await?;
         let _ = progress_tx.send(ProgressUpdate::percent(100.0)).await;
         Ok(HttpFetcherResult {
             bytes_written: downloaded,
@@ -847,6 +856,7 @@ async fn download_segment(
                 let slice = &chunk[..take as usize];
                 file.write_all(slice).await?;
                 written += take;
+                crate::core::direct_downloader::throttle(slice.len()).await;
                 seg.downloaded.fetch_add(take, Ordering::Relaxed);
                 seg.last_progress_unix_nanos
                     .store(now_unix_nanos(), Ordering::Relaxed);
@@ -910,9 +920,7 @@ async fn ensure_part_file(part_path: &Path, total: u64) -> anyhow::Result<()> {
 }
 
 pub fn part_path_for(output: &Path) -> PathBuf {
-    let mut s = output.as_os_str().to_owned();
-    s.push(".part");
-    PathBuf::from(s)
+    crate::core::temp_dir::part_path_for(output)
 }
 
 fn sidecar_path_for(part_path: &Path) -> PathBuf {
@@ -89,3 +89,116 @@ async fn query_codecs(flag: &str) -> Vec<String> {
         })
         .collect()
 }
+
+/// Which encoder family a conversion may use for H.264/HEVC output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncoderBackend {
+    /// First detected GPU encoder, software otherwise.
+    #[default]
+    Auto,
+    Software,
+    Nvenc,
+    Qsv,
+    VideoToolbox,
+}
+
+impl EncoderBackend {
+    fn suffixes(self) -> &'static [&'static str] {
+        match self {
+            EncoderBackend::Auto => &["nvenc", "qsv", "videotoolbox"],
+            EncoderBackend::Software => &[],
+            EncoderBackend::Nvenc => &["nvenc"],
+            EncoderBackend::Qsv => &["qsv"],
+            EncoderBackend::VideoToolbox => &["videotoolbox"],
+        }
+    }
+}
+
+/// `h264` or `hevc` for any encoder name of those codecs (`libx264`,
+/// `hevc_nvenc`, plain `h264`, ...).
+fn codec_family(codec: &str) -> Option<&'static str> {
+    let codec = codec.to_ascii_lowercase();
+    if codec == "libx264" || codec == "h264" || codec.starts_with("h264_") {
+        Some("h264")
+    } else if codec == "libx265" || codec == "hevc" || codec == "h265" || codec.starts_with("hevc_")
+    {
+        Some("hevc")
+    } else {
+        None
+    }
+}
+
+/// CPU encoder for the codec family of `codec`, if it is H.264 or HEVC.
+pub fn software_encoder(codec: &str) -> Option<&'static str> {
+    match codec_family(codec)? {
+        "h264" => Some("libx264"),
+        _ => Some("libx265"),
+    }
+}
+
+/// GPU encoder to try for `codec` given the user's `backend` choice and the
+/// encoders ffmpeg reported. `None` means encode in software.
+pub fn hardware_encoder(
+    codec: &str,
+    backend: EncoderBackend,
+    available: &[String],
+) -> Option<String> {
+    let family = codec_family(codec)?;
+    if backend == EncoderBackend::Auto
+        && codec.starts_with(family)
+        && codec.contains('_')
+        && available.iter().any(|e| e == codec)
+    {
+        return Some(codec.to_string());
+    }
+    backend
+        .suffixes()
+        .iter()
+        .map(|suffix| format!("{}_{}", family, suffix))
+        .find(|name| available.iter().any(|e| e == name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn available() -> Vec<String> {
+        vec!["h264_qsv".to_string(), "hevc_nvenc".to_string()]
+    }
+
+    #[test]
+    fn picks_detected_encoder_for_backend() {
+        assert_eq!(
+            hardware_encoder("libx264", EncoderBackend::Auto, &available()).as_deref(),
+            Some("h264_qsv")
+        );
+        assert_eq!(
+            hardware_encoder("libx265", EncoderBackend::Nvenc, &available()).as_deref(),
+            Some("hevc_nvenc")
+        );
+        assert_eq!(
+            hardware_encoder("libx264", EncoderBackend::Nvenc, &available()),
+            None
+        );
+        assert_eq!(
+            hardware_encoder("libx264", EncoderBackend::Software, &available()),
+            None
+        );
+        assert_eq!(
+            hardware_encoder("libvpx-vp9", EncoderBackend::Auto, &available()),
+            None
+        );
+    }
+
+    #[test]
+    fn explicit_gpu_encoder_keeps_software_fallback() {
+        assert_eq!(
+            hardware_encoder("h264_qsv", EncoderBackend::Auto, &available()).as_deref(),
+            Some("h264_qsv")
+        );
+        assert_eq!(software_encoder("h264_qsv"), Some("libx264"));
+        assert_eq!(software_encoder("hevc_nvenc"), Some("libx265"));
+        assert_eq!(software_encoder("copy"), None);
+    }
+}
@@ -0,0 +1,162 @@
+//! Localized messages for error categories produced by `core::errors`.
+//!
+//! The frontend owns most UI strings; this only covers text the backend
+//! composes itself, such as queue error messages and system notifications.
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Reduces a language tag like `pt-BR` to a locale this module ships.
+pub fn normalize_locale(language: &str) -> &'static str {
+    let primary = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match primary.as_str() {
+        "pt" => "pt",
+        _ => DEFAULT_LOCALE,
+    }
+}
+
+/// Message for an error category in `language`, falling back to English.
+pub fn error_message(language: &str, category: &str) -> Option<&'static str> {
+    match normalize_locale(language) {
+        "pt" => error_message_pt(category).or_else(|| error_message_en(category)),
+        _ => error_message_en(category),
+    }
+}
+
+fn error_message_en(category: &str) -> Option<&'static str> {
+    Some(match category {
+        "auth_required" => "This content requires login. Install the browser extension and visit the site while logged in.",
+        "rate_limited" => "Too many requests. Try again in a few minutes.",
+        "restricted" => "This content is private or age-restricted.",
+        "geo_blocked" => "This content is blocked in your region. Enable a proxy in Settings and retry.",
+        "file_missing" => "Downloaded file could not be located in the output folder.",
+        "not_found" => "Content not found or has been deleted.",
+        "ffmpeg_needed" => "FFmpeg is required for this download. Install it from Settings.",
+        "ytdlp_needed" => "yt-dlp is required. Install it from Settings.",
+        "ytdlp_outdated" => "yt-dlp needs updating. Restart the app to auto-update.",
+        "unsupported" => "This link or media type is not supported.",
+        "live_stream" => "This stream is still live. Turn on live recording in Settings to record it until it ends.",
+        "network" => "Network error. Check your connection or proxy settings.",
+        "insufficient_space" => "Not enough free disk space in the download folder.",
+        "too_large" => "Skipped: larger than the maximum file size set in Settings.",
+        _ => return None,
+    })
+}
+
+/// Text of a system notification the backend shows, in `language`,
+/// falling back to English.
+pub fn notification_text(language: &str, key: &str) -> Option<&'static str> {
+    match normalize_locale(language) {
+        "pt" => notification_text_pt(key).or_else(|| notification_text_en(key)),
+        _ => notification_text_en(key),
+    }
+}
+
+fn notification_text_en(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "hotkey_no_link" => "Nothing to download",
+        "hotkey_no_link_body" => "The clipboard doesn't hold a link.",
+        "hotkey_queued" => "Download queued",
+        "hotkey_already_queued" => "Already downloading",
+        "hotkey_failed" => "Download failed",
+        _ => return None,
+    })
+}
+
+fn notification_text_pt(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "hotkey_no_link" => "Nada para baixar",
+        "hotkey_no_link_body" => "A área de transferência não contém um link.",
+        "hotkey_queued" => "Download na fila",
+        "hotkey_already_queued" => "Já está sendo baixado",
+        "hotkey_failed" => "Falha no download",
+        _ => return None,
+    })
+}
+
+fn error_message_pt(category: &str) -> Option<&'static str> {
+    Some(match category {
+        "auth_required" => {
+            "Este conteúdo exige login. Instale a extensão do navegador e visite o site logado."
+        }
+        "rate_limited" => "Muitas requisições. Tente novamente em alguns minutos.",
+        "restricted" => "Este conteúdo é privado ou tem restrição de idade.",
+        "geo_blocked" => {
+            "Este conteúdo está bloqueado na sua região. Ative um proxy nas Configurações e tente novamente."
+        }
+        "file_missing" => "O arquivo baixado não foi encontrado na pasta de destino.",
+        "not_found" => "Conteúdo não encontrado ou removido.",
+        "ffmpeg_needed" => "O FFmpeg é necessário para este download. Instale-o nas Configurações.",
+        "ytdlp_needed" => "O yt-dlp é necessário. Instale-o nas Configurações.",
+        "ytdlp_outdated" => {
+            "O yt-dlp precisa ser atualizado. Reinicie o app para atualizar automaticamente."
+        }
+        "unsupported" => "Este link ou tipo de mídia não é suportado.",
+        "live_stream" => {
+            "Esta transmissão ainda está ao vivo. Ative a gravação de lives nas Configurações para gravá-la até o fim."
+        }
+        "network" => "Erro de rede. Verifique sua conexão ou as configurações de proxy.",
+        "insufficient_space" => "Espaço em disco insuficiente na pasta de download.",
+        "too_large" => {
+            "Ignorado: maior que o tamanho máximo de arquivo definido nas Configurações."
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_regional_tags() {
+        assert_eq!(normalize_locale("pt-BR"), "pt");
+        assert_eq!(normalize_locale("PT_pt"), "pt");
+        assert_eq!(normalize_locale("ja"), "en");
+        assert_eq!(normalize_locale(""), "en");
+    }
+
+    #[test]
+    fn every_english_category_has_a_portuguese_message() {
+        for category in [
+            "auth_required",
+            "rate_limited",
+            "restricted",
+            "geo_blocked",
+            "file_missing",
+            "not_found",
+            "ffmpeg_needed",
+            "ytdlp_needed",
+            "ytdlp_outdated",
+            "unsupported",
+            "network",
+            "insufficient_space",
+            "too_large",
+        ] {
+            assert!(error_message_en(category).is_some(), "{}", category);
+            assert!(error_message_pt(category).is_some(), "{}", category);
+        }
+        assert_eq!(error_message("pt", "unknown"), None);
+    }
+
+    #[test]
+    fn every_english_notification_has_a_portuguese_text() {
+        for key in [
+            "hotkey_no_link",
+            "hotkey_no_link_body",
+            "hotkey_queued",
+            "hotkey_already_queued",
+            "hotkey_failed",
+        ] {
+            assert!(notification_text_en(key).is_some(), "{}", key);
+            assert!(notification_text_pt(key).is_some(), "{}", key);
+        }
+        assert_eq!(
+            notification_text("ja", "hotkey_queued"),
+            Some("Download queued")
+        );
+    }
+}
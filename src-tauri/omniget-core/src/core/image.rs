@@ -0,0 +1,249 @@
+//! Removes EXIF/XMP/IPTC and text metadata from downloaded images.
+//!
+//! Works on the container level so pixels are never re-encoded: JPEG
+//! application segments, PNG ancillary text chunks and WebP EXIF/XMP chunks
+//! are dropped, everything else is copied through unchanged.
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::anyhow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// PNG chunks that carry EXIF, free text or timestamps.
+const PNG_METADATA_CHUNKS: &[&[u8; 4]] = &[b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+fn sniff(header: &[u8]) -> Option<ImageFormat> {
+    if header.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageFormat::Jpeg)
+    } else if header.starts_with(PNG_SIGNATURE) {
+        Some(ImageFormat::Png)
+    } else if header.len() >= 12 && &header[..4] == b"RIFF" && &header[8..12] == b"WEBP" {
+        Some(ImageFormat::Webp)
+    } else {
+        None
+    }
+}
+
+/// Keeps APP0 (JFIF), APP2 (ICC profile) and APP14 (Adobe colour transform)
+/// since they affect how the image is decoded; drops the other APPn and COM
+/// segments, which is where EXIF, GPS, XMP and IPTC live.
+fn strip_jpeg(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return Err(anyhow!("Malformed JPEG segment at {}", pos));
+        }
+        let marker = data[pos + 1];
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        // Start of scan: the rest is entropy-coded image data.
+        if marker == 0xDA {
+            out.extend_from_slice(&data[pos..]);
+            return Ok(out);
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let end = pos + 2 + len;
+        if len < 2 || end > data.len() {
+            return Err(anyhow!("Truncated JPEG segment at {}", pos));
+        }
+        let is_metadata = matches!(marker, 0xE1 | 0xE3..=0xED | 0xEF | 0xFE);
+        if !is_metadata {
+            out.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+    }
+    Err(anyhow!("JPEG has no image data"))
+}
+
+fn strip_png(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(PNG_SIGNATURE);
+    let mut pos = PNG_SIGNATURE.len();
+    while pos + 12 <= data.len() {
+        let len = u32::from_be_bytes(data[pos..pos + 4].try_into()?) as usize;
+        let end = pos + 12 + len;
+        if end > data.len() {
+            return Err(anyhow!("Truncated PNG chunk at {}", pos));
+        }
+        let kind = &data[pos + 4..pos + 8];
+        if !PNG_METADATA_CHUNKS.iter().any(|c| c.as_slice() == kind) {
+            out.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+        if kind == b"IEND" {
+            return Ok(out);
+        }
+    }
+    Err(anyhow!("PNG has no IEND chunk"))
+}
+
+fn strip_webp(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    const VP8X_XMP: u8 = 0x04;
+    const VP8X_EXIF: u8 = 0x08;
+
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..12]);
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let len = u32::from_le_bytes(data[pos + 4..pos + 8].try_into()?) as usize;
+        if pos + 8 + len > data.len() {
+            return Err(anyhow!("Truncated WebP chunk at {}", pos));
+        }
+        // Chunks are padded to an even size.
+        let end = (pos + 8 + len + (len & 1)).min(data.len());
+        let kind = &data[pos..pos + 4];
+        match kind {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" if len >= 1 => {
+                let flags_at = out.len() + 8;
+                out.extend_from_slice(&data[pos..end]);
+                out[flags_at] &= !(VP8X_XMP | VP8X_EXIF);
+            }
+            _ => out.extend_from_slice(&data[pos..end]),
+        }
+        pos = end;
+    }
+    let riff_size = u32::try_from(out.len() - 8)?;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(out)
+}
+
+/// Strips metadata from an in-memory image. `Ok(None)` when the bytes are
+/// not a supported image format.
+pub fn strip_metadata_bytes(data: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+    let stripped = match sniff(data) {
+        Some(ImageFormat::Jpeg) => strip_jpeg(data)?,
+        Some(ImageFormat::Png) => strip_png(data)?,
+        Some(ImageFormat::Webp) => strip_webp(data)?,
+        None => return Ok(None),
+    };
+    Ok(Some(stripped))
+}
+
+/// Rewrites `path` without metadata if it is a JPEG, PNG or WebP image.
+/// Returns whether the file was rewritten.
+pub fn strip_metadata(path: &Path) -> anyhow::Result<bool> {
+    let mut header = [0u8; 12];
+    let read = std::fs::File::open(path)?.read(&mut header)?;
+    if sniff(&header[..read]).is_none() {
+        return Ok(false);
+    }
+
+    let data = std::fs::read(path)?;
+    let Some(stripped) = strip_metadata_bytes(&data)? else {
+        return Ok(false);
+    };
+    if stripped.len() == data.len() {
+        return Ok(false);
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".strip");
+    let tmp = std::path::PathBuf::from(tmp);
+    std::fs::write(&tmp, &stripped)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(true)
+}
+
+/// Post-download hook for photo branches: strips metadata when `enabled`
+/// and returns the file size afterwards. Failures are logged and leave the
+/// file as downloaded.
+pub fn strip_downloaded_image(path: &Path, size: u64, enabled: bool) -> u64 {
+    if !enabled {
+        return size;
+    }
+    match strip_metadata(path) {
+        Ok(true) => std::fs::metadata(path).map(|m| m.len()).unwrap_or(size),
+        Ok(false) => size,
+        Err(e) => {
+            tracing::warn!(
+                "[image] could not strip metadata from {}: {}",
+                path.display(),
+                e
+            );
+            size
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jpeg_with_exif() -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8];
+        data.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, b'J', b'F']);
+        data.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x06, b'E', b'x', b'i', b'f']);
+        data.extend_from_slice(&[0xFF, 0xFE, 0x00, 0x03, b'c']);
+        data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+        data
+    }
+
+    fn png_chunk(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut chunk = (body.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(body);
+        chunk.extend_from_slice(&[0, 0, 0, 0]);
+        chunk
+    }
+
+    #[test]
+    fn jpeg_drops_exif_and_comments() {
+        let stripped = strip_metadata_bytes(&jpeg_with_exif()).unwrap().unwrap();
+        let mut expected = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, b'J', b'F'];
+        expected.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+        assert_eq!(stripped, expected);
+    }
+
+    #[test]
+    fn png_drops_text_chunks() {
+        let mut data = PNG_SIGNATURE.to_vec();
+        data.extend(png_chunk(b"IHDR", &[1; 13]));
+        data.extend(png_chunk(b"tEXt", b"GPS"));
+        data.extend(png_chunk(b"IDAT", &[2; 4]));
+        data.extend(png_chunk(b"IEND", &[]));
+
+        let stripped = strip_metadata_bytes(&data).unwrap().unwrap();
+        let mut expected = PNG_SIGNATURE.to_vec();
+        expected.extend(png_chunk(b"IHDR", &[1; 13]));
+        expected.extend(png_chunk(b"IDAT", &[2; 4]));
+        expected.extend(png_chunk(b"IEND", &[]));
+        assert_eq!(stripped, expected);
+    }
+
+    #[test]
+    fn webp_drops_exif_and_clears_flags() {
+        let mut data = b"RIFF\0\0\0\0WEBP".to_vec();
+        data.extend_from_slice(b"VP8X\x0a\0\0\0");
+        data.extend_from_slice(&[0x0C, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        data.extend_from_slice(b"EXIF\x03\0\0\0abc\0");
+        data.extend_from_slice(b"VP8 \x02\0\0\0xy");
+        let size = (data.len() - 8) as u32;
+        data[4..8].copy_from_slice(&size.to_le_bytes());
+
+        let stripped = strip_metadata_bytes(&data).unwrap().unwrap();
+        assert_eq!(stripped[20], 0);
+        assert!(!stripped.windows(4).any(|w| w == b"EXIF"));
+        let riff_size = u32::from_le_bytes(stripped[4..8].try_into().unwrap());
+        assert_eq!(riff_size as usize, stripped.len() - 8);
+    }
+
+    #[test]
+    fn non_images_are_left_alone() {
+        assert!(strip_metadata_bytes(b"\0\0\0\x18ftypmp42")
+            .unwrap()
+            .is_none());
+    }
+}
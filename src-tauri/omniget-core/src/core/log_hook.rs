@@ -1,8 +1,12 @@
 use std::sync::{Arc, OnceLock};
 
+use crate::models::media::DownloadPhase;
+
 pub type LogSink = Arc<dyn Fn(u64, &str) + Send + Sync + 'static>;
+pub type PhaseSink = Arc<dyn Fn(u64, DownloadPhase) + Send + Sync + 'static>;
 
 static SINK: OnceLock<LogSink> = OnceLock::new();
+static PHASE_SINK: OnceLock<PhaseSink> = OnceLock::new();
 
 pub fn set_log_sink(sink: LogSink) {
     let _ = SINK.set(sink);
@@ -14,6 +18,24 @@ pub fn emit_log(id: u64, line: &str) {
     }
 }
 
+pub fn set_phase_sink(sink: PhaseSink) {
+    let _ = PHASE_SINK.set(sink);
+}
+
+pub fn emit_phase(id: u64, phase: DownloadPhase) {
+    if let Some(s) = PHASE_SINK.get() {
+        s(id, phase);
+    }
+}
+
+/// Reports the step the current download is in. A no-op outside a
+/// download task, e.g. for a bare info probe.
+pub fn report_phase(phase: DownloadPhase) {
+    if let Some(id) = current_download_id() {
+        emit_phase(id, phase);
+    }
+}
+
 tokio::task_local! {
     pub static CURRENT_DOWNLOAD_ID: u64;
     pub static CURRENT_COOKIE_SLUG: Option<String>;
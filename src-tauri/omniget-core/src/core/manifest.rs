@@ -0,0 +1,110 @@
+//! `<file>.omniget.done` sidecars written next to finished downloads.
+//!
+//! A manifest only exists once the download (and any post-processing) has
+//! fully completed, so its presence plus a matching file size is what the
+//! queue trusts when deciding whether a URL was already downloaded.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+const MANIFEST_SUFFIX: &str = ".omniget.done";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletionManifest {
+    pub source_url: String,
+    pub platform: String,
+    pub file_size_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Unix timestamp in seconds.
+    pub completed_at: i64,
+}
+
+pub fn manifest_path(file: &Path) -> PathBuf {
+    let mut name = file.file_name().unwrap_or_default().to_os_string();
+    name.push(MANIFEST_SUFFIX);
+    file.with_file_name(name)
+}
+
+pub fn write(
+    file: &Path,
+    source_url: &str,
+    platform: &str,
+    sha256: Option<String>,
+) -> anyhow::Result<CompletionManifest> {
+    let manifest = CompletionManifest {
+        source_url: source_url.to_string(),
+        platform: platform.to_string(),
+        file_size_bytes: std::fs::metadata(file)?.len(),
+        sha256,
+        completed_at: chrono::Utc::now().timestamp(),
+    };
+    std::fs::write(manifest_path(file), serde_json::to_vec_pretty(&manifest)?)?;
+    Ok(manifest)
+}
+
+pub fn read(file: &Path) -> Option<CompletionManifest> {
+    let bytes = std::fs::read(manifest_path(file)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Returns the manifest only if `file` still has the size it was recorded with.
+pub fn validate(file: &Path) -> Option<CompletionManifest> {
+    let manifest = read(file)?;
+    let size = std::fs::metadata(file).ok()?.len();
+    (size == manifest.file_size_bytes).then_some(manifest)
+}
+
+pub fn remove(file: &Path) {
+    let _ = std::fs::remove_file(manifest_path(file));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("omniget-manifest-{}-{}", tag, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn manifest_path_appends_suffix() {
+        assert_eq!(
+            manifest_path(Path::new("/dl/video.mp4")),
+            PathBuf::from("/dl/video.mp4.omniget.done")
+        );
+    }
+
+    #[test]
+    fn write_then_validate_round_trips() {
+        let dir = temp_dir("roundtrip");
+        let file = dir.join("clip.mp4");
+        std::fs::write(&file, b"0123456789").unwrap();
+
+        let written = write(&file, "https://example.com/v/1", "youtube", None).unwrap();
+        assert_eq!(written.file_size_bytes, 10);
+        assert_eq!(validate(&file), Some(written));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn validate_rejects_size_mismatch() {
+        let dir = temp_dir("mismatch");
+        let file = dir.join("clip.mp4");
+        std::fs::write(&file, b"0123456789").unwrap();
+        write(&file, "https://example.com/v/1", "youtube", None).unwrap();
+
+        std::fs::write(&file, b"01234").unwrap();
+        assert!(read(&file).is_some());
+        assert!(validate(&file).is_none());
+
+        remove(&file);
+        assert!(read(&file).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -13,8 +13,11 @@ pub mod hls_downloader;
 pub mod http_client;
 pub mod http_fetcher;
 pub mod hwaccel;
+pub mod i18n;
+pub mod image;
 pub mod livechat;
 pub mod log_hook;
+pub mod manifest;
 pub mod media_processor;
 pub mod paths;
 pub mod pdfium;
@@ -22,5 +25,8 @@ pub mod pokemon_names;
 pub mod process;
 pub mod redirect;
 pub mod registry;
+pub mod stats;
 pub mod subtitle_merge;
+pub mod temp_dir;
+pub mod url;
 pub mod ytdlp;
@@ -33,6 +33,30 @@ pub fn app_data_dir() -> Option<std::path::PathBuf> {
     Some(new_path)
 }
 
+/// Where downloads go until the user picks a folder: an `OmniGet` folder
+/// inside the system's Downloads folder, or the home folder without one.
+pub fn default_output_dir() -> std::path::PathBuf {
+    dirs::download_dir()
+        .or_else(|| dirs::home_dir().map(|home| home.join("Downloads")))
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("OmniGet")
+}
+
+/// Creates `dir` on first run when it is still the built-in default. A
+/// folder the user picked is left alone if it's missing, since it may be
+/// on a drive that isn't mounted right now.
+pub fn ensure_default_output_dir(dir: &std::path::Path) {
+    if dir == default_output_dir() && !dir.exists() {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            tracing::warn!(
+                "[paths] failed to create default output dir {}: {}",
+                dir.display(),
+                e
+            );
+        }
+    }
+}
+
 fn copy_dir_recursive(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
     if src.is_dir() {
         std::fs::create_dir_all(dst)?;
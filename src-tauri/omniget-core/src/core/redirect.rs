@@ -1,13 +1,307 @@
 use anyhow::anyhow;
 
+use crate::core::log_hook;
+use crate::models::media::DownloadPhase;
+
+const MAX_REDIRECTS: usize = 10;
+
+const SHORT_LINK_UA: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko)";
+/// Instagram only answers share links with a redirect for non-browser agents.
+const INSTAGRAM_SHARE_UA: &str = "curl/7.88.1";
+
+/// Hosts whose links are only redirects to the real content.
+const SHORT_LINK_HOSTS: &[&str] = &[
+    "vm.tiktok.com",
+    "vt.tiktok.com",
+    "v.redd.it",
+    "redd.it",
+    "pin.it",
+    "on.soundcloud.com",
+    "fb.watch",
+    "t.co",
+    "bit.ly",
+    "tinyurl.com",
+];
+
+/// Query parameters that only identify who shared a link, on any site.
+/// `utm_*` is matched by prefix.
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid"];
+
+/// Share trackers with short names other sites can use for real
+/// parameters, like YouTube's `t=` timestamp, so they're only stripped on
+/// the hosts that add them.
+const HOST_TRACKING_PARAMS: &[(&str, &[&str])] = &[
+    ("youtube.com", &["si", "feature", "pp", "ab_channel"]),
+    ("youtu.be", &["si", "feature"]),
+    ("spotify.com", &["si"]),
+    ("instagram.com", &["igsh", "igshid"]),
+    (
+        "tiktok.com",
+        &[
+            "_r",
+            "_t",
+            "_d",
+            "share_id",
+            "is_from_webapp",
+            "sender_device",
+            "checksum",
+            "enable_checksum",
+            "sec_user_id",
+            "share_app_id",
+            "share_link_id",
+            "timestamp",
+            "tt_from",
+            "u_code",
+            "user_id",
+        ],
+    ),
+    ("twitter.com", &["ref_src", "s", "t"]),
+    ("x.com", &["ref_src", "s", "t"]),
+    ("reddit.com", &["context", "rdt"]),
+    ("vimeo.com", &["share"]),
+];
+
 pub async fn resolve_redirect(client: &reqwest::Client, url: &str) -> anyhow::Result<String> {
     let response = client.get(url).send().await?;
 
     let final_url = response.url().to_string();
 
     if final_url == url {
-        return Err(anyhow!("Nenhum redirect encontrado para {}", url));
+        return Err(anyhow!("No redirect found for {}", url));
     }
 
     Ok(final_url)
 }
+
+/// Whether `url` has to be fetched to learn where it points.
+pub fn is_short_link(url: &str) -> bool {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = parsed.host_str().map(|h| h.to_lowercase()) else {
+        return false;
+    };
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let segments: Vec<&str> = parsed
+        .path_segments()
+        .map(|s| s.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    let first_segment = segments.first().copied().unwrap_or_default();
+
+    SHORT_LINK_HOSTS.contains(&host)
+        || (host == "tiktok.com" && first_segment == "t")
+        || (host == "instagram.com" && first_segment == "share")
+        || (host == "facebook.com" && first_segment == "share")
+        || (host == "reddit.com" && segments.len() >= 4 && segments[0] == "r" && segments[2] == "s")
+}
+
+/// Rewrites short links that don't need a request, like `youtu.be/<id>`.
+fn expand_offline(parsed: &url::Url) -> Option<String> {
+    if parsed.host_str()? != "youtu.be" {
+        return None;
+    }
+    let id = parsed.path_segments()?.next().filter(|id| !id.is_empty())?;
+    let mut expanded = url::Url::parse("https://www.youtube.com/watch").ok()?;
+    expanded.query_pairs_mut().append_pair("v", id);
+    for (key, value) in parsed.query_pairs() {
+        expanded.query_pairs_mut().append_pair(&key, &value);
+    }
+    Some(expanded.to_string())
+}
+
+/// Drops share trackers from the query of `url`. The rest of the query is
+/// kept byte for byte, and a URL without trackers comes back unchanged.
+pub fn strip_tracking_params(url: &str) -> String {
+    let Ok(mut parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return url.to_string();
+    }
+    let Some(query) = parsed.query() else {
+        return url.to_string();
+    };
+    let host = parsed.host_str().unwrap_or_default().to_lowercase();
+    let host_params: &[&str] = HOST_TRACKING_PARAMS
+        .iter()
+        .find(|(domain, _)| host == *domain || host.ends_with(&format!(".{}", domain)))
+        .map(|(_, params)| *params)
+        .unwrap_or_default();
+    let is_tracker = |piece: &str| {
+        let key = url::form_urlencoded::parse(piece.as_bytes())
+            .next()
+            .map(|(key, _)| key)
+            .unwrap_or_default();
+        key.starts_with("utm_")
+            || TRACKING_PARAMS.contains(&key.as_ref())
+            || host_params.contains(&key.as_ref())
+    };
+    let pieces: Vec<&str> = query.split('&').collect();
+    let kept: Vec<&str> = pieces.iter().copied().filter(|p| !is_tracker(p)).collect();
+    if kept.len() == pieces.len() {
+        return url.to_string();
+    }
+    let kept = kept.join("&");
+    parsed.set_query((!kept.is_empty()).then_some(kept.as_str()));
+    parsed.to_string()
+}
+
+/// Follows up to `MAX_REDIRECTS` hops by hand so every hop goes through
+/// the proxy and the chain can't loop forever.
+pub async fn expand_short_link(url: &str) -> anyhow::Result<String> {
+    log_hook::report_phase(DownloadPhase::Resolving);
+    let expanded = follow_short_link(url).await;
+    // Extraction carries on from the page the link pointed to.
+    log_hook::report_phase(DownloadPhase::Extracting);
+    expanded
+}
+
+async fn follow_short_link(url: &str) -> anyhow::Result<String> {
+    let client = crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(std::time::Duration::from_secs(15))
+        .build()?;
+
+    let mut current = url::Url::parse(url)?;
+    for _ in 0..MAX_REDIRECTS {
+        let ua = match current.host_str() {
+            Some(host) if host.ends_with("instagram.com") => INSTAGRAM_SHARE_UA,
+            _ => SHORT_LINK_UA,
+        };
+        let response = client
+            .get(current.clone())
+            .header(reqwest::header::USER_AGENT, ua)
+            .send()
+            .await?;
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| anyhow!("Redirect without location for {}", current))?;
+            current = current.join(location)?;
+            if !is_short_link(current.as_str()) {
+                return Ok(current.to_string());
+            }
+            continue;
+        }
+
+        // TikTok sometimes answers 200 with a bare `<a href="...">` body.
+        let html = response.text().await.unwrap_or_default();
+        if let Some(href) = html
+            .strip_prefix("<a href=\"")
+            .and_then(|rest| rest.split('"').next())
+            .filter(|href| href.starts_with("https://"))
+        {
+            return Ok(href.replace("&amp;", "&"));
+        }
+        break;
+    }
+
+    if current.as_str() == url {
+        return Err(anyhow!("Could not resolve short link {}", url));
+    }
+    Ok(current.to_string())
+}
+
+/// Best-effort canonical form of `url`: short links are expanded and
+/// tracking parameters dropped. Falls back to the input on failure.
+pub async fn canonicalize(url: &str) -> String {
+    let Ok(parsed) = url::Url::parse(url.trim()) else {
+        return url.to_string();
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return url.to_string();
+    }
+
+    let expanded = if let Some(expanded) = expand_offline(&parsed) {
+        expanded
+    } else if is_short_link(parsed.as_str()) {
+        match expand_short_link(parsed.as_str()).await {
+            Ok(expanded) => expanded,
+            Err(e) => {
+                tracing::warn!("[redirect] short link expansion failed: {}", e);
+                parsed.to_string()
+            }
+        }
+    } else {
+        parsed.to_string()
+    };
+
+    strip_tracking_params(&expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_short_links() {
+        assert!(is_short_link("https://vm.tiktok.com/ZMabc/"));
+        assert!(is_short_link("https://www.tiktok.com/t/ZTabc/"));
+        assert!(is_short_link("https://pin.it/abc"));
+        assert!(is_short_link("https://v.redd.it/abc123"));
+        assert!(is_short_link("https://www.instagram.com/share/BAabc/"));
+        assert!(is_short_link("https://www.reddit.com/r/rust/s/AbCdEf"));
+        assert!(is_short_link("https://fb.watch/abcDEF/"));
+        assert!(is_short_link("https://www.facebook.com/share/v/1AbCdEf/"));
+        assert!(!is_short_link(
+            "https://www.reddit.com/r/rust/comments/abc/x/"
+        ));
+        assert!(!is_short_link("https://www.tiktok.com/@user/video/123"));
+        assert!(!is_short_link("magnet:?xt=urn:btih:abc"));
+    }
+
+    #[test]
+    fn youtu_be_expands_offline_and_keeps_timestamp() {
+        let parsed = url::Url::parse("https://youtu.be/dQw4w9WgXcQ?si=tracker&t=42").unwrap();
+        let expanded = expand_offline(&parsed).unwrap();
+        assert_eq!(
+            strip_tracking_params(&expanded),
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42"
+        );
+    }
+
+    #[test]
+    fn strips_only_tracking_params() {
+        assert_eq!(
+            strip_tracking_params("https://www.instagram.com/p/abc/?igsh=xyz&utm_source=ig"),
+            "https://www.instagram.com/p/abc/"
+        );
+        assert_eq!(
+            strip_tracking_params("https://www.youtube.com/watch?v=id&list=PL1&utm_medium=x"),
+            "https://www.youtube.com/watch?v=id&list=PL1"
+        );
+        assert_eq!(
+            strip_tracking_params("magnet:?xt=urn:btih:abc&si=1"),
+            "magnet:?xt=urn:btih:abc&si=1"
+        );
+    }
+
+    #[test]
+    fn kept_query_is_not_reencoded() {
+        let signed = "https://cdn.example.com/v.mp4?sig=a%2Fb+c&e=1&flag";
+        assert_eq!(strip_tracking_params(signed), signed);
+        assert_eq!(
+            strip_tracking_params("https://cdn.example.com/v.mp4?sig=a%2Fb+c&utm_source=x&flag"),
+            "https://cdn.example.com/v.mp4?sig=a%2Fb+c&flag"
+        );
+    }
+
+    #[test]
+    fn short_trackers_are_only_stripped_on_their_hosts() {
+        assert_eq!(
+            strip_tracking_params("https://open.spotify.com/track/abc?si=xyz"),
+            "https://open.spotify.com/track/abc"
+        );
+        assert_eq!(
+            strip_tracking_params("https://x.com/user/status/1?s=20&t=abc"),
+            "https://x.com/user/status/1"
+        );
+        assert_eq!(
+            strip_tracking_params("https://example.com/video?si=2&_t=5&t=9&fbclid=x"),
+            "https://example.com/video?si=2&_t=5&t=9"
+        );
+    }
+}
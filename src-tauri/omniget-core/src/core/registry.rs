@@ -1,6 +1,10 @@
 use std::sync::Arc;
 
 use crate::platforms::traits::PlatformDownloader;
+use crate::platforms::{backend_preference, Platform, PlatformBackend, PlatformDescriptor};
+
+/// Name of the catch-all yt-dlp downloader.
+const GENERIC_PLATFORM: &str = "generic";
 
 pub struct PlatformRegistry {
     platforms: Vec<Arc<dyn PlatformDownloader>>,
@@ -17,8 +21,69 @@ impl PlatformRegistry {
         self.platforms.push(platform);
     }
 
+    /// Downloader for `url`, honouring the user's backend preference: a
+    /// platform set to `Ytdlp` is routed to the generic yt-dlp downloader.
     pub fn find_platform(&self, url: &str) -> Option<Arc<dyn PlatformDownloader>> {
-        self.platforms.iter().find(|p| p.can_handle(url)).cloned()
+        let found = self.platforms.iter().find(|p| p.can_handle(url))?;
+        if found.name() != GENERIC_PLATFORM
+            && backend_preference(found.name()) == PlatformBackend::Ytdlp
+        {
+            if let Some(generic) = self.platforms.iter().find(|p| p.name() == GENERIC_PLATFORM) {
+                return Some(generic.clone());
+            }
+        }
+        Some(found.clone())
+    }
+
+    /// First downloader other than the generic one that claims `url`,
+    /// regardless of backend preference.
+    pub fn find_dedicated(&self, url: &str) -> Option<Arc<dyn PlatformDownloader>> {
+        self.platforms
+            .iter()
+            .find(|p| p.name() != GENERIC_PLATFORM && p.can_handle(url))
+            .cloned()
+    }
+
+    /// Downloader registered under `name`, ignoring backend preference.
+    pub fn find_by_name(&self, name: &str) -> Option<Arc<dyn PlatformDownloader>> {
+        self.platforms.iter().find(|p| p.name() == name).cloned()
+    }
+
+    /// Known platforms that have a downloader registered, with the hosts it
+    /// claims. Built from the downloaders so it can't drift from what
+    /// `find_platform` actually matches.
+    pub fn supported_platforms(&self) -> Vec<PlatformDescriptor> {
+        Platform::all()
+            .iter()
+            .filter_map(|platform| {
+                let downloader = self.find_by_name(&platform.to_string())?;
+                Some(PlatformDescriptor {
+                    platform: platform.to_string(),
+                    display_name: platform.display_name().to_string(),
+                    hosts: downloader
+                        .supported_hosts()
+                        .iter()
+                        .map(|host| host.to_string())
+                        .collect(),
+                    examples: platform
+                        .example_urls()
+                        .iter()
+                        .map(|url| url.to_string())
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Expands short links before detection, so e.g. a `t.co` link is
+    /// matched by the platform it points to. Returns the canonical URL too.
+    pub async fn find_platform_resolved(
+        &self,
+        url: &str,
+    ) -> (String, Option<Arc<dyn PlatformDownloader>>) {
+        let canonical = crate::core::redirect::canonicalize(url).await;
+        let platform = self.find_platform(&canonical);
+        (canonical, platform)
     }
 }
 
@@ -27,3 +92,127 @@ impl Default for PlatformRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo};
+    use crate::platforms::set_backend_preferences;
+    use crate::platforms::traits::ProgressUpdate;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+
+    struct Stub {
+        name: &'static str,
+        host: &'static str,
+    }
+
+    #[async_trait]
+    impl PlatformDownloader for Stub {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn can_handle(&self, url: &str) -> bool {
+            url.contains(self.host)
+        }
+
+        fn supported_hosts(&self) -> &[&'static str] {
+            std::slice::from_ref(&self.host)
+        }
+
+        async fn get_media_info(&self, _url: &str) -> anyhow::Result<MediaInfo> {
+            Err(anyhow::anyhow!("stub has no media"))
+        }
+
+        async fn download(
+            &self,
+            _info: &MediaInfo,
+            _opts: &DownloadOptions,
+            _progress: tokio::sync::mpsc::Sender<ProgressUpdate>,
+        ) -> anyhow::Result<DownloadResult> {
+            Err(anyhow::anyhow!("stub can't download"))
+        }
+    }
+
+    /// Clears the global backend preferences when dropped, so a failing
+    /// assertion doesn't leave them set for other tests.
+    struct ResetPreferences;
+
+    impl Drop for ResetPreferences {
+        fn drop(&mut self) {
+            set_backend_preferences(HashMap::new());
+        }
+    }
+
+    fn registry() -> PlatformRegistry {
+        let mut registry = PlatformRegistry::new();
+        registry.register(Arc::new(Stub {
+            name: "stubtube",
+            host: "stubtube.test",
+        }));
+        registry.register(Arc::new(Stub {
+            name: GENERIC_PLATFORM,
+            host: "",
+        }));
+        registry
+    }
+
+    #[test]
+    fn ytdlp_preference_routes_to_generic() {
+        let registry = registry();
+        let url = "https://stubtube.test/watch/1";
+
+        let _reset = ResetPreferences;
+        set_backend_preferences(HashMap::new());
+        assert_eq!(registry.find_platform(url).unwrap().name(), "stubtube");
+
+        set_backend_preferences(HashMap::from([(
+            "stubtube".to_string(),
+            PlatformBackend::Ytdlp,
+        )]));
+        assert_eq!(
+            registry.find_platform(url).unwrap().name(),
+            GENERIC_PLATFORM
+        );
+        assert_eq!(registry.find_dedicated(url).unwrap().name(), "stubtube");
+        assert!(registry.find_dedicated("https://other.test/").is_none());
+
+        set_backend_preferences(HashMap::from([(
+            "stubtube".to_string(),
+            PlatformBackend::Native,
+        )]));
+        assert_eq!(registry.find_platform(url).unwrap().name(), "stubtube");
+        assert!(crate::platforms::check_ytdlp_fallback("stubtube").is_err());
+        assert!(crate::platforms::check_ytdlp_fallback("other").is_ok());
+    }
+
+    #[test]
+    fn find_by_name_matches_exact_name() {
+        let registry = registry();
+        assert_eq!(
+            registry.find_by_name("stubtube").unwrap().name(),
+            "stubtube"
+        );
+        assert_eq!(
+            registry.find_by_name("stubtube").unwrap().capabilities(),
+            crate::platforms::traits::Capabilities::default()
+        );
+        assert!(registry.find_by_name("stub").is_none());
+    }
+
+    #[test]
+    fn supported_platforms_lists_registered_ones() {
+        let mut registry = registry();
+        registry.register(Arc::new(Stub {
+            name: "vimeo",
+            host: "vimeo.com",
+        }));
+        let supported = registry.supported_platforms();
+        assert_eq!(supported.len(), 1);
+        assert_eq!(supported[0].platform, "vimeo");
+        assert_eq!(supported[0].display_name, "Vimeo");
+        assert_eq!(supported[0].hosts, vec!["vimeo.com"]);
+        assert!(!supported[0].examples.is_empty());
+    }
+}
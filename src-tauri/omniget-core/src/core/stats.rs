@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+
+/// Running totals for the current session. The download queue reports
+/// finished items and their final sizes; the native transfers and yt-dlp
+/// report the rate-limit responses they hit.
+pub struct SessionStats {
+    bytes_downloaded: AtomicU64,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    rate_limited: AtomicU64,
+}
+
+static SESSION: SessionStats = SessionStats::new();
+
+pub fn session() -> &'static SessionStats {
+    &SESSION
+}
+
+/// What the UI shows in its status bar. The queue-dependent fields
+/// (`active`, `queued`, `speed_bytes_per_sec`) are filled in by the caller.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DownloadStats {
+    pub bytes_downloaded: u64,
+    pub active: u32,
+    pub queued: u32,
+    pub completed: u64,
+    pub failed: u64,
+    pub speed_bytes_per_sec: f64,
+    pub rate_limit_429_count: u64,
+}
+
+impl SessionStats {
+    pub const fn new() -> Self {
+        Self {
+            bytes_downloaded: AtomicU64::new(0),
+            completed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            rate_limited: AtomicU64::new(0),
+        }
+    }
+
+    /// Size of a finished download's output.
+    pub fn record_bytes(&self, bytes: u64) {
+        self.bytes_downloaded.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn record_finished(&self, success: bool) {
+        let counter = if success {
+            &self.completed
+        } else {
+            &self.failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// An HTTP 429 from any backend.
+    pub fn record_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> DownloadStats {
+        DownloadStats {
+            bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            rate_limit_429_count: self.rate_limited.load(Ordering::Relaxed),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_reflects_recorded_events() {
+        let stats = SessionStats::new();
+        stats.record_bytes(1024);
+        stats.record_bytes(512);
+        stats.record_finished(true);
+        stats.record_finished(true);
+        stats.record_finished(false);
+        stats.record_rate_limited();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.bytes_downloaded, 1536);
+        assert_eq!(snapshot.completed, 2);
+        assert_eq!(snapshot.failed, 1);
+        assert_eq!(snapshot.rate_limit_429_count, 1);
+        assert_eq!(snapshot.active, 0);
+    }
+}
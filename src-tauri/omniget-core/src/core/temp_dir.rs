@@ -0,0 +1,199 @@
+//! Where downloads keep their intermediates: `.part` files, separate
+//! video and audio tracks waiting to be muxed, and remux outputs.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// The user's `download.temp_dir`. `None` keeps intermediates next to the
+/// output, on the same volume, so finishing a download is a rename.
+static TEMP_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+pub fn set_global_temp_dir(dir: Option<PathBuf>) {
+    if let Ok(mut guard) = TEMP_DIR.write() {
+        *guard = dir.filter(|d| !d.as_os_str().is_empty());
+    }
+}
+
+pub fn get_global_temp_dir() -> Option<PathBuf> {
+    TEMP_DIR.read().ok().and_then(|guard| guard.clone())
+}
+
+/// Directory for the intermediates of a download that ends up in
+/// `output_dir`. A configured folder that has gone missing, like an
+/// unplugged drive, falls back to `output_dir`.
+pub fn for_output(output_dir: &Path) -> PathBuf {
+    match get_global_temp_dir() {
+        Some(dir) if dir.is_dir() => dir,
+        Some(dir) => {
+            tracing::warn!(
+                "[temp_dir] {} is missing, keeping intermediates next to the output",
+                dir.display()
+            );
+            output_dir.to_path_buf()
+        }
+        None => output_dir.to_path_buf(),
+    }
+}
+
+/// Path for an intermediate named `name` of a download whose final file
+/// is `output`.
+pub fn intermediate_for(output: &Path, name: &str) -> PathBuf {
+    for_output(output.parent().unwrap_or(Path::new("."))).join(name)
+}
+
+/// The `.part` file a download of `output` is written to until it is
+/// complete. In a shared temp folder the name carries a hash of the full
+/// output path, so two downloads of `clip.mp4` to different folders don't
+/// write to the same file.
+pub fn part_path_for(output: &Path) -> PathBuf {
+    let output_dir = output.parent().unwrap_or(Path::new("."));
+    let dir = for_output(output_dir);
+    let shared = dir != output_dir;
+    dir.join(part_name(output, shared))
+}
+
+fn part_name(output: &Path, shared: bool) -> String {
+    let name = output.file_name().unwrap_or_default().to_string_lossy();
+    if !shared {
+        return format!("{}.part", name);
+    }
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(output.to_string_lossy().as_bytes());
+    let hash: String = digest[..4].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}.{}.part", name, hash)
+}
+
+/// Where ffmpeg writes a merge or remux meant for `output` before
+/// `move_file` puts it in place. Keeps the extension, which ffmpeg picks
+/// the container from.
+pub fn staging_for(output: &Path) -> PathBuf {
+    let ext = output.extension().and_then(|e| e.to_str()).unwrap_or("tmp");
+    intermediate_for(
+        output,
+        &format!(".omniget_part_{}.{}", uuid::Uuid::new_v4(), ext),
+    )
+}
+
+/// A folder of its own under the temp folder for a tool that scatters
+/// intermediates, like yt-dlp, so cleaning up after one download can't
+/// touch the files of another running alongside it. Removed with
+/// everything in it when the returned guard is dropped.
+pub fn work_dir_for(output_dir: &Path) -> WorkDir {
+    WorkDir(for_output(output_dir).join(format!(".omniget_work_{}", uuid::Uuid::new_v4())))
+}
+
+pub struct WorkDir(PathBuf);
+
+impl WorkDir {
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Checks that `dir` exists, is a directory and can be written to.
+pub fn validate(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!(
+            "Temporary folder {} doesn't exist or isn't a folder",
+            dir.display()
+        ));
+    }
+    let probe = dir.join(format!(".omniget_write_test_{}", uuid::Uuid::new_v4()));
+    std::fs::write(&probe, b"")
+        .map_err(|e| format!("Temporary folder {} isn't writable: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Moves a finished intermediate into place. A rename when both are on
+/// the same volume; across volumes the intermediate is copied to a hidden
+/// name next to `to` and renamed over it, so a failed copy never touches
+/// an existing `to`. Any other rename error, like a locked file, is
+/// returned as is.
+pub async fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    match tokio::fs::rename(from, to).await {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() != std::io::ErrorKind::CrossesDevices => return Err(e),
+        Err(_) => {}
+    }
+    let name = to.file_name().unwrap_or_default().to_string_lossy();
+    let copied = to.with_file_name(format!(".{}.omniget_move_{}", name, uuid::Uuid::new_v4()));
+    let placed = match tokio::fs::copy(from, &copied).await {
+        Ok(_) => tokio::fs::rename(&copied, to).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = placed {
+        let _ = tokio::fs::remove_file(&copied).await;
+        return Err(e);
+    }
+    let _ = tokio::fs::remove_file(from).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_and_staging_paths_stay_next_to_output_by_default() {
+        let output = Path::new("downloads/clip.mp4");
+        assert_eq!(
+            part_path_for(output),
+            PathBuf::from("downloads/clip.mp4.part")
+        );
+        let staging = staging_for(output);
+        assert_eq!(staging.parent(), Some(Path::new("downloads")));
+        assert_eq!(staging.extension().and_then(|e| e.to_str()), Some("mp4"));
+    }
+
+    #[test]
+    fn part_names_in_a_shared_folder_differ_per_destination() {
+        let a = part_name(Path::new("music/clip.mp4"), true);
+        let b = part_name(Path::new("videos/clip.mp4"), true);
+        assert_ne!(a, b);
+        assert!(a.starts_with("clip.mp4.") && a.ends_with(".part"));
+        assert_eq!(a, part_name(Path::new("music/clip.mp4"), true));
+        assert_eq!(
+            part_name(Path::new("music/clip.mp4"), false),
+            "clip.mp4.part"
+        );
+    }
+
+    #[test]
+    fn validate_rejects_missing_dirs_and_files() {
+        let dir = std::env::temp_dir().join(format!("omniget-temp-dir-{}", std::process::id()));
+        assert!(validate(&dir).is_err());
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(validate(&dir).is_ok());
+        let file = dir.join("file");
+        std::fs::write(&file, b"x").unwrap();
+        assert!(validate(&file).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn move_file_replaces_intermediate() {
+        let dir = std::env::temp_dir().join(format!("omniget-temp-move-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let from = dir.join("a.part");
+        let to = dir.join("out").join("a.mp4");
+        std::fs::write(&from, b"data").unwrap();
+        move_file(&from, &to).await.unwrap();
+        assert!(!from.exists());
+        assert_eq!(std::fs::read(&to).unwrap(), b"data");
+
+        // A failed move leaves the finished file alone.
+        assert!(move_file(&dir.join("missing.part"), &to).await.is_err());
+        assert_eq!(std::fs::read(&to).unwrap(), b"data");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
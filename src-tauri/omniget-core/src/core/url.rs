@@ -0,0 +1,177 @@
+const LEADING_WRAPPERS: &[char] = &['<', '"', '\'', '(', '['];
+const TRAILING_WRAPPERS: &[char] = &['>', ']', '.', ',', ';', ':', '!', '?', '\'', '"'];
+
+/// Cleans up a URL as pasted by a user: surrounding whitespace, angle
+/// brackets, quotes and markdown link syntax, trailing punctuation from the
+/// sentence it was copied out of, a missing scheme and tracking parameters.
+/// Non-web input such as magnet links or local paths comes back trimmed.
+/// `None` means there was nothing URL-like to begin with.
+pub fn normalize(raw: &str) -> Option<String> {
+    let text = unwrap_paste(raw.trim());
+    if text.is_empty() || text.chars().any(char::is_whitespace) {
+        return None;
+    }
+    if std::path::Path::new(text).exists() {
+        return Some(text.to_string());
+    }
+
+    let with_scheme = if text.starts_with("//") {
+        format!("https:{}", text)
+    } else if looks_like_bare_host(text) {
+        format!("https://{}", text)
+    } else {
+        text.to_string()
+    };
+
+    let Ok(parsed) = url::Url::parse(&with_scheme) else {
+        return None;
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Some(text.to_string());
+    }
+    parsed.host_str()?;
+
+    Some(crate::core::redirect::strip_tracking_params(
+        parsed.as_str(),
+    ))
+}
+
+/// The part of a YouTube video a link points at, from its `t`/`start` and
+/// `end` parameters, in seconds. `None` when the link starts at the
+/// beginning and has no end.
+pub fn time_section(raw: &str) -> Option<(f64, Option<f64>)> {
+    let parsed = url::Url::parse(raw).ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    if !(host == "youtu.be" || host == "youtube.com" || host.ends_with(".youtube.com")) {
+        return None;
+    }
+    let param = |names: &[&str]| {
+        parsed
+            .query_pairs()
+            .find(|(key, _)| names.contains(&key.as_ref()))
+            .and_then(|(_, value)| crate::core::ytdlp::parse_timestamp(&value))
+    };
+    let start = param(&["t", "start"]).unwrap_or(0.0);
+    let end = param(&["end"]).filter(|end| *end > start);
+    (start > 0.0 || end.is_some()).then_some((start, end))
+}
+
+/// Peels wrappers a URL picks up in chats and documents, repeatedly, since
+/// they nest (`<"https://…">.`).
+fn unwrap_paste(mut text: &str) -> &str {
+    loop {
+        let before = text;
+        if let Some(target) = markdown_link_target(text) {
+            text = target;
+        }
+        text = text
+            .trim_start_matches(LEADING_WRAPPERS)
+            .trim_end_matches(TRAILING_WRAPPERS);
+        // A closing paren is part of the URL only when it has an opening one,
+        // like Wikipedia's `Rust_(programming_language)`.
+        if text.ends_with(')') && text.matches('(').count() < text.matches(')').count() {
+            text = &text[..text.len() - 1];
+        }
+        text = text.trim();
+        if text == before {
+            return text;
+        }
+    }
+}
+
+/// `https://x` from `[label](https://x)`.
+fn markdown_link_target(text: &str) -> Option<&str> {
+    let rest = text.strip_prefix('[')?;
+    let (_, target) = rest.split_once("](")?;
+    target.strip_suffix(')')
+}
+
+/// `youtube.com/watch?v=…` or `www.instagram.com/p/…`, not a relative path
+/// or something that already has a scheme.
+fn looks_like_bare_host(text: &str) -> bool {
+    let host = text.split(['/', '?', '#']).next().unwrap_or_default();
+    !text.contains("://")
+        && !host.contains(':')
+        && host.contains('.')
+        && !host.starts_with('.')
+        && !host.ends_with('.')
+        && host
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unwraps_pasted_links() {
+        assert_eq!(
+            normalize("  <https://www.youtube.com/watch?v=abc>  ").as_deref(),
+            Some("https://www.youtube.com/watch?v=abc")
+        );
+        assert_eq!(
+            normalize("[my clip](https://vimeo.com/123?h=deadbeef)").as_deref(),
+            Some("https://vimeo.com/123?h=deadbeef")
+        );
+        assert_eq!(
+            normalize("see https://x.com/user/status/1?s=20&t=abc.").as_deref(),
+            None
+        );
+        assert_eq!(
+            normalize("\"https://x.com/user/status/1?s=20&t=abc\".").as_deref(),
+            Some("https://x.com/user/status/1")
+        );
+        assert_eq!(
+            normalize("(https://en.wikipedia.org/wiki/Rust_(programming_language))").as_deref(),
+            Some("https://en.wikipedia.org/wiki/Rust_(programming_language)")
+        );
+    }
+
+    #[test]
+    fn adds_a_missing_scheme() {
+        assert_eq!(
+            normalize("youtube.com/watch?v=abc&list=PL1").as_deref(),
+            Some("https://youtube.com/watch?v=abc&list=PL1")
+        );
+        assert_eq!(
+            normalize("//www.instagram.com/p/xyz/?igsh=1").as_deref(),
+            Some("https://www.instagram.com/p/xyz/")
+        );
+        assert_eq!(normalize("not a url"), None);
+        assert_eq!(normalize("   "), None);
+    }
+
+    #[test]
+    fn keeps_meaningful_params_and_other_schemes() {
+        assert_eq!(
+            normalize("https://www.youtube.com/watch?v=abc&t=42&si=x&feature=share").as_deref(),
+            Some("https://www.youtube.com/watch?v=abc&t=42")
+        );
+        assert_eq!(
+            normalize("https://www.facebook.com/watch/?v=1&fbclid=abc").as_deref(),
+            Some("https://www.facebook.com/watch/?v=1")
+        );
+        assert_eq!(
+            normalize("magnet:?xt=urn:btih:abc&dn=x").as_deref(),
+            Some("magnet:?xt=urn:btih:abc&dn=x")
+        );
+    }
+
+    #[test]
+    fn youtube_links_carry_a_time_section() {
+        assert_eq!(
+            time_section("https://www.youtube.com/watch?v=abc&t=1m30s"),
+            Some((90.0, None))
+        );
+        assert_eq!(
+            time_section("https://www.youtube.com/embed/abc?start=10&end=40"),
+            Some((10.0, Some(40.0)))
+        );
+        assert_eq!(
+            time_section("https://www.youtube.com/watch?v=abc&t=0"),
+            None
+        );
+        assert_eq!(time_section("https://example.com/video?t=90"), None);
+    }
+}
@@ -10,8 +10,13 @@ use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 use crate::core::log_hook;
-use crate::models::media::{DownloadResult, FormatInfo};
-use crate::models::progress::ProgressUpdate;
+use crate::core::errors::DownloadError;
+use crate::core::filename::with_quality_suffix;
+use crate::models::media::{
+    DownloadNote, DownloadOptions, DownloadPhase, DownloadResult, FfmpegMissingPolicy, FormatInfo,
+    MediaInfo, QualityPreference, VideoQuality,
+};
+use crate::models::progress::{forward_part, ProgressUpdate};
 
 type ExtCookiePathFn = Box<dyn Fn() -> PathBuf + Send + Sync>;
 type GlobalCookieFileFn = Box<dyn Fn() -> Option<String> + Send + Sync>;
@@ -28,12 +33,15 @@ type EmbedMetadataFn = Box<dyn Fn() -> bool + Send + Sync>;
 type EmbedThumbnailFn = Box<dyn Fn() -> bool + Send + Sync>;
 type SpeedLimitFn = Box<dyn Fn() -> Option<String> + Send + Sync>;
 type LiveFromStartFn = Box<dyn Fn() -> bool + Send + Sync>;
+type RecordLiveFn = Box<dyn Fn() -> bool + Send + Sync>;
 type ConcurrentFragmentsFn = Box<dyn Fn() -> u32 + Send + Sync>;
 type UserAgentFn = Box<dyn Fn() -> Option<String> + Send + Sync>;
 type SponsorBlockModeFn = Box<dyn Fn() -> String + Send + Sync>;
 type SponsorBlockCategoriesFn = Box<dyn Fn() -> Vec<String> + Send + Sync>;
 type PerDomainCookieFn = Box<dyn Fn(&str) -> Option<PathBuf> + Send + Sync>;
 type ManagedCookiesOnlyFn = Box<dyn Fn() -> bool + Send + Sync>;
+type FfmpegMissingPolicyFn = Box<dyn Fn() -> FfmpegMissingPolicy + Send + Sync>;
+type ConvertKeepOriginalFn = Box<dyn Fn() -> bool + Send + Sync>;
 
 static EXT_COOKIE_PATH_FN: OnceLock<ExtCookiePathFn> = OnceLock::new();
 static GLOBAL_COOKIE_FILE_FN: OnceLock<GlobalCookieFileFn> = OnceLock::new();
@@ -52,10 +60,13 @@ static EMBED_METADATA_FN: OnceLock<EmbedMetadataFn> = OnceLock::new();
 static EMBED_THUMBNAIL_FN: OnceLock<EmbedThumbnailFn> = OnceLock::new();
 static SPEED_LIMIT_FN: OnceLock<SpeedLimitFn> = OnceLock::new();
 static LIVE_FROM_START_FN: OnceLock<LiveFromStartFn> = OnceLock::new();
+static RECORD_LIVE_FN: OnceLock<RecordLiveFn> = OnceLock::new();
 static CONCURRENT_FRAGMENTS_FN: OnceLock<ConcurrentFragmentsFn> = OnceLock::new();
 static USER_AGENT_FN: OnceLock<UserAgentFn> = OnceLock::new();
 static SPONSORBLOCK_MODE_FN: OnceLock<SponsorBlockModeFn> = OnceLock::new();
 static SPONSORBLOCK_CATEGORIES_FN: OnceLock<SponsorBlockCategoriesFn> = OnceLock::new();
+static FFMPEG_MISSING_POLICY_FN: OnceLock<FfmpegMissingPolicyFn> = OnceLock::new();
+static CONVERT_KEEP_ORIGINAL_FN: OnceLock<ConvertKeepOriginalFn> = OnceLock::new();
 
 pub fn set_ext_cookie_path_fn(f: impl Fn() -> PathBuf + Send + Sync + 'static) {
     let _ = EXT_COOKIE_PATH_FN.set(Box::new(f));
@@ -131,6 +142,14 @@ fn keep_vtt_setting() -> bool {
     KEEP_VTT_FN.get().map(|f| f()).unwrap_or(false)
 }
 
+pub fn set_convert_keep_original_fn(f: impl Fn() -> bool + Send + Sync + 'static) {
+    let _ = CONVERT_KEEP_ORIGINAL_FN.set(Box::new(f));
+}
+
+pub(crate) fn convert_keep_original_setting() -> bool {
+    CONVERT_KEEP_ORIGINAL_FN.get().map(|f| f()).unwrap_or(false)
+}
+
 pub fn set_translate_metadata_fn(f: impl Fn() -> Option<String> + Send + Sync + 'static) {
     let _ = TRANSLATE_METADATA_FN.set(Box::new(f));
 }
@@ -266,6 +285,14 @@ fn live_from_start_enabled() -> bool {
     LIVE_FROM_START_FN.get().map(|f| f()).unwrap_or(false)
 }
 
+pub fn set_record_live_fn(f: impl Fn() -> bool + Send + Sync + 'static) {
+    let _ = RECORD_LIVE_FN.set(Box::new(f));
+}
+
+fn record_live_enabled() -> bool {
+    RECORD_LIVE_FN.get().map(|f| f()).unwrap_or(false)
+}
+
 pub fn set_concurrent_fragments_fn(f: impl Fn() -> u32 + Send + Sync + 'static) {
     let _ = CONCURRENT_FRAGMENTS_FN.set(Box::new(f));
 }
@@ -274,6 +301,17 @@ fn concurrent_fragments_value() -> u32 {
     CONCURRENT_FRAGMENTS_FN.get().map(|f| f()).unwrap_or(1)
 }
 
+pub fn set_ffmpeg_missing_policy_fn(f: impl Fn() -> FfmpegMissingPolicy + Send + Sync + 'static) {
+    let _ = FFMPEG_MISSING_POLICY_FN.set(Box::new(f));
+}
+
+pub fn ffmpeg_missing_policy() -> FfmpegMissingPolicy {
+    FFMPEG_MISSING_POLICY_FN
+        .get()
+        .map(|f| f())
+        .unwrap_or_default()
+}
+
 pub fn set_user_agent_fn(f: impl Fn() -> Option<String> + Send + Sync + 'static) {
     let _ = USER_AGENT_FN.set(Box::new(f));
 }
@@ -444,6 +482,7 @@ fn rate_limit_429_count() -> u64 {
 
 fn rate_limit_429_increment() {
     RATE_LIMIT_429_COUNT.fetch_add(1, Ordering::Relaxed);
+    crate::core::stats::session().record_rate_limited();
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -535,6 +574,90 @@ fn has_explicit_cookie_header(args: &[String]) -> bool {
     })
 }
 
+/// Flags that run commands or load code from disk. User flags may only use
+/// them in advanced mode.
+const UNSAFE_USER_FLAGS: &[&str] = &[
+    "--exec",
+    "--exec-before-download",
+    "--postprocessor-args",
+    "--ppa",
+    "--use-postprocessor",
+    "--downloader",
+    "--external-downloader",
+    "--downloader-args",
+    "--external-downloader-args",
+    "--config-location",
+    "--config-locations",
+    "--plugin-dirs",
+    "--netrc-cmd",
+    "--batch-file",
+    "-a",
+    "--ffmpeg-location",
+];
+
+/// Flags `download_video` relies on for output paths and progress parsing,
+/// plus yt-dlp's self-update, which the app manages. User flags never
+/// override them.
+const MANAGED_FLAGS: &[&str] = &[
+    "-o",
+    "--output",
+    "-P",
+    "--paths",
+    "--progress-template",
+    "--newline",
+    "-q",
+    "--quiet",
+    "--no-progress",
+    "-O",
+    "--print",
+    "--print-to-file",
+    "-j",
+    "--dump-json",
+    "-J",
+    "--dump-single-json",
+    "-s",
+    "--simulate",
+    "--skip-download",
+    "-U",
+    "--update",
+    "--update-to",
+];
+
+fn flag_name(arg: &str) -> Option<&str> {
+    if let Some(long) = arg.strip_prefix("--") {
+        let name_len = long.find('=').unwrap_or(long.len());
+        return Some(&arg[..name_len + 2]);
+    }
+    let short = arg.strip_prefix('-')?;
+    let c = short.chars().next().filter(|c| c.is_ascii_alphabetic())?;
+    Some(&arg[..1 + c.len_utf8()])
+}
+
+/// Splits user-supplied yt-dlp flags into the ones that can be passed and
+/// the ones that were refused. A refused flag takes the values after it
+/// along, up to the next flag.
+pub fn sanitize_user_args(args: &[String], allow_unsafe: bool) -> (Vec<String>, Vec<String>) {
+    let mut kept = Vec::new();
+    let mut refused = Vec::new();
+    let mut skipping = false;
+    for arg in args.iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+        match flag_name(arg) {
+            Some(name) => {
+                skipping = MANAGED_FLAGS.contains(&name)
+                    || (!allow_unsafe && UNSAFE_USER_FLAGS.contains(&name));
+                if skipping {
+                    refused.push(name.to_string());
+                } else {
+                    kept.push(arg.to_string());
+                }
+            }
+            None if skipping => {}
+            None => kept.push(arg.to_string()),
+        }
+    }
+    (kept, refused)
+}
+
 fn append_cookie_header(args: &mut Vec<String>, cookie_header: &str) {
     args.push("--add-headers".to_string());
     args.push(format!("Cookie:{}", cookie_header));
@@ -872,6 +995,49 @@ pub async fn update_ytdlp() -> anyhow::Result<PathBuf> {
     Ok(path)
 }
 
+/// Where the yt-dlp binary in use comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YtdlpSource {
+    /// Downloaded and kept up to date by the app.
+    Managed,
+    /// Provided by the Flatpak runtime.
+    Flatpak,
+    /// Found on the system PATH.
+    System,
+}
+
+pub fn ytdlp_source(path: &Path) -> YtdlpSource {
+    if managed_ytdlp_path().is_some_and(|managed| managed == path) {
+        YtdlpSource::Managed
+    } else if crate::core::dependencies::is_flatpak() && path.starts_with("/app") {
+        YtdlpSource::Flatpak
+    } else {
+        YtdlpSource::System
+    }
+}
+
+/// Runs `yt-dlp --version` and returns the trimmed version string.
+pub async fn ytdlp_binary_version(ytdlp: &Path) -> anyhow::Result<String> {
+    let output = crate::core::process::command(ytdlp)
+        .arg("--version")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "yt-dlp --version failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if version.is_empty() {
+        return Err(anyhow!("yt-dlp --version printed nothing"));
+    }
+    Ok(version)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum YtdlpChannel {
     Stable,
@@ -1104,6 +1270,18 @@ async fn find_ffmpeg_location_cached() -> Option<String> {
     result
 }
 
+/// Cookie file a native downloader can read for `url`: the cookie
+/// manager's file for the domain, else the global `cookie_file` setting.
+/// Unlike the yt-dlp paths no copy is made, since it is only read.
+pub fn cookie_source_for_url(url: &str) -> Option<PathBuf> {
+    PER_DOMAIN_COOKIE_FN
+        .get()
+        .and_then(|f| f(url))
+        .filter(|p| p.is_file())
+        .or_else(|| global_cookie_file().map(PathBuf::from))
+        .filter(|p| p.is_file())
+}
+
 fn per_domain_cookie_file(url: &str) -> Option<std::path::PathBuf> {
     let source = PER_DOMAIN_COOKIE_FN.get()?(url)?;
     if !source.exists() {
@@ -1239,6 +1417,28 @@ fn is_youtube_url(url: &str) -> bool {
     lower.contains("youtube.com") || lower.contains("youtu.be")
 }
 
+fn is_youtube_music_url(url: &str) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_lowercase))
+        .is_some_and(|host| host == "music.youtube.com")
+}
+
+/// Library tags for YouTube Music tracks and podcast episodes: the track
+/// name rather than the video title, the first credited artist as album
+/// artist, and the release year (or upload year) as the date. yt-dlp's
+/// `--embed-metadata` already fills artist and album.
+fn youtube_music_metadata_args() -> Vec<String> {
+    [
+        "%(track,title)s:%(meta_title)s",
+        "%(artists.0,artist,uploader)s:%(meta_album_artist)s",
+        "%(release_year,upload_date>%Y)s:%(meta_date)s",
+    ]
+    .iter()
+    .flat_map(|mapping| ["--parse-metadata".to_string(), mapping.to_string()])
+    .collect()
+}
+
 /// Extracts the most meaningful error line from yt-dlp stderr output.
 /// Prefers lines starting with "ERROR:", falls back to "WARNING:", then raw trimmed output.
 fn extract_error_message(stderr: &str) -> String {
@@ -1269,6 +1469,7 @@ pub async fn get_video_info(
     extra_flags: &[String],
 ) -> anyhow::Result<serde_json::Value> {
     let _timer_start = std::time::Instant::now();
+    log_hook::report_phase(DownloadPhase::Extracting);
 
     if is_youtube_url(url) {
         yt_rate_limiter().acquire().await;
@@ -1812,6 +2013,21 @@ pub async fn get_playlist_info_incremental(
     Ok((title, entries))
 }
 
+/// The step a yt-dlp output line starts, if any. A new destination means
+/// the next file of a multi-file download is being fetched.
+fn line_phase(line: &str) -> Option<DownloadPhase> {
+    let line = line.trim_start();
+    if line.starts_with("[download] Destination:") {
+        return Some(DownloadPhase::Downloading);
+    }
+    let tag = line.strip_prefix('[')?.split(']').next()?;
+    match tag {
+        "Merger" | "VideoRemuxer" => Some(DownloadPhase::Muxing),
+        "ExtractAudio" | "VideoConvertor" => Some(DownloadPhase::Converting),
+        _ => None,
+    }
+}
+
 fn parse_destination_line(line: &str) -> Option<String> {
     let line = line.trim();
 
@@ -1829,6 +2045,16 @@ fn parse_destination_line(line: &str) -> Option<String> {
         }
     }
 
+    // Only printed when a temp folder is set; the second path is the final one.
+    if let Some(rest) = line.strip_prefix("[MoveFiles] Moving file \"") {
+        if let Some((_, to)) = rest.rsplit_once("\" to \"") {
+            let path = to.trim_end_matches('"');
+            if !path.is_empty() {
+                return Some(path.to_string());
+            }
+        }
+    }
+
     None
 }
 
@@ -1872,6 +2098,182 @@ pub async fn write_netscape_cookie_file(
     Ok(())
 }
 
+/// Builds the `-f` selector. The requested height and `pref` narrow the
+/// video stream; a preferred codec gets its own tier ahead of the
+/// codec-agnostic one so it never makes a download fail.
+fn build_format_selector(
+    mode: &str,
+    format_id: Option<&str>,
+    quality_height: Option<u32>,
+    ffmpeg_available: bool,
+    pref: &QualityPreference,
+) -> String {
+    if mode == "audio" {
+        return match format_id {
+            Some(fid) if quality_height.is_some_and(|h| h > 0) => format!("{}/ba/b", fid),
+            Some(fid) => fid.to_string(),
+            None => "ba/b".to_string(),
+        };
+    }
+
+    let height = target_height(quality_height, pref);
+    let mut base = String::new();
+    if let Some(h) = height {
+        base.push_str(&format!("[height<={}]", h));
+    }
+    if let Some(mb) = pref.prefer_max_filesize_mb() {
+        base.push_str(&format!("[filesize<?{}M]", mb));
+    }
+    let mut tiers = Vec::new();
+    if let Some(codec) = pref.prefer_codec() {
+        tiers.push(format!("{}[vcodec^={}]", base, codec));
+    }
+    tiers.push(base.clone());
+    let constrained = tiers.len() > 1 || !base.is_empty();
+
+    let selector = match (mode, ffmpeg_available) {
+        ("mute", _) => {
+            let mut parts: Vec<String> = tiers.iter().map(|t| format!("bv*{}", t)).collect();
+            if !base.is_empty() {
+                parts.push("bv*".to_string());
+            }
+            parts.push("b".to_string());
+            parts.join("/")
+        }
+        (_, true) => {
+            let mut parts: Vec<String> = tiers
+                .iter()
+                .flat_map(|t| [format!("bv*{}+ba[ext=m4a]", t), format!("bv*{}+ba", t)])
+                .collect();
+            if !base.is_empty() {
+                parts.push(format!("b{}", base));
+            }
+            parts.push("b".to_string());
+            parts.join("/")
+        }
+        (_, false) => {
+            let mut parts: Vec<String> = tiers.iter().map(|t| format!("b{}", t)).collect();
+            parts.push(format!("bv*{}", base));
+            if !base.is_empty() {
+                parts.push("b".to_string());
+            }
+            parts.join("/")
+        }
+    };
+
+    match format_id {
+        Some(fid) if constrained => format!("{}/{}", fid, selector),
+        Some(fid) => fid.to_string(),
+        None => selector,
+    }
+}
+
+fn target_height(quality_height: Option<u32>, pref: &QualityPreference) -> Option<u32> {
+    match (quality_height.filter(|h| *h > 0), pref.max_height()) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Best video and best audio as two files, for when ffmpeg isn't there to
+/// merge them. Falls back to a combined stream for sites without separate
+/// video.
+fn separate_tracks_selector(quality_height: Option<u32>, pref: &QualityPreference) -> String {
+    match target_height(quality_height, pref) {
+        Some(h) => format!("(bv[height<={}]/bv/b),ba", h),
+        None => "(bv/b),ba".to_string(),
+    }
+}
+
+/// Seconds from `90`, `90s`, `1m30s`, `1h2m3s`, `1:30` or `01:02:03.5`,
+/// the forms YouTube links and users write.
+pub fn parse_timestamp(raw: &str) -> Option<f64> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    if raw.contains(':') {
+        let mut total = 0.0;
+        for part in raw.split(':') {
+            total = total * 60.0 + part.parse::<f64>().ok().filter(|v| *v >= 0.0)?;
+        }
+        return Some(total);
+    }
+    if let Ok(secs) = raw.parse::<f64>() {
+        return (secs >= 0.0).then_some(secs);
+    }
+    let mut total = 0.0;
+    let mut number = String::new();
+    for c in raw.chars() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600.0,
+            'm' => 60.0,
+            's' => 1.0,
+            _ => return None,
+        };
+        total += number.parse::<f64>().ok()? * unit;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(total)
+}
+
+/// `start-end` or `start-inf` into seconds; `None` for an open end.
+pub fn parse_section(range: &str) -> Option<(f64, Option<f64>)> {
+    let (start, end) = range.trim().split_once('-')?;
+    let start = parse_timestamp(start)?;
+    let end = match end.trim() {
+        "inf" | "" => None,
+        end => Some(parse_timestamp(end).filter(|end| *end > start)?),
+    };
+    Some((start, end))
+}
+
+/// Flags that make yt-dlp download only `start..end` of a video. Cutting
+/// runs through ffmpeg, so without it the whole video is downloaded. ffmpeg
+/// also reports its position on stdout, which `download_video` turns into
+/// progress for the section instead of the full file.
+pub async fn section_args(start: Option<f64>, end: Option<f64>) -> Vec<String> {
+    if start.is_none() && end.is_none() {
+        return Vec::new();
+    }
+    if !crate::core::ffmpeg::is_ffmpeg_available().await {
+        tracing::warn!(
+            "[yt-dlp] ffmpeg not available, downloading the whole video instead of a section"
+        );
+        return Vec::new();
+    }
+    let end = end.map_or_else(|| "inf".to_string(), |end| end.to_string());
+    vec![
+        "--download-sections".to_string(),
+        format!("*{}-{}", start.unwrap_or(0.0), end),
+        "--downloader-args".to_string(),
+        "ffmpeg_o:-progress pipe:1 -nostats".to_string(),
+    ]
+}
+
+/// Length in seconds of a closed `--download-sections` range in `flags`.
+fn section_length(flags: &[String]) -> Option<f64> {
+    let pos = flags.iter().position(|f| f == "--download-sections")?;
+    let range = flags.get(pos + 1)?.strip_prefix('*')?;
+    let (start, end) = parse_section(range)?;
+    Some(end? - start)
+}
+
+/// Seconds of output written so far, from ffmpeg's `-progress` lines.
+fn parse_ffmpeg_out_time(line: &str) -> Option<f64> {
+    let micros = line
+        .strip_prefix("out_time_us=")
+        .or_else(|| line.strip_prefix("out_time_ms="))?;
+    micros.trim().parse::<f64>().ok().map(|us| us / 1_000_000.0)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn download_video(
     ytdlp: &Path,
@@ -1889,6 +2291,8 @@ pub async fn download_video(
     download_subtitles: bool,
     extra_flags: &[String],
     audio_format: Option<&str>,
+    quality_pref: &QualityPreference,
+    user_args: &[String],
 ) -> anyhow::Result<DownloadResult> {
     let _timer_start = std::time::Instant::now();
 
@@ -1901,57 +2305,39 @@ pub async fn download_video(
 
     let mode = download_mode.unwrap_or("auto");
     let is_audio_only = mode == "audio";
+    let section_secs = section_length(extra_flags).filter(|len| *len > 0.0);
     let (ffmpeg_available, ffmpeg_location, aria2c_path) = tokio::join!(
         crate::core::ffmpeg::is_ffmpeg_available(),
         find_ffmpeg_location_cached(),
         crate::core::dependencies::ensure_aria2c(),
     );
 
-    let format_selector = if let Some(fid) = format_id {
-        if let Some(h) = quality_height.filter(|h| *h > 0) {
-            let fallback = match mode {
-                "audio" => "ba/b".to_string(),
-                "mute" => format!("bv*[height<={}]/bv*/b", h),
-                _ => {
-                    if ffmpeg_available {
-                        format!(
-                            "bv*[height<={}]+ba[ext=m4a]/bv*[height<={}]+ba/b[height<={}]/b",
-                            h, h, h
-                        )
-                    } else {
-                        format!("b[height<={}]/bv*[height<={}]/b", h, h)
-                    }
-                }
-            };
-            format!("{}/{}", fid, fallback)
-        } else {
-            fid.to_string()
-        }
-    } else {
-        match mode {
-            "audio" => "ba/b".to_string(),
-            "mute" => match quality_height {
-                Some(h) if h > 0 => format!("bv*[height<={}]/bv*/b", h),
-                _ => "bv*/b".to_string(),
-            },
-            _ => {
-                if ffmpeg_available {
-                    match quality_height {
-                        Some(h) if h > 0 => format!(
-                            "bv*[height<={}]+ba[ext=m4a]/bv*[height<={}]+ba/b[height<={}]/b",
-                            h, h, h
-                        ),
-                        _ => "bv*+ba[ext=m4a]/bv*+ba/b".to_string(),
-                    }
-                } else {
-                    tracing::warn!("[yt-dlp] ffmpeg not available, using fallback format selector");
-                    match quality_height {
-                        Some(h) if h > 0 => format!("b[height<={}]/bv*[height<={}]/b", h, h),
-                        _ => "b/bv*".to_string(),
-                    }
-                }
+    let note = (format_id.is_none() && !ffmpeg_available && !matches!(mode, "audio" | "mute"))
+        .then(|| match ffmpeg_missing_policy() {
+            FfmpegMissingPolicy::SingleStream => DownloadNote::FfmpegMissingSingleStream,
+            FfmpegMissingPolicy::SeparateTracks => DownloadNote::FfmpegMissingSeparateTracks,
+        });
+    let separate_tracks = note == Some(DownloadNote::FfmpegMissingSeparateTracks);
+    if note.is_some() {
+        tracing::warn!(
+            "[yt-dlp] ffmpeg not available, {}",
+            if separate_tracks {
+                "saving video and audio separately"
+            } else {
+                "using a single-stream format"
             }
-        }
+        );
+    }
+    let format_selector = if separate_tracks {
+        separate_tracks_selector(quality_height, quality_pref)
+    } else {
+        build_format_selector(
+            mode,
+            format_id,
+            quality_height,
+            ffmpeg_available,
+            quality_pref,
+        )
     };
 
     let dir_len = output_dir.to_string_lossy().len();
@@ -1960,10 +2346,23 @@ pub async fn download_video(
     } else {
         200
     };
-    let template = filename_template
+    let mut template = filename_template
         .map(|t| t.to_string())
         .unwrap_or_else(|| format!("%(title).{}s [%(id)s].%(ext)s", max_name));
-    let output_template = output_dir.join(&template).to_string_lossy().to_string();
+    if separate_tracks {
+        // Both tracks can share an extension, so tell them apart.
+        template = template.replace(".%(ext)s", ".%(format_id)s.%(ext)s");
+    }
+    // With a temp folder set, yt-dlp keeps `.part` files and merge inputs
+    // in a folder of this download's own there and moves the finished file
+    // into `output_dir` itself.
+    let separate_work_dir = crate::core::temp_dir::for_output(output_dir) != output_dir;
+    let work_dir = separate_work_dir.then(|| crate::core::temp_dir::work_dir_for(output_dir));
+    let output_template = if separate_work_dir {
+        template.clone()
+    } else {
+        output_dir.join(&template).to_string_lossy().to_string()
+    };
 
     std::fs::create_dir_all(output_dir)?;
 
@@ -2084,6 +2483,12 @@ pub async fn download_video(
             base_args.push("-x".to_string());
             base_args.push("--audio-format".to_string());
             base_args.push(target_fmt.to_string());
+            if convert_keep_original_setting() {
+                // The file as downloaded, before extraction replaces it.
+                base_args.push("-k".to_string());
+                base_args.push("--print".to_string());
+                base_args.push("post_process:OMNIGET_ORIGINAL:%(filepath)s".to_string());
+            }
         }
     }
 
@@ -2193,6 +2598,15 @@ pub async fn download_video(
         output_template,
         "--skip-unavailable-fragments".to_string(),
     ]);
+    if let Some(work_dir) = &work_dir {
+        std::fs::create_dir_all(work_dir.path())?;
+        base_args.extend([
+            "-P".to_string(),
+            format!("home:{}", output_dir.to_string_lossy()),
+            "-P".to_string(),
+            format!("temp:{}", work_dir.path().to_string_lossy()),
+        ]);
+    }
 
     base_args.extend(proxy_args());
     base_args.extend(extra_flags.iter().cloned());
@@ -2222,9 +2636,13 @@ pub async fn download_video(
         base_args.push("--split-chapters".to_string());
     }
 
-    if embed_metadata_enabled() {
+    let music_tags = mode == "audio" && is_youtube_music_url(url);
+    if embed_metadata_enabled() || music_tags {
         base_args.push("--embed-metadata".to_string());
     }
+    if music_tags {
+        base_args.extend(youtube_music_metadata_args());
+    }
 
     if embed_thumbnail_enabled() {
         base_args.push("--embed-thumbnail".to_string());
@@ -2336,6 +2754,9 @@ pub async fn download_video(
             );
             tokio::time::sleep(std::time::Duration::from_secs(wait)).await;
             cleanup_part_files(output_dir).await;
+            if let Some(work_dir) = &work_dir {
+                cleanup_part_files(work_dir.path()).await;
+            }
         }
 
         let mut args = base_args.clone();
@@ -2359,15 +2780,21 @@ pub async fn download_video(
                 args.push("--downloader".to_string());
                 args.push(a2_path.to_string_lossy().to_string());
                 args.push("--downloader-args".to_string());
-                let aria2c_proxy = match crate::core::http_client::proxy_url() {
+                let mut aria2c_extra = match crate::core::http_client::proxy_url() {
                     Some(url) => format!(" --all-proxy={}", url),
                     None => String::new(),
                 };
-                args.push(format!("aria2c:-x {} -k 1M -j {} --min-split-size=1M --file-allocation=none --optimize-concurrent-downloads=true --auto-file-renaming=false --summary-interval=1 --console-log-level=warn{}", conns, conns, aria2c_proxy));
+                // yt-dlp's --limit-rate only applies to its native downloader.
+                if let Some(rate) = speed_limit_value() {
+                    aria2c_extra.push_str(&format!(" --max-overall-download-limit={}", rate));
+                }
+                args.push(format!("aria2c:-x {} -k 1M -j {} --min-split-size=1M --file-allocation=none --optimize-concurrent-downloads=true --auto-file-renaming=false --summary-interval=1 --console-log-level=warn{}", conns, conns, aria2c_extra));
             }
         }
 
         args.extend(extra_args.iter().cloned());
+        // Last so they win over the managed flags they repeat.
+        args.extend(user_args.iter().cloned());
         args.push(url.to_string());
 
         let mut cmd = crate::core::process::command(ytdlp);
@@ -2399,6 +2826,11 @@ pub async fn download_video(
         let progress_tx = progress.clone();
         let captured_path: Arc<Mutex<Option<PathBuf>>> = Arc::new(Mutex::new(None));
         let captured_path_writer = captured_path.clone();
+        let captured_original: Arc<Mutex<Option<PathBuf>>> = Arc::new(Mutex::new(None));
+        let captured_original_writer = captured_original.clone();
+        // Every finished file; separate tracks print one line per track.
+        let captured_tracks: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(Vec::new()));
+        let captured_tracks_writer = captured_tracks.clone();
         let log_id = log_hook::current_download_id();
 
         let line_reader = tokio::spawn(async move {
@@ -2421,12 +2853,23 @@ pub async fn download_video(
                         _timer_start.elapsed()
                     );
                 }
+                if let Some(rest) = line.strip_prefix("OMNIGET_ORIGINAL:") {
+                    let original = rest.trim();
+                    if !original.is_empty() && original != "NA" {
+                        *captured_original_writer.lock().unwrap() = Some(PathBuf::from(original));
+                    }
+                    continue;
+                }
                 if let Some(rest) = line.strip_prefix("OMNIGET_FILEPATH:") {
                     let final_path = rest.trim();
                     if !final_path.is_empty() && final_path != "NA" {
                         authoritative_capture = true;
                         let mut guard = captured_path_writer.lock().unwrap();
                         *guard = Some(PathBuf::from(final_path));
+                        captured_tracks_writer
+                            .lock()
+                            .unwrap()
+                            .push(PathBuf::from(final_path));
                     }
                     continue;
                 }
@@ -2445,6 +2888,20 @@ pub async fn download_video(
                         *guard = Some(dest_path);
                     }
                 }
+                if let (Some(len), Some(done)) = (section_secs, parse_ffmpeg_out_time(&line)) {
+                    let pct = (done / len * 100.0).clamp(0.0, 99.0);
+                    if pct > max_reported && last_send.elapsed() >= throttle {
+                        max_reported = pct;
+                        let _ = progress_tx.send(ProgressUpdate::percent(pct)).await;
+                        last_send = std::time::Instant::now();
+                    }
+                    continue;
+                }
+                if let Some(id) = log_id {
+                    if let Some(phase) = line_phase(&line) {
+                        log_hook::emit_phase(id, phase);
+                    }
+                }
                 if line.contains("[Merger]") {
                     let merging_progress = max_reported.max(95.0).min(98.0);
                     if merging_progress > max_reported {
@@ -2536,6 +2993,9 @@ pub async fn download_video(
                 let _ = line_reader.await;
                 let _ = stderr_reader.await;
                 cleanup_part_files(output_dir).await;
+                if let Some(work_dir) = &work_dir {
+                    cleanup_part_files(work_dir.path()).await;
+                }
                 tracing::debug!("[perf] download_video took {:?}", _timer_start.elapsed());
                 anyhow::bail!("Download cancelled");
             }
@@ -2597,13 +3057,23 @@ pub async fn download_video(
                 convert_vtt_sidecars_to_srt(&file_path).await;
             }
 
+            let mut extra_files: Vec<PathBuf> =
+                std::mem::take(&mut *captured_tracks.lock().unwrap());
+            extra_files.extend(captured_original.lock().unwrap().take());
+            if download_subtitles {
+                extra_files.extend(subtitle_sidecars(&file_path, download_started_at));
+            }
+            if user_args.iter().any(|a| a == "--write-info-json") {
+                extra_files.push(file_path.with_extension("info.json"));
+            }
+            extra_files.retain(|p| *p != file_path && p.is_file());
+
             let meta = std::fs::metadata(&file_path)?;
             tracing::debug!("[perf] download_video took {:?}", _timer_start.elapsed());
             return Ok(DownloadResult {
-                file_path,
-                file_size_bytes: meta.len(),
-                duration_seconds: 0.0,
-                torrent_id: None,
+                note,
+                extra_files,
+                ..DownloadResult::new(file_path, meta.len())
             });
         }
 
@@ -2802,6 +3272,68 @@ pub async fn download_video(
     Err(translate_ytdlp_error(&last_error))
 }
 
+/// Multi-quality mode: runs `download_video` once per quality height with
+/// the quality label added to the filename. Progress is split evenly across
+/// the files; a quality that fails is skipped unless every one does.
+pub async fn download_qualities(
+    ytdlp: &Path,
+    qualities: &[&VideoQuality],
+    opts: &DownloadOptions,
+    referer: Option<&str>,
+    download_subtitles: bool,
+    progress: mpsc::Sender<ProgressUpdate>,
+) -> anyhow::Result<DownloadResult> {
+    if qualities.is_empty() {
+        return Err(anyhow!("None of the requested qualities are available"));
+    }
+    let template = opts
+        .filename_template
+        .as_deref()
+        .unwrap_or("%(title)s [%(id)s].%(ext)s");
+
+    let mut results = Vec::new();
+    let mut last_err = None;
+    for (i, quality) in qualities.iter().enumerate() {
+        if opts.cancel_token.is_cancelled() {
+            anyhow::bail!("Download cancelled");
+        }
+        let (part_tx, forwarder) = forward_part(progress.clone(), i, qualities.len());
+        let quality_template = with_quality_suffix(template, quality.short_label());
+        match download_video(
+            ytdlp,
+            &quality.url,
+            &opts.output_dir,
+            Some(quality.height).filter(|h| *h > 0),
+            part_tx,
+            opts.download_mode.as_deref(),
+            None,
+            Some(&quality_template),
+            referer,
+            opts.cancel_token.clone(),
+            None,
+            opts.concurrent_fragments,
+            download_subtitles,
+            &[],
+            opts.audio_format.as_deref(),
+            &opts.quality_preference,
+            &opts.extra_ytdlp_args,
+        )
+        .await
+        {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                tracing::warn!("[yt-dlp] quality {} failed: {}", quality.label, e);
+                last_err = Some(e);
+            }
+        }
+        let _ = forwarder.await;
+    }
+
+    let _ = progress.send(ProgressUpdate::percent(100.0)).await;
+    DownloadResult::combine(results)
+        .ok_or_else(|| last_err.unwrap_or_else(|| anyhow!("No quality was downloaded")))
+}
+
 async fn convert_vtt_sidecars_to_srt(video_path: &Path) {
     let dir = match video_path.parent() {
         Some(d) => d.to_path_buf(),
@@ -2937,6 +3469,45 @@ fn collect_subtitle_candidates(
     }
 }
 
+/// Subtitle files written next to `media_path` by this download, named
+/// exactly `{stem}.{lang}.{ext}` such as `Title [id].en.vtt`. Files older
+/// than `started_at` were already there and are left out.
+fn subtitle_sidecars(media_path: &Path, started_at: std::time::SystemTime) -> Vec<PathBuf> {
+    let (Some(dir), Some(stem)) = (
+        media_path.parent(),
+        media_path.file_stem().and_then(|s| s.to_str()),
+    ) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", stem);
+    let cutoff = started_at
+        .checked_sub(std::time::Duration::from_secs(5))
+        .unwrap_or(started_at);
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut sidecars: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| {
+            e.metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|m| m >= cutoff)
+        })
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && is_subtitle_path(p)
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_prefix(&prefix))
+                    .and_then(|rest| rest.split_once('.'))
+                    .is_some_and(|(lang, ext)| !lang.is_empty() && !ext.contains('.'))
+        })
+        .collect();
+    sidecars.sort();
+    sidecars
+}
+
 fn is_subtitle_path(path: &Path) -> bool {
     let ext = path
         .extension()
@@ -2984,7 +3555,9 @@ fn move_file_best_effort(from: &Path, to: &Path) -> bool {
     false
 }
 
-fn sanitize_log_line(line: &str) -> String {
+/// Drops the query string from every URL in `line`; signed CDN links and
+/// tokens live there.
+pub fn sanitize_log_line(line: &str) -> String {
     let mut result = String::with_capacity(line.len());
     let mut remaining = line;
     loop {
@@ -3015,6 +3588,15 @@ fn sanitize_log_line(line: &str) -> String {
     result
 }
 
+/// yt-dlp appends "This video is available in Japan, Korea." to geo
+/// restriction errors when the extractor knows the allowed countries.
+fn geo_country_hint(stderr: &str) -> Option<String> {
+    const MARKER: &str = "this video is available in ";
+    let start = stderr.to_ascii_lowercase().find(MARKER)? + MARKER.len();
+    let countries = stderr.get(start..)?.split(['.', '\n']).next()?.trim();
+    (!countries.is_empty()).then(|| countries.to_string())
+}
+
 fn translate_ytdlp_error(stderr: &str) -> anyhow::Error {
     let lower = stderr.to_lowercase();
 
@@ -3028,6 +3610,12 @@ fn translate_ytdlp_error(stderr: &str) -> anyhow::Error {
         );
     }
 
+    if crate::core::errors::is_geo_block_message(stderr) {
+        return DownloadError::GeoBlocked {
+            country_hint: geo_country_hint(stderr),
+        }
+        .into();
+    }
     if lower.contains("http error 429") {
         return anyhow!("Server returned error 429 (too many requests). Try again later.");
     }
@@ -3071,9 +3659,6 @@ fn translate_ytdlp_error(stderr: &str) -> anyhow::Error {
     if lower.contains("copyright") {
         return anyhow!("Video blocked due to copyright.");
     }
-    if lower.contains("geo") && lower.contains("block") {
-        return anyhow!("Video restricted in your region.");
-    }
     if lower.contains("timed out") || lower.contains("timeout") {
         return anyhow!("Connection timed out. Check your internet and try again.");
     }
@@ -3120,7 +3705,7 @@ fn translate_ytdlp_error(stderr: &str) -> anyhow::Error {
 
 pub fn get_rate_limit_stats() -> serde_json::Value {
     serde_json::json!({
-        "rate_limit_429_count": RATE_LIMIT_429_COUNT.load(Ordering::Relaxed)
+        "rate_limit_429_count": rate_limit_429_count()
     })
 }
 
@@ -3196,7 +3781,7 @@ fn parse_downloaded_bytes_line(line: &str) -> Option<u64> {
         .map(|v| v as u64)
 }
 
-fn parse_size_token(token: &str) -> Option<f64> {
+pub(crate) fn parse_size_token(token: &str) -> Option<f64> {
     let t = token.trim().trim_end_matches("/s").trim();
     let split = t.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(t.len());
     let (num, unit) = t.split_at(split);
@@ -3389,12 +3974,67 @@ pub fn parse_formats(json: &serde_json::Value) -> Vec<FormatInfo> {
             has_video,
             has_audio,
             format_note,
+            needs_mux: false,
+            progressive: false,
         });
     }
 
     result
 }
 
+/// Sets fps, codecs, bitrate and size on `quality` from a yt-dlp format
+/// entry, replacing whatever another format put there. Fields the entry
+/// lacks are cleared.
+pub fn apply_format_details(quality: &mut VideoQuality, format: &serde_json::Value) {
+    let codec = |key: &str| {
+        format
+            .get(key)
+            .and_then(|v| v.as_str())
+            .filter(|c| *c != "none")
+            .map(|c| c.to_string())
+    };
+    quality.fps = format.get("fps").and_then(|v| v.as_f64());
+    quality.vcodec = codec("vcodec");
+    quality.acodec = codec("acodec");
+    quality.bitrate = format
+        .get("tbr")
+        .and_then(|v| v.as_f64())
+        .map(|tbr| (tbr * 1000.0) as u64);
+    quality.filesize = format
+        .get("filesize")
+        .or_else(|| format.get("filesize_approx"))
+        .and_then(|v| v.as_u64());
+}
+
+/// A format's total bitrate in kbit/s, or 0 when yt-dlp doesn't know it.
+/// Among the formats of one height, yt-dlp downloads the one with the
+/// highest.
+pub fn format_tbr(format: &serde_json::Value) -> f64 {
+    format.get("tbr").and_then(|v| v.as_f64()).unwrap_or(0.0)
+}
+
+/// Whether a yt-dlp info dict describes a stream that is on air right now.
+pub fn is_live_info(json: &serde_json::Value) -> bool {
+    json.get("is_live")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+        || json.get("live_status").and_then(|v| v.as_str()) == Some("is_live")
+}
+
+/// Refuses live media unless live recording is on. Only yt-dlp can record
+/// a stream until it ends, so native qualities are refused either way
+/// instead of downloading a truncated playlist.
+pub fn ensure_live_allowed(info: &MediaInfo) -> anyhow::Result<()> {
+    if !info.is_live {
+        return Ok(());
+    }
+    let via_ytdlp = info.available_qualities.iter().all(|q| q.format == "ytdlp");
+    if !record_live_enabled() || !via_ytdlp {
+        return Err(DownloadError::LiveStream.into());
+    }
+    Ok(())
+}
+
 fn extract_id_from_url(url: &str) -> Option<String> {
     let parsed = url::Url::parse(url).ok()?;
     let host = parsed.host_str()?.to_lowercase();
@@ -3411,7 +4051,8 @@ fn extract_id_from_url(url: &str) -> Option<String> {
 
     if host.contains("youtube.com") {
         let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
-        if segments.first() == Some(&"shorts") {
+        // Not `/clip/`: a clip's ID isn't the video ID its file is named after.
+        if matches!(segments.first(), Some(&"shorts" | &"live")) {
             return segments.get(1).map(|s| s.to_string());
         }
         return parsed
@@ -3437,6 +4078,56 @@ fn extract_id_from_url(url: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn user_args_refuse_managed_and_unsafe_flags() {
+        let args: Vec<String> = [
+            "--extractor-args",
+            "youtube:player_client=android",
+            "--exec",
+            "rm -rf ~",
+            "--output=%(id)s.%(ext)s",
+            "-q",
+            "--no-part",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let (kept, refused) = sanitize_user_args(&args, false);
+        assert_eq!(
+            kept,
+            vec![
+                "--extractor-args",
+                "youtube:player_client=android",
+                "--no-part"
+            ]
+        );
+        assert_eq!(refused, vec!["--exec", "--output", "-q"]);
+
+        let (kept, refused) = sanitize_user_args(&args, true);
+        assert!(kept.contains(&"--exec".to_string()));
+        assert_eq!(refused, vec!["--output", "-q"]);
+
+        let (_, refused) = sanitize_user_args(&["-oout.mp4".to_string()], true);
+        assert_eq!(refused, vec!["-o"]);
+
+        let args: Vec<String> = [
+            "--ffmpeg-location",
+            "/tmp/ff",
+            "-U",
+            "--update-to",
+            "nightly",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let (kept, refused) = sanitize_user_args(&args, false);
+        assert!(kept.is_empty());
+        assert_eq!(refused, vec!["--ffmpeg-location", "-U", "--update-to"]);
+        let (_, refused) = sanitize_user_args(&args, true);
+        assert_eq!(refused, vec!["-U", "--update-to"]);
+    }
+
     #[test]
     fn parse_progress_download_prefix() {
         assert_eq!(parse_progress_line("download:  45.2%"), Some(45.2));
@@ -3584,6 +4275,27 @@ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  yt-dlp.exe\n\
         assert_eq!(parse_eta_line("  45.2%|eta:30"), None);
     }
 
+    #[test]
+    fn timestamps_and_sections_parse() {
+        assert_eq!(parse_timestamp("90"), Some(90.0));
+        assert_eq!(parse_timestamp("90s"), Some(90.0));
+        assert_eq!(parse_timestamp("1h2m3s"), Some(3723.0));
+        assert_eq!(parse_timestamp("1:30"), Some(90.0));
+        assert_eq!(parse_timestamp("01:02:03.5"), Some(3723.5));
+        assert_eq!(parse_timestamp("1m30"), None);
+        assert_eq!(parse_timestamp("soon"), None);
+
+        assert_eq!(parse_section("1:30-2:00"), Some((90.0, Some(120.0))));
+        assert_eq!(parse_section("90-inf"), Some((90.0, None)));
+        assert_eq!(parse_section("120-90"), None);
+
+        let flags = ["--download-sections".to_string(), "*90-150".to_string()];
+        assert_eq!(section_length(&flags), Some(60.0));
+        assert_eq!(section_length(&flags[..1]), None);
+        assert_eq!(parse_ffmpeg_out_time("out_time_us=30000000"), Some(30.0));
+        assert_eq!(parse_ffmpeg_out_time("frame=12"), None);
+    }
+
     #[test]
     fn parse_progress_garbage_returns_none() {
         assert_eq!(parse_progress_line("[info] Writing video subtitles"), None);
@@ -3629,6 +4341,12 @@ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  yt-dlp.exe\n\
             parse_destination_line("[Merger] Merging formats into \"/tmp/video.mp4\""),
             Some("/tmp/video.mp4".to_string())
         );
+        assert_eq!(
+            parse_destination_line(
+                "[MoveFiles] Moving file \"/scratch/video.mp4\" to \"/home/u/video.mp4\""
+            ),
+            Some("/home/u/video.mp4".to_string())
+        );
     }
 
     #[test]
@@ -3663,6 +4381,18 @@ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  yt-dlp.exe\n\
         assert!(!is_youtube_url("https://vimeo.com/123456"));
     }
 
+    #[test]
+    fn youtube_music_urls_get_library_tags() {
+        assert!(is_youtube_music_url(
+            "https://music.youtube.com/watch?v=abc&list=OLAK5uy_x"
+        ));
+        assert!(!is_youtube_music_url("https://www.youtube.com/watch?v=abc"));
+        let args = youtube_music_metadata_args();
+        assert_eq!(args.len(), 6);
+        assert!(args.chunks(2).all(|pair| pair[0] == "--parse-metadata"));
+        assert!(args.contains(&"%(track,title)s:%(meta_title)s".to_string()));
+    }
+
     #[test]
     fn sanitize_strips_query_params() {
         let input = "Error downloading https://example.com/video?token=secret&key=123 failed";
@@ -3723,6 +4453,14 @@ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  yt-dlp.exe\n\
         );
     }
 
+    #[test]
+    fn extract_id_live() {
+        assert_eq!(
+            extract_id_from_url("https://www.youtube.com/live/abc123?feature=share"),
+            Some("abc123".to_string())
+        );
+    }
+
     #[test]
     fn extract_id_non_youtube() {
         assert_eq!(extract_id_from_url("https://vimeo.com/123456"), None);
@@ -3763,6 +4501,24 @@ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  yt-dlp.exe\n\
         );
     }
 
+    #[test]
+    fn translate_error_geo_blocked() {
+        let err = translate_ytdlp_error(
+            "ERROR: [Niconico] sm9: The uploader has not made this video available in your country. This video is available in Japan.\nYou might want to use a VPN or a proxy server (with --proxy) to workaround.",
+        );
+        assert_eq!(
+            err.downcast_ref::<DownloadError>(),
+            Some(&DownloadError::GeoBlocked {
+                country_hint: Some("Japan".to_string())
+            })
+        );
+
+        let err = translate_ytdlp_error(
+            "Video unavailable. This video is not available from your location",
+        );
+        assert_eq!(crate::core::errors::classify_error(&err), "geo_blocked");
+    }
+
     #[test]
     fn translate_error_private() {
         let err = translate_ytdlp_error("This is a private video");
@@ -3787,6 +4543,58 @@ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  yt-dlp.exe\n\
         assert!(parse_formats(&json).is_empty());
     }
 
+    #[test]
+    fn apply_format_details_skips_none_codecs() {
+        let mut quality = VideoQuality {
+            label: "1080p".to_string(),
+            height: 1080,
+            ..Default::default()
+        };
+        apply_format_details(
+            &mut quality,
+            &serde_json::json!({
+                "fps": 60.0,
+                "vcodec": "avc1.640028",
+                "acodec": "none",
+                "tbr": 4500.5,
+                "filesize_approx": 12_345u64
+            }),
+        );
+        assert_eq!(quality.fps, Some(60.0));
+        assert_eq!(quality.vcodec.as_deref(), Some("avc1.640028"));
+        assert_eq!(quality.acodec, None);
+        assert_eq!(quality.bitrate, Some(4_500_500));
+        assert_eq!(quality.filesize, Some(12_345));
+    }
+
+    #[test]
+    fn live_media_is_refused_without_recording() {
+        assert!(is_live_info(&serde_json::json!({ "is_live": true })));
+        assert!(is_live_info(
+            &serde_json::json!({ "live_status": "is_live" })
+        ));
+        assert!(!is_live_info(
+            &serde_json::json!({ "live_status": "was_live" })
+        ));
+
+        let mut info = MediaInfo {
+            available_qualities: vec![VideoQuality {
+                format: "ytdlp".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(ensure_live_allowed(&info).is_ok());
+
+        info.is_live = true;
+        let err = ensure_live_allowed(&info).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<DownloadError>(),
+            Some(&DownloadError::LiveStream)
+        );
+        assert_eq!(crate::core::errors::classify_error(&err), "live_stream");
+    }
+
     #[test]
     fn parse_formats_extracts_fields() {
         let json = serde_json::json!({
@@ -3833,4 +4641,131 @@ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  yt-dlp.exe\n\
         assert!(formats[0].has_video);
         assert!(!formats[0].has_audio);
     }
+
+    #[test]
+    fn format_selector_matches_legacy_output_without_preference() {
+        let none = QualityPreference::default();
+        assert_eq!(
+            build_format_selector("auto", None, Some(720), true, &none),
+            "bv*[height<=720]+ba[ext=m4a]/bv*[height<=720]+ba/b[height<=720]/b"
+        );
+        assert_eq!(
+            build_format_selector("auto", None, None, true, &none),
+            "bv*+ba[ext=m4a]/bv*+ba/b"
+        );
+        assert_eq!(
+            build_format_selector("auto", None, None, false, &none),
+            "b/bv*"
+        );
+        assert_eq!(
+            build_format_selector("auto", None, Some(480), false, &none),
+            "b[height<=480]/bv*[height<=480]/b"
+        );
+        assert_eq!(
+            build_format_selector("mute", None, Some(480), true, &none),
+            "bv*[height<=480]/bv*/b"
+        );
+        assert_eq!(
+            build_format_selector("mute", None, None, true, &none),
+            "bv*/b"
+        );
+        assert_eq!(
+            build_format_selector("audio", None, None, true, &none),
+            "ba/b"
+        );
+        assert_eq!(
+            build_format_selector("auto", Some("137"), None, true, &none),
+            "137"
+        );
+        assert_eq!(
+            build_format_selector("audio", Some("251"), Some(720), true, &none),
+            "251/ba/b"
+        );
+    }
+
+    #[test]
+    fn format_selector_applies_quality_preference() {
+        let pref = QualityPreference {
+            max_height: Some(1080),
+            prefer_codec: Some("avc1".to_string()),
+            prefer_max_filesize_mb: Some(500),
+        };
+        assert_eq!(
+            build_format_selector("auto", None, Some(2160), true, &pref),
+            "bv*[height<=1080][filesize<?500M][vcodec^=avc1]+ba[ext=m4a]/\
+bv*[height<=1080][filesize<?500M][vcodec^=avc1]+ba/\
+bv*[height<=1080][filesize<?500M]+ba[ext=m4a]/\
+bv*[height<=1080][filesize<?500M]+ba/\
+b[height<=1080][filesize<?500M]/b"
+        );
+
+        let codec_only = QualityPreference {
+            prefer_codec: Some("vp9".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            build_format_selector("mute", Some("303"), None, true, &codec_only),
+            "303/bv*[vcodec^=vp9]/bv*/b"
+        );
+    }
+
+    #[test]
+    fn separate_tracks_selector_respects_height() {
+        let pref = QualityPreference {
+            max_height: Some(1080),
+            ..Default::default()
+        };
+        assert_eq!(
+            separate_tracks_selector(Some(720), &pref),
+            "(bv[height<=720]/bv/b),ba"
+        );
+        assert_eq!(
+            separate_tracks_selector(None, &QualityPreference::default()),
+            "(bv/b),ba"
+        );
+    }
+
+    #[test]
+    fn subtitle_sidecars_match_the_media_name() {
+        let dir = std::env::temp_dir().join(format!("omniget-sidecars-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "Clip [abc].mp4",
+            "Clip [abc].en.vtt",
+            "Clip [abc].pt-BR.srt",
+            "Clip [abc].jpg",
+            "Clip [abc].part2.en.vtt",
+            "Other [xyz].en.vtt",
+        ] {
+            std::fs::write(dir.join(name), b"x").unwrap();
+        }
+        let started = std::time::SystemTime::now();
+        let found: Vec<_> = subtitle_sidecars(&dir.join("Clip [abc].mp4"), started)
+            .into_iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(found, ["Clip [abc].en.vtt", "Clip [abc].pt-BR.srt"]);
+
+        let later = started + std::time::Duration::from_secs(60);
+        assert!(subtitle_sidecars(&dir.join("Clip [abc].mp4"), later).is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn line_phase_maps_download_and_postprocessor_lines() {
+        assert_eq!(
+            line_phase("[download] Destination: /tmp/a.f137.mp4"),
+            Some(DownloadPhase::Downloading)
+        );
+        assert_eq!(
+            line_phase("[Merger] Merging formats into \"/tmp/a.mp4\""),
+            Some(DownloadPhase::Muxing)
+        );
+        assert_eq!(
+            line_phase("[ExtractAudio] Destination: /tmp/a.mp3"),
+            Some(DownloadPhase::Converting)
+        );
+        assert_eq!(line_phase("[download]  42.0% of 10MiB"), None);
+        assert_eq!(line_phase("plain output"), None);
+    }
 }
@@ -1,12 +1,17 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::platforms::Platform;
 use tokio_util::sync::CancellationToken;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Platforms fill what they know and leave the rest to
+/// `..Default::default()`, so new optional fields don't touch every site.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MediaInfo {
+    /// The platform's own id for the item, for `%(id)s` in file names.
+    #[serde(default)]
+    pub id: Option<String>,
     pub title: String,
     pub author: String,
     pub platform: String,
@@ -15,10 +20,71 @@ pub struct MediaInfo {
     pub available_qualities: Vec<VideoQuality>,
     pub media_type: MediaType,
     pub file_size_bytes: Option<u64>,
+    /// Whether the stream picked by default carries a platform watermark.
+    /// `None` when the platform doesn't distinguish watermarked streams.
+    #[serde(default)]
+    pub watermarked: Option<bool>,
+    /// The media is a stream that is on air right now.
+    #[serde(default)]
+    pub is_live: bool,
+    /// Post details for the `.info.json` sidecar, from native extractors
+    /// that have them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PostMetadata>,
+}
+
+/// Caption, date and engagement of a post, as the platform's own JSON
+/// reported them. Counts the platform doesn't expose stay `None`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PostMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Unix seconds the post was published at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub like_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_count: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl PostMetadata {
+    /// `#tags` written in a caption, without the `#`, in order and once each.
+    pub fn hashtags_in(text: &str) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for word in text.split(|c: char| c.is_whitespace()) {
+            let Some(tag) = word.strip_prefix('#') else {
+                continue;
+            };
+            let tag: String = tag
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+}
+
+/// A count that platforms send either as a number or as a string.
+pub fn json_count(value: Option<&serde_json::Value>) -> Option<u64> {
+    let value = value?;
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub enum MediaType {
+    #[default]
     Video,
     Audio,
     Photo,
@@ -29,20 +95,308 @@ pub enum MediaType {
     File,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One downloadable rendition. The optional stream details mirror
+/// `FormatInfo` so native platforms can fill the same quality picker as
+/// yt-dlp; they stay `None` when the source doesn't say.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct VideoQuality {
+    /// Stable id that `DownloadOptions.format_id` can select, like a
+    /// yt-dlp format id. Native platforms derive it from the stream.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub label: String,
     pub width: u32,
     pub height: u32,
     pub url: String,
     pub format: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vcodec: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acodec: Option<String>,
+    /// Bits per second.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filesize: Option<u64>,
+    /// Video-only stream that needs ffmpeg to merge in the audio. The UI
+    /// says so in its own words; the label stays a plain height.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub needs_mux: bool,
+    /// Best single file with both video and audio, offered for installs
+    /// without ffmpeg.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub progressive: bool,
+}
+
+/// What to do when the planned output file already exists with content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverwritePolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    /// Keep both files by appending ` (1)`, ` (2)`, ... to the new one.
+    Rename,
+}
+
+/// How to download media whose best quality comes as separate video and
+/// audio streams when ffmpeg isn't there to merge them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FfmpegMissingPolicy {
+    /// Take the best stream that already has both, even if lower quality.
+    #[default]
+    SingleStream,
+    /// Keep the best quality and save video and audio as two files.
+    SeparateTracks,
+}
+
+/// Something about a finished download the user should be told, because
+/// the result isn't quite what they asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadNote {
+    /// ffmpeg was missing, so a single stream was picked over merging.
+    FfmpegMissingSingleStream,
+    /// ffmpeg was missing, so video and audio were saved side by side.
+    FfmpegMissingSeparateTracks,
+    /// ffmpeg was missing, so a muted download kept its audio.
+    FfmpegMissingKeptAudio,
+    /// ffmpeg was missing, so an audio-only download kept the video.
+    FfmpegMissingKeptVideo,
+    /// The video has no audio track (a GIF), so an audio-only download
+    /// kept it as it was.
+    NoAudioKeptVideo,
+}
+
+/// Coarse step a download is in, shown while there is no byte progress to
+/// report (long metadata fetches, muxing, conversion).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadPhase {
+    /// Following short links or redirects to the real media page.
+    Resolving,
+    /// Fetching metadata and picking formats.
+    Extracting,
+    Downloading,
+    /// Merging video and audio into one file.
+    Muxing,
+    /// Re-encoding or extracting audio after the download.
+    Converting,
+    Done,
+}
+
+impl DownloadPhase {
+    /// Same spelling as the serialized form.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Resolving => "resolving",
+            Self::Extracting => "extracting",
+            Self::Downloading => "downloading",
+            Self::Muxing => "muxing",
+            Self::Converting => "converting",
+            Self::Done => "done",
+        }
+    }
+}
+
+/// Constraints for picking the best available quality when no exact label
+/// is requested (or the requested label doesn't exist on this media).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualityPreference {
+    #[serde(default)]
+    pub max_height: Option<u32>,
+    /// Codec prefix such as `avc1`, `vp9` or `av01`; other codecs are still
+    /// used when nothing matches.
+    #[serde(default)]
+    pub prefer_codec: Option<String>,
+    /// Formats above this many MB are passed over while smaller ones exist.
+    /// Unlike `DownloadSettings::max_filesize_mb` it never fails a download.
+    #[serde(default, alias = "max_filesize_mb")]
+    pub prefer_max_filesize_mb: Option<u64>,
+}
+
+impl QualityPreference {
+    pub fn max_height(&self) -> Option<u32> {
+        self.max_height.filter(|h| *h > 0)
+    }
+
+    pub fn prefer_codec(&self) -> Option<&str> {
+        self.prefer_codec
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+    }
+
+    pub fn prefer_max_filesize_mb(&self) -> Option<u64> {
+        self.prefer_max_filesize_mb.filter(|mb| *mb > 0)
+    }
+
+    /// Highest quality within `max_height`, preferring `prefer_codec` when
+    /// the quality's format names one. Qualities without a known height
+    /// (`0`) are only used when nothing else fits.
+    pub fn pick<'a>(&self, qualities: &'a [VideoQuality]) -> Option<&'a VideoQuality> {
+        let fits = |q: &&VideoQuality| self.max_height().is_none_or(|max| q.height <= max);
+        let codec_rank = |q: &VideoQuality| {
+            self.prefer_codec()
+                .is_some_and(|c| q.format.to_lowercase().contains(&c.to_lowercase()))
+        };
+        qualities
+            .iter()
+            .filter(fits)
+            .max_by_key(|q| (q.height > 0, codec_rank(q), q.height))
+            .or_else(|| qualities.iter().min_by_key(|q| q.height))
+    }
+}
+
+/// Which of the available qualities a download saves. `All` and `List`
+/// save one file per quality, with the label appended to the filename.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase", tag = "mode", content = "value")]
+pub enum QualitySelection {
+    #[default]
+    Best,
+    Single(String),
+    All,
+    List(Vec<String>),
+}
+
+impl QualitySelection {
+    /// Reads the legacy `quality` string: `all` saves every quality, a
+    /// comma-separated list saves those labels, anything else is one quality.
+    pub fn from_quality(quality: Option<&str>) -> Self {
+        let Some(quality) = quality.map(str::trim).filter(|q| !q.is_empty()) else {
+            return Self::Best;
+        };
+        if quality.eq_ignore_ascii_case("all") {
+            return Self::All;
+        }
+        if quality.contains(',') {
+            let labels: Vec<String> = quality
+                .split(',')
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect();
+            return Self::List(labels);
+        }
+        match quality.to_ascii_lowercase().as_str() {
+            "best" | "highest" => Self::Best,
+            _ => Self::Single(quality.to_string()),
+        }
+    }
+
+    pub fn is_multi(&self) -> bool {
+        matches!(self, Self::All | Self::List(_))
+    }
+
+    /// Qualities a multi selection saves, in the order `qualities` lists
+    /// them and without repeated labels. Empty for `Best` and `Single`.
+    pub fn select<'a>(&self, qualities: &'a [VideoQuality]) -> Vec<&'a VideoQuality> {
+        let wanted = |q: &VideoQuality| match self {
+            Self::All => true,
+            Self::List(labels) => labels.iter().any(|l| q.matches_label(l)),
+            Self::Best | Self::Single(_) => false,
+        };
+        let mut seen = std::collections::HashSet::new();
+        qualities
+            .iter()
+            .filter(|q| wanted(q) && seen.insert(q.label.as_str()))
+            .collect()
+    }
+}
+
+impl VideoQuality {
+    /// Short label used in filenames: `1080p (HD)` becomes `1080p`.
+    pub fn short_label(&self) -> &str {
+        self.label.split_whitespace().next().unwrap_or(&self.label)
+    }
+
+    /// Case-insensitive match against the full or short label.
+    pub fn matches_label(&self, wanted: &str) -> bool {
+        let wanted = wanted.trim();
+        self.label.eq_ignore_ascii_case(wanted) || self.short_label().eq_ignore_ascii_case(wanted)
+    }
+
+    /// The quality `format_id` names, by id and then by label, else the
+    /// one labelled `label`. `None` when neither is requested or found.
+    pub fn find_requested<'a>(
+        qualities: &'a [VideoQuality],
+        format_id: Option<&str>,
+        label: Option<&str>,
+    ) -> Option<&'a VideoQuality> {
+        let by_format = format_id
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .and_then(|f| {
+                qualities
+                    .iter()
+                    .find(|q| q.id.as_deref() == Some(f))
+                    .or_else(|| qualities.iter().find(|q| q.matches_label(f)))
+            });
+        by_format.or_else(|| label.and_then(|l| qualities.iter().find(|q| q.matches_label(l))))
+    }
+
+    /// This quality as an entry of the format picker, so native platforms
+    /// list their streams the same way yt-dlp formats are listed.
+    pub fn to_format_info(&self) -> FormatInfo {
+        let has_video = self.height > 0 || self.vcodec.is_some();
+        FormatInfo {
+            format_id: self.id.clone().unwrap_or_else(|| self.label.clone()),
+            ext: self.format.clone(),
+            resolution: (self.width > 0 && self.height > 0)
+                .then(|| format!("{}x{}", self.width, self.height)),
+            width: (self.width > 0).then_some(self.width),
+            height: (self.height > 0).then_some(self.height),
+            fps: self.fps,
+            vcodec: self.vcodec.clone(),
+            acodec: self.acodec.clone(),
+            filesize: self.filesize,
+            tbr: self.bitrate.map(|b| b as f64 / 1000.0),
+            has_video,
+            has_audio: self.acodec.is_some() || !has_video,
+            format_note: Some(self.label.clone()),
+            needs_mux: self.needs_mux,
+            progressive: self.progressive,
+        }
+    }
+}
+
+impl MediaInfo {
+    /// Expected size of a single-file download: the reported size, else the
+    /// size yt-dlp (or the platform) gave for the `quality` label, else the
+    /// largest rendition's, since that's what "best" picks. `None` for
+    /// carousels and playlists, and when nothing reports a size.
+    pub fn estimated_size_bytes(&self, quality: Option<&str>) -> Option<u64> {
+        if let Some(size) = self.file_size_bytes.filter(|s| *s > 0) {
+            return Some(size);
+        }
+        if matches!(self.media_type, MediaType::Carousel | MediaType::Playlist) {
+            return None;
+        }
+        let requested = quality
+            .filter(|q| !q.eq_ignore_ascii_case("best"))
+            .and_then(|q| self.available_qualities.iter().find(|v| v.matches_label(q)))
+            .and_then(|v| v.filesize);
+        requested.or_else(|| {
+            self.available_qualities
+                .iter()
+                .filter_map(|v| v.filesize)
+                .max()
+        })
+    }
 }
 
 #[derive(Clone)]
 pub struct DownloadOptions {
     pub quality: Option<String>,
+    pub quality_selection: QualitySelection,
     pub output_dir: PathBuf,
     pub filename_template: Option<String>,
+    /// Subfolder template that was expanded into `output_dir`.
+    pub output_subfolder_template: Option<String>,
     pub download_subtitles: bool,
     pub include_auto_subtitles: bool,
     pub download_mode: Option<String>,
@@ -61,6 +415,158 @@ pub struct DownloadOptions {
     pub torrent_files: Option<Vec<usize>>,
     pub torrent_auto_trackers: bool,
     pub torrent_upnp: bool,
+    pub prefer_no_watermark: bool,
+    pub overwrite: OverwritePolicy,
+    pub quality_preference: QualityPreference,
+    pub hls_max_concurrent_segments: u32,
+    pub hls_max_retries: u32,
+    pub strip_metadata: bool,
+    /// User flags for yt-dlp, already sanitized, passed after the managed
+    /// ones.
+    pub extra_ytdlp_args: Vec<String>,
+    /// 0-based items of a carousel to download; `None` or empty means all.
+    /// Items keep their original number in the filename.
+    pub selected_indices: Option<Vec<usize>>,
+    /// Only download from `section_start` to `section_end` seconds, for
+    /// yt-dlp downloads; see `ytdlp::section_args`.
+    pub section_start: Option<f64>,
+    pub section_end: Option<f64>,
+    /// `downloaded-ids.txt` for "only new" mode. Platforms that download a
+    /// whole playlist in one item skip the entries it lists; yt-dlp gets
+    /// it as `--download-archive`.
+    pub download_archive: Option<PathBuf>,
+    /// Save `MediaInfo.metadata` as `<basename>.info.json` next to the
+    /// download; yt-dlp downloads get `--write-info-json` instead.
+    pub save_metadata_json: bool,
+}
+
+impl DownloadOptions {
+    /// Options for a plain best-quality download into `output_dir`, with
+    /// no template, cookies or post-processing; set the rest where needed.
+    pub fn new(output_dir: PathBuf) -> Self {
+        Self {
+            quality: None,
+            quality_selection: QualitySelection::Best,
+            output_dir,
+            filename_template: None,
+            output_subfolder_template: None,
+            download_subtitles: false,
+            include_auto_subtitles: false,
+            download_mode: None,
+            audio_format: None,
+            format_id: None,
+            referer: None,
+            extra_headers: None,
+            page_url: None,
+            user_agent: None,
+            cancel_token: CancellationToken::new(),
+            concurrent_fragments: 1,
+            ytdlp_path: None,
+            torrent_listen_port: None,
+            torrent_id_slot: None,
+            custom_ytdlp_args: None,
+            torrent_files: None,
+            torrent_auto_trackers: false,
+            torrent_upnp: false,
+            prefer_no_watermark: false,
+            overwrite: OverwritePolicy::default(),
+            quality_preference: QualityPreference::default(),
+            hls_max_concurrent_segments: 20,
+            hls_max_retries: 3,
+            strip_metadata: false,
+            extra_ytdlp_args: Vec::new(),
+            selected_indices: None,
+            section_start: None,
+            section_end: None,
+            download_archive: None,
+            save_metadata_json: false,
+        }
+    }
+
+    /// The quality a single-file native download saves: the one
+    /// `format_id` selects, else the `quality` label, else the best that
+    /// fits `quality_preference`.
+    pub fn pick_quality<'a>(&self, qualities: &'a [VideoQuality]) -> Option<&'a VideoQuality> {
+        VideoQuality::find_requested(
+            qualities,
+            self.format_id.as_deref(),
+            self.quality.as_deref(),
+        )
+        .or_else(|| self.quality_preference.pick(qualities))
+    }
+
+    /// Writes `<basename>.info.json` next to `media_path` with what the
+    /// extractor knew about the post, and returns its path. Does nothing
+    /// when the option is off, the platform gave no metadata, or yt-dlp
+    /// already wrote the file.
+    pub fn write_info_json(&self, info: &MediaInfo, media_path: &Path) -> Option<PathBuf> {
+        if !self.save_metadata_json {
+            return None;
+        }
+        let metadata = info.metadata.as_ref()?;
+        let path = media_path.with_extension("info.json");
+        if path.exists() {
+            return None;
+        }
+        let mut doc = serde_json::json!({
+            "id": info.id,
+            "platform": info.platform,
+            "title": info.title,
+            "author": info.author,
+        });
+        if let (Some(doc), Ok(serde_json::Value::Object(fields))) =
+            (doc.as_object_mut(), serde_json::to_value(metadata))
+        {
+            doc.extend(fields);
+        }
+        let written = serde_json::to_vec_pretty(&doc)
+            .map_err(std::io::Error::from)
+            .and_then(|bytes| std::fs::write(&path, bytes));
+        match written {
+            Ok(()) => Some(path),
+            Err(e) => {
+                tracing::warn!("[metadata] failed to write {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Whether the user asked for the video without its audio.
+    pub fn is_mute(&self) -> bool {
+        self.download_mode.as_deref() == Some("mute")
+    }
+
+    pub fn is_audio(&self) -> bool {
+        self.download_mode.as_deref() == Some("audio")
+    }
+
+    /// Whether item `index` of a multi-item post should be downloaded.
+    pub fn is_selected(&self, index: usize) -> bool {
+        match &self.selected_indices {
+            Some(indices) if !indices.is_empty() => indices.contains(&index),
+            _ => true,
+        }
+    }
+
+    /// File name for a native download of `info`, following
+    /// `filename_template` like yt-dlp would. Items of a multi-item post add
+    /// their number with `filename::with_quality_suffix`.
+    pub fn media_filename(&self, info: &MediaInfo, ext: &str) -> String {
+        let template = self
+            .filename_template
+            .as_deref()
+            .unwrap_or("%(title)s.%(ext)s");
+        crate::core::filename::render_filename(template, info, ext)
+    }
+
+    /// Where to save `filename` in `output_dir` under `overwrite`. When the
+    /// file already exists and the policy is `Skip`, the `Err` holds the
+    /// skipped result to return as-is, before any request is made.
+    pub fn output_path(&self, filename: &str) -> Result<PathBuf, DownloadResult> {
+        let path = self.output_dir.join(filename);
+        crate::core::filename::apply_overwrite_policy(&path, self.overwrite)
+            .ok_or_else(|| DownloadResult::skipped(path))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +584,11 @@ pub struct FormatInfo {
     pub has_video: bool,
     pub has_audio: bool,
     pub format_note: Option<String>,
+    /// See `VideoQuality::needs_mux`; only native listings set these two.
+    #[serde(default)]
+    pub needs_mux: bool,
+    #[serde(default)]
+    pub progressive: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,6 +599,70 @@ pub struct DownloadResult {
     /// Torrent ID within the shared librqbit session (magnet downloads only).
     #[serde(default)]
     pub torrent_id: Option<usize>,
+    /// The output already existed and `OverwritePolicy::Skip` left it alone;
+    /// `file_path` is the existing file.
+    #[serde(default)]
+    pub skipped: bool,
+    #[serde(default)]
+    pub note: Option<DownloadNote>,
+    /// Other files the download saved besides `file_path`: the rest of a
+    /// carousel or multi-quality download, or the original kept next to a
+    /// converted file. Files that were already on disk are never listed,
+    /// since removing a download can delete everything listed here.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_files: Vec<PathBuf>,
+}
+
+impl DownloadResult {
+    /// Result for a download that saved `file_size_bytes` to `file_path`
+    /// and nothing else; set the other fields where they differ.
+    pub fn new(file_path: PathBuf, file_size_bytes: u64) -> Self {
+        Self {
+            file_path,
+            file_size_bytes,
+            duration_seconds: 0.0,
+            torrent_id: None,
+            skipped: false,
+            note: None,
+            extra_files: Vec::new(),
+        }
+    }
+
+    /// Result for a download that was skipped because `path` already holds
+    /// the file.
+    pub fn skipped(path: PathBuf) -> Self {
+        let file_size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Self {
+            skipped: true,
+            ..Self::new(path, file_size_bytes)
+        }
+    }
+
+    /// Merges the files saved by a multi-quality download: the first file
+    /// stands in for the download, the others become `extra_files` and
+    /// sizes are summed and the first note is kept. Skipped results were
+    /// already on disk and are left out, unless every one was skipped.
+    /// `None` when empty.
+    pub fn combine(results: Vec<DownloadResult>) -> Option<DownloadResult> {
+        let (saved, skipped): (Vec<_>, Vec<_>) = results.into_iter().partition(|r| !r.skipped);
+        let total_bytes = saved.iter().map(|r| r.file_size_bytes).sum();
+        let mut results = saved.into_iter();
+        let Some(mut combined) = results.next() else {
+            return skipped.into_iter().next();
+        };
+        for rest in results {
+            combined.extra_files.push(rest.file_path);
+            combined.extra_files.extend(rest.extra_files);
+            combined.note = combined.note.or(rest.note);
+        }
+        combined.file_size_bytes = total_bytes;
+        Some(combined)
+    }
+
+    /// Every file the download saved, `file_path` first.
+    pub fn all_files(&self) -> impl Iterator<Item = &PathBuf> {
+        std::iter::once(&self.file_path).chain(self.extra_files.iter())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,3 +690,216 @@ pub struct DownloadedFile {
     pub media_type: MediaType,
     pub size_bytes: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quality(label: &str, height: u32, format: &str) -> VideoQuality {
+        VideoQuality {
+            label: label.to_string(),
+            width: 0,
+            height,
+            url: format!("https://example.com/{}", label),
+            format: format.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pick_respects_max_height_and_codec() {
+        let qualities = vec![
+            quality("2160p", 2160, "vp9"),
+            quality("1080p-vp9", 1080, "vp9"),
+            quality("1080p-avc", 1080, "avc1"),
+            quality("480p", 480, "avc1"),
+        ];
+        let pref = QualityPreference {
+            max_height: Some(1080),
+            prefer_codec: Some("avc1".to_string()),
+            prefer_max_filesize_mb: None,
+        };
+        assert_eq!(pref.pick(&qualities).unwrap().label, "1080p-avc");
+        assert_eq!(
+            QualityPreference::default().pick(&qualities).unwrap().label,
+            "2160p"
+        );
+    }
+
+    #[test]
+    fn find_requested_prefers_format_id_over_label() {
+        let mut hd = quality("1080p", 1080, "mp4");
+        hd.id = Some("dash-1080".to_string());
+        let sd = quality("480p", 480, "mp4");
+        let qualities = vec![hd, sd];
+        let find = |f: Option<&str>, l: Option<&str>| {
+            VideoQuality::find_requested(&qualities, f, l).map(|q| q.label.as_str())
+        };
+        assert_eq!(find(Some("dash-1080"), Some("480p")), Some("1080p"));
+        assert_eq!(find(Some("480p"), None), Some("480p"));
+        assert_eq!(find(Some("hls-720"), Some("480p")), Some("480p"));
+        assert_eq!(find(None, Some("best")), None);
+    }
+
+    #[test]
+    fn estimated_size_prefers_reported_then_requested_quality() {
+        let mut hd = quality("1080p", 1080, "mp4");
+        hd.filesize = Some(900);
+        let mut sd = quality("480p", 480, "mp4");
+        sd.filesize = Some(200);
+        let mut info = MediaInfo {
+            available_qualities: vec![sd, hd],
+            ..Default::default()
+        };
+        assert_eq!(info.estimated_size_bytes(Some("480p")), Some(200));
+        assert_eq!(info.estimated_size_bytes(Some("best")), Some(900));
+        assert_eq!(info.estimated_size_bytes(None), Some(900));
+
+        info.file_size_bytes = Some(50);
+        assert_eq!(info.estimated_size_bytes(Some("480p")), Some(50));
+
+        info.file_size_bytes = None;
+        info.media_type = MediaType::Playlist;
+        assert_eq!(info.estimated_size_bytes(None), None);
+    }
+
+    #[test]
+    fn combine_keeps_every_file() {
+        let result = |name: &str, bytes: u64| DownloadResult::new(PathBuf::from(name), bytes);
+        let combined = DownloadResult::combine(vec![
+            result("a.mp4", 10),
+            DownloadResult::skipped(PathBuf::from("old.mp4")),
+            result("b.mp4", 20),
+            result("c.mp4", 30),
+        ])
+        .unwrap();
+        assert_eq!(combined.file_path, PathBuf::from("a.mp4"));
+        assert_eq!(combined.file_size_bytes, 60);
+        let all: Vec<_> = combined.all_files().map(|p| p.to_str().unwrap()).collect();
+        assert_eq!(all, ["a.mp4", "b.mp4", "c.mp4"]);
+        assert!(DownloadResult::combine(Vec::new()).is_none());
+
+        let only_skipped =
+            DownloadResult::combine(vec![DownloadResult::skipped(PathBuf::from("old.mp4"))]);
+        assert!(only_skipped.is_some_and(|r| r.skipped && r.extra_files.is_empty()));
+    }
+
+    #[test]
+    fn hashtags_in_caption() {
+        assert_eq!(
+            PostMetadata::hashtags_in("Sunset #beach #Travel_2024! again #beach #"),
+            ["beach", "Travel_2024"]
+        );
+        assert_eq!(json_count(Some(&serde_json::json!("1200"))), Some(1200));
+        assert_eq!(json_count(Some(&serde_json::json!(5))), Some(5));
+    }
+
+    #[test]
+    fn output_path_follows_overwrite_policy() {
+        let dir = std::env::temp_dir().join(format!("omniget-output-path-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("clip.mp4"), b"data").unwrap();
+        let mut opts = DownloadOptions::new(dir.clone());
+
+        let skipped = opts.output_path("clip.mp4").unwrap_err();
+        assert!(skipped.skipped);
+        assert_eq!(skipped.file_size_bytes, 4);
+        assert_eq!(opts.output_path("new.mp4").unwrap(), dir.join("new.mp4"));
+
+        opts.overwrite = OverwritePolicy::Rename;
+        assert_eq!(
+            opts.output_path("clip.mp4").unwrap(),
+            dir.join("clip (1).mp4")
+        );
+        opts.overwrite = OverwritePolicy::Overwrite;
+        assert_eq!(opts.output_path("clip.mp4").unwrap(), dir.join("clip.mp4"));
+
+        assert!(opts.is_selected(7));
+        opts.selected_indices = Some(vec![1, 4]);
+        assert!(opts.is_selected(4));
+        assert!(!opts.is_selected(0));
+
+        let mut info = MediaInfo {
+            id: Some("42".to_string()),
+            title: "clip".to_string(),
+            author: "someone".to_string(),
+            platform: "tiktok".to_string(),
+            metadata: Some(PostMetadata {
+                description: Some("hi #cats".to_string()),
+                like_count: Some(7),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(opts.write_info_json(&info, &dir.join("clip.mp4")), None);
+        opts.save_metadata_json = true;
+        let sidecar = opts.write_info_json(&info, &dir.join("clip.mp4")).unwrap();
+        assert_eq!(sidecar, dir.join("clip.info.json"));
+        let doc: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&sidecar).unwrap()).unwrap();
+        assert_eq!(doc["author"], "someone");
+        assert_eq!(doc["like_count"], 7);
+        assert!(doc.get("view_count").is_none());
+        info.metadata = None;
+        assert_eq!(opts.write_info_json(&info, &dir.join("new.mp4")), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn pick_falls_back_to_smallest_when_nothing_fits() {
+        let qualities = vec![quality("1080p", 1080, "mp4"), quality("720p", 720, "mp4")];
+        let pref = QualityPreference {
+            max_height: Some(360),
+            ..Default::default()
+        };
+        assert_eq!(pref.pick(&qualities).unwrap().label, "720p");
+        assert!(pref.pick(&[]).is_none());
+    }
+
+    #[test]
+    fn quality_selection_reads_legacy_quality_string() {
+        assert_eq!(QualitySelection::from_quality(None), QualitySelection::Best);
+        assert_eq!(
+            QualitySelection::from_quality(Some("best")),
+            QualitySelection::Best
+        );
+        assert_eq!(
+            QualitySelection::from_quality(Some("ALL")),
+            QualitySelection::All
+        );
+        assert_eq!(
+            QualitySelection::from_quality(Some("720p")),
+            QualitySelection::Single("720p".to_string())
+        );
+        assert_eq!(
+            QualitySelection::from_quality(Some("1080p, 480p,")),
+            QualitySelection::List(vec!["1080p".to_string(), "480p".to_string()])
+        );
+    }
+
+    #[test]
+    fn quality_selection_picks_listed_labels_once() {
+        let qualities = vec![
+            quality("1080p (HD)", 1080, "mp4"),
+            quality("720p", 720, "mp4"),
+            quality("720p", 720, "webm"),
+            quality("480p", 480, "mp4"),
+        ];
+        let labels = |sel: QualitySelection| -> Vec<String> {
+            sel.select(&qualities)
+                .iter()
+                .map(|q| q.label.clone())
+                .collect()
+        };
+        assert_eq!(
+            labels(QualitySelection::All),
+            ["1080p (HD)", "720p", "480p"]
+        );
+        assert_eq!(
+            labels(QualitySelection::List(vec!["480P".into(), "1080p".into()])),
+            ["1080p (HD)", "480p"]
+        );
+        assert!(labels(QualitySelection::Single("720p".into())).is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+use tokio::sync::mpsc;
+
 #[derive(Debug, Clone, Default)]
 pub struct ProgressUpdate {
     pub percent: f64,
@@ -41,3 +43,51 @@ impl From<f64> for ProgressUpdate {
         Self::percent(percent)
     }
 }
+
+/// Forwards a sub-download's progress as part `index` of `count` equal
+/// parts of the overall progress, e.g. one file of a multi-quality download.
+/// The task ends once every clone of the returned sender is dropped.
+pub fn forward_part(
+    progress: mpsc::Sender<ProgressUpdate>,
+    index: usize,
+    count: usize,
+) -> (mpsc::Sender<ProgressUpdate>, tokio::task::JoinHandle<()>) {
+    let (tx, mut rx) = mpsc::channel::<ProgressUpdate>(16);
+    let count = count.max(1) as f64;
+    let handle = tokio::spawn(async move {
+        let mut max_pct = 0.0_f64;
+        while let Some(pu) = rx.recv().await {
+            max_pct = max_pct.max(pu.percent.clamp(0.0, 100.0));
+            let overall = (index as f64 * 100.0 + max_pct) / count;
+            let _ = progress
+                .send(ProgressUpdate::rich(
+                    overall,
+                    None,
+                    None,
+                    pu.speed_bps,
+                    None,
+                ))
+                .await;
+        }
+    });
+    (tx, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn forward_part_scales_into_its_share() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let (part, handle) = forward_part(tx, 1, 4);
+        part.send(ProgressUpdate::percent(50.0)).await.unwrap();
+        part.send(ProgressUpdate::percent(20.0)).await.unwrap();
+        drop(part);
+        handle.await.unwrap();
+
+        assert_eq!(rx.recv().await.unwrap().percent, 37.5);
+        // Progress never moves backwards within a part.
+        assert_eq!(rx.recv().await.unwrap().percent, 37.5);
+    }
+}
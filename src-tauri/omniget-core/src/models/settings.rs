@@ -1,5 +1,16 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::models::media::{FfmpegMissingPolicy, OverwritePolicy, QualityPreference};
+use crate::platforms::PlatformBackend;
+
+/// Shape of the stored settings written by this build. Bump it when a field
+/// is renamed, moved or changes type, and add the step to [`migrate`].
+pub const SETTINGS_SCHEMA_VERSION: u32 = 3;
+
+/// How many folders `recent_output_dirs` remembers.
+pub const RECENT_OUTPUT_DIRS_LIMIT: usize = 10;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
@@ -23,12 +34,17 @@ pub struct AppSettings {
     pub legal_acknowledged: bool,
     #[serde(default)]
     pub last_download_options: LastDownloadOptions,
+    /// Folders downloads finished into, most recent first.
+    #[serde(default)]
+    pub recent_output_dirs: Vec<PathBuf>,
     #[serde(default)]
     pub typography: TypographySettings,
     #[serde(default)]
     pub rpc: RpcSettings,
     #[serde(default)]
     pub bridge: BridgeSettings,
+    #[serde(default)]
+    pub local_api: LocalApiSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +93,43 @@ pub struct AppearanceSettings {
     pub language: String,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlatformDefault {
+    #[serde(default)]
+    pub quality: Option<String>,
+    /// `audio` or `mute`; anything else downloads video with audio.
+    #[serde(default)]
+    pub download_mode: Option<String>,
+    /// Format for audio-mode downloads, overriding `music_audio_format`.
+    #[serde(default)]
+    pub audio_format: Option<String>,
+}
+
+/// How downloaded files are named, for both yt-dlp and native downloaders.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilenameStyle {
+    Title,
+    #[default]
+    TitleWithId,
+    IdOnly,
+    /// Uses `DownloadSettings::filename_template`.
+    Custom,
+}
+
+impl FilenameStyle {
+    /// The yt-dlp output template for this style.
+    pub fn template(self, custom: &str) -> String {
+        match self {
+            FilenameStyle::Title => "%(title).200s.%(ext)s".into(),
+            FilenameStyle::TitleWithId => default_filename_template(),
+            FilenameStyle::IdOnly => "%(id)s.%(ext)s".into(),
+            FilenameStyle::Custom if custom.trim().is_empty() => default_filename_template(),
+            FilenameStyle::Custom => custom.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadSettings {
     pub default_output_dir: PathBuf,
@@ -93,10 +146,32 @@ pub struct DownloadSettings {
     pub clipboard_detection: bool,
     #[serde(default)]
     pub auto_download_on_paste: bool,
+    /// Watch the clipboard in the background and queue supported URLs,
+    /// see `core::clipboard_watch`.
+    #[serde(default)]
+    pub clipboard_watch: bool,
+    /// Native vs yt-dlp choice per platform, keyed by downloader name such
+    /// as `youtube` or `twitter`. Missing platforms use `Auto`.
+    #[serde(default)]
+    pub platform_backend: HashMap<String, PlatformBackend>,
+    /// Choices used for downloads from a platform that don't make their
+    /// own, keyed like `platform_backend`.
+    #[serde(default)]
+    pub platform_defaults: HashMap<String, PlatformDefault>,
+    /// What to do when the best quality needs merging and ffmpeg is
+    /// missing.
+    #[serde(default)]
+    pub ffmpeg_missing_policy: FfmpegMissingPolicy,
+    #[serde(default)]
+    pub filename_style: FilenameStyle,
     #[serde(default = "default_filename_template")]
     pub filename_template: String,
     #[serde(default)]
     pub organize_by_platform: bool,
+    /// Subfolder template such as `%(platform)s/%(author)s/`, see
+    /// `core::filename::render_subfolder_template`.
+    #[serde(default)]
+    pub output_subfolder_template: Option<String>,
     #[serde(default)]
     pub download_subtitles: bool,
     #[serde(default)]
@@ -117,8 +192,14 @@ pub struct DownloadSettings {
     pub split_by_chapters: bool,
     #[serde(default)]
     pub live_from_start: bool,
+    /// Record ongoing live streams until they end instead of refusing them.
+    #[serde(default)]
+    pub record_live: bool,
     #[serde(default)]
     pub speed_limit: String,
+    /// Global cap across all downloads; takes precedence over `speed_limit`.
+    #[serde(default)]
+    pub max_download_speed_kbps: Option<u64>,
     #[serde(default)]
     pub hotkey_enabled: bool,
     #[serde(default = "default_hotkey_binding")]
@@ -133,8 +214,13 @@ pub struct DownloadSettings {
     pub music_hotkey_binding: String,
     #[serde(default = "default_music_audio_format")]
     pub music_audio_format: String,
+    /// Passed to yt-dlp after the managed flags, see
+    /// `ytdlp::sanitize_user_args`.
     #[serde(default)]
     pub extra_ytdlp_flags: Vec<String>,
+    /// Lets `extra_ytdlp_flags` use flags that run commands, like `--exec`.
+    #[serde(default)]
+    pub extra_ytdlp_flags_unsafe: bool,
     #[serde(default = "default_true")]
     pub copy_to_clipboard_on_hotkey: bool,
     #[serde(default)]
@@ -175,6 +261,134 @@ pub struct DownloadSettings {
     pub bilibili_preferred_codec: u32,
     #[serde(default = "default_bilibili_preferred_audio_qn")]
     pub bilibili_preferred_audio_qn: u32,
+    #[serde(default = "default_true")]
+    pub tiktok_no_watermark: bool,
+    /// Download the media of every tweet in the author's own thread, not
+    /// only the linked tweet.
+    #[serde(default)]
+    pub twitter_full_thread: bool,
+    #[serde(default)]
+    pub overwrite_policy: OverwritePolicy,
+    #[serde(default)]
+    pub quality_preference: QualityPreference,
+    #[serde(default = "default_hls_max_retries")]
+    pub hls_max_retries: u32,
+    /// Whole-request timeout of the native downloaders' API clients.
+    #[serde(default = "default_http_timeout_secs")]
+    pub http_timeout_secs: u64,
+    #[serde(default = "default_http_connect_timeout_secs")]
+    pub http_connect_timeout_secs: u64,
+    /// Attempts a native file transfer gets before it fails.
+    #[serde(default = "default_http_max_retries")]
+    pub http_max_retries: u32,
+    /// Gives up on a native file transfer after this many minutes, so a
+    /// connection that trickles bytes can't hold a slot forever. 0 means
+    /// no limit.
+    #[serde(default)]
+    pub download_timeout_mins: u64,
+    /// Remove EXIF (including GPS), XMP and text metadata from downloaded photos.
+    #[serde(default)]
+    pub strip_metadata: bool,
+    /// Items expected to be larger than this many MB fail instead of
+    /// downloading, so one huge file doesn't stall a batch. 0 means no limit.
+    #[serde(default)]
+    pub max_filesize_mb: u64,
+    /// Where `.part` files and tracks waiting to be muxed go, see
+    /// `core::temp_dir`. `None` keeps them next to the output.
+    #[serde(default)]
+    pub temp_dir: Option<PathBuf>,
+    /// Decode each finished file with ffmpeg and flag the ones with
+    /// errors. Off by default: it reads the whole file again.
+    #[serde(default)]
+    pub verify_after_download: bool,
+    /// When an audio-mode download is converted to `music_audio_format`,
+    /// keep the downloaded file next to the converted one.
+    #[serde(default)]
+    pub convert_keep_original: bool,
+    /// Save the post's caption, author, date and stats as
+    /// `<basename>.info.json` next to each download.
+    #[serde(default)]
+    pub save_metadata_json: bool,
+}
+
+impl DownloadSettings {
+    pub const MAX_HLS_RETRIES: u32 = 10;
+    pub const MAX_HTTP_TIMEOUT_SECS: u64 = 3600;
+    pub const MAX_HTTP_CONNECT_TIMEOUT_SECS: u64 = 300;
+    pub const MAX_HTTP_RETRIES: u32 = 10;
+
+    /// Keeps the HLS retry count within sane bounds. Segment parallelism is
+    /// `AdvancedSettings::max_concurrent_segments`.
+    pub fn clamp_hls(&mut self) {
+        self.hls_max_retries = self.hls_max_retries.clamp(1, Self::MAX_HLS_RETRIES);
+    }
+
+    /// Keeps the HTTP timeouts and retry count within sane bounds.
+    pub fn clamp_http(&mut self) {
+        self.http_timeout_secs = self.http_timeout_secs.clamp(5, Self::MAX_HTTP_TIMEOUT_SECS);
+        self.http_connect_timeout_secs = self
+            .http_connect_timeout_secs
+            .clamp(1, Self::MAX_HTTP_CONNECT_TIMEOUT_SECS);
+        self.http_max_retries = self.http_max_retries.clamp(1, Self::MAX_HTTP_RETRIES);
+    }
+
+    /// Speed cap in KiB/s, falling back to the older yt-dlp style
+    /// `speed_limit` string (`"500K"`, `"2M"`, or bytes per second).
+    pub fn effective_speed_limit_kbps(&self) -> Option<u64> {
+        if let Some(kbps) = self.max_download_speed_kbps.filter(|k| *k > 0) {
+            return Some(kbps);
+        }
+        let raw = self.speed_limit.trim();
+        let (number, multiplier) = match raw.char_indices().last()? {
+            (i, 'k' | 'K') => (&raw[..i], 1.0),
+            (i, 'm' | 'M') => (&raw[..i], 1024.0),
+            _ => (raw, 1.0 / 1024.0),
+        };
+        let kbps = (number.trim().parse::<f64>().ok()? * multiplier).ceil();
+        (kbps >= 1.0).then_some(kbps as u64)
+    }
+
+    /// Mode, quality and audio format for a download from `platform`. What
+    /// the download asked for wins, then the platform's default, then the
+    /// global setting. The audio format is only set for audio downloads.
+    pub fn download_choices(
+        &self,
+        platform: &str,
+        download_mode: Option<String>,
+        quality: Option<String>,
+    ) -> (Option<String>, Option<String>, Option<String>) {
+        let default = self.platform_defaults.get(platform);
+        let download_mode = download_mode.or_else(|| {
+            default
+                .and_then(|d| d.download_mode.clone())
+                .filter(|mode| mode == "audio" || mode == "mute")
+        });
+        let quality = quality
+            .or_else(|| default.and_then(|d| d.quality.clone()))
+            .or_else(|| Some(self.video_quality.clone()));
+        let audio_format = (download_mode.as_deref() == Some("audio")).then(|| {
+            default
+                .and_then(|d| d.audio_format.clone())
+                .unwrap_or_else(|| self.music_audio_format.clone())
+        });
+        (download_mode, quality, audio_format)
+    }
+}
+
+fn default_hls_max_retries() -> u32 {
+    3
+}
+
+fn default_http_timeout_secs() -> u64 {
+    120
+}
+
+fn default_http_connect_timeout_secs() -> u64 {
+    15
+}
+
+fn default_http_max_retries() -> u32 {
+    3
 }
 
 fn default_bilibili_preferred_qn() -> u32 {
@@ -231,6 +445,22 @@ pub struct AdvancedSettings {
     pub concurrent_fragments: u32,
     #[serde(default = "default_stagger_delay_ms")]
     pub stagger_delay_ms: u64,
+    /// Wait before the first automatic retry of a failed download; each
+    /// further retry waits twice as long.
+    #[serde(default = "default_retry_base_delay_secs")]
+    pub retry_base_delay_secs: u64,
+    /// Cap on simultaneous downloads from one host, on top of
+    /// `max_concurrent_downloads`, so batches from one CDN don't get throttled.
+    #[serde(default = "default_max_downloads_per_host")]
+    pub max_downloads_per_host: u32,
+    /// Hours in which queued downloads may start, for running big batches
+    /// overnight. `None` starts them any time.
+    #[serde(default)]
+    pub allowed_hours: Option<AllowedHours>,
+    /// Hand large files from native downloaders (Vimeo, Twitch, Reddit...)
+    /// to aria2c for multi-connection transfers when it is available.
+    #[serde(default = "default_native_aria2c")]
+    pub native_aria2c: bool,
     #[serde(default = "default_torrent_listen_port")]
     pub torrent_listen_port: u16,
     #[serde(default = "default_torrent_auto_trackers")]
@@ -245,6 +475,62 @@ pub struct AdvancedSettings {
     pub twitter_manual_cookie: String,
     #[serde(default)]
     pub user_agent: String,
+    /// User-Agent and extra headers per native downloader, keyed by
+    /// downloader name such as `tiktok` or `reddit`. Missing platforms keep
+    /// their built-in headers.
+    #[serde(default)]
+    pub header_profiles: HashMap<String, HeaderProfile>,
+}
+
+/// HTTP identity override for one platform's native downloader.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HeaderProfile {
+    /// User-Agent pool; one entry is picked each time the platform's client
+    /// is built. Empty keeps the built-in User-Agent.
+    #[serde(default)]
+    pub user_agents: Vec<String>,
+    /// `Name: value` lines sent as default headers. Headers a downloader
+    /// sets on a specific request still take precedence.
+    #[serde(default)]
+    pub headers: Vec<String>,
+}
+
+/// Hours of the day, in local time, in which queued downloads may start.
+/// `end_hour` is exclusive and may be below `start_hour` for a window
+/// across midnight, like 23 to 7. Equal hours allow the whole day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowedHours {
+    pub start_hour: u8,
+    pub end_hour: u8,
+}
+
+impl AllowedHours {
+    /// Whether a download may start during `hour` (0-23).
+    pub fn contains(&self, hour: u8) -> bool {
+        let (start, end) = (self.start_hour % 24, self.end_hour % 24);
+        if start <= end {
+            start == end || (start..end).contains(&hour)
+        } else {
+            hour >= start || hour < end
+        }
+    }
+
+    pub fn is_open_now(&self) -> bool {
+        use chrono::Timelike;
+        self.contains(chrono::Local::now().hour() as u8)
+    }
+}
+
+impl AdvancedSettings {
+    pub const MIN_CONCURRENCY: u32 = 1;
+    pub const MAX_CONCURRENCY: u32 = 64;
+
+    /// Keeps user-edited segment/fragment parallelism within sane bounds.
+    pub fn clamp_concurrency(&mut self) {
+        let (min, max) = (Self::MIN_CONCURRENCY, Self::MAX_CONCURRENCY);
+        self.max_concurrent_segments = self.max_concurrent_segments.clamp(min, max);
+        self.concurrent_fragments = self.concurrent_fragments.clamp(min, max);
+    }
 }
 
 fn default_concurrent_fragments() -> u32 {
@@ -271,6 +557,14 @@ fn default_stagger_delay_ms() -> u64 {
     150
 }
 
+fn default_retry_base_delay_secs() -> u64 {
+    1
+}
+
+fn default_max_downloads_per_host() -> u32 {
+    2
+}
+
 fn default_torrent_auto_trackers() -> bool {
     true
 }
@@ -283,6 +577,10 @@ fn default_prevent_sleep() -> bool {
     true
 }
 
+fn default_native_aria2c() -> bool {
+    true
+}
+
 fn default_torrent_listen_port() -> u16 {
     6881
 }
@@ -425,6 +723,20 @@ fn default_bridge_enabled() -> bool {
     true
 }
 
+/// Optional local HTTP API for scripting OmniGet from other tools on the same
+/// machine. Unlike the extension bridge it stays off until a port is set, and
+/// both fields are only read at startup.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocalApiSettings {
+    /// Port on `127.0.0.1`. `None` keeps the server off.
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Bearer token required on every request.
+    /// Empty means "generate on next launch".
+    #[serde(default)]
+    pub token: String,
+}
+
 impl Default for TypographySettings {
     fn default() -> Self {
         Self {
@@ -441,13 +753,13 @@ impl Default for TypographySettings {
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
-            schema_version: 1,
+            schema_version: SETTINGS_SCHEMA_VERSION,
             appearance: AppearanceSettings {
                 theme: "system".into(),
                 language: "en".into(),
             },
             download: DownloadSettings {
-                default_output_dir: dirs::download_dir().unwrap_or_else(|| PathBuf::from(".")),
+                default_output_dir: crate::core::paths::default_output_dir(),
                 always_ask_path: false,
                 video_quality: "720p".into(),
                 skip_existing: true,
@@ -457,8 +769,14 @@ impl Default for AppSettings {
                 embed_thumbnail: true,
                 clipboard_detection: false,
                 auto_download_on_paste: false,
+                clipboard_watch: false,
+                platform_backend: HashMap::new(),
+                platform_defaults: HashMap::new(),
+                ffmpeg_missing_policy: FfmpegMissingPolicy::default(),
+                filename_style: FilenameStyle::default(),
                 filename_template: default_filename_template(),
                 organize_by_platform: false,
+                output_subfolder_template: None,
                 download_subtitles: false,
                 include_auto_subtitles: false,
                 caption_locale: default_caption_locale(),
@@ -473,7 +791,9 @@ impl Default for AppSettings {
                 ],
                 split_by_chapters: false,
                 live_from_start: false,
+                record_live: false,
                 speed_limit: String::new(),
+                max_download_speed_kbps: None,
                 hotkey_enabled: false,
                 hotkey_binding: default_hotkey_binding(),
                 clip_hotkey_enabled: false,
@@ -482,6 +802,7 @@ impl Default for AppSettings {
                 music_hotkey_binding: default_music_hotkey_binding(),
                 music_audio_format: default_music_audio_format(),
                 extra_ytdlp_flags: Vec::new(),
+                extra_ytdlp_flags_unsafe: false,
                 copy_to_clipboard_on_hotkey: true,
                 cookie_file: String::new(),
                 always_use_managed_cookies: true,
@@ -502,6 +823,21 @@ impl Default for AppSettings {
                 bilibili_preferred_qn: default_bilibili_preferred_qn(),
                 bilibili_preferred_codec: default_bilibili_preferred_codec(),
                 bilibili_preferred_audio_qn: default_bilibili_preferred_audio_qn(),
+                tiktok_no_watermark: true,
+                twitter_full_thread: false,
+                overwrite_policy: OverwritePolicy::default(),
+                quality_preference: QualityPreference::default(),
+                hls_max_retries: default_hls_max_retries(),
+                http_timeout_secs: default_http_timeout_secs(),
+                http_connect_timeout_secs: default_http_connect_timeout_secs(),
+                http_max_retries: default_http_max_retries(),
+                download_timeout_mins: 0,
+                strip_metadata: false,
+                max_filesize_mb: 0,
+                temp_dir: None,
+                verify_after_download: false,
+                convert_keep_original: false,
+                save_metadata_json: false,
             },
             advanced: AdvancedSettings {
                 max_concurrent_segments: 20,
@@ -509,6 +845,10 @@ impl Default for AppSettings {
                 max_concurrent_downloads: 2,
                 concurrent_fragments: 8,
                 stagger_delay_ms: 150,
+                retry_base_delay_secs: default_retry_base_delay_secs(),
+                max_downloads_per_host: default_max_downloads_per_host(),
+                allowed_hours: None,
+                native_aria2c: true,
                 torrent_listen_port: 6881,
                 torrent_auto_trackers: true,
                 torrent_upnp: true,
@@ -516,6 +856,7 @@ impl Default for AppSettings {
                 cookies_from_browser: String::new(),
                 twitter_manual_cookie: String::new(),
                 user_agent: String::new(),
+                header_profiles: HashMap::new(),
             },
             telegram: TelegramSettings::default(),
             proxy: ProxySettings::default(),
@@ -525,9 +866,270 @@ impl Default for AppSettings {
             portable_mode: false,
             legal_acknowledged: false,
             last_download_options: LastDownloadOptions::default(),
+            recent_output_dirs: Vec::new(),
             typography: TypographySettings::default(),
             rpc: RpcSettings::default(),
             bridge: BridgeSettings::default(),
+            local_api: LocalApiSettings::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Moves `dir` to the front of `recent_output_dirs`, dropping the oldest
+    /// past the limit. `false` when it already was the most recent, so
+    /// callers can skip saving.
+    pub fn remember_output_dir(&mut self, dir: &Path) -> bool {
+        if self.recent_output_dirs.first().map(PathBuf::as_path) == Some(dir) {
+            return false;
+        }
+        self.recent_output_dirs.retain(|recent| recent != dir);
+        self.recent_output_dirs.insert(0, dir.to_path_buf());
+        self.recent_output_dirs.truncate(RECENT_OUTPUT_DIRS_LIMIT);
+        true
+    }
+}
+
+/// Upgrades stored settings from any earlier `schema_version` and
+/// deserializes them. Fields that still don't fit fall back to their
+/// defaults one at a time, so one bad value doesn't reset everything else.
+pub fn migrate(raw: serde_json::Value) -> AppSettings {
+    let serde_json::Value::Object(mut stored) = raw else {
+        return AppSettings::default();
+    };
+    let version = stored
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    if version < 2 {
+        // `start_with_windows` became `start_with_system` once autostart
+        // worked on every desktop OS.
+        if let Some(value) = stored.remove("start_with_windows") {
+            stored.entry("start_with_system").or_insert(value);
+        }
+    }
+
+    if version < 3 {
+        // Before `filename_style` existed the template was the only choice,
+        // so an edited one means the user wants it kept.
+        if let Some(download) = stored.get_mut("download").and_then(|d| d.as_object_mut()) {
+            let customized = download
+                .get("filename_template")
+                .and_then(|t| t.as_str())
+                .is_some_and(|t| t != default_filename_template());
+            if customized {
+                download
+                    .entry("filename_style")
+                    .or_insert_with(|| "custom".into());
+            }
+        }
+    }
+
+    stored.insert("schema_version".to_string(), SETTINGS_SCHEMA_VERSION.into());
+    let mut stored = serde_json::Value::Object(stored);
+    if let Ok(settings) = serde_json::from_value(stored.clone()) {
+        return settings;
+    }
+    let Ok(defaults) = serde_json::to_value(AppSettings::default()) else {
+        return AppSettings::default();
+    };
+    keep_valid_fields(&mut stored, &defaults);
+    serde_json::from_value(stored).unwrap_or_default()
+}
+
+fn deserializes(value: &serde_json::Value) -> bool {
+    serde_json::from_value::<AppSettings>(value.clone()).is_ok()
+}
+
+/// Rebuilds `stored` on top of `defaults`, keeping each top-level value, or
+/// each field of a section, only if it deserializes. Missing required
+/// fields end up with their defaults the same way.
+fn keep_valid_fields(stored: &mut serde_json::Value, defaults: &serde_json::Value) {
+    let (Some(stored_map), Some(default_map)) = (stored.as_object(), defaults.as_object()) else {
+        *stored = defaults.clone();
+        return;
+    };
+    let mut result = defaults.clone();
+    for (key, value) in stored_map {
+        if !default_map.contains_key(key) {
+            continue;
+        }
+        let mut candidate = result.clone();
+        candidate[key] = value.clone();
+        if deserializes(&candidate) {
+            result = candidate;
+            continue;
+        }
+        let (Some(section), Some(_)) = (value.as_object(), default_map[key].as_object()) else {
+            continue;
+        };
+        for (field, field_value) in section {
+            let mut candidate = result.clone();
+            candidate[key][field] = field_value.clone();
+            if deserializes(&candidate) {
+                result = candidate;
+            }
         }
     }
+    *stored = result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn allowed_hours_wrap_past_midnight() {
+        let night = AllowedHours {
+            start_hour: 23,
+            end_hour: 7,
+        };
+        assert!(night.contains(23));
+        assert!(night.contains(3));
+        assert!(!night.contains(7));
+        assert!(!night.contains(12));
+
+        let day = AllowedHours {
+            start_hour: 9,
+            end_hour: 17,
+        };
+        assert!(day.contains(9) && !day.contains(17) && !day.contains(20));
+        let always = AllowedHours {
+            start_hour: 0,
+            end_hour: 0,
+        };
+        assert!(always.contains(13));
+    }
+
+    #[test]
+    fn migrate_renames_old_fields_and_fills_missing_ones() {
+        let settings = migrate(json!({
+            "schema_version": 1,
+            "start_with_windows": true,
+            "appearance": { "theme": "dark" },
+            "download": { "video_quality": "1080p" },
+        }));
+        assert_eq!(settings.schema_version, SETTINGS_SCHEMA_VERSION);
+        assert!(settings.start_with_system);
+        assert_eq!(settings.appearance.theme, "dark");
+        assert_eq!(settings.appearance.language, "en");
+        assert_eq!(settings.download.video_quality, "1080p");
+    }
+
+    #[test]
+    fn clamp_http_keeps_timeouts_usable() {
+        let mut download = AppSettings::default().download;
+        assert_eq!(download.http_timeout_secs, 120);
+        assert_eq!(download.http_connect_timeout_secs, 15);
+        download.http_timeout_secs = 0;
+        download.http_connect_timeout_secs = 100_000;
+        download.http_max_retries = 0;
+        download.clamp_http();
+        assert_eq!(download.http_timeout_secs, 5);
+        assert_eq!(
+            download.http_connect_timeout_secs,
+            DownloadSettings::MAX_HTTP_CONNECT_TIMEOUT_SECS
+        );
+        assert_eq!(download.http_max_retries, 1);
+    }
+
+    #[test]
+    fn migrate_keeps_good_fields_next_to_bad_ones() {
+        let mut stored = serde_json::to_value(AppSettings::default()).unwrap();
+        stored["appearance"]["theme"] = json!("light");
+        stored["advanced"]["max_retries"] = json!("lots");
+        stored["onboarding_completed"] = json!(true);
+
+        let settings = migrate(stored);
+        assert_eq!(settings.appearance.theme, "light");
+        assert_eq!(settings.advanced.max_retries, 3);
+        assert!(settings.onboarding_completed);
+
+        assert_eq!(
+            migrate(json!("garbage")).schema_version,
+            SETTINGS_SCHEMA_VERSION
+        );
+    }
+
+    #[test]
+    fn migrate_keeps_an_edited_filename_template() {
+        let edited = migrate(json!({
+            "schema_version": 2,
+            "download": { "filename_template": "%(uploader)s - %(title)s.%(ext)s" },
+        }));
+        assert_eq!(edited.download.filename_style, FilenameStyle::Custom);
+        assert_eq!(
+            edited
+                .download
+                .filename_style
+                .template(&edited.download.filename_template),
+            "%(uploader)s - %(title)s.%(ext)s"
+        );
+
+        let untouched = migrate(json!({
+            "schema_version": 2,
+            "download": { "filename_template": default_filename_template() },
+        }));
+        assert_eq!(
+            untouched.download.filename_style,
+            FilenameStyle::TitleWithId
+        );
+    }
+
+    #[test]
+    fn platform_defaults_fill_unset_choices() {
+        let mut download = AppSettings::default().download;
+        download.video_quality = "720p".to_string();
+        download.music_audio_format = "m4a".to_string();
+        download.platform_defaults.insert(
+            "youtube".to_string(),
+            PlatformDefault {
+                quality: Some("1080p".to_string()),
+                download_mode: Some("audio".to_string()),
+                audio_format: Some("opus".to_string()),
+            },
+        );
+
+        assert_eq!(
+            download.download_choices("youtube", None, None),
+            (
+                Some("audio".to_string()),
+                Some("1080p".to_string()),
+                Some("opus".to_string())
+            )
+        );
+        assert_eq!(
+            download.download_choices(
+                "youtube",
+                Some("auto".to_string()),
+                Some("best".to_string())
+            ),
+            (Some("auto".to_string()), Some("best".to_string()), None)
+        );
+        assert_eq!(
+            download.download_choices("tiktok", Some("audio".to_string()), None),
+            (
+                Some("audio".to_string()),
+                Some("720p".to_string()),
+                Some("m4a".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn recent_output_dirs_keep_the_latest_first() {
+        let mut settings = AppSettings::default();
+        for i in 0..RECENT_OUTPUT_DIRS_LIMIT + 2 {
+            assert!(settings.remember_output_dir(Path::new(&format!("/dl/{}", i))));
+        }
+        assert_eq!(settings.recent_output_dirs.len(), RECENT_OUTPUT_DIRS_LIMIT);
+        assert_eq!(settings.recent_output_dirs[0], Path::new("/dl/11"));
+
+        assert!(!settings.remember_output_dir(Path::new("/dl/11")));
+        assert!(settings.remember_output_dir(Path::new("/dl/5")));
+        assert_eq!(settings.recent_output_dirs[0], Path::new("/dl/5"));
+        assert_eq!(settings.recent_output_dirs.len(), RECENT_OUTPUT_DIRS_LIMIT);
+    }
 }
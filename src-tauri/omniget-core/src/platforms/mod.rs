@@ -1,8 +1,10 @@
 pub mod traits;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
 
 const DIRECT_FILE_EXTENSIONS: &[&str] = &[
     "zip", "rar", "7z", "tar", "gz", "tgz", "bz2", "xz", "zst", "exe", "msi", "dmg", "pkg", "deb",
@@ -28,6 +30,31 @@ pub fn is_direct_file_url(url_str: &str) -> bool {
     !name.is_empty() && DIRECT_FILE_EXTENSIONS.contains(&ext)
 }
 
+/// Whether `url`'s host is one of `hosts` or a subdomain of one. A pattern
+/// ending in `.*` matches the name under any top-level domain, for sites
+/// like Pinterest that run one per country.
+pub fn url_matches_hosts(url_str: &str, hosts: &[&str]) -> bool {
+    let Ok(parsed) = url::Url::parse(url_str) else {
+        return false;
+    };
+    let Some(host) = parsed.host_str().map(str::to_lowercase) else {
+        return false;
+    };
+    hosts.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(name) => host.starts_with(name) || host.contains(&format!(".{}", name)),
+        None => host == *pattern || host.ends_with(&format!(".{}", pattern)),
+    })
+}
+
+/// What the UI and help text show for a supported platform.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformDescriptor {
+    pub platform: String,
+    pub display_name: String,
+    pub hosts: Vec<String>,
+    pub examples: Vec<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Platform {
@@ -44,6 +71,10 @@ pub enum Platform {
     Vimeo,
     Udemy,
     Bilibili,
+    Kick,
+    Dailymotion,
+    SoundCloud,
+    Facebook,
     Other(String),
 }
 
@@ -63,6 +94,10 @@ impl fmt::Display for Platform {
             Platform::Vimeo => "vimeo",
             Platform::Udemy => "udemy",
             Platform::Bilibili => "bilibili",
+            Platform::Kick => "kick",
+            Platform::Dailymotion => "dailymotion",
+            Platform::SoundCloud => "soundcloud",
+            Platform::Facebook => "facebook",
             Platform::Other(ref name) => name.as_str(),
         };
         write!(f, "{}", name)
@@ -87,6 +122,10 @@ impl FromStr for Platform {
             "vimeo" => Ok(Platform::Vimeo),
             "udemy" => Ok(Platform::Udemy),
             "bilibili" | "b站" => Ok(Platform::Bilibili),
+            "kick" => Ok(Platform::Kick),
+            "dailymotion" | "dm" => Ok(Platform::Dailymotion),
+            "soundcloud" | "sc" => Ok(Platform::SoundCloud),
+            "facebook" | "fb" => Ok(Platform::Facebook),
             _ => Err(format!("Unknown platform: {}", s)),
         }
     }
@@ -141,6 +180,14 @@ impl Platform {
             Some(Platform::Udemy)
         } else if matches("bilibili.com") || matches("bilibili.tv") || host == "b23.tv" {
             Some(Platform::Bilibili)
+        } else if matches("kick.com") {
+            Some(Platform::Kick)
+        } else if matches("dailymotion.com") || host == "dai.ly" {
+            Some(Platform::Dailymotion)
+        } else if matches("soundcloud.com") || host == "snd.sc" {
+            Some(Platform::SoundCloud)
+        } else if matches("facebook.com") || matches("fb.com") || host == "fb.watch" {
+            Some(Platform::Facebook)
         } else if matches("kiwify.com.br") {
             Some(Platform::Other("kiwify".to_string()))
         } else if matches("gumroad.com") {
@@ -193,6 +240,218 @@ impl Platform {
             Platform::Vimeo,
             Platform::Udemy,
             Platform::Bilibili,
+            Platform::Kick,
+            Platform::Dailymotion,
+            Platform::SoundCloud,
+            Platform::Facebook,
         ]
     }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            Platform::Hotmart => "Hotmart",
+            Platform::YouTube => "YouTube",
+            Platform::Instagram => "Instagram",
+            Platform::TikTok => "TikTok",
+            Platform::Twitter => "Twitter / X",
+            Platform::Reddit => "Reddit",
+            Platform::Twitch => "Twitch",
+            Platform::Pinterest => "Pinterest",
+            Platform::Bluesky => "Bluesky",
+            Platform::Telegram => "Telegram",
+            Platform::Vimeo => "Vimeo",
+            Platform::Udemy => "Udemy",
+            Platform::Bilibili => "Bilibili",
+            Platform::Kick => "Kick",
+            Platform::Dailymotion => "Dailymotion",
+            Platform::SoundCloud => "SoundCloud",
+            Platform::Facebook => "Facebook",
+            Platform::Other(ref name) => name.as_str(),
+        }
+    }
+
+    /// Links of the shapes the platform's downloader takes, for help text.
+    pub fn example_urls(&self) -> &'static [&'static str] {
+        match self {
+            Platform::Hotmart => &["https://hotmart.com/pt-br/club/my-course"],
+            Platform::YouTube => &[
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                "https://youtu.be/dQw4w9WgXcQ",
+                "https://www.youtube.com/playlist?list=PLxxxxxxxx",
+                "https://music.youtube.com/watch?v=dQw4w9WgXcQ",
+            ],
+            Platform::Instagram => &[
+                "https://www.instagram.com/p/Cxxxxxxxxxx/",
+                "https://www.instagram.com/reel/Cxxxxxxxxxx/",
+            ],
+            Platform::TikTok => &["https://www.tiktok.com/@user/video/7000000000000000000"],
+            Platform::Twitter => &["https://x.com/user/status/1700000000000000000"],
+            Platform::Reddit => &["https://www.reddit.com/r/videos/comments/abc123/title/"],
+            Platform::Twitch => &["https://clips.twitch.tv/SlugOfTheClip"],
+            Platform::Pinterest => &["https://www.pinterest.com/pin/100000000000000000/"],
+            Platform::Bluesky => &["https://bsky.app/profile/user.bsky.social/post/3kxxxxxxxxx"],
+            Platform::Telegram => &["https://t.me/channel/123"],
+            Platform::Vimeo => &["https://vimeo.com/76979871"],
+            Platform::Udemy => &["https://www.udemy.com/course/course-name/"],
+            Platform::Bilibili => &["https://www.bilibili.com/video/BV1xx411c7mD"],
+            Platform::Kick => &[
+                "https://kick.com/channel/clips/clip_01HXXXXXXXXXXXXXXXXXXXXXXX",
+                "https://kick.com/channel/videos/00000000-0000-0000-0000-000000000000",
+            ],
+            Platform::Dailymotion => &[
+                "https://www.dailymotion.com/video/x8xxxxx",
+                "https://dai.ly/x8xxxxx",
+            ],
+            Platform::SoundCloud => &[
+                "https://soundcloud.com/artist/track-name",
+                "https://soundcloud.com/artist/sets/playlist-name",
+            ],
+            Platform::Facebook => &[
+                "https://www.facebook.com/watch/?v=1000000000000000",
+                "https://www.facebook.com/reel/1000000000000000",
+                "https://fb.watch/xxxxxxxxxx/",
+            ],
+            Platform::Other(_) => &[],
+        }
+    }
+}
+
+/// Which extractor a platform should use when it has both a native one and
+/// yt-dlp support.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlatformBackend {
+    /// Native first, yt-dlp if native extraction fails.
+    #[default]
+    Auto,
+    /// Native only; failures are reported instead of retried with yt-dlp.
+    Native,
+    /// Skip the native downloader and go straight to the generic yt-dlp one.
+    Ytdlp,
+}
+
+fn backend_preferences() -> &'static RwLock<HashMap<String, PlatformBackend>> {
+    static PREFERENCES: OnceLock<RwLock<HashMap<String, PlatformBackend>>> = OnceLock::new();
+    PREFERENCES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Replaces the per-platform backend choices, keyed by downloader name.
+pub fn set_backend_preferences(preferences: HashMap<String, PlatformBackend>) {
+    if let Ok(mut guard) = backend_preferences().write() {
+        *guard = preferences;
+    }
+}
+
+pub fn backend_preference(platform: &str) -> PlatformBackend {
+    backend_preferences()
+        .read()
+        .ok()
+        .and_then(|guard| guard.get(platform).copied())
+        .unwrap_or_default()
+}
+
+/// Errors when the user forced the native extractor for `platform`, so a
+/// downloader can bail out before falling back to yt-dlp.
+pub fn check_ytdlp_fallback(platform: &str) -> anyhow::Result<()> {
+    if backend_preference(platform) == PlatformBackend::Native {
+        return Err(anyhow::anyhow!(
+            "yt-dlp fallback is disabled for {} in settings",
+            platform
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts_match_subdomains_and_any_tld() {
+        let hosts = ["youtube.com", "youtu.be", "pinterest.*"];
+        assert!(url_matches_hosts(
+            "https://www.youtube.com/watch?v=1",
+            &hosts
+        ));
+        assert!(url_matches_hosts("https://YOUTU.BE/abc", &hosts));
+        assert!(url_matches_hosts(
+            "https://br.pinterest.com.br/pin/1/",
+            &hosts
+        ));
+        assert!(url_matches_hosts("https://pinterest.fr/pin/1/", &hosts));
+        assert!(!url_matches_hosts("https://notyoutube.com/", &hosts));
+        assert!(!url_matches_hosts("https://mypinterest.com/", &hosts));
+        assert!(!url_matches_hosts("not a url", &hosts));
+    }
+
+    #[test]
+    fn youtube_music_links_are_youtube() {
+        assert_eq!(
+            Platform::from_url("https://music.youtube.com/watch?v=abc&list=RDAMVM1"),
+            Some(Platform::YouTube)
+        );
+        assert_eq!(
+            Platform::from_url("https://music.youtube.com/podcast/abc"),
+            Some(Platform::YouTube)
+        );
+    }
+
+    #[test]
+    fn promoted_ytdlp_sites_have_their_own_platform() {
+        assert_eq!(
+            Platform::from_url("https://kick.com/xqc/clips/clip_01HABC"),
+            Some(Platform::Kick)
+        );
+        assert_eq!(
+            Platform::from_url("https://www.dailymotion.com/video/x8abcd"),
+            Some(Platform::Dailymotion)
+        );
+        assert_eq!(
+            Platform::from_url("https://dai.ly/x8abcd"),
+            Some(Platform::Dailymotion)
+        );
+        assert_eq!("dailymotion".parse::<Platform>(), Ok(Platform::Dailymotion));
+        assert_eq!(Platform::Kick.to_string(), "kick");
+    }
+
+    #[test]
+    fn soundcloud_links_are_recognized() {
+        assert_eq!(
+            Platform::from_url("https://soundcloud.com/artist/sets/playlist"),
+            Some(Platform::SoundCloud)
+        );
+        assert_eq!(
+            Platform::from_url("https://on.soundcloud.com/AbCdE"),
+            Some(Platform::SoundCloud)
+        );
+        assert_eq!("sc".parse::<Platform>(), Ok(Platform::SoundCloud));
+    }
+
+    #[test]
+    fn facebook_links_are_recognized() {
+        for url in [
+            "https://www.facebook.com/watch/?v=123",
+            "https://m.facebook.com/reel/123",
+            "https://fb.watch/abcDEF/",
+            "https://fb.com/page/videos/123",
+        ] {
+            assert_eq!(Platform::from_url(url), Some(Platform::Facebook), "{}", url);
+        }
+        assert_eq!("fb".parse::<Platform>(), Ok(Platform::Facebook));
+    }
+
+    #[test]
+    fn reddit_image_hosts_are_not_reddit() {
+        for url in ["https://v.redd.it/abc123", "https://redd.it/abc123"] {
+            assert_eq!(Platform::from_url(url), Some(Platform::Reddit), "{}", url);
+        }
+        assert_ne!(
+            Platform::from_url("https://i.redd.it/cat.jpg"),
+            Some(Platform::Reddit)
+        );
+        assert_ne!(
+            Platform::from_url("https://preview.redd.it/cat.jpg"),
+            Some(Platform::Reddit)
+        );
+    }
 }
@@ -1,13 +1,49 @@
 use async_trait::async_trait;
+use serde::Serialize;
 
 use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo};
 pub use crate::models::progress::ProgressUpdate;
 
+/// What a downloader can do, so the UI can show only the options that
+/// apply to a platform.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    /// Honours `quality`/`quality_selection` when several variants exist.
+    pub supports_quality_selection: bool,
+    /// Honours `download_mode: "audio"`.
+    pub supports_audio_only: bool,
+    /// Honours `download_subtitles`.
+    pub supports_subtitles: bool,
+    /// Turns collection URLs (playlist, album, feed) into items.
+    pub supports_expansion: bool,
+    /// Nothing can be fetched without a logged-in session.
+    pub requires_auth: bool,
+    /// `get_media_info` lists the platform's own streams with ids that
+    /// `format_id` selects, so the format picker shows those instead of
+    /// asking yt-dlp.
+    pub native_formats: bool,
+}
+
 #[async_trait]
 pub trait PlatformDownloader: Send + Sync {
     fn name(&self) -> &str;
     fn can_handle(&self, url: &str) -> bool;
+    /// Hosts `can_handle` accepts, in the form `url_matches_hosts` takes.
+    /// Empty for downloaders that match on something other than the host,
+    /// like a scheme or a file extension.
+    fn supported_hosts(&self) -> &[&'static str] {
+        &[]
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo>;
+    /// Lists the item URLs behind a collection URL (album, showcase,
+    /// channel) so the queue can enqueue them one by one. `None` means
+    /// `url` is a single item.
+    async fn expand(&self, _url: &str) -> anyhow::Result<Option<Vec<String>>> {
+        Ok(None)
+    }
     async fn download(
         &self,
         info: &MediaInfo,
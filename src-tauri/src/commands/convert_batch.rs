@@ -0,0 +1,124 @@
+use serde::Serialize;
+use tauri::Emitter;
+use tokio_util::sync::CancellationToken;
+
+use omniget_core::core::ffmpeg::{self, BatchEvent, ConversionOptions};
+
+use crate::commands::host_queue::next_external_id;
+use crate::AppState;
+
+const DEFAULT_MAX_CONCURRENT: usize = 2;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConvertBatchStarted {
+    pub batch_id: u64,
+    /// One id per job, in job order, as used by `convert-progress` and
+    /// `convert-complete`.
+    pub conversion_ids: Vec<u64>,
+}
+
+/// Starts converting `jobs` in the background. Per-file progress goes out
+/// on the same `convert-progress`/`convert-complete` events as single
+/// conversions; `convert-batch-progress` carries the aggregate and
+/// `convert-batch-complete` fires once every job has finished.
+#[tauri::command]
+pub async fn convert_batch(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    jobs: Vec<ConversionOptions>,
+    max_concurrent: Option<usize>,
+) -> Result<ConvertBatchStarted, String> {
+    if jobs.is_empty() {
+        return Err("No files to convert".to_string());
+    }
+    if !ffmpeg::is_ffmpeg_available().await {
+        return Err("ffmpeg not found".to_string());
+    }
+
+    let batch_id = next_external_id();
+    let conversion_ids: Vec<u64> = jobs.iter().map(|_| next_external_id()).collect();
+    let cancel_token = CancellationToken::new();
+    state
+        .active_conversions
+        .lock()
+        .await
+        .insert(batch_id, cancel_token.clone());
+
+    let total = jobs.len();
+    let ids = conversion_ids.clone();
+    let active_conversions = state.active_conversions.clone();
+    tauri::async_runtime::spawn(async move {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<BatchEvent>(64);
+        let batch = ffmpeg::convert_batch(
+            jobs,
+            max_concurrent.unwrap_or(DEFAULT_MAX_CONCURRENT),
+            cancel_token,
+            tx,
+        );
+        let forward = async {
+            let mut completed = 0;
+            while let Some(event) = rx.recv().await {
+                match event {
+                    BatchEvent::Progress {
+                        index,
+                        percent,
+                        overall,
+                    } => {
+                        let _ = app.emit(
+                            "convert-progress",
+                            serde_json::json!({ "id": ids[index], "percent": percent }),
+                        );
+                        let _ = app.emit(
+                            "convert-batch-progress",
+                            serde_json::json!({
+                                "batch_id": batch_id,
+                                "percent": overall,
+                                "completed": completed,
+                                "total": total,
+                            }),
+                        );
+                    }
+                    BatchEvent::Finished { index, result } => {
+                        completed += 1;
+                        let _ = app.emit(
+                            "convert-complete",
+                            serde_json::json!({
+                                "id": ids[index],
+                                "success": result.success,
+                                "error": result.error.clone(),
+                                "result": result,
+                            }),
+                        );
+                    }
+                }
+            }
+        };
+        let (results, _) = tokio::join!(batch, forward);
+
+        active_conversions.lock().await.remove(&batch_id);
+        let failed = results.iter().filter(|r| !r.success).count();
+        let _ = app.emit(
+            "convert-batch-complete",
+            serde_json::json!({ "batch_id": batch_id, "total": total, "failed": failed }),
+        );
+    });
+
+    Ok(ConvertBatchStarted {
+        batch_id,
+        conversion_ids,
+    })
+}
+
+#[tauri::command]
+pub async fn cancel_conversion_batch(
+    state: tauri::State<'_, AppState>,
+    batch_id: u64,
+) -> Result<(), String> {
+    match state.active_conversions.lock().await.get(&batch_id) {
+        Some(token) => {
+            token.cancel();
+            Ok(())
+        }
+        None => Err("Batch not found".to_string()),
+    }
+}
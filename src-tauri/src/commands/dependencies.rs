@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use serde::Serialize;
 
-use crate::core::{dependencies, pdfium};
+use crate::core::{dependencies, pdfium, ytdlp};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct DependencyStatus {
@@ -11,6 +11,13 @@ pub struct DependencyStatus {
     pub version: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct YtdlpVersionInfo {
+    pub version: String,
+    pub path: String,
+    pub source: ytdlp::YtdlpSource,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct DependencyVariantInfo {
     pub id: String,
@@ -56,6 +63,33 @@ pub async fn check_ytdlp_available() -> Result<bool, String> {
     Ok(crate::core::ytdlp::find_ytdlp_cached().await.is_some())
 }
 
+async fn ytdlp_version_info(path: PathBuf) -> Result<YtdlpVersionInfo, String> {
+    let version = ytdlp::ytdlp_binary_version(&path)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(YtdlpVersionInfo {
+        version,
+        source: ytdlp::ytdlp_source(&path),
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+#[tauri::command]
+pub async fn ytdlp_version() -> Result<YtdlpVersionInfo, String> {
+    let path = ytdlp::find_ytdlp_cached()
+        .await
+        .ok_or_else(|| "yt-dlp is not installed".to_string())?;
+    ytdlp_version_info(path).await
+}
+
+/// Re-downloads the managed yt-dlp binary now instead of waiting for the
+/// periodic freshness check.
+#[tauri::command]
+pub async fn ytdlp_update() -> Result<YtdlpVersionInfo, String> {
+    let path = ytdlp::update_ytdlp().await.map_err(|e| e.to_string())?;
+    ytdlp_version_info(path).await
+}
+
 #[tauri::command]
 pub async fn install_dependency(
     name: String,
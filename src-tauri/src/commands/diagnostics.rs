@@ -1,4 +1,176 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::core::{dependencies, ytdlp};
+use crate::storage::config;
+
+/// Endpoints probed to tell "no network" apart from "one site is blocking".
+const REACHABILITY_TARGETS: &[(&str, &str)] = &[
+    ("YouTube", "https://www.youtube.com/"),
+    ("Instagram", "https://www.instagram.com/"),
+    ("TikTok", "https://www.tiktok.com/"),
+    ("GitHub", "https://github.com/"),
+];
+
+const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const DEFAULT_LOG_LINES: usize = 500;
+const MAX_LOG_LINES: usize = 5000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCheck {
+    pub name: String,
+    pub path: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteCheck {
+    pub path: String,
+    pub writable: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReachabilityCheck {
+    pub name: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub tools: Vec<ToolCheck>,
+    pub download_dir: WriteCheck,
+    pub network: Vec<ReachabilityCheck>,
+    pub rate_limit_429_count: u64,
+}
+
 #[tauri::command]
 pub async fn get_hwaccel_info() -> omniget_core::core::hwaccel::HwAccelInfo {
     omniget_core::core::hwaccel::detect_hwaccel().await
 }
+
+/// Everything worth attaching to a "downloads don't work" report: the
+/// external tools, whether the output folder takes files, whether the big
+/// platforms answer, and how often YouTube rate-limited us lately.
+#[tauri::command]
+pub async fn run_diagnostics(app: tauri::AppHandle) -> DiagnosticsReport {
+    let settings = config::load_settings(&app);
+    crate::core::http_client::init_proxy(settings.proxy);
+
+    let (tools, network) = tokio::join!(
+        futures::future::join_all(
+            ["yt-dlp", "ffmpeg", "ffprobe", "aria2c"]
+                .into_iter()
+                .map(check_tool)
+        ),
+        check_reachability(),
+    );
+    let download_dir = check_writable(&settings.download.default_output_dir).await;
+    let rate_limit_429_count = ytdlp::get_rate_limit_stats()["rate_limit_429_count"]
+        .as_u64()
+        .unwrap_or(0);
+
+    DiagnosticsReport {
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        tools,
+        download_dir,
+        network,
+        rate_limit_429_count,
+    }
+}
+
+/// The tail of the log file, sanitized, for pasting into a bug report.
+#[tauri::command]
+pub async fn get_recent_logs(lines: Option<usize>) -> String {
+    let limit = lines.unwrap_or(DEFAULT_LOG_LINES).clamp(1, MAX_LOG_LINES);
+    tokio::task::spawn_blocking(move || crate::core::logging::recent_lines(limit).join("\n"))
+        .await
+        .unwrap_or_default()
+}
+
+async fn check_tool(name: &str) -> ToolCheck {
+    let (path, version) = tokio::join!(
+        dependencies::find_tool(name),
+        dependencies::check_version(name)
+    );
+    ToolCheck {
+        name: name.to_string(),
+        path: path.map(|p| p.to_string_lossy().to_string()),
+        version,
+    }
+}
+
+/// Creates and removes a probe file, since permissions alone don't cover
+/// read-only mounts or sandboxed folders.
+async fn check_writable(dir: &Path) -> WriteCheck {
+    let probe = dir.join(".omniget-write-test");
+    let result = async {
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(&probe, b"").await?;
+        tokio::fs::remove_file(&probe).await
+    }
+    .await;
+    WriteCheck {
+        path: dir.to_string_lossy().to_string(),
+        writable: result.is_ok(),
+        error: result.err().map(|e| e.to_string()),
+    }
+}
+
+async fn check_reachability() -> Vec<ReachabilityCheck> {
+    let client = match crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
+        .timeout(REACHABILITY_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            return REACHABILITY_TARGETS
+                .iter()
+                .map(|(name, url)| ReachabilityCheck {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                    status: None,
+                    latency_ms: None,
+                    error: Some(e.to_string()),
+                })
+                .collect();
+        }
+    };
+
+    futures::future::join_all(REACHABILITY_TARGETS.iter().map(|(name, url)| {
+        let client = client.clone();
+        async move {
+            let started = Instant::now();
+            let response = client.head(*url).send().await;
+            let latency_ms = started.elapsed().as_millis() as u64;
+            match response {
+                Ok(response) => ReachabilityCheck {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                    status: Some(response.status().as_u16()),
+                    latency_ms: Some(latency_ms),
+                    error: None,
+                },
+                Err(e) => ReachabilityCheck {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                    status: None,
+                    latency_ms: None,
+                    error: Some(e.to_string()),
+                },
+            }
+        }
+    }))
+    .await
+}
@@ -3,8 +3,10 @@ use std::sync::Arc;
 use serde::Serialize;
 
 use crate::core::queue::{self, emit_queue_state_from_state};
+use crate::core::stats::DownloadStats;
 use crate::core::url_parser;
-use crate::platforms::Platform;
+use crate::platforms::traits::Capabilities;
+use crate::platforms::{Platform, PlatformDescriptor};
 use crate::storage::config;
 use crate::AppState;
 
@@ -59,6 +61,7 @@ pub fn validate_output_path(output_dir: String) -> PathLimitInfo {
 #[tauri::command]
 pub async fn detect_platform(url: String) -> Result<PlatformInfo, String> {
     let _timer_start = std::time::Instant::now();
+    let url = crate::core::url::normalize(&url).unwrap_or(url);
     match Platform::from_url(&url) {
         Some(platform) => {
             let parsed = url_parser::parse_url(&url);
@@ -101,8 +104,14 @@ pub async fn detect_platform(url: String) -> Result<PlatformInfo, String> {
 
 #[cfg(not(target_os = "android"))]
 #[tauri::command]
-pub async fn get_media_formats(url: String) -> Result<Vec<FormatInfo>, String> {
+pub async fn get_media_formats(
+    state: tauri::State<'_, AppState>,
+    url: String,
+) -> Result<Vec<FormatInfo>, String> {
     let _timer_start = std::time::Instant::now();
+    if let Some(formats) = native_formats(&state, &url).await {
+        return Ok(formats);
+    }
     let ytdlp_path = ytdlp::ensure_ytdlp()
         .await
         .map_err(|e| format!("yt-dlp unavailable: {}", e))?;
@@ -115,6 +124,51 @@ pub async fn get_media_formats(url: String) -> Result<Vec<FormatInfo>, String> {
     Ok(ytdlp::parse_formats(&json))
 }
 
+/// The streams a platform with `native_formats` lists itself. `None` when
+/// the platform doesn't, or fell back to yt-dlp for this URL.
+#[cfg(not(target_os = "android"))]
+async fn native_formats(state: &AppState, url: &str) -> Option<Vec<FormatInfo>> {
+    let (url, downloader) = state.registry.find_platform_resolved(url).await;
+    let downloader = downloader.filter(|d| d.capabilities().native_formats)?;
+    let info = match downloader.get_media_info(&url).await {
+        Ok(info) => info,
+        Err(e) => {
+            tracing::debug!("[formats] native listing failed, asking yt-dlp: {}", e);
+            return None;
+        }
+    };
+    let formats: Vec<FormatInfo> = info
+        .available_qualities
+        .iter()
+        .filter(|q| q.id.is_some())
+        .map(|q| q.to_format_info())
+        .collect();
+    (!formats.is_empty()).then_some(formats)
+}
+
+/// Options the downloader registered as `platform` honours, for showing
+/// only the relevant controls.
+#[tauri::command]
+pub async fn get_platform_capabilities(
+    state: tauri::State<'_, AppState>,
+    platform: String,
+) -> Result<Capabilities, String> {
+    state
+        .registry
+        .find_by_name(&platform)
+        .map(|d| d.capabilities())
+        .ok_or_else(|| format!("Unknown platform: {}", platform))
+}
+
+/// Platforms with a dedicated downloader, for help text and for telling
+/// users up front whether a link will work.
+#[tauri::command]
+pub async fn list_supported_platforms(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<PlatformDescriptor>, String> {
+    Ok(state.registry.supported_platforms())
+}
+
 #[cfg(not(target_os = "android"))]
 #[tauri::command]
 pub async fn prefetch_media_info(
@@ -125,12 +179,13 @@ pub async fn prefetch_media_info(
     let settings = config::load_settings(&app);
     crate::core::http_client::init_proxy(settings.proxy);
 
+    let (url, downloader) = state.registry.find_platform_resolved(&url).await;
     let platform = Platform::from_url(&url);
     let platform_name = platform
         .map(|p| p.to_string())
         .unwrap_or_else(|| "generic".to_string());
 
-    let downloader = match state.registry.find_platform(&url) {
+    let downloader = match downloader {
         Some(d) => d,
         None => return Err("No downloader available".to_string()),
     };
@@ -155,18 +210,9 @@ pub async fn prefetch_media_info(
 pub struct DownloadStarted {
     pub id: u64,
     pub title: String,
-}
-
-fn is_valid_time_range(r: &str) -> bool {
-    let Some((a, b)) = r.split_once('-') else {
-        return false;
-    };
-    let part_ok = |s: &str| {
-        !s.is_empty()
-            && s.chars()
-                .all(|c| c.is_ascii_digit() || c == ':' || c == '.')
-    };
-    part_ok(a) && (b == "inf" || part_ok(b))
+    /// What `enqueue` made of the request; `DuplicateSkipped` when the URL
+    /// was already queued or its earlier output is still there.
+    pub status: queue::QueueStatus,
 }
 
 #[derive(Clone, Serialize)]
@@ -428,6 +474,89 @@ pub async fn thumbnail_save(
     Ok(safe)
 }
 
+/// Saves only the cover image of `url` into `output_dir`, named after the
+/// media's title, and returns the saved path. YouTube gets its max-res
+/// thumbnail; platforms that leave `thumbnail_url` empty fall back to the
+/// one yt-dlp reports.
+#[cfg(not(target_os = "android"))]
+#[tauri::command]
+pub async fn download_thumbnail(
+    state: tauri::State<'_, AppState>,
+    url: String,
+    output_dir: String,
+) -> Result<String, String> {
+    let (url, downloader) = state.registry.find_platform_resolved(&url).await;
+    let downloader = downloader.ok_or_else(|| "No downloader available".to_string())?;
+    let info = downloader
+        .get_media_info(&url)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut candidates: Vec<String> = Vec::new();
+    if Platform::from_url(&url) == Some(Platform::YouTube) {
+        candidates.extend(crate::platforms::youtube::YouTubeDownloader::max_res_thumbnail(&url));
+    }
+    candidates.extend(info.thumbnail_url.clone().filter(|u| !u.is_empty()));
+    if candidates.is_empty() {
+        if let Some(ytdlp_path) = ytdlp::find_ytdlp_cached().await {
+            if let Ok(json) = ytdlp::get_video_info(&ytdlp_path, &url, &[]).await {
+                candidates.extend(
+                    json.get("thumbnail")
+                        .and_then(|v| v.as_str())
+                        .filter(|u| !u.is_empty())
+                        .map(str::to_string),
+                );
+            }
+        }
+    }
+    if candidates.is_empty() {
+        return Err("No thumbnail available".to_string());
+    }
+
+    let client = crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
+        .build()
+        .map_err(|e| e.to_string())?;
+    let dir = std::path::Path::new(&output_dir);
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| e.to_string())?;
+    let stem = sanitize_filename::sanitize(&info.title);
+    let stem = if stem.trim().is_empty() {
+        "thumbnail".to_string()
+    } else {
+        stem
+    };
+
+    let mut last_err = String::new();
+    for thumb_url in candidates {
+        let ext = thumbnail_extension(&thumb_url);
+        let path = crate::core::filename::next_free_path(&dir.join(format!("{}.{}", stem, ext)));
+        let (tx, _rx) = tokio::sync::mpsc::channel(8);
+        match crate::core::direct_downloader::download_direct(&client, &thumb_url, &path, tx, None)
+            .await
+        {
+            Ok(_) => return Ok(path.to_string_lossy().to_string()),
+            Err(e) => {
+                tracing::debug!("[thumbnail] {} failed: {}", thumb_url, e);
+                let _ = tokio::fs::remove_file(&path).await;
+                last_err = e.to_string();
+            }
+        }
+    }
+    Err(last_err)
+}
+
+/// Image extension of a thumbnail URL, ignoring its query; `jpg` when the
+/// URL doesn't name a known one.
+#[cfg(not(target_os = "android"))]
+fn thumbnail_extension(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+    ["webp", "png", "gif", "jpeg"]
+        .into_iter()
+        .find(|ext| path.ends_with(&format!(".{}", ext)))
+        .unwrap_or("jpg")
+}
+
 #[derive(Clone, Serialize)]
 pub struct SubtitleFormat {
     pub ext: String,
@@ -862,16 +991,71 @@ pub async fn download_from_url(
     torrent_files: Option<Vec<usize>>,
     scheduled_at: Option<u64>,
     stop_at: Option<u64>,
+    download_archive: Option<bool>,
+) -> Result<DownloadStarted, String> {
+    let archive = if download_archive.unwrap_or(false) {
+        let url = crate::core::url::normalize(&url).unwrap_or_else(|| url.clone());
+        crate::core::download_archive::path_for_source(&url)
+    } else {
+        None
+    };
+    queue_download(
+        app,
+        state,
+        url,
+        output_dir,
+        download_mode,
+        quality,
+        format_id,
+        referer,
+        cookie_slug,
+        time_range,
+        playlist_items,
+        torrent_files,
+        scheduled_at,
+        stop_at,
+        None,
+        None,
+        archive,
+    )
+    .await
+}
+
+/// `download_from_url` for one URL. Items of an expanded collection come
+/// back through here with their `playlist_position` and `batch_tag` set.
+/// With an `archive`, items it already lists are left out of the expansion
+/// and the rest are recorded there once they finish.
+#[cfg(not(target_os = "android"))]
+#[allow(clippy::too_many_arguments)]
+async fn queue_download(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    url: String,
+    output_dir: String,
+    download_mode: Option<String>,
+    quality: Option<String>,
+    format_id: Option<String>,
+    referer: Option<String>,
+    cookie_slug: Option<String>,
+    time_range: Option<String>,
+    playlist_items: Option<Vec<u32>>,
+    torrent_files: Option<Vec<usize>>,
+    scheduled_at: Option<u64>,
+    stop_at: Option<u64>,
+    playlist_position: Option<queue::PlaylistPosition>,
+    batch_tag: Option<String>,
+    archive: Option<std::path::PathBuf>,
 ) -> Result<DownloadStarted, String> {
     let _timer_start = std::time::Instant::now();
+    let url = crate::core::url::normalize(&url).unwrap_or(url);
+    let (url, resolved_downloader) = state.registry.find_platform_resolved(&url).await;
     let platform = Platform::from_url(&url);
 
-    let custom_ytdlp_args = match time_range.as_deref().map(str::trim) {
-        Some(r) if !r.is_empty() && is_valid_time_range(r) => {
-            Some(vec!["--download-sections".to_string(), format!("*{}", r)])
-        }
-        _ => None,
-    };
+    // An explicit range wins over the `t=`/`end=` of a YouTube link.
+    let section = time_range
+        .as_deref()
+        .and_then(ytdlp::parse_section)
+        .or_else(|| crate::core::url::time_section(&url));
 
     if let Err(err) = crate::core::path_limits::validate_output_dir(&output_dir) {
         return Err(format!(
@@ -893,10 +1077,27 @@ pub async fn download_from_url(
         crate::core::http_fetcher::set_global_max_concurrent_segments(
             settings.advanced.max_concurrent_segments as usize,
         );
+        crate::core::direct_downloader::set_global_speed_limit_kbps(
+            settings.download.effective_speed_limit_kbps(),
+        );
+        crate::core::direct_downloader::set_global_use_aria2c(settings.advanced.native_aria2c);
+        crate::core::http_client::set_timeouts(
+            settings.download.http_timeout_secs,
+            settings.download.http_connect_timeout_secs,
+        );
+        crate::core::direct_downloader::set_global_max_retries(settings.download.http_max_retries);
+        crate::core::direct_downloader::set_global_download_timeout_mins(
+            settings.download.download_timeout_mins,
+        );
+        crate::core::temp_dir::set_global_temp_dir(settings.download.temp_dir.clone());
         let mut q = download_queue.lock().await;
         q.max_concurrent = settings.advanced.max_concurrent_downloads.max(1);
         q.stagger_delay_ms = settings.advanced.stagger_delay_ms;
+        q.max_per_host = settings.advanced.max_downloads_per_host.max(1);
+        q.allowed_hours = settings.advanced.allowed_hours;
         q.default_max_retries = settings.advanced.max_retries;
+        q.default_retry_base_delay_secs = settings.advanced.retry_base_delay_secs;
+        q.overwrite_policy = queue::overwrite_policy_for(&settings.download);
         if q.has_url(&url) {
             tracing::debug!("[perf] download_from_url took {:?}", _timer_start.elapsed());
             return Err("Download already in progress for this URL".to_string());
@@ -904,7 +1105,7 @@ pub async fn download_from_url(
         download_id = q.next_available_id(download_id);
     }
 
-    let downloader = match state.registry.find_platform(&url) {
+    let downloader = match resolved_downloader {
         Some(d) => d,
         None => {
             tracing::debug!("[perf] download_from_url took {:?}", _timer_start.elapsed());
@@ -912,6 +1113,56 @@ pub async fn download_from_url(
         }
     };
 
+    if let Some(mut item_urls) = downloader.expand(&url).await.map_err(|e| e.to_string())? {
+        if let Some(archive) = &archive {
+            let total = item_urls.len();
+            item_urls = crate::core::download_archive::filter_new(archive, item_urls);
+            tracing::info!(
+                "[download] archive: {} of {} items are new",
+                item_urls.len(),
+                total
+            );
+            if item_urls.is_empty() {
+                return Err("No new items in this collection".to_string());
+            }
+        }
+        let mut first_started = None;
+        let count = item_urls.len() as u32;
+        let batch_tag = queue::batch_tag_for(&url);
+        for (i, item_url) in item_urls.into_iter().enumerate() {
+            match Box::pin(queue_download(
+                app.clone(),
+                state.clone(),
+                item_url,
+                output_dir.clone(),
+                download_mode.clone(),
+                quality.clone(),
+                format_id.clone(),
+                referer.clone(),
+                cookie_slug.clone(),
+                time_range.clone(),
+                None,
+                None,
+                scheduled_at,
+                stop_at,
+                Some(queue::PlaylistPosition {
+                    index: i as u32 + 1,
+                    count,
+                }),
+                Some(batch_tag.clone()),
+                archive.clone(),
+            ))
+            .await
+            {
+                Ok(started) => {
+                    first_started.get_or_insert(started);
+                }
+                Err(e) => tracing::warn!("[download] skipping collection item: {}", e),
+            }
+        }
+        return first_started.ok_or_else(|| "No items from this collection could be queued".into());
+    }
+
     let platform_name = platform
         .map(|p| p.to_string())
         .unwrap_or_else(|| "generic".to_string());
@@ -940,10 +1191,19 @@ pub async fn download_from_url(
             (info, _) => info,
         }
     };
+    // Playlists were narrowed above; for anything else the picked numbers
+    // select items of a multi-item post.
+    let selected_indices: Option<Vec<usize>> = match (&cached_info, &playlist_items) {
+        (Some(info), _) if info.media_type == MediaType::Playlist => None,
+        (_, Some(sel)) if !sel.is_empty() => {
+            Some(sel.iter().map(|n| n.saturating_sub(1) as usize).collect())
+        }
+        _ => None,
+    };
 
-    let state_to_emit = {
+    let (status, state_to_emit) = {
         let mut q = download_queue.lock().await;
-        q.enqueue(
+        let status = q.enqueue(
             download_id,
             url,
             platform_name,
@@ -963,19 +1223,35 @@ pub async fn download_from_url(
             ytdlp_path,
             false,
             cookie_slug,
-            custom_ytdlp_args,
+            None,
             torrent_files,
             scheduled_at,
             stop_at,
         );
+        if let Some(position) = playlist_position {
+            q.set_playlist_position(download_id, position);
+        }
+        if let Some(tag) = &batch_tag {
+            q.add_tag(download_id, tag);
+        }
+        if let Some(archive) = archive {
+            q.set_archive(download_id, archive);
+        }
+        if let Some(indices) = selected_indices {
+            q.set_selected_indices(download_id, indices);
+        }
+        if let Some((start, end)) = section {
+            q.set_section(download_id, start, end);
+        }
 
         let next_ids = q.next_queued_ids();
         for nid in &next_ids {
             q.mark_active(*nid);
         }
-        q.get_state()
+        (status, q.get_state())
     };
     emit_queue_state_from_state(&app, state_to_emit);
+    queue::prefetch_queued_info(app.clone(), download_queue.clone());
 
     let q_clone = download_queue.clone();
     let app_clone = app.clone();
@@ -1011,6 +1287,7 @@ pub async fn download_from_url(
     Ok(DownloadStarted {
         id: download_id,
         title,
+        status,
     })
 }
 
@@ -1045,7 +1322,11 @@ pub async fn download_with_custom_args(
         let mut q = download_queue.lock().await;
         q.max_concurrent = settings.advanced.max_concurrent_downloads.max(1);
         q.stagger_delay_ms = settings.advanced.stagger_delay_ms;
+        q.max_per_host = settings.advanced.max_downloads_per_host.max(1);
+        q.allowed_hours = settings.advanced.allowed_hours;
         q.default_max_retries = settings.advanced.max_retries;
+        q.default_retry_base_delay_secs = settings.advanced.retry_base_delay_secs;
+        q.overwrite_policy = queue::overwrite_policy_for(&settings.download);
         if q.has_url(&url) {
             return Err("Download already in progress for this URL".to_string());
         }
@@ -1058,9 +1339,9 @@ pub async fn download_with_custom_args(
     let title = url.clone();
     let ytdlp_path = ytdlp::find_ytdlp_cached().await;
 
-    let state_to_emit = {
+    let (status, state_to_emit) = {
         let mut q = download_queue.lock().await;
-        q.enqueue(
+        let status = q.enqueue(
             download_id,
             url,
             "generic".to_string(),
@@ -1089,9 +1370,10 @@ pub async fn download_with_custom_args(
         for nid in &next_ids {
             q.mark_active(*nid);
         }
-        q.get_state()
+        (status, q.get_state())
     };
     emit_queue_state_from_state(&app, state_to_emit);
+    queue::prefetch_queued_info(app.clone(), download_queue.clone());
 
     let q_clone = download_queue.clone();
     let app_clone = app.clone();
@@ -1117,6 +1399,7 @@ pub async fn download_with_custom_args(
     Ok(DownloadStarted {
         id: download_id,
         title,
+        status,
     })
 }
 
@@ -1245,10 +1528,11 @@ pub async fn retry_download(
 }
 
 // Deletes only the exact recorded final path (file → unlink, dir → recursive)
-// when it exists, plus http_fetcher sidecars derived from that exact path.
-// Bounded by construction: every target is derived from the stored file_path,
-// so it can never touch an unrelated file. Best-effort: failures here never
-// fail the list removal.
+// when it exists, plus http_fetcher sidecars and the `.part` file derived
+// from that exact path (see `temp_dir::part_path_for`).
+// Bounded by construction: every target is derived from a stored file_path
+// or extra_files entry, so it can never touch an unrelated file.
+// Best-effort: failures here never fail the list removal.
 fn delete_downloaded_path(path: &str) {
     let p = std::path::Path::new(path);
     if !p.is_absolute() {
@@ -1274,6 +1558,10 @@ fn delete_downloaded_path(path: &str) {
             let _ = std::fs::remove_file(sp);
         }
     }
+    let part = crate::core::temp_dir::part_path_for(p);
+    if part.is_file() {
+        let _ = std::fs::remove_file(part);
+    }
 }
 
 #[tauri::command]
@@ -1283,19 +1571,26 @@ pub async fn remove_download(
     download_id: u64,
     delete_file: Option<bool>,
 ) -> Result<String, String> {
-    let (state_to_emit, seeding_torrent_id, file_path) = {
+    let (state_to_emit, seeding_torrent_id, file_paths) = {
         let mut q = state.download_queue.lock().await;
-        let path = if delete_file.unwrap_or(false) {
+        let paths: Vec<String> = if delete_file.unwrap_or(false) {
             q.items
                 .iter()
                 .find(|i| i.id == download_id)
-                .and_then(|i| i.file_path.clone())
+                .map(|i| {
+                    i.file_path
+                        .iter()
+                        .chain(i.extra_files.iter())
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default()
         } else {
-            None
+            Vec::new()
         };
         match q.remove(download_id) {
-            Some(torrent_id) => (Some(q.get_state()), torrent_id, path),
-            None => (None, None, None),
+            Some(torrent_id) => (Some(q.get_state()), torrent_id, paths),
+            None => (None, None, Vec::new()),
         }
     };
     if let Some(tid) = seeding_torrent_id {
@@ -1305,8 +1600,8 @@ pub async fn remove_download(
                 .await;
         }
     }
-    if let Some(path) = file_path {
-        delete_downloaded_path(&path);
+    for path in &file_paths {
+        delete_downloaded_path(path);
     }
     if let Some(s) = state_to_emit {
         crate::core::download_log::clear(download_id);
@@ -1328,13 +1623,58 @@ pub fn get_recovery_items() -> Vec<crate::core::recovery::RecoveryItem> {
     crate::core::recovery::list()
 }
 
+const HISTORY_PAGE_SIZE: u32 = 100;
+const HISTORY_MAX_PAGE_SIZE: u32 = 500;
+
+/// Completed downloads, newest first, including ones removed from the queue.
+#[tauri::command]
+pub fn history_list(
+    filter: Option<crate::core::queue_history::HistoryFilter>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Vec<crate::core::queue_history::HistoryEntry> {
+    crate::core::queue_history::query(
+        &filter.unwrap_or_default(),
+        None,
+        limit
+            .unwrap_or(HISTORY_PAGE_SIZE)
+            .min(HISTORY_MAX_PAGE_SIZE),
+        offset.unwrap_or(0),
+    )
+}
+
+/// History entries whose title, URL or file path contains `query`.
 #[tauri::command]
-pub fn get_download_history() -> Vec<crate::core::queue_history::HistoryEntry> {
-    crate::core::queue_history::list()
+pub fn history_search(query: String) -> Vec<crate::core::queue_history::HistoryEntry> {
+    crate::core::queue_history::query(&Default::default(), Some(&query), HISTORY_MAX_PAGE_SIZE, 0)
+}
+
+/// Queues a history entry again from its page URL rather than the stored
+/// media URL, which may have been signed and expired since.
+#[tauri::command]
+pub async fn history_redownload(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: u64,
+) -> Result<DownloadStarted, String> {
+    let entry =
+        crate::core::queue_history::get(id).ok_or_else(|| "History entry not found".to_string())?;
+    let url = entry.source_url().to_string();
+    queue::evict_cached_info(&url).await;
+    let output_dir = config::load_settings(&app).download.default_output_dir;
+    if output_dir.as_os_str().is_empty() {
+        return Err("No default output directory configured".to_string());
+    }
+    let output_dir = output_dir.to_string_lossy().into_owned();
+    download_from_url(
+        app, state, url, output_dir, None, None, None, None, None, None, None, None, None, None,
+        None,
+    )
+    .await
 }
 
 #[tauri::command]
-pub fn clear_download_history() {
+pub fn history_clear() {
     crate::core::queue_history::clear_all();
 }
 
@@ -1367,6 +1707,7 @@ pub async fn restore_recovery(
             None,
             None,
             None,
+            None,
         )
         .await
         {
@@ -1398,6 +1739,24 @@ pub fn parse_batch_file(path: String) -> Result<Vec<String>, String> {
     Ok(urls)
 }
 
+/// Session totals plus what the queue is doing right now, for the status bar.
+#[tauri::command]
+pub async fn get_stats(state: tauri::State<'_, AppState>) -> Result<DownloadStats, String> {
+    let mut stats = crate::core::stats::session().snapshot();
+    let q = state.download_queue.lock().await;
+    for item in &q.items {
+        match item.status {
+            queue::QueueStatus::Active => {
+                stats.active += 1;
+                stats.speed_bytes_per_sec += item.speed_bytes_per_sec;
+            }
+            queue::QueueStatus::Queued => stats.queued += 1,
+            _ => {}
+        }
+    }
+    Ok(stats)
+}
+
 #[tauri::command]
 pub async fn update_max_concurrent(
     app: tauri::AppHandle,
@@ -1464,6 +1823,60 @@ pub async fn resume_all_downloads(
     Ok(count)
 }
 
+/// Cancels everything in the queue plus any download or conversion running
+/// outside it.
+/// Each item goes through the same path as `cancel_generic_download`, so
+/// partial files are handled the same way. Returns how many were stopped.
+/// With `tag`, only the queue items carrying it are cancelled.
+#[tauri::command]
+pub async fn cancel_all_downloads(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    tag: Option<String>,
+) -> Result<u32, String> {
+    let (state_to_emit, cancelled) = {
+        let mut q = state.download_queue.lock().await;
+        let cancelled = match tag.as_deref() {
+            Some(tag) => q.cancel_tagged(tag),
+            None => q.cancel_all(),
+        };
+        (q.get_state(), cancelled)
+    };
+    if let Some(session) = state.torrent_session.lock().await.as_ref() {
+        for tid in cancelled.iter().filter_map(|(_, tid)| *tid) {
+            let _ = session
+                .delete(librqbit::api::TorrentIdOrHash::Id(tid), false)
+                .await;
+        }
+    }
+    let mut count = cancelled.len() as u32;
+    // Downloads started outside the queue carry no tags.
+    if tag.is_some() {
+        emit_queue_state_from_state(&app, state_to_emit);
+        return Ok(count);
+    }
+    for token in state.active_downloads.lock().await.values() {
+        if !token.is_cancelled() {
+            token.cancel();
+            count += 1;
+        }
+    }
+    for (_, token) in state.active_generic_downloads.lock().await.values() {
+        if !token.is_cancelled() {
+            token.cancel();
+            count += 1;
+        }
+    }
+    for token in state.active_conversions.lock().await.values() {
+        if !token.is_cancelled() {
+            token.cancel();
+            count += 1;
+        }
+    }
+    emit_queue_state_from_state(&app, state_to_emit);
+    Ok(count)
+}
+
 #[tauri::command]
 pub async fn reorder_queue(
     app: tauri::AppHandle,
@@ -1481,6 +1894,175 @@ pub async fn reorder_queue(
     Ok(changed)
 }
 
+#[tauri::command]
+pub async fn move_to_front(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: u64,
+) -> Result<bool, String> {
+    let (changed, state_to_emit) = {
+        let mut q = state.download_queue.lock().await;
+        let ok = q.move_to_front(id);
+        (ok, q.get_state())
+    };
+    if changed {
+        emit_queue_state_from_state(&app, state_to_emit);
+    }
+    Ok(changed)
+}
+
+#[tauri::command]
+pub async fn move_to_back(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: u64,
+) -> Result<bool, String> {
+    let (changed, state_to_emit) = {
+        let mut q = state.download_queue.lock().await;
+        let ok = q.move_to_back(id);
+        (ok, q.get_state())
+    };
+    if changed {
+        emit_queue_state_from_state(&app, state_to_emit);
+    }
+    Ok(changed)
+}
+
+#[tauri::command]
+pub async fn set_priority(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: u64,
+    priority: i32,
+) -> Result<bool, String> {
+    let (changed, state_to_emit) = {
+        let mut q = state.download_queue.lock().await;
+        let ok = q.set_priority(id, priority);
+        (ok, q.get_state())
+    };
+    if changed {
+        emit_queue_state_from_state(&app, state_to_emit);
+    }
+    Ok(changed)
+}
+
+/// Defers a queued download until `at_ms` (Unix ms), or clears its start
+/// time with `None` so it runs on its turn.
+#[tauri::command]
+pub async fn schedule_download(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: u64,
+    at_ms: Option<u64>,
+) -> Result<bool, String> {
+    let (changed, state_to_emit) = {
+        let mut q = state.download_queue.lock().await;
+        let ok = q.set_schedule(id, at_ms);
+        (ok, q.get_state())
+    };
+    if changed {
+        emit_queue_state_from_state(&app, state_to_emit);
+        if at_ms.is_none() {
+            queue::try_start_next(app, state.download_queue.clone()).await;
+        }
+    }
+    Ok(changed)
+}
+
+/// The queue as the `queue-state-update` event carries it, optionally only
+/// the items tagged `tag`.
+#[tauri::command]
+pub async fn get_queue_state(
+    state: tauri::State<'_, AppState>,
+    tag: Option<String>,
+) -> Result<Vec<queue::QueueItemInfo>, String> {
+    let q = state.download_queue.lock().await;
+    Ok(match tag.as_deref() {
+        Some(tag) => q.get_state_tagged(tag),
+        None => q.get_state(),
+    })
+}
+
+#[tauri::command]
+pub async fn add_tag(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: u64,
+    tag: String,
+) -> Result<bool, String> {
+    let (changed, state_to_emit) = {
+        let mut q = state.download_queue.lock().await;
+        let ok = q.add_tag(id, &tag);
+        (ok, q.get_state())
+    };
+    if changed {
+        emit_queue_state_from_state(&app, state_to_emit);
+    }
+    Ok(changed)
+}
+
+#[tauri::command]
+pub async fn remove_tag(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: u64,
+    tag: String,
+) -> Result<bool, String> {
+    let (changed, state_to_emit) = {
+        let mut q = state.download_queue.lock().await;
+        let ok = q.remove_tag(id, &tag);
+        (ok, q.get_state())
+    };
+    if changed {
+        emit_queue_state_from_state(&app, state_to_emit);
+    }
+    Ok(changed)
+}
+
+/// Remuxes a finished download into a fresh container, then decodes it
+/// again. Clears the item's suspect flag when the result is clean.
+#[tauri::command]
+pub async fn repair_download(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: u64,
+) -> Result<omniget_core::core::ffmpeg::MediaHealth, String> {
+    use omniget_core::core::ffmpeg;
+
+    let path = {
+        let q = state.download_queue.lock().await;
+        q.items
+            .iter()
+            .find(|i| i.id == id)
+            .and_then(|i| i.file_path.clone())
+            .map(std::path::PathBuf::from)
+            .ok_or_else(|| format!("download {} has no file", id))?
+    };
+    if !path.is_file() {
+        return Err(format!("file not found: {}", path.display()));
+    }
+    if !ffmpeg::is_ffmpeg_available().await {
+        return Err("ffmpeg not found".to_string());
+    }
+
+    let size = ffmpeg::repair_media(&path)
+        .await
+        .map_err(|e| e.to_string())?;
+    let health = ffmpeg::verify_media(&path, &tokio_util::sync::CancellationToken::new())
+        .await
+        .map_err(|e| e.to_string())?;
+    let state_to_emit = {
+        let mut q = state.download_queue.lock().await;
+        q.set_suspect(id, !health.ok);
+        if let Some(item) = q.items.iter_mut().find(|i| i.id == id) {
+            item.file_size_bytes = Some(size);
+        }
+        q.get_state()
+    };
+    emit_queue_state_from_state(&app, state_to_emit);
+    Ok(health)
+}
+
 #[tauri::command]
 pub async fn clear_finished_downloads(
     app: tauri::AppHandle,
@@ -1495,24 +2077,60 @@ pub async fn clear_finished_downloads(
     Ok("Finished downloads cleared".to_string())
 }
 
+/// Error returned by the file-opening commands. `code` is stable so the UI
+/// can tell a moved/deleted download apart from a launcher failure.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileActionError {
+    pub code: String,
+    pub message: String,
+}
+
+impl FileActionError {
+    fn missing(path: &std::path::Path) -> Self {
+        Self {
+            code: "file_missing".to_string(),
+            message: format!("File no longer exists: {}", path.display()),
+        }
+    }
+
+    fn launch_failed(message: String) -> Self {
+        Self {
+            code: "launch_failed".to_string(),
+            message,
+        }
+    }
+}
+
+impl From<std::io::Error> for FileActionError {
+    fn from(e: std::io::Error) -> Self {
+        Self::launch_failed(e.to_string())
+    }
+}
+
 #[cfg(not(target_os = "android"))]
 #[tauri::command]
-pub async fn reveal_file(path: String) -> Result<(), String> {
+pub async fn reveal_file(path: String) -> Result<(), FileActionError> {
+    let target = std::path::Path::new(&path);
+    if target.is_dir() {
+        return open::that_detached(target).map_err(FileActionError::from);
+    }
+    if !target.exists() {
+        return Err(FileActionError::missing(target));
+    }
+
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
         std::process::Command::new("explorer")
             .raw_arg(format!("/select,\"{}\"", path))
-            .spawn()
-            .map_err(|e| e.to_string())?;
+            .spawn()?;
     }
 
     #[cfg(target_os = "macos")]
     {
         std::process::Command::new("open")
             .args(["-R", &path])
-            .spawn()
-            .map_err(|e| e.to_string())?;
+            .spawn()?;
     }
 
     #[cfg(target_os = "linux")]
@@ -1611,8 +2229,7 @@ pub async fn reveal_file(path: String) -> Result<(), String> {
                     .arg(dir_path)
                     .stdout(Stdio::null())
                     .stderr(Stdio::null())
-                    .spawn()
-                    .map_err(|e| e.to_string())?;
+                    .spawn()?;
             }
         }
     }
@@ -1620,6 +2237,18 @@ pub async fn reveal_file(path: String) -> Result<(), String> {
     Ok(())
 }
 
+#[cfg(not(target_os = "android"))]
+#[tauri::command]
+pub async fn open_file(path: String) -> Result<(), FileActionError> {
+    let target = std::path::Path::new(&path);
+    if !target.exists() {
+        return Err(FileActionError::missing(target));
+    }
+    open_path_default(path)
+        .await
+        .map_err(FileActionError::launch_failed)
+}
+
 #[cfg(not(target_os = "android"))]
 #[tauri::command]
 pub async fn open_path_default(path: String) -> Result<(), String> {
@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::Emitter;
+use tokio_util::sync::CancellationToken;
+
+use omniget_core::core::ffmpeg::{self, AudioCodec, AudioPreset, BitrateMode, ConversionResult};
+use omniget_core::models::progress::ProgressUpdate;
+
+use crate::commands::host_queue::next_external_id;
+use crate::AppState;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtractAudioRequest {
+    pub input_path: String,
+    pub output_path: Option<String>,
+    pub codec: AudioCodec,
+    pub bitrate_kbps: u32,
+    #[serde(default)]
+    pub mode: BitrateMode,
+}
+
+#[tauri::command]
+pub fn list_audio_presets() -> Vec<AudioPreset> {
+    ffmpeg::AUDIO_PRESETS.to_vec()
+}
+
+/// Rips the audio track of a downloaded file. Emits
+/// `extract-audio-progress` with `{ input_path, percent }` while ffmpeg runs.
+/// Registered as an active conversion so `cancel_all_downloads` stops it.
+#[tauri::command]
+pub async fn extract_audio(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    req: ExtractAudioRequest,
+) -> Result<ConversionResult, String> {
+    if !ffmpeg::is_ffmpeg_available().await {
+        return Err("ffmpeg not found".to_string());
+    }
+
+    let input = PathBuf::from(&req.input_path);
+    if !input.is_file() {
+        return Err(format!("source not found: {}", req.input_path));
+    }
+    let output = req
+        .output_path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| default_output_path(&input, req.codec));
+
+    let id = next_external_id();
+    let cancel_token = CancellationToken::new();
+    state
+        .active_conversions
+        .lock()
+        .await
+        .insert(id, cancel_token.clone());
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<ProgressUpdate>(16);
+    let input_path = req.input_path.clone();
+    let forwarder = tokio::spawn(async move {
+        while let Some(update) = rx.recv().await {
+            let _ = app.emit(
+                "extract-audio-progress",
+                serde_json::json!({ "input_path": input_path, "percent": update.percent }),
+            );
+        }
+    });
+
+    let result = ffmpeg::convert_to_audio(
+        &input,
+        &output,
+        req.codec,
+        req.bitrate_kbps,
+        req.mode,
+        cancel_token,
+        tx,
+    )
+    .await;
+    let _ = forwarder.await;
+    state.active_conversions.lock().await.remove(&id);
+
+    let result = result.map_err(|e| e.to_string())?;
+    match result.error {
+        Some(error) if !result.success => Err(error),
+        _ => Ok(result),
+    }
+}
+
+/// `<stem>.<ext>` next to the input, or `<stem>.audio.<ext>` when that name
+/// is taken (including by the input itself).
+fn default_output_path(input: &Path, codec: AudioCodec) -> PathBuf {
+    let stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("out");
+    let parent = input
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let plain = parent.join(format!("{}.{}", stem, codec.extension()));
+    if plain.exists() {
+        parent.join(format!("{}.audio.{}", stem, codec.extension()))
+    } else {
+        plain
+    }
+}
@@ -15,7 +15,7 @@ use crate::AppState;
 
 static EXTERNAL_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
 
-fn next_external_id() -> u64 {
+pub(crate) fn next_external_id() -> u64 {
     let now_ms = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|d| d.as_millis() as u64)
@@ -90,6 +90,8 @@ pub async fn enqueue_external_inner(
         thumbnail_url_override: args.thumbnail_url,
         retry_count: 0,
         max_retries: 0,
+        retry_base_delay_secs: 0,
+        retry_at_ms: None,
         resume_state: None,
         concurrent_segments: None,
         segment_size_bytes: None,
@@ -99,6 +101,16 @@ pub async fn enqueue_external_inner(
         torrent_files: None,
         scheduled_at_ms: None,
         stop_at_ms: None,
+        priority: 0,
+        proxy_retried: false,
+        playlist_position: None,
+        selected_indices: None,
+        section: None,
+        archive: None,
+        tags: Vec::new(),
+        suspect: false,
+        extra_files: Vec::new(),
+        phase: None,
     };
 
     {
@@ -6,16 +6,20 @@ pub mod bilibili_auth;
 pub mod browser_extension;
 pub mod channels;
 pub mod clip;
+pub mod convert_batch;
 pub mod diagnostics;
 pub mod downloads;
+pub mod extract_audio;
 pub mod host_queue;
 pub mod integration;
 pub mod p2p;
 pub mod plugins;
+pub mod probe;
 pub mod reencode;
 pub mod rpc;
 pub mod settings;
 pub mod subtitle_ws;
+pub mod trim;
 pub mod video_ops;
 pub mod yt_templates;
 
@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use omniget_core::core::ffmpeg::{self, MediaHealth, MediaProbeInfo};
+
+/// Container, per-stream details (codec, resolution, language, channel
+/// layout) and chapters of a local media file.
+#[tauri::command]
+pub async fn probe_file(path: String) -> Result<MediaProbeInfo, String> {
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(format!("file not found: {}", path.display()));
+    }
+    ffmpeg::probe(&path).await.map_err(|e| e.to_string())
+}
+
+/// Decodes a local media file and reports any errors ffmpeg hit.
+#[tauri::command]
+pub async fn verify_media_file(path: String) -> Result<MediaHealth, String> {
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(format!("file not found: {}", path.display()));
+    }
+    if !ffmpeg::is_ffmpeg_available().await {
+        return Err("ffmpeg not found".to_string());
+    }
+    ffmpeg::verify_media(&path, &tokio_util::sync::CancellationToken::new())
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use serde::Serialize;
 
 use crate::core::ytdlp;
@@ -13,6 +15,16 @@ pub struct SearchResult {
     pub platform: String,
 }
 
+/// yt-dlp search prefixes for the platforms the unified search can query.
+fn search_prefix(platform: &str) -> Option<&'static str> {
+    match platform {
+        "youtube" => Some("ytsearch"),
+        "soundcloud" => Some("scsearch"),
+        "bilibili" => Some("bilisearch"),
+        _ => None,
+    }
+}
+
 #[tauri::command]
 pub async fn search_videos(
     query: String,
@@ -24,14 +36,76 @@ pub async fn search_videos(
         .map_err(|e| format!("yt-dlp unavailable: {}", e))?;
 
     let n = max_results.clamp(1, 20);
+    let prefix = search_prefix(&platform).unwrap_or("ytsearch");
+
+    run_search(&ytdlp_path, prefix, &platform, &query, n).await
+}
+
+/// Searches several platforms concurrently. Results are grouped in the order
+/// the platforms were requested; platforms that fail are logged and skipped
+/// unless every one of them failed.
+#[tauri::command]
+pub async fn search(
+    query: String,
+    platforms: Vec<String>,
+    limit: u32,
+) -> Result<Vec<SearchResult>, String> {
+    let targets: Vec<(String, &'static str)> = platforms
+        .iter()
+        .map(|p| p.trim().to_lowercase())
+        .filter_map(|p| search_prefix(&p).map(|prefix| (p, prefix)))
+        .collect();
+    if targets.is_empty() {
+        return Err(format!(
+            "Search is not supported for: {}",
+            platforms.join(", ")
+        ));
+    }
+
+    let ytdlp_path = ytdlp::ensure_ytdlp()
+        .await
+        .map_err(|e| format!("yt-dlp unavailable: {}", e))?;
 
-    let search_query = match platform.as_str() {
-        "youtube" => format!("ytsearch{}:{}", n, query),
-        "soundcloud" => format!("scsearch{}:{}", n, query),
-        _ => format!("ytsearch{}:{}", n, query),
-    };
+    let n = limit.clamp(1, 20);
+    let outcomes = futures::future::join_all(
+        targets
+            .iter()
+            .map(|(platform, prefix)| run_search(&ytdlp_path, prefix, platform, &query, n)),
+    )
+    .await;
 
-    let output = crate::core::process::command(&ytdlp_path)
+    let mut results = Vec::new();
+    let mut first_error = None;
+    let mut any_ok = false;
+    for ((platform, _), outcome) in targets.iter().zip(outcomes) {
+        match outcome {
+            Ok(found) => {
+                any_ok = true;
+                results.extend(found);
+            }
+            Err(e) => {
+                tracing::warn!("[search] {} search failed: {}", platform, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    match first_error {
+        Some(e) if !any_ok => Err(e),
+        _ => Ok(results),
+    }
+}
+
+async fn run_search(
+    ytdlp_path: &Path,
+    prefix: &str,
+    platform: &str,
+    query: &str,
+    n: u32,
+) -> Result<Vec<SearchResult>, String> {
+    let search_query = format!("{}{}:{}", prefix, n, query);
+
+    let output = crate::core::process::command(ytdlp_path)
         .args([
             "--flat-playlist",
             "--dump-json",
@@ -104,11 +178,12 @@ pub async fn search_videos(
             .get("url")
             .or_else(|| json.get("webpage_url"))
             .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| match platform.as_str() {
-                "youtube" => format!("https://www.youtube.com/watch?v={}", id),
-                _ => format!("https://www.youtube.com/watch?v={}", id),
-            });
+            .map(|s| s.to_string());
+        let url = match (url, platform) {
+            (Some(url), _) => url,
+            (None, "youtube") => format!("https://www.youtube.com/watch?v={}", id),
+            (None, _) => continue,
+        };
 
         results.push(SearchResult {
             id,
@@ -117,7 +192,7 @@ pub async fn search_videos(
             duration,
             thumbnail_url,
             url,
-            platform: platform.clone(),
+            platform: platform.to_string(),
         });
     }
 
@@ -1,6 +1,8 @@
+use crate::core::backup::{self, ExportedState, ImportSummary};
 use crate::hotkey;
-use crate::models::settings::AppSettings;
+use crate::models::settings::{self, AppSettings};
 use crate::storage::config;
+use crate::AppState;
 
 #[tauri::command]
 pub fn get_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
@@ -9,6 +11,7 @@ pub fn get_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
 
 #[tauri::command]
 pub fn update_settings(app: tauri::AppHandle, partial: String) -> Result<AppSettings, String> {
+    let lock = config::write_lock();
     let mut current = config::load_settings(&app);
     let old_hotkey_enabled = current.download.hotkey_enabled;
     let old_hotkey_binding = current.download.hotkey_binding.clone();
@@ -18,6 +21,7 @@ pub fn update_settings(app: tauri::AppHandle, partial: String) -> Result<AppSett
     let old_music_hotkey_binding = current.download.music_hotkey_binding.clone();
     let old_start_with_system = current.start_with_system;
     let old_rpc = current.rpc.clone();
+    let old_temp_dir = current.download.temp_dir.clone();
 
     let patch: serde_json::Value =
         serde_json::from_str(&partial).map_err(|e| format!("Invalid JSON: {}", e))?;
@@ -25,12 +29,36 @@ pub fn update_settings(app: tauri::AppHandle, partial: String) -> Result<AppSett
         serde_json::to_value(&current).map_err(|e| format!("Serialize: {}", e))?;
     merge_json(&mut current_val, &patch);
     current = serde_json::from_value(current_val).map_err(|e| format!("Deserialize: {}", e))?;
+    current.advanced.clamp_concurrency();
+    current.download.clamp_hls();
+    current.download.clamp_http();
+    if current.download.temp_dir != old_temp_dir {
+        if let Some(dir) = &current.download.temp_dir {
+            crate::core::temp_dir::validate(dir)?;
+        }
+    }
     config::save_settings(&app, &current).map_err(|e| format!("Save: {}", e))?;
+    drop(lock);
 
     crate::core::http_client::init_proxy(current.proxy.clone());
+    crate::core::http_client::init_header_profiles(current.advanced.header_profiles.clone());
+    omniget_core::platforms::set_backend_preferences(current.download.platform_backend.clone());
     crate::core::http_fetcher::set_global_max_concurrent_segments(
         current.advanced.max_concurrent_segments as usize,
     );
+    crate::core::direct_downloader::set_global_speed_limit_kbps(
+        current.download.effective_speed_limit_kbps(),
+    );
+    crate::core::direct_downloader::set_global_use_aria2c(current.advanced.native_aria2c);
+    crate::core::http_client::set_timeouts(
+        current.download.http_timeout_secs,
+        current.download.http_connect_timeout_secs,
+    );
+    crate::core::direct_downloader::set_global_max_retries(current.download.http_max_retries);
+    crate::core::direct_downloader::set_global_download_timeout_mins(
+        current.download.download_timeout_mins,
+    );
+    crate::core::temp_dir::set_global_temp_dir(current.download.temp_dir.clone());
 
     if old_hotkey_enabled != current.download.hotkey_enabled
         || old_hotkey_binding != current.download.hotkey_binding
@@ -64,11 +92,150 @@ pub fn update_settings(app: tauri::AppHandle, partial: String) -> Result<AppSett
 pub fn reset_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
     let defaults = AppSettings::default();
     config::save_settings(&app, &defaults).map_err(|e| format!("Save: {}", e))?;
+    crate::core::paths::ensure_default_output_dir(&defaults.download.default_output_dir);
+    omniget_core::platforms::set_backend_preferences(defaults.download.platform_backend.clone());
+    crate::core::http_client::init_header_profiles(defaults.advanced.header_profiles.clone());
     hotkey::reregister(&app);
     let _ = crate::commands::autostart::apply_autostart(&app, defaults.start_with_system);
     Ok(defaults)
 }
 
+/// Settings, the unfinished part of the queue and a history summary as
+/// JSON, for moving to another install. Tokens and passwords are blanked
+/// unless `include_secrets` is set.
+#[tauri::command]
+pub async fn export_state(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    include_secrets: Option<bool>,
+) -> Result<String, String> {
+    let include_secrets = include_secrets.unwrap_or(false);
+    let mut settings = serde_json::to_value(config::load_settings(&app))
+        .map_err(|e| format!("Serialize: {}", e))?;
+    if !include_secrets {
+        backup::strip_secrets(&mut settings);
+    }
+    let queue = backup::pending_items(&*state.download_queue.lock().await);
+    let exported = ExportedState {
+        format_version: backup::FORMAT_VERSION,
+        app_version: app.package_info().version.to_string(),
+        exported_at: crate::core::queue_history::now_unix_seconds(),
+        settings,
+        includes_secrets: include_secrets,
+        queue,
+        history: backup::summarize_history(&crate::core::queue_history::list()),
+    };
+    serde_json::to_string_pretty(&exported).map_err(|e| format!("Serialize: {}", e))
+}
+
+/// Applies an `export_state` file: its settings go through the same
+/// migration as stored ones and replace the current ones, keeping current
+/// secrets where the file has none and the protected settings always, then
+/// its queue items are queued again, the paused ones held until they're
+/// resumed.
+#[tauri::command]
+pub async fn import_state(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    json: String,
+) -> Result<ImportSummary, String> {
+    let imported = backup::parse(&json)?;
+    let current = serde_json::to_value(config::load_settings(&app))
+        .map_err(|e| format!("Serialize: {}", e))?;
+    let mut incoming = serde_json::to_value(settings::migrate(imported.settings))
+        .map_err(|e| format!("Serialize: {}", e))?;
+    backup::keep_current_secrets(&mut incoming, &current);
+    let kept_settings = backup::keep_protected_settings(&mut incoming, &current);
+    if !kept_settings.is_empty() {
+        tracing::warn!(
+            "[backup] import left these settings unchanged: {:?}",
+            kept_settings
+        );
+    }
+    // A temp folder from the other machine would fail validation here.
+    if let Some(dir) = incoming.pointer_mut("/download/temp_dir") {
+        if dir
+            .as_str()
+            .is_some_and(|d| !std::path::Path::new(d).is_dir())
+        {
+            *dir = serde_json::Value::Null;
+        }
+    }
+    let applied = update_settings(app.clone(), incoming.to_string())?;
+    crate::core::paths::ensure_default_output_dir(&applied.download.default_output_dir);
+
+    let mut summary = ImportSummary {
+        settings_applied: true,
+        kept_settings,
+        ..Default::default()
+    };
+    let fallback_dir = applied
+        .download
+        .default_output_dir
+        .to_string_lossy()
+        .to_string();
+    for item in imported.queue {
+        // Folders from the other machine usually don't exist here.
+        let output_dir = if std::path::Path::new(&item.output_dir).is_dir() {
+            item.output_dir
+        } else {
+            fallback_dir.clone()
+        };
+        // Paused items are scheduled out of reach so they can't start
+        // before they're held.
+        let scheduled_at = item.paused.then_some(u64::MAX);
+        match crate::commands::downloads::download_from_url(
+            app.clone(),
+            state.clone(),
+            item.url,
+            output_dir,
+            item.download_mode,
+            item.quality,
+            item.format_id,
+            item.referer,
+            None,
+            None,
+            None,
+            None,
+            scheduled_at,
+            None,
+            None,
+        )
+        .await
+        {
+            Ok(started) => {
+                summary.queued += 1;
+                let mut q = state.download_queue.lock().await;
+                for tag in &item.tags {
+                    q.add_tag(started.id, tag);
+                }
+                if item.paused {
+                    q.hold(started.id);
+                }
+            }
+            Err(e) => {
+                summary.failed += 1;
+                tracing::warn!("[backup] could not queue imported item: {}", e);
+            }
+        }
+    }
+    let queue_state = state.download_queue.lock().await.get_state();
+    crate::core::queue::emit_queue_state_from_state(&app, queue_state);
+    Ok(summary)
+}
+
+/// Folders downloads recently finished into, most recent first, leaving
+/// out ones that no longer exist.
+#[tauri::command]
+pub fn get_recent_dirs(app: tauri::AppHandle) -> Vec<String> {
+    config::load_settings(&app)
+        .recent_output_dirs
+        .into_iter()
+        .filter(|dir| dir.is_dir())
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect()
+}
+
 #[tauri::command]
 pub fn mark_onboarding_complete(app: tauri::AppHandle) -> Result<(), String> {
     let mut current = config::load_settings(&app);
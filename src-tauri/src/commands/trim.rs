@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::Emitter;
+use tokio_util::sync::CancellationToken;
+
+use omniget_core::core::ffmpeg;
+use omniget_core::models::progress::ProgressUpdate;
+
+use crate::commands::host_queue::next_external_id;
+use crate::AppState;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrimStarted {
+    /// Id used by `convert-progress`/`convert-complete` and `cancel_trim`.
+    pub id: u64,
+    pub output_path: String,
+    pub start: f64,
+    pub end: f64,
+    /// The requested range ran past the end of the file and was shortened.
+    pub clamped: bool,
+}
+
+/// Cuts `start..end` (seconds) out of `input` into `<stem>.trim.<ext>` next
+/// to it. Runs in the background and reports on the same events as
+/// conversions.
+#[tauri::command]
+pub async fn trim_file(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    input: String,
+    start: f64,
+    end: Option<f64>,
+    reencode: bool,
+) -> Result<TrimStarted, String> {
+    if !ffmpeg::is_ffmpeg_available().await {
+        return Err("ffmpeg not found".to_string());
+    }
+    let input_path = PathBuf::from(&input);
+    if !input_path.is_file() {
+        return Err(format!("source not found: {}", input));
+    }
+
+    let info = ffmpeg::probe(&input_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    let range =
+        ffmpeg::clamp_trim_range(start, end, info.duration_seconds).map_err(|e| e.to_string())?;
+    if range.clamped {
+        tracing::warn!(
+            "[trim] requested {:.3}-{:?} exceeds {:.3}s, using {:.3}-{:.3}",
+            start,
+            end,
+            info.duration_seconds,
+            range.start,
+            range.end
+        );
+    }
+
+    let output = output_path(&input_path);
+    let opts = ffmpeg::trim_options(&input_path, &output, range, reencode);
+
+    let id = next_external_id();
+    let cancel_token = CancellationToken::new();
+    state
+        .active_conversions
+        .lock()
+        .await
+        .insert(id, cancel_token.clone());
+
+    let active_conversions = state.active_conversions.clone();
+    tauri::async_runtime::spawn(async move {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<ProgressUpdate>(16);
+        let progress_app = app.clone();
+        let forwarder = tokio::spawn(async move {
+            while let Some(update) = rx.recv().await {
+                let _ = progress_app.emit(
+                    "convert-progress",
+                    serde_json::json!({ "id": id, "percent": update.percent }),
+                );
+            }
+        });
+
+        let result = ffmpeg::convert(&opts, cancel_token, tx).await;
+        let _ = forwarder.await;
+        active_conversions.lock().await.remove(&id);
+
+        let payload = match result {
+            Ok(result) => serde_json::json!({
+                "id": id,
+                "success": result.success,
+                "error": result.error.clone(),
+                "result": result,
+            }),
+            Err(e) => serde_json::json!({
+                "id": id,
+                "success": false,
+                "error": e.to_string(),
+                "result": null,
+            }),
+        };
+        let _ = app.emit("convert-complete", payload);
+    });
+
+    Ok(TrimStarted {
+        id,
+        output_path: output.to_string_lossy().into_owned(),
+        start: range.start,
+        end: range.end,
+        clamped: range.clamped,
+    })
+}
+
+#[tauri::command]
+pub async fn cancel_trim(state: tauri::State<'_, AppState>, id: u64) -> Result<(), String> {
+    match state.active_conversions.lock().await.get(&id) {
+        Some(token) => {
+            token.cancel();
+            Ok(())
+        }
+        None => Err("Trim not found".to_string()),
+    }
+}
+
+fn output_path(input: &Path) -> PathBuf {
+    let stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("out");
+    let ext = input.extension().and_then(|s| s.to_str()).unwrap_or("mp4");
+    let parent = input
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."));
+    parent.join(format!("{}.trim.{}", stem, ext))
+}
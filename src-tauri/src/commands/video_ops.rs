@@ -120,6 +120,29 @@ pub async fn waveform_peaks(input: String, buckets: Option<usize>) -> Result<Vec
     Ok(ffmpeg_ops::pcm_s16le_peaks(&output.stdout, n))
 }
 
+/// Tiles `columns` x `rows` evenly spaced frames into a JPEG next to the
+/// input. `width` is the width of the whole sheet in pixels.
+#[cfg(not(target_os = "android"))]
+#[tauri::command]
+pub async fn generate_contact_sheet(
+    video_path: String,
+    columns: Option<u32>,
+    rows: Option<u32>,
+    width: Option<u32>,
+) -> Result<VideoOpResult, String> {
+    let input_path = ensure_input(&video_path)?;
+    let columns = columns.unwrap_or(4).clamp(1, 12);
+    let rows = rows.unwrap_or(4).clamp(1, 12);
+    let width = width.unwrap_or(1280).clamp(320, 7680);
+    let output = output_for(&input_path, "contact", "jpg");
+    omniget_core::core::ffmpeg::generate_contact_sheet(&input_path, &output, columns, rows, width)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(VideoOpResult {
+        output_path: output.to_string_lossy().to_string(),
+    })
+}
+
 #[cfg(not(target_os = "android"))]
 #[tauri::command]
 pub async fn video_op_preset(
@@ -190,3 +213,103 @@ pub async fn video_op_run(
         output_path: output.to_string_lossy().to_string(),
     })
 }
+
+/// Runs `opts` through the conversion pipeline, forwarding its progress as
+/// `video-op-percent` with `{ input, percent }`. The run is registered as an
+/// active conversion so `cancel_all_downloads` stops it.
+#[cfg(not(target_os = "android"))]
+async fn run_conversion(
+    app: &tauri::AppHandle,
+    opts: &omniget_core::core::ffmpeg::ConversionOptions,
+) -> Result<(), String> {
+    use omniget_core::core::ffmpeg;
+    use omniget_core::models::progress::ProgressUpdate;
+    use tauri::Manager;
+
+    if !ffmpeg::is_ffmpeg_available().await {
+        return Err("ffmpeg unavailable".to_string());
+    }
+    let _ = app.emit("video-op-progress", "running");
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<ProgressUpdate>(16);
+    let progress_app = app.clone();
+    let input = opts.input_path.clone();
+    let forwarder = tokio::spawn(async move {
+        while let Some(update) = rx.recv().await {
+            let _ = progress_app.emit(
+                "video-op-percent",
+                serde_json::json!({ "input": input, "percent": update.percent }),
+            );
+        }
+    });
+    let id = crate::commands::host_queue::next_external_id();
+    let cancel_token = tokio_util::sync::CancellationToken::new();
+    let active_conversions = app.state::<crate::AppState>().active_conversions.clone();
+    active_conversions
+        .lock()
+        .await
+        .insert(id, cancel_token.clone());
+    let result = ffmpeg::convert(opts, cancel_token, tx).await;
+    let _ = forwarder.await;
+    active_conversions.lock().await.remove(&id);
+
+    let error = match result {
+        Ok(result) if result.success => None,
+        Ok(result) => Some(
+            result
+                .error
+                .unwrap_or_else(|| "ffmpeg processing failed".to_string()),
+        ),
+        Err(e) => Some(e.to_string()),
+    };
+    if let Some(error) = error {
+        let _ = app.emit("video-op-progress", "error");
+        return Err(error);
+    }
+    let _ = app.emit("video-op-progress", "done");
+    Ok(())
+}
+
+/// Turns a clip into a palette-optimized GIF next to it. `fps` and `width`
+/// default to 15 and 480; the GIF loops unless `looping` is false.
+#[cfg(not(target_os = "android"))]
+#[tauri::command]
+pub async fn convert_to_gif(
+    app: tauri::AppHandle,
+    input: String,
+    fps: Option<u32>,
+    width: Option<u32>,
+    looping: Option<bool>,
+) -> Result<VideoOpResult, String> {
+    let input_path = ensure_input(&input)?;
+    let fps = fps.unwrap_or(15).clamp(1, 50);
+    let width = width.unwrap_or(480).clamp(64, 1920);
+    let output = output_for(&input_path, "to_gif", "gif");
+    let opts = omniget_core::core::ffmpeg::gif_options(
+        &input_path,
+        &output,
+        fps,
+        width,
+        looping.unwrap_or(true),
+    );
+    run_conversion(&app, &opts).await?;
+    Ok(VideoOpResult {
+        output_path: output.to_string_lossy().to_string(),
+    })
+}
+
+/// Re-encodes a GIF as a much smaller silent MP4 next to it.
+#[cfg(not(target_os = "android"))]
+#[tauri::command]
+pub async fn convert_gif_to_mp4(
+    app: tauri::AppHandle,
+    input: String,
+) -> Result<VideoOpResult, String> {
+    let input_path = ensure_input(&input)?;
+    let output = output_for(&input_path, "to_mp4", "mp4");
+    let opts = omniget_core::core::ffmpeg::gif_to_mp4_options(&input_path, &output);
+    run_conversion(&app, &opts).await?;
+    Ok(VideoOpResult {
+        output_path: output.to_string_lossy().to_string(),
+    })
+}
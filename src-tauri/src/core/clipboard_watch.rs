@@ -0,0 +1,151 @@
+//! Background clipboard watcher behind the `clipboard_watch` setting.
+//!
+//! Unlike the frontend monitor, which only fills the omnibox while the
+//! window has focus, this keeps running when OmniGet sits in the tray and
+//! queues supported URLs directly.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::storage::config;
+use crate::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(1500);
+/// How many recently queued URLs are remembered to avoid re-triggering.
+const RECENT_CAPACITY: usize = 32;
+
+/// First token of the clipboard text if it is an http(s) or magnet URL.
+fn clipboard_url(text: &str) -> Option<String> {
+    let candidate = text.split_whitespace().next()?;
+    let parsed = url::Url::parse(candidate).ok()?;
+    matches!(parsed.scheme(), "http" | "https" | "magnet").then(|| candidate.to_string())
+}
+
+/// Debounces clipboard reads: a URL fires once it has been seen on two
+/// consecutive polls, and never again while it is in the recent list.
+#[derive(Default)]
+struct Watcher {
+    armed: bool,
+    pending: Option<String>,
+    recent: VecDeque<String>,
+}
+
+impl Watcher {
+    fn observe(&mut self, text: &str) -> Option<String> {
+        let candidate = clipboard_url(text);
+        if !self.armed {
+            // Whatever was on the clipboard before watching started is not
+            // something the user just copied.
+            self.armed = true;
+            self.remember(candidate);
+            return None;
+        }
+
+        let Some(url) = candidate.filter(|u| !self.recent.contains(u)) else {
+            self.pending = None;
+            return None;
+        };
+        if self.pending.as_ref() != Some(&url) {
+            self.pending = Some(url);
+            return None;
+        }
+
+        self.pending = None;
+        self.remember(Some(url.clone()));
+        Some(url)
+    }
+
+    fn remember(&mut self, url: Option<String>) {
+        if let Some(url) = url {
+            if self.recent.len() == RECENT_CAPACITY {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(url);
+        }
+    }
+}
+
+/// Only URLs a dedicated platform claims are queued; the generic yt-dlp
+/// fallback would accept any web page. Short links are let through since
+/// the queue resolves them before picking a platform.
+fn is_supported(app: &AppHandle, url: &str) -> bool {
+    if crate::core::redirect::is_short_link(url) {
+        return true;
+    }
+    app.state::<AppState>()
+        .registry
+        .find_dedicated(url)
+        .is_some()
+}
+
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut watcher = Watcher::default();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            if !config::load_settings(&app).download.clipboard_watch {
+                watcher = Watcher::default();
+                continue;
+            }
+
+            let Ok(text) = app.clipboard().read_text() else {
+                continue;
+            };
+            let Some(url) = watcher.observe(&text) else {
+                continue;
+            };
+            if !is_supported(&app, &url) {
+                continue;
+            }
+
+            match crate::external_url::queue_url_with_defaults(&app, url.clone(), false, None).await
+            {
+                Ok(crate::external_url::QueueUrlOutcome::Queued) => {
+                    tracing::info!("[clipboard] queued {}", url);
+                    let _ = app.emit(
+                        "clipboard-download-queued",
+                        serde_json::json!({ "url": url }),
+                    );
+                }
+                Ok(crate::external_url::QueueUrlOutcome::AlreadyQueued) => {}
+                Err(e) => tracing::warn!("[clipboard] could not queue {}: {}", url, e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_non_url_text() {
+        assert_eq!(clipboard_url("just some notes"), None);
+        assert_eq!(clipboard_url("file:///etc/passwd"), None);
+        assert_eq!(
+            clipboard_url("  https://youtu.be/abc extra words"),
+            Some("https://youtu.be/abc".to_string())
+        );
+    }
+
+    #[test]
+    fn fires_once_per_url_after_settling() {
+        let mut watcher = Watcher::default();
+        assert_eq!(watcher.observe("https://example.com/old"), None);
+        assert_eq!(watcher.observe("https://example.com/old"), None);
+
+        assert_eq!(watcher.observe("https://example.com/new"), None);
+        assert_eq!(
+            watcher.observe("https://example.com/new"),
+            Some("https://example.com/new".to_string())
+        );
+        assert_eq!(watcher.observe("https://example.com/new"), None);
+
+        assert_eq!(watcher.observe("notes"), None);
+        assert_eq!(watcher.observe("https://example.com/new"), None);
+        assert_eq!(watcher.observe("https://example.com/new"), None);
+    }
+}
@@ -0,0 +1,57 @@
+use std::path::Path;
+
+/// Bytes the current user can still write on the volume holding `path`.
+/// `path` doesn't have to exist yet; the nearest existing ancestor is
+/// asked instead. `None` when the platform call fails.
+pub fn available_bytes(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    query(existing)
+}
+
+#[cfg(unix)]
+fn query(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+fn query(path: &Path) -> Option<u64> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let mut available = 0u64;
+    unsafe {
+        GetDiskFreeSpaceExW(
+            &HSTRING::from(path),
+            Some(&mut available as *mut u64),
+            None,
+            None,
+        )
+    }
+    .ok()?;
+    Some(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn query(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_path_uses_existing_ancestor() {
+        let dir = std::env::temp_dir().join("omniget-disk-space-test/not/created");
+        assert!(available_bytes(&dir).is_some());
+    }
+}
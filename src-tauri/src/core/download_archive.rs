@@ -0,0 +1,162 @@
+//! "Only new" mode for channels and playlists: every source keeps a
+//! `downloaded-ids.txt` with one `<extractor> <id>` line per finished
+//! item, the same format yt-dlp's `--download-archive` reads and writes,
+//! so both can share one file.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use sha2::{Digest, Sha256};
+
+/// Archive lines already read, with the file length they were read at.
+/// yt-dlp appends to the same files, so a length change means re-reading.
+struct Loaded {
+    len: u64,
+    entries: HashSet<String>,
+}
+
+static LOADED: LazyLock<Mutex<HashMap<PathBuf, Loaded>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Archive file for the collection at `source_url`, under the app data
+/// folder. `None` when there is no data folder.
+pub fn path_for_source(source_url: &str) -> Option<PathBuf> {
+    let data_dir = omniget_core::core::paths::app_data_dir()?;
+    Some(
+        data_dir
+            .join("archive")
+            .join(source_key(source_url))
+            .join("downloaded-ids.txt"),
+    )
+}
+
+/// Folder name for a source: its readable tag plus a hash of the full
+/// URL, so two channels with the same handle on different sites differ.
+fn source_key(source_url: &str) -> String {
+    let digest = Sha256::digest(source_url.trim().as_bytes());
+    let hash: String = digest[..6].iter().map(|b| format!("{:02x}", b)).collect();
+    let tag = sanitize_filename::sanitize(crate::core::queue::batch_tag_for(source_url));
+    let tag: String = tag.chars().take(48).collect();
+    format!("{}-{}", tag, hash)
+}
+
+/// Archive line for an item URL: `<platform> <id>` when the id can be
+/// read from the URL (`youtube dQw4w9WgXcQ`, as yt-dlp writes it), else
+/// `url <url>`.
+pub fn entry_for(url: &str) -> String {
+    let parsed = crate::core::url_parser::parse_url(url);
+    match parsed.and_then(|p| p.content_id.map(|id| (p.platform, id))) {
+        Some((platform, id)) => format!("{} {}", platform, id),
+        None => format!("url {}", url.trim()),
+    }
+}
+
+fn file_len(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn read_entries(path: &Path) -> HashSet<String> {
+    std::fs::read_to_string(path)
+        .map(|content| {
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Runs `f` on the cached lines of `path`, reading the file first when it
+/// isn't cached or changed size since.
+fn with_loaded<R>(path: &Path, f: impl FnOnce(&mut Loaded) -> R) -> R {
+    let mut cache = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+    let len = file_len(path);
+    let loaded = cache.entry(path.to_path_buf()).or_insert_with(|| Loaded {
+        len: u64::MAX,
+        entries: HashSet::new(),
+    });
+    if loaded.len != len {
+        *loaded = Loaded {
+            len,
+            entries: read_entries(path),
+        };
+    }
+    f(loaded)
+}
+
+/// Lines of the archive at `path`; empty when it doesn't exist yet.
+pub fn load(path: &Path) -> HashSet<String> {
+    with_loaded(path, |loaded| loaded.entries.clone())
+}
+
+/// Adds `entry` to the archive at `path` unless it is already there.
+pub fn record(path: &Path, entry: &str) -> std::io::Result<()> {
+    with_loaded(path, |loaded| {
+        if loaded.entries.contains(entry) {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", entry)?;
+        loaded.entries.insert(entry.to_string());
+        loaded.len = file_len(path);
+        Ok(())
+    })
+}
+
+/// `urls` without the items the archive at `path` already lists.
+pub fn filter_new(path: &Path, urls: Vec<String>) -> Vec<String> {
+    with_loaded(path, |loaded| {
+        urls.into_iter()
+            .filter(|u| !loaded.entries.contains(&entry_for(u)))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_match_ytdlp_archive_lines() {
+        assert_eq!(
+            entry_for("https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            "youtube dQw4w9WgXcQ"
+        );
+        assert_eq!(
+            entry_for("https://example.com/video.mp4"),
+            "url https://example.com/video.mp4"
+        );
+    }
+
+    #[test]
+    fn record_skips_known_entries_and_filters_urls() {
+        let dir = std::env::temp_dir().join(format!("omniget-archive-{}", std::process::id()));
+        let path = dir.join("downloaded-ids.txt");
+        let old = "https://www.youtube.com/watch?v=aaaaaaaaaaa".to_string();
+        let new = "https://www.youtube.com/watch?v=bbbbbbbbbbb".to_string();
+
+        record(&path, &entry_for(&old)).unwrap();
+        record(&path, &entry_for(&old)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(filter_new(&path, vec![old, new.clone()]), vec![new.clone()]);
+
+        // Lines appended by yt-dlp show up without a restart.
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        writeln!(file, "{}", entry_for(&new)).unwrap();
+        assert!(load(&path).contains(&entry_for(&new)));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+const LOG_FILE_PREFIX: &str = "omniget";
+const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+
+pub fn logs_dir() -> Option<PathBuf> {
+    crate::core::paths::app_data_dir().map(|dir| dir.join("logs"))
+}
+
+/// Logs to stdout as before and to a daily file in `logs_dir()`, keeping a
+/// week of files. The file also gets our own debug output (`[perf]` timings
+/// and the like) so bug reports can include it. Keep the guard alive for
+/// the life of the app; dropping it flushes and stops the file writer.
+pub fn init() -> Option<WorkerGuard> {
+    let stdout = tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO);
+
+    let appender = logs_dir().map(|dir| {
+        Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix(LOG_FILE_SUFFIX)
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+    });
+    let appender = match appender {
+        Some(Ok(appender)) => appender,
+        other => {
+            tracing_subscriber::registry().with(stdout).init();
+            if let Some(Err(e)) = other {
+                tracing::warn!("[logging] file logging disabled: {}", e);
+            }
+            return None;
+        }
+    };
+
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    let file = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_filter(
+            Targets::new()
+                .with_default(LevelFilter::INFO)
+                .with_target("omniget_lib", LevelFilter::DEBUG)
+                .with_target("omniget_core", LevelFilter::DEBUG),
+        );
+    tracing_subscriber::registry()
+        .with(stdout)
+        .with(file)
+        .init();
+    Some(guard)
+}
+
+/// The last `limit` lines across the log files, oldest first, with URL
+/// query strings stripped since they can carry tokens.
+pub fn recent_lines(limit: usize) -> Vec<String> {
+    let Some(dir) = logs_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX))
+        })
+        .collect();
+    // Rotated names end in the date, so they sort oldest to newest.
+    files.sort();
+
+    let mut lines = VecDeque::with_capacity(limit);
+    for file in files.iter().rev() {
+        if lines.len() >= limit {
+            break;
+        }
+        let Ok(bytes) = std::fs::read(file) else {
+            continue;
+        };
+        let text = String::from_utf8_lossy(&bytes);
+        let wanted = limit - lines.len();
+        for line in text.lines().rev().take(wanted) {
+            lines.push_front(crate::core::ytdlp::sanitize_log_line(line));
+        }
+    }
+    lines.into()
+}
@@ -3,30 +3,43 @@ pub use omniget_core::core::cookie_parser;
 pub use omniget_core::core::course_utils;
 pub use omniget_core::core::dependencies;
 pub use omniget_core::core::direct_downloader;
+pub use omniget_core::core::errors;
 pub use omniget_core::core::ffmpeg;
 pub use omniget_core::core::filename;
 pub use omniget_core::core::hls_downloader;
 pub use omniget_core::core::http_client;
 pub use omniget_core::core::http_fetcher;
 pub use omniget_core::core::hwaccel;
+pub use omniget_core::core::i18n;
+pub use omniget_core::core::image;
+pub use omniget_core::core::manifest;
 pub use omniget_core::core::media_processor;
 pub use omniget_core::core::paths;
 pub use omniget_core::core::pdfium;
 pub use omniget_core::core::process;
 pub use omniget_core::core::redirect;
 pub use omniget_core::core::registry;
+pub use omniget_core::core::stats;
+pub use omniget_core::core::temp_dir;
+pub use omniget_core::core::url;
 pub use omniget_core::core::ytdlp;
 
 pub mod awake;
+pub mod backup;
 pub mod channel_poller;
 pub mod channels;
+pub mod clipboard_watch;
 pub mod db;
+pub mod disk_space;
+pub mod download_archive;
 pub mod download_log;
 pub mod events;
 pub mod host_limiter;
+pub mod logging;
 pub mod path_limits;
 pub mod queue;
 pub mod queue_history;
+pub mod rate_breaker;
 pub mod recovery;
 pub mod rpc;
 pub mod trackers;
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, OnceLock};
@@ -37,7 +37,9 @@ fn shared_http_client() -> &'static reqwest::Client {
 }
 
 use crate::core::ffmpeg::{self, MetadataEmbed};
-use crate::models::media::MediaInfo;
+use crate::core::rate_breaker::RateBreaker;
+use crate::models::media::{DownloadPhase, MediaInfo, OverwritePolicy};
+use crate::models::settings::{AdvancedSettings, AllowedHours, DownloadSettings};
 use crate::platforms::traits::PlatformDownloader;
 use crate::storage::config;
 
@@ -111,6 +113,79 @@ pub enum QueueStatus {
     Seeding,
     Complete { success: bool },
     Error { message: String, retryable: bool },
+    /// Failed because the content is blocked in the user's region; the UI
+    /// offers the proxy settings instead of a plain retry.
+    GeoBlocked {
+        message: String,
+        country_hint: Option<String>,
+    },
+    /// Not downloaded because the URL is already queued or its output already exists.
+    DuplicateSkipped { existing_path: Option<String> },
+}
+
+/// Where an item sits in the playlist or collection it was expanded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PlaylistPosition {
+    /// 1-based, in the order the collection listed its items.
+    pub index: u32,
+    pub count: u32,
+}
+
+/// Expands the playlist fields of `template` for an item at `position`.
+/// A template that doesn't number items gets the zero-padded position in
+/// front of its file name, so native and yt-dlp downloads are numbered
+/// the same way, in this one place.
+fn playlist_template(template: String, position: Option<PlaylistPosition>) -> String {
+    let Some(p) = position else {
+        return template;
+    };
+    let template = if omniget_core::core::filename::uses_playlist_index(&template) {
+        template
+    } else {
+        let file_start = template.rfind('/').map_or(0, |i| i + 1);
+        format!(
+            "{}%(playlist_index)02d - {}",
+            &template[..file_start],
+            &template[file_start..]
+        )
+    };
+    omniget_core::core::filename::expand_playlist_fields(&template, p.index, p.count)
+}
+
+/// Tag for the items expanded from a collection link: its `list=` id, else
+/// the last segment of its path (`sets/<slug>`, `album/<id>`), else its
+/// host.
+pub fn batch_tag_for(url: &str) -> String {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+    if let Some((_, list)) = parsed.query_pairs().find(|(k, _)| k == "list") {
+        return list.into_owned();
+    }
+    parsed
+        .path_segments()
+        .and_then(|mut segments| segments.rfind(|s| !s.is_empty()))
+        .map(String::from)
+        .or_else(|| parsed.host_str().map(String::from))
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// A matching `.omniget.done` manifest marks a finished file. Outputs from
+/// before manifests existed count as finished when they're non-empty.
+fn is_finished_output(path: &std::path::Path) -> bool {
+    if crate::core::manifest::read(path).is_some() {
+        return crate::core::manifest::validate(path).is_some();
+    }
+    omniget_core::core::filename::is_nonempty_file(path)
+}
+
+/// Maps the legacy `skip_existing` toggle onto the overwrite policy: when it's
+/// off, existing files are overwritten instead of skipped.
+pub fn overwrite_policy_for(settings: &DownloadSettings) -> OverwritePolicy {
+    match settings.overwrite_policy {
+        OverwritePolicy::Skip if !settings.skip_existing => OverwritePolicy::Overwrite,
+        policy => policy,
+    }
 }
 
 pub fn is_retryable_error_message(message: &str) -> bool {
@@ -119,7 +194,7 @@ pub fn is_retryable_error_message(message: &str) -> bool {
         return false;
     }
     let (category, _) = omniget_core::core::errors::classify_download_error(message);
-    matches!(category, "unknown" | "rate_limited")
+    is_retryable_category(category)
 }
 
 #[derive(Clone, Serialize)]
@@ -147,6 +222,24 @@ pub struct QueueItemInfo {
     pub quality: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub download_mode: Option<String>,
+    pub priority: i32,
+    /// Automatic retries used so far, out of `max_retries`.
+    pub retry_count: u32,
+    pub max_retries: u32,
+    /// When a backed-off retry becomes eligible to start.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_at_ms: Option<u64>,
+    /// When a queued item is scheduled to start.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_at_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub suspect: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_files: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<DownloadPhase>,
 }
 
 pub struct QueueItem {
@@ -181,6 +274,11 @@ pub struct QueueItem {
     pub thumbnail_url_override: Option<String>,
     pub retry_count: u32,
     pub max_retries: u32,
+    /// Delay before the first automatic retry; doubles on each further one.
+    pub retry_base_delay_secs: u64,
+    /// Set while a failed item waits out its backoff; `next_queued_ids`
+    /// passes it over until then.
+    pub retry_at_ms: Option<u64>,
     pub resume_state: Option<serde_json::Value>,
     pub concurrent_segments: Option<usize>,
     pub segment_size_bytes: Option<u64>,
@@ -190,9 +288,54 @@ pub struct QueueItem {
     pub torrent_files: Option<Vec<usize>>,
     pub scheduled_at_ms: Option<u64>,
     pub stop_at_ms: Option<u64>,
+    /// Higher runs first among queued items; ties keep their position.
+    pub priority: i32,
+    /// Set once a geo-blocked failure was re-queued to run through the
+    /// configured proxy, so it only happens once.
+    pub proxy_retried: bool,
+    /// Set for items queued from an expanded playlist, so the filename
+    /// template can number them.
+    pub playlist_position: Option<PlaylistPosition>,
+    /// 0-based items of a multi-item post to download, `None` for all.
+    pub selected_indices: Option<Vec<usize>>,
+    /// Part of the video to download, in seconds; see `set_section`.
+    pub section: Option<(f64, Option<f64>)>,
+    /// `downloaded-ids.txt` of the channel or playlist this item came from
+    /// when it was queued in "only new" mode; see `core::download_archive`.
+    pub archive: Option<PathBuf>,
+    /// User labels for organizing the queue; see `add_tag`.
+    pub tags: Vec<String>,
+    /// Set when `verify_after_download` found decode errors in the output.
+    pub suspect: bool,
+    /// Files saved besides `file_path`, see `DownloadResult::extra_files`.
+    pub extra_files: Vec<String>,
+    /// Step of the current attempt; `None` until it starts.
+    pub phase: Option<DownloadPhase>,
 }
 
 impl QueueItem {
+    /// Host this item counts against for `max_per_host`: the media URL once
+    /// `get_media_info` has resolved it, the page URL before that. `None`
+    /// for non-HTTP sources such as magnets, which aren't host-limited.
+    pub fn host_key(&self) -> Option<String> {
+        let media_url = self
+            .media_info
+            .as_ref()
+            .and_then(|m| m.available_qualities.first())
+            .map(|q| q.url.as_str())
+            .filter(|u| u.starts_with("http://") || u.starts_with("https://"));
+        let url = media_url.unwrap_or(&self.url);
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return None;
+        }
+        Some(crate::core::host_limiter::host_key_for_url(url))
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim();
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
     pub fn to_info(&self) -> QueueItemInfo {
         QueueItemInfo {
             id: self.id,
@@ -217,6 +360,17 @@ impl QueueItem {
             eta_seconds: self.eta_seconds,
             quality: self.quality.clone(),
             download_mode: self.download_mode.clone(),
+            priority: self.priority,
+            retry_count: self.retry_count,
+            max_retries: self.max_retries,
+            retry_at_ms: self.retry_at_ms,
+            scheduled_at_ms: self
+                .scheduled_at_ms
+                .filter(|_| self.status == QueueStatus::Queued),
+            tags: self.tags.clone(),
+            suspect: self.suspect,
+            extra_files: self.extra_files.clone(),
+            phase: self.phase,
         }
     }
 }
@@ -225,7 +379,18 @@ pub struct DownloadQueue {
     pub items: Vec<QueueItem>,
     pub max_concurrent: u32,
     pub stagger_delay_ms: u64,
+    /// Active downloads allowed per host key, see `QueueItem::host_key`.
+    pub max_per_host: u32,
     pub default_max_retries: u32,
+    pub default_retry_base_delay_secs: u64,
+    pub overwrite_policy: OverwritePolicy,
+    /// Outside these hours nothing new starts; see `AllowedHours`.
+    pub allowed_hours: Option<AllowedHours>,
+    /// Queued items whose info is being resolved ahead of their turn.
+    prefetching: HashSet<u64>,
+    /// Items already prefetched once, successfully or not.
+    prefetched: HashSet<u64>,
+    rate_breaker: RateBreaker,
 }
 
 impl DownloadQueue {
@@ -234,10 +399,30 @@ impl DownloadQueue {
             items: Vec::new(),
             max_concurrent,
             stagger_delay_ms: 150,
+            max_per_host: 2,
             default_max_retries: 3,
+            default_retry_base_delay_secs: 1,
+            overwrite_policy: OverwritePolicy::Skip,
+            allowed_hours: None,
+            prefetching: HashSet::new(),
+            prefetched: HashSet::new(),
+            rate_breaker: RateBreaker::default(),
         }
     }
 
+    /// Output of a finished queue item for `url` that is still on disk.
+    fn existing_output_for(&self, url: &str) -> Option<String> {
+        self.items
+            .iter()
+            .filter(|i| i.url == url && i.status == QueueStatus::Complete { success: true })
+            .filter_map(|i| i.file_path.clone())
+            .find(|p| is_finished_output(std::path::Path::new(p)))
+    }
+
+    /// Adds a download to the queue and returns its initial status. URLs that
+    /// are already pending come back as `DuplicateSkipped` without being added;
+    /// under `OverwritePolicy::Skip`, a URL whose previous output still exists
+    /// is added as `DuplicateSkipped` pointing at that file.
     #[allow(clippy::too_many_arguments)]
     pub fn enqueue(
         &mut self,
@@ -264,14 +449,30 @@ impl DownloadQueue {
         torrent_files: Option<Vec<usize>>,
         scheduled_at_ms: Option<u64>,
         stop_at_ms: Option<u64>,
-    ) {
+    ) -> QueueStatus {
+        if self.has_url(&url) {
+            return QueueStatus::DuplicateSkipped {
+                existing_path: None,
+            };
+        }
+        let existing_path = if self.overwrite_policy == OverwritePolicy::Skip {
+            self.existing_output_for(&url)
+        } else {
+            None
+        };
+        let status = match &existing_path {
+            Some(path) => QueueStatus::DuplicateSkipped {
+                existing_path: Some(path.clone()),
+            },
+            None => QueueStatus::Queued,
+        };
         let computed_kind = Some(kind_from_platform(&platform));
-        let item = QueueItem {
+        let mut item = QueueItem {
             id,
             url,
             platform,
             title,
-            status: QueueStatus::Queued,
+            status: status.clone(),
             cancel_token: CancellationToken::new(),
             output_dir,
             download_mode,
@@ -298,6 +499,8 @@ impl DownloadQueue {
             thumbnail_url_override: None,
             retry_count: 0,
             max_retries: self.default_max_retries,
+            retry_base_delay_secs: self.default_retry_base_delay_secs,
+            retry_at_ms: None,
             resume_state: None,
             concurrent_segments: None,
             segment_size_bytes: None,
@@ -307,7 +510,24 @@ impl DownloadQueue {
             torrent_files,
             scheduled_at_ms,
             stop_at_ms,
+            priority: 0,
+            proxy_retried: false,
+            playlist_position: None,
+            selected_indices: None,
+            section: None,
+            archive: None,
+            tags: Vec::new(),
+            suspect: false,
+            extra_files: Vec::new(),
+            phase: None,
         };
+        if let Some(path) = existing_path {
+            item.percent = 100.0;
+            item.file_size_bytes = std::fs::metadata(&path).ok().map(|m| m.len());
+            item.file_path = Some(path);
+            self.items.push(item);
+            return status;
+        }
         crate::core::recovery::persist(crate::core::recovery::RecoveryItem {
             id: item.id,
             url: item.url.clone(),
@@ -320,6 +540,8 @@ impl DownloadQueue {
             referer: item.referer.clone(),
         });
         self.items.push(item);
+        self.sort_queued_by_priority();
+        status
     }
 
     pub fn hydrate_from_history(&mut self) {
@@ -384,6 +606,8 @@ impl DownloadQueue {
                 thumbnail_url_override: entry.thumbnail_url.clone(),
                 retry_count: 0,
                 max_retries: 0,
+                retry_base_delay_secs: 0,
+                retry_at_ms: None,
                 resume_state: None,
                 concurrent_segments: None,
                 segment_size_bytes: None,
@@ -393,6 +617,16 @@ impl DownloadQueue {
                 torrent_files: None,
                 scheduled_at_ms: None,
                 stop_at_ms: None,
+                priority: 0,
+                proxy_retried: false,
+                playlist_position: None,
+                selected_indices: None,
+                section: None,
+                archive: None,
+                tags: Vec::new(),
+                suspect: false,
+                extra_files: entry.extra_files.clone(),
+                phase: None,
             };
             self.items.push(item);
         }
@@ -405,16 +639,85 @@ impl DownloadQueue {
             .count() as u32
     }
 
+    /// Queued items that can start now: bounded by the free global slots and
+    /// by `max_per_host`, so items from busy hosts are passed over in favour
+    /// of ones from other hosts further down the queue. None outside
+    /// `allowed_hours` or during the rate breaker's cooldown.
     pub fn next_queued_ids(&self) -> Vec<u64> {
-        let slots = self.max_concurrent.saturating_sub(self.active_count()) as usize;
         let now = now_ms();
-        self.items
+        if self.allowed_hours.is_some_and(|w| !w.is_open_now())
+            || self.rate_breaker.cooldown_left_ms(now) > 0
+        {
+            return Vec::new();
+        }
+        let slots = self
+            .rate_breaker
+            .limit(self.max_concurrent)
+            .saturating_sub(self.active_count()) as usize;
+        let active_hosts = self
+            .items
+            .iter()
+            .filter(|i| i.status == QueueStatus::Active)
+            .map(|i| i.host_key());
+        let candidates = self
+            .items
             .iter()
             .filter(|i| i.status == QueueStatus::Queued)
             .filter(|i| i.scheduled_at_ms.map(|t| now >= t).unwrap_or(true))
+            .filter(|i| i.retry_at_ms.map(|t| now >= t).unwrap_or(true))
+            .map(|i| (i.id, i.host_key()));
+        admit_within_host_limit(active_hosts, candidates, slots, self.max_per_host)
+    }
+
+    /// Queued items without info, in queue order, up to the free prefetch
+    /// slots. They stay claimed until `finish_prefetch`.
+    fn claim_prefetch(&mut self) -> Vec<PrefetchJob> {
+        let slots = PREFETCH_CONCURRENCY.saturating_sub(self.prefetching.len());
+        let jobs: Vec<PrefetchJob> = self
+            .items
+            .iter()
+            .filter(|i| i.status == QueueStatus::Queued && i.media_info.is_none())
+            .filter(|i| !self.prefetching.contains(&i.id) && !self.prefetched.contains(&i.id))
             .take(slots)
-            .map(|i| i.id)
-            .collect()
+            .map(|i| PrefetchJob {
+                id: i.id,
+                url: i.url.clone(),
+                platform: i.platform.clone(),
+                downloader: i.downloader.clone(),
+                ytdlp_path: i.ytdlp_path.clone(),
+                cookie_slug: i.cookie_slug.clone(),
+            })
+            .collect();
+        self.prefetching.extend(jobs.iter().map(|j| j.id));
+        jobs
+    }
+
+    /// Stores prefetched info on an item that is still waiting, the same way
+    /// `enqueue` takes cached info. Returns whether anything changed.
+    fn finish_prefetch(&mut self, id: u64, info: Option<MediaInfo>) -> bool {
+        self.prefetching.remove(&id);
+        let Some(item) = self.items.iter_mut().find(|i| i.id == id) else {
+            return false;
+        };
+        self.prefetched.insert(id);
+        let Some(info) = info else {
+            return false;
+        };
+        if item.status != QueueStatus::Queued || item.media_info.is_some() {
+            return false;
+        }
+        if !is_generic_title(&info.title) {
+            item.title = info.title.clone();
+        }
+        item.total_bytes = info.file_size_bytes;
+        if matches!(
+            info.media_type,
+            crate::models::media::MediaType::Carousel | crate::models::media::MediaType::Playlist
+        ) {
+            item.file_count = Some(info.available_qualities.len() as u32);
+        }
+        item.media_info = Some(info);
+        true
     }
 
     pub fn next_available_id(&self, preferred: u64) -> u64 {
@@ -433,6 +736,9 @@ impl DownloadQueue {
         {
             item.status = QueueStatus::Active;
             item.cancel_token = CancellationToken::new();
+            item.retry_at_ms = None;
+            item.phase = None;
+            self.rate_breaker.record_start(now_ms());
         }
     }
 
@@ -446,9 +752,15 @@ impl DownloadQueue {
     ) {
         if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
             let error_for_history = error.clone();
+            let stats = crate::core::stats::session();
+            stats.record_finished(success);
+            if success {
+                stats.record_bytes(file_size_bytes.unwrap_or(0));
+            }
             if success {
                 item.status = QueueStatus::Complete { success: true };
                 item.percent = 100.0;
+                item.phase = Some(DownloadPhase::Done);
             } else {
                 let msg = error.unwrap_or_default();
                 let retryable = is_retryable_error_message(&msg);
@@ -481,12 +793,172 @@ impl DownloadQueue {
                             .and_then(|m| m.thumbnail_url.clone())
                     }),
                     kind: item.kind,
+                    page_url: item.page_url.clone(),
+                    extra_files: item.extra_files.clone(),
                 };
                 crate::core::queue_history::record(entry);
             }
         }
     }
 
+    pub fn set_playlist_position(&mut self, id: u64, position: PlaylistPosition) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.playlist_position = Some(position);
+        }
+    }
+
+    pub fn set_archive(&mut self, id: u64, archive: PathBuf) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.archive = Some(archive);
+        }
+    }
+
+    pub fn set_selected_indices(&mut self, id: u64, indices: Vec<usize>) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.selected_indices = Some(indices);
+        }
+    }
+
+    pub fn set_section(&mut self, id: u64, start: f64, end: Option<f64>) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.section = Some((start, end));
+        }
+    }
+
+    /// Labels an item. Tags are trimmed and compared case-insensitively;
+    /// returns false for an unknown item, an empty tag or one it already has.
+    pub fn add_tag(&mut self, id: u64, tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return false;
+        }
+        let Some(item) = self.items.iter_mut().find(|i| i.id == id) else {
+            return false;
+        };
+        if item.has_tag(tag) {
+            return false;
+        }
+        item.tags.push(tag.to_string());
+        true
+    }
+
+    pub fn remove_tag(&mut self, id: u64, tag: &str) -> bool {
+        let tag = tag.trim();
+        let Some(item) = self.items.iter_mut().find(|i| i.id == id) else {
+            return false;
+        };
+        let before = item.tags.len();
+        item.tags.retain(|t| !t.eq_ignore_ascii_case(tag));
+        item.tags.len() != before
+    }
+
+    /// Flags or clears an item whose output failed verification. Returns
+    /// false for an unknown item.
+    pub fn set_suspect(&mut self, id: u64, suspect: bool) -> bool {
+        let Some(item) = self.items.iter_mut().find(|i| i.id == id) else {
+            return false;
+        };
+        item.suspect = suspect;
+        true
+    }
+
+    /// Records the step an active item is in. Returns false when nothing
+    /// changed, so callers only emit real transitions.
+    pub fn set_phase(&mut self, id: u64, phase: DownloadPhase) -> bool {
+        let Some(item) = self
+            .items
+            .iter_mut()
+            .find(|i| i.id == id && i.status == QueueStatus::Active)
+        else {
+            return false;
+        };
+        if item.phase == Some(phase) {
+            return false;
+        }
+        item.phase = Some(phase);
+        true
+    }
+
+    /// Records the files an item saved besides its `file_path`. Returns
+    /// false for an unknown item.
+    pub fn set_extra_files(&mut self, id: u64, files: Vec<String>) -> bool {
+        let Some(item) = self.items.iter_mut().find(|i| i.id == id) else {
+            return false;
+        };
+        item.extra_files = files;
+        true
+    }
+
+    /// Finishes an item whose downloader found the output already on disk
+    /// and left it alone under `OverwritePolicy::Skip`. Nothing was
+    /// downloaded, so unlike `mark_complete` it leaves the session stats alone.
+    pub fn mark_skipped(&mut self, id: u64, existing_path: String, file_size_bytes: u64) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.status = QueueStatus::DuplicateSkipped {
+                existing_path: Some(existing_path.clone()),
+            };
+            item.percent = 100.0;
+            item.file_path = Some(existing_path);
+            item.file_size_bytes = Some(file_size_bytes);
+            item.speed_bytes_per_sec = 0.0;
+            item.eta_seconds = None;
+            crate::core::recovery::remove(id);
+        }
+    }
+
+    /// Turns a failed item into `GeoBlocked`, keeping its error message.
+    pub fn mark_geo_blocked(&mut self, id: u64, country_hint: Option<String>) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            if let QueueStatus::Error { message, .. } = &item.status {
+                item.status = QueueStatus::GeoBlocked {
+                    message: message.clone(),
+                    country_hint,
+                };
+            }
+        }
+    }
+
+    /// Re-queues a failed item after its backoff when `category` is worth
+    /// retrying and it has attempts left. Returns `(attempt, max, delay_secs)`
+    /// for the retry, or `None` when the failure is final.
+    pub fn schedule_retry(&mut self, id: u64, category: &str) -> Option<(u32, u32, u64)> {
+        let item = self.items.iter_mut().find(|i| i.id == id)?;
+        // A run that got this far made real progress; start the count over.
+        if item.downloaded_bytes > 5 * 1024 * 1024 {
+            item.retry_count = 0;
+        }
+        if !is_retryable_category(category) || item.retry_count >= item.max_retries {
+            return None;
+        }
+        item.retry_count += 1;
+        let delay_secs = retry_delay_secs(item.retry_base_delay_secs, item.retry_count);
+        item.status = QueueStatus::Queued;
+        item.cancel_token = CancellationToken::new();
+        item.percent = 0.0;
+        item.speed_bytes_per_sec = 0.0;
+        item.downloaded_bytes = 0;
+        item.retry_at_ms = Some(now_ms() + delay_secs * 1000);
+        Some((item.retry_count, item.max_retries, delay_secs))
+    }
+
+    /// Re-queues an item so it runs once more through the configured proxy.
+    /// `false` when it already had that retry.
+    pub fn requeue_through_proxy(&mut self, id: u64) -> bool {
+        let Some(item) = self.items.iter_mut().find(|i| i.id == id) else {
+            return false;
+        };
+        if item.proxy_retried {
+            return false;
+        }
+        item.proxy_retried = true;
+        item.status = QueueStatus::Queued;
+        item.cancel_token = CancellationToken::new();
+        item.percent = 0.0;
+        item.speed_bytes_per_sec = 0.0;
+        item.downloaded_bytes = 0;
+        true
+    }
+
     pub fn mark_seeding(
         &mut self,
         id: u64,
@@ -558,7 +1030,10 @@ impl DownloadQueue {
                 if item.platform != "magnet"
                     && !omniget_core::core::ytdlp::resume_download_process(id)
                 {
-                    return false;
+                    // No process to wake, like an item `hold` paused before
+                    // it started: it waits for its turn again.
+                    item.status = QueueStatus::Queued;
+                    return true;
                 }
                 item.status = QueueStatus::Active;
                 return true;
@@ -567,6 +1042,21 @@ impl DownloadQueue {
         false
     }
 
+    /// Pauses a queued item before it starts, so it stays put until it is
+    /// resumed. Returns false unless the item is queued.
+    pub fn hold(&mut self, id: u64) -> bool {
+        let Some(item) = self
+            .items
+            .iter_mut()
+            .find(|i| i.id == id && i.status == QueueStatus::Queued)
+        else {
+            return false;
+        };
+        item.status = QueueStatus::Paused;
+        item.scheduled_at_ms = None;
+        true
+    }
+
     pub fn pause_all(&mut self) -> Vec<(u64, Option<usize>)> {
         let mut paused = Vec::new();
         for item in self.items.iter_mut() {
@@ -661,6 +1151,90 @@ impl DownloadQueue {
         true
     }
 
+    /// Stable-sorts the queued items by descending priority, leaving every
+    /// other item where it is.
+    fn sort_queued_by_priority(&mut self) {
+        let mut queued: Vec<(i32, u64)> = self
+            .items
+            .iter()
+            .filter(|i| i.status == QueueStatus::Queued)
+            .map(|i| (i.priority, i.id))
+            .collect();
+        queued.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+        self.reorder(queued.into_iter().map(|(_, id)| id).collect());
+    }
+
+    fn other_queued_priorities(&self, id: u64) -> impl Iterator<Item = i32> + '_ {
+        self.items
+            .iter()
+            .filter(move |i| i.status == QueueStatus::Queued && i.id != id)
+            .map(|i| i.priority)
+    }
+
+    /// Moves a queued item ahead of every other queued item by raising its
+    /// priority above theirs, so later sorts keep it there. Items that are
+    /// already downloading are left alone.
+    pub fn move_to_front(&mut self, id: u64) -> bool {
+        let top = self.other_queued_priorities(id).max();
+        let Some(item) = self
+            .items
+            .iter_mut()
+            .find(|i| i.id == id && i.status == QueueStatus::Queued)
+        else {
+            return false;
+        };
+        if let Some(top) = top.filter(|top| item.priority <= *top) {
+            item.priority = top.saturating_add(1);
+        }
+        self.sort_queued_by_priority();
+        true
+    }
+
+    /// Counterpart of `move_to_front`: drops the priority below every other
+    /// queued item's.
+    pub fn move_to_back(&mut self, id: u64) -> bool {
+        let bottom = self.other_queued_priorities(id).min();
+        let Some(item) = self
+            .items
+            .iter_mut()
+            .find(|i| i.id == id && i.status == QueueStatus::Queued)
+        else {
+            return false;
+        };
+        if let Some(bottom) = bottom.filter(|bottom| item.priority >= *bottom) {
+            item.priority = bottom.saturating_sub(1);
+        }
+        self.sort_queued_by_priority();
+        true
+    }
+
+    /// Holds a queued item until `at_ms`, or lets it start on its turn
+    /// with `None`. Returns false unless the item is queued.
+    pub fn set_schedule(&mut self, id: u64, at_ms: Option<u64>) -> bool {
+        let Some(item) = self
+            .items
+            .iter_mut()
+            .find(|i| i.id == id && i.status == QueueStatus::Queued)
+        else {
+            return false;
+        };
+        item.scheduled_at_ms = at_ms;
+        true
+    }
+
+    pub fn set_priority(&mut self, id: u64, priority: i32) -> bool {
+        let Some(item) = self
+            .items
+            .iter_mut()
+            .find(|i| i.id == id && i.status == QueueStatus::Queued)
+        else {
+            return false;
+        };
+        item.priority = priority;
+        self.sort_queued_by_priority();
+        true
+    }
+
     /// Cancel an item. Returns the torrent_id if the item needs torrent cleanup (caller should delete from session).
     pub fn cancel(&mut self, id: u64) -> (bool, Option<usize>) {
         let result = self.cancel_inner(id);
@@ -670,6 +1244,34 @@ impl DownloadQueue {
         result
     }
 
+    /// Cancels every queued, active, paused and seeding item. Returns the
+    /// cancelled ids with the torrent ids that need session cleanup.
+    pub fn cancel_all(&mut self) -> Vec<(u64, Option<usize>)> {
+        let ids: Vec<u64> = self.items.iter().map(|i| i.id).collect();
+        ids.into_iter()
+            .filter_map(|id| {
+                let (cancelled, torrent_id) = self.cancel(id);
+                cancelled.then_some((id, torrent_id))
+            })
+            .collect()
+    }
+
+    /// `cancel_all` limited to the items carrying `tag`.
+    pub fn cancel_tagged(&mut self, tag: &str) -> Vec<(u64, Option<usize>)> {
+        let ids: Vec<u64> = self
+            .items
+            .iter()
+            .filter(|i| i.has_tag(tag))
+            .map(|i| i.id)
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| {
+                let (cancelled, torrent_id) = self.cancel(id);
+                cancelled.then_some((id, torrent_id))
+            })
+            .collect()
+    }
+
     fn cancel_inner(&mut self, id: u64) -> (bool, Option<usize>) {
         if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
             match &item.status {
@@ -723,7 +1325,10 @@ impl DownloadQueue {
 
     pub fn retry(&mut self, id: u64) -> bool {
         if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
-            if matches!(item.status, QueueStatus::Error { .. }) {
+            if matches!(
+                item.status,
+                QueueStatus::Error { .. } | QueueStatus::GeoBlocked { .. }
+            ) {
                 item.status = QueueStatus::Queued;
                 item.cancel_token = CancellationToken::new();
                 item.percent = 0.0;
@@ -731,7 +1336,11 @@ impl DownloadQueue {
                 item.downloaded_bytes = 0;
                 item.file_path = None;
                 item.file_size_bytes = None;
+                item.extra_files.clear();
+                item.phase = None;
                 item.retry_count = 0;
+                item.retry_at_ms = None;
+                item.proxy_retried = false;
                 return true;
             }
         }
@@ -743,7 +1352,7 @@ impl DownloadQueue {
         let result = self.remove_inner(id);
         if result.is_some() {
             crate::core::recovery::remove(id);
-            crate::core::queue_history::remove(id);
+            crate::core::queue_history::dismiss(id);
         }
         result
     }
@@ -751,7 +1360,11 @@ impl DownloadQueue {
     fn remove_inner(&mut self, id: u64) -> Option<Option<usize>> {
         if let Some(pos) = self.items.iter().position(|i| i.id == id) {
             let item = &self.items[pos];
-            if item.status == QueueStatus::Active {
+            // Also stops the verification of a finished item.
+            if matches!(
+                item.status,
+                QueueStatus::Active | QueueStatus::Complete { .. }
+            ) {
                 item.cancel_token.cancel();
             }
             // For paused magnet items, the cancel_token was not cancelled during pause
@@ -766,6 +1379,7 @@ impl DownloadQueue {
                 None
             };
             self.items.remove(pos);
+            self.prefetched.remove(&id);
             return Some(torrent_id);
         }
         None
@@ -778,19 +1392,26 @@ impl DownloadQueue {
             .filter(|i| {
                 matches!(
                     i.status,
-                    QueueStatus::Complete { .. } | QueueStatus::Error { .. }
+                    QueueStatus::Complete { .. }
+                        | QueueStatus::Error { .. }
+                        | QueueStatus::GeoBlocked { .. }
+                        | QueueStatus::DuplicateSkipped { .. }
                 )
             })
             .map(|i| i.id)
             .collect();
         for id in &to_remove {
             crate::core::recovery::remove(*id);
-            crate::core::queue_history::remove(*id);
+            crate::core::queue_history::dismiss(*id);
+            self.prefetched.remove(id);
         }
         self.items.retain(|i| {
             !matches!(
                 i.status,
-                QueueStatus::Complete { .. } | QueueStatus::Error { .. }
+                QueueStatus::Complete { .. }
+                    | QueueStatus::Error { .. }
+                    | QueueStatus::GeoBlocked { .. }
+                    | QueueStatus::DuplicateSkipped { .. }
             )
         });
     }
@@ -799,6 +1420,15 @@ impl DownloadQueue {
         self.items.iter().map(|i| i.to_info()).collect()
     }
 
+    /// `get_state` narrowed to the items carrying `tag`.
+    pub fn get_state_tagged(&self, tag: &str) -> Vec<QueueItemInfo> {
+        self.items
+            .iter()
+            .filter(|i| i.has_tag(tag))
+            .map(|i| i.to_info())
+            .collect()
+    }
+
     pub fn has_url(&self, url: &str) -> bool {
         self.items.iter().any(|i| {
             i.url == url
@@ -837,6 +1467,13 @@ impl ProgressThrottle {
     }
 }
 
+/// Sent as `queue-rate-limited` when the queue starts or stops slowing
+/// down because of 429s, see `rate_breaker`.
+#[derive(Clone, Serialize)]
+pub struct RateLimitedPayload {
+    pub active: bool,
+}
+
 #[derive(Clone, Serialize)]
 pub struct QueueItemProgress {
     pub id: u64,
@@ -972,7 +1609,28 @@ pub fn spawn_download(
     Box::pin(async move {
         let _timer_start = std::time::Instant::now();
         let slot = ActiveJobSlot::new(app.clone(), queue.clone(), item_id);
-        spawn_download_inner(app, queue, item_id).await;
+        let proxy_retry = {
+            let q = queue.lock().await;
+            q.items.iter().any(|i| i.id == item_id && i.proxy_retried)
+        };
+        match proxy_retry
+            .then(crate::core::http_client::geo_retry_proxy)
+            .flatten()
+        {
+            Some(proxy) => {
+                append_download_log(
+                    &app,
+                    item_id,
+                    "[network] retrying geo-blocked download through the configured proxy",
+                );
+                crate::core::http_client::with_proxy_override(
+                    proxy,
+                    spawn_download_inner(app, queue, item_id, true),
+                )
+                .await;
+            }
+            None => spawn_download_inner(app, queue, item_id, false).await,
+        }
         slot.disarm();
         tracing::debug!(
             "[perf] spawn_download {} took {:?}",
@@ -982,10 +1640,13 @@ pub fn spawn_download(
     })
 }
 
+/// `through_proxy` is set when this run is inside a proxy override, so
+/// downloaders holding a prebuilt client are swapped for fresh ones.
 async fn spawn_download_inner(
     app: tauri::AppHandle,
     queue: Arc<tokio::sync::Mutex<DownloadQueue>>,
     item_id: u64,
+    through_proxy: bool,
 ) {
     tracing::info!("[queue] download {} started", item_id);
 
@@ -1035,6 +1696,10 @@ async fn spawn_download_inner(
         cookie_slug,
         custom_ytdlp_args,
         torrent_files,
+        playlist_position,
+        selected_indices,
+        section,
+        archive,
     ) = {
         let q = queue.lock().await;
         let item = match q.items.iter().find(|i| i.id == item_id) {
@@ -1060,8 +1725,17 @@ async fn spawn_download_inner(
             item.cookie_slug.clone(),
             item.custom_ytdlp_args.clone(),
             item.torrent_files.clone(),
+            item.playlist_position,
+            item.selected_indices.clone(),
+            item.section,
+            item.archive.clone(),
         )
     };
+    let downloader = if through_proxy {
+        crate::platforms::rebuild_with_fresh_client(downloader.name()).unwrap_or(downloader)
+    } else {
+        downloader
+    };
 
     {
         let settings = crate::storage::config::load_settings(&app);
@@ -1133,6 +1807,10 @@ async fn spawn_download_inner(
                     eta_seconds: None,
                 },
             );
+            queue
+                .lock()
+                .await
+                .set_phase(item_id, DownloadPhase::Extracting);
 
             let info_future = fetch_and_cache_info(
                 &url,
@@ -1183,9 +1861,16 @@ async fn spawn_download_inner(
                             e
                         ),
                     );
+                    let geo_block = geo_block_hint(&e);
+                    if geo_block.is_some() && requeue_through_proxy(&app, &queue, item_id).await {
+                        return;
+                    }
                     let state = {
                         let mut q = queue.lock().await;
                         q.mark_complete(item_id, false, Some(e.to_string()), None, None);
+                        if let Some(country_hint) = geo_block {
+                            q.mark_geo_blocked(item_id, country_hint);
+                        }
                         q.get_state()
                     };
                     emit_queue_state_from_state(&app, state);
@@ -1230,6 +1915,23 @@ async fn spawn_download_inner(
         info_start.elapsed()
     );
 
+    if let Err(e) = omniget_core::core::ytdlp::ensure_live_allowed(&info) {
+        append_download_log(&app, item_id, format!("[omniget] {}", e));
+        let category = omniget_core::core::errors::classify_error(&e);
+        let language = config::load_settings(&app).appearance.language;
+        let message = crate::core::i18n::error_message(&language, category)
+            .map(str::to_string)
+            .unwrap_or_else(|| e.to_string());
+        let state = {
+            let mut q = queue.lock().await;
+            q.mark_complete(item_id, false, Some(message), None, None);
+            q.get_state()
+        };
+        emit_queue_state_from_state(&app, state);
+        try_start_next(app, queue).await;
+        return;
+    }
+
     let mut info = info;
     if is_generic_title(&info.title) {
         let pokemon = omniget_core::core::pokemon_names::random_pokemon_name();
@@ -1244,7 +1946,13 @@ async fn spawn_download_inner(
             let fc = if info.media_type == crate::models::media::MediaType::Carousel
                 || info.media_type == crate::models::media::MediaType::Playlist
             {
-                info.available_qualities.len() as u32
+                match selected_indices.as_deref() {
+                    Some(sel) if !sel.is_empty() => sel
+                        .iter()
+                        .filter(|i| **i < info.available_qualities.len())
+                        .count() as u32,
+                    _ => info.available_qualities.len() as u32,
+                }
             } else {
                 1
             };
@@ -1271,17 +1979,33 @@ async fn spawn_download_inner(
     );
 
     let settings = config::load_settings(&app);
-    let tmpl = settings.download.filename_template.clone();
+    let tmpl = settings
+        .download
+        .filename_style
+        .template(&settings.download.filename_template);
+    let tmpl = playlist_template(tmpl, playlist_position);
     let mut final_output_dir = std::path::PathBuf::from(&output_dir);
     if settings.download.organize_by_platform {
         final_output_dir = final_output_dir.join(&platform_name);
     }
+    let subfolder_template = settings.download.output_subfolder_template.clone();
+    match omniget_core::core::filename::prepare_output_dir(
+        &final_output_dir,
+        subfolder_template.as_deref(),
+        &info,
+    ) {
+        Ok(dir) => final_output_dir = dir,
+        Err(e) => tracing::warn!(
+            "[queue] could not create output subfolder for {}: {}",
+            item_id,
+            e
+        ),
+    }
     let torrent_id_slot = Arc::new(tokio::sync::Mutex::new(None));
-    let audio_format = if download_mode.as_deref() == Some("audio") {
-        Some(settings.download.music_audio_format.clone())
-    } else {
-        None
-    };
+    let (download_mode, quality, audio_format) =
+        settings
+            .download
+            .download_choices(&platform_name, download_mode, quality);
     let custom_ytdlp_args = {
         let mut args = custom_ytdlp_args.clone();
         if settings.download.skip_existing {
@@ -1292,10 +2016,55 @@ async fn spawn_download_inner(
         }
         args
     };
-    let opts = crate::models::media::DownloadOptions {
-        quality: quality.or_else(|| Some(settings.download.video_quality.clone())),
-        output_dir: final_output_dir,
+    let (mut extra_ytdlp_args, refused_flags) = omniget_core::core::ytdlp::sanitize_user_args(
+        &settings.download.extra_ytdlp_flags,
+        settings.download.extra_ytdlp_flags_unsafe,
+    );
+    if settings.download.save_metadata_json
+        && !extra_ytdlp_args.iter().any(|f| f == "--write-info-json")
+    {
+        extra_ytdlp_args.push("--write-info-json".to_string());
+    }
+    if !refused_flags.is_empty() {
+        append_download_log(
+            &app,
+            item_id,
+            format!(
+                "[omniget] ignoring extra yt-dlp flags: {}",
+                refused_flags.join(", ")
+            ),
+        );
+    }
+
+    if let Err(e) = check_output_space(
+        &info,
+        quality.as_deref(),
+        &final_output_dir,
+        settings.download.max_filesize_mb,
+    ) {
+        let raw_err = e.to_string();
+        append_download_log(&app, item_id, format!("[omniget] {}", raw_err));
+        let message = crate::core::i18n::error_message(&settings.appearance.language, e.category())
+            .map(|hint| format!("{} ({})", hint, raw_err))
+            .unwrap_or(raw_err);
+        let state = {
+            let mut q = queue.lock().await;
+            q.mark_complete(item_id, false, Some(message), None, None);
+            q.get_state()
+        };
+        emit_queue_state_from_state(&app, state);
+        try_start_next(app, queue).await;
+        return;
+    }
+
+    let opts = crate::models::media::DownloadOptions {
+        quality_selection: crate::models::media::QualitySelection::from_quality(
+            quality.as_deref(),
+        ),
+        quality,
+        output_dir: final_output_dir,
         filename_template: Some(tmpl),
+        output_subfolder_template: subfolder_template,
         download_subtitles: settings.download.download_subtitles,
         include_auto_subtitles: settings.download.include_auto_subtitles,
         download_mode,
@@ -1314,6 +2083,24 @@ async fn spawn_download_inner(
         torrent_files: torrent_files.clone(),
         torrent_auto_trackers: settings.advanced.torrent_auto_trackers,
         torrent_upnp: settings.advanced.torrent_upnp,
+        prefer_no_watermark: settings.download.tiktok_no_watermark,
+        overwrite: overwrite_policy_for(&settings.download),
+        quality_preference: settings.download.quality_preference.clone(),
+        hls_max_concurrent_segments: settings.advanced.max_concurrent_segments.clamp(
+            AdvancedSettings::MIN_CONCURRENCY,
+            AdvancedSettings::MAX_CONCURRENCY,
+        ),
+        hls_max_retries: settings
+            .download
+            .hls_max_retries
+            .clamp(1, DownloadSettings::MAX_HLS_RETRIES),
+        strip_metadata: settings.download.strip_metadata,
+        extra_ytdlp_args,
+        selected_indices,
+        section_start: section.map(|(start, _)| start),
+        section_end: section.and_then(|(_, end)| end),
+        download_archive: archive.clone(),
+        save_metadata_json: settings.download.save_metadata_json,
     };
 
     let total_bytes = info.file_size_bytes;
@@ -1460,7 +2247,7 @@ async fn spawn_download_inner(
                     }
                 });
 
-            {
+            let postprocessing = {
                 let mut q = queue_progress.lock().await;
                 let tid = { *torrent_id_slot_progress.lock().await };
                 q.update_progress(
@@ -1472,7 +2259,13 @@ async fn spawn_download_inner(
                     tid,
                     eta_seconds,
                 );
-            }
+                q.items
+                    .iter()
+                    .find(|i| i.id == item_id)
+                    .and_then(|i| i.phase)
+                    .filter(|p| matches!(p, DownloadPhase::Muxing | DownloadPhase::Converting))
+            };
+            let phase = postprocessing.map(DownloadPhase::as_str).unwrap_or(phase);
 
             let _ = app_progress.emit(
                 "queue-item-progress",
@@ -1508,6 +2301,10 @@ async fn spawn_download_inner(
             platform_name, log_title, url
         ),
     );
+    queue
+        .lock()
+        .await
+        .set_phase(item_id, DownloadPhase::Downloading);
     let dl_future = async {
         tokio::select! {
             r = downloader.download(&info, &opts, tx) => r,
@@ -1552,7 +2349,7 @@ async fn spawn_download_inner(
     }
 
     match result {
-        Ok(dl) => {
+        Ok(mut dl) => {
             append_download_log(
                 &app,
                 item_id,
@@ -1562,6 +2359,20 @@ async fn spawn_download_inner(
                     dl.file_size_bytes
                 ),
             );
+            if dl.skipped {
+                let state = {
+                    let mut q = queue.lock().await;
+                    q.mark_skipped(
+                        item_id,
+                        dl.file_path.to_string_lossy().to_string(),
+                        dl.file_size_bytes,
+                    );
+                    q.get_state()
+                };
+                emit_queue_state_from_state(&app, state);
+                try_start_next(app, queue).await;
+                return;
+            }
             let is_seeding = platform_name == "magnet" && dl.torrent_id.is_some();
             if !is_seeding {
                 if let Err(msg) = validate_download_output(&dl.file_path).await {
@@ -1611,6 +2422,11 @@ async fn spawn_download_inner(
                 }
             }
 
+            let verify_path = (settings.download.verify_after_download
+                && !is_seeding
+                && ffmpeg::is_verifiable(&dl.file_path))
+            .then(|| dl.file_path.clone());
+
             if from_hotkey && settings.download.copy_to_clipboard_on_hotkey {
                 #[cfg(not(target_os = "android"))]
                 {
@@ -1630,9 +2446,40 @@ async fn spawn_download_inner(
                 }
             }
 
+            if !is_seeding {
+                if let Some(sidecar) = opts.write_info_json(&info, &dl.file_path) {
+                    dl.extra_files.push(sidecar);
+                }
+            }
+
+            if !is_seeding && dl.file_path.is_file() {
+                if let Err(e) =
+                    crate::core::manifest::write(&dl.file_path, &url, &platform_name, None)
+                {
+                    tracing::warn!("[queue] failed to write completion manifest: {}", e);
+                }
+            }
+            // A whole playlist handed to yt-dlp records its own items.
+            let is_collection = info.media_type == crate::models::media::MediaType::Playlist;
+            if let Some(archive) = archive.as_deref().filter(|_| !is_seeding && !is_collection) {
+                let entry = crate::core::download_archive::entry_for(&url);
+                if let Err(e) = crate::core::download_archive::record(archive, &entry) {
+                    tracing::warn!("[queue] failed to record {} in the archive: {}", entry, e);
+                }
+            }
+            remember_output_dir(&app, &output_dir);
+            if let Some(note) = dl.note {
+                append_download_log(&app, item_id, format!("[omniget] note: {:?}", note));
+                // Lets the UI explain the result or offer to install ffmpeg.
+                let _ = app.emit(
+                    "download-note",
+                    serde_json::json!({ "id": item_id, "note": note }),
+                );
+            }
+
             let state = {
                 let mut q = queue.lock().await;
-                if platform_name == "magnet" && dl.torrent_id.is_some() {
+                if is_seeding {
                     q.mark_seeding(
                         item_id,
                         Some(dl.file_path.to_string_lossy().to_string()),
@@ -1640,6 +2487,13 @@ async fn spawn_download_inner(
                         dl.torrent_id,
                     );
                 } else {
+                    // Before mark_complete, which writes the history entry.
+                    let extra = dl
+                        .extra_files
+                        .iter()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .collect();
+                    q.set_extra_files(item_id, extra);
                     q.mark_complete(
                         item_id,
                         true,
@@ -1651,6 +2505,17 @@ async fn spawn_download_inner(
                 q.get_state()
             };
             emit_queue_state_from_state(&app, state);
+            // Decoding the whole file can take minutes, so it runs after
+            // the item has given its slot to the next download.
+            if let Some(path) = verify_path {
+                tauri::async_runtime::spawn(verify_finished(
+                    app.clone(),
+                    queue.clone(),
+                    item_id,
+                    path,
+                    cancel_token.clone(),
+                ));
+            }
         }
         Err(e) => {
             let raw_err = e.to_string();
@@ -1659,8 +2524,10 @@ async fn spawn_download_inner(
                 item_id,
                 format!("[omniget] download failed: {}", raw_err),
             );
-            let (category, hint) = omniget_core::core::errors::classify_download_error(&raw_err);
-            let user_msg = if category != "unknown" {
+            let category = omniget_core::core::errors::classify_error(&e);
+            let user_msg = if let Some(hint) =
+                crate::core::i18n::error_message(&settings.appearance.language, category)
+            {
                 format!("{} ({})", hint, raw_err)
             } else {
                 raw_err.clone()
@@ -1674,26 +2541,11 @@ async fn spawn_download_inner(
 
             let retry_decision = {
                 let mut q = queue.lock().await;
-                if let Some(item) = q.items.iter_mut().find(|i| i.id == item_id) {
-                    if item.downloaded_bytes > 5 * 1024 * 1024 {
-                        item.retry_count = 0;
-                    }
-                    let retryable = is_retryable_category(category);
-                    let attempt = item.retry_count;
-                    let max = item.max_retries;
-                    if retryable && attempt < max {
-                        item.retry_count = attempt + 1;
-                        Some((attempt + 1, max))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
+                q.schedule_retry(item_id, category)
+                    .map(|decision| (decision, q.get_state()))
             };
 
-            if let Some((next_attempt, max)) = retry_decision {
-                let delay_secs = (1u64 << (next_attempt - 1).min(5)).min(30);
+            if let Some(((next_attempt, max, delay_secs), state)) = retry_decision {
                 tracing::warn!(
                     "[queue] retry {}/{} for {} in {}s (category={})",
                     next_attempt,
@@ -1702,17 +2554,6 @@ async fn spawn_download_inner(
                     delay_secs,
                     category
                 );
-                let state = {
-                    let mut q = queue.lock().await;
-                    if let Some(item) = q.items.iter_mut().find(|i| i.id == item_id) {
-                        item.status = QueueStatus::Queued;
-                        item.cancel_token = CancellationToken::new();
-                        item.percent = 0.0;
-                        item.speed_bytes_per_sec = 0.0;
-                        item.downloaded_bytes = 0;
-                    }
-                    q.get_state()
-                };
                 emit_queue_state_from_state(&app, state);
                 let app_for_retry = app.clone();
                 let queue_for_retry = queue.clone();
@@ -1723,9 +2564,16 @@ async fn spawn_download_inner(
                 return;
             }
 
+            let geo_block = geo_block_hint(&e);
+            if geo_block.is_some() && requeue_through_proxy(&app, &queue, item_id).await {
+                return;
+            }
             let state = {
                 let mut q = queue.lock().await;
                 q.mark_complete(item_id, false, Some(user_msg), None, None);
+                if let Some(country_hint) = geo_block {
+                    q.mark_geo_blocked(item_id, country_hint);
+                }
                 q.get_state()
             };
             emit_queue_state_from_state(&app, state);
@@ -1735,8 +2583,109 @@ async fn spawn_download_inner(
     try_start_next(app, queue).await;
 }
 
+/// Records `dir` as the most recently used output folder.
+fn remember_output_dir(app: &tauri::AppHandle, dir: &str) {
+    let _lock = config::write_lock();
+    let mut settings = config::load_settings(app);
+    if settings.remember_output_dir(std::path::Path::new(dir)) {
+        if let Err(e) = config::save_settings(app, &settings) {
+            tracing::warn!("[queue] failed to save recent output dirs: {}", e);
+        }
+    }
+}
+
+/// `Some(country_hint)` when `error` means the content is region-blocked.
+fn geo_block_hint(error: &anyhow::Error) -> Option<Option<String>> {
+    use omniget_core::core::errors::{self, DownloadError};
+    match errors::find_download_error(error) {
+        Some(DownloadError::GeoBlocked { country_hint }) => Some(country_hint.clone()),
+        _ => (errors::classify_error(error) == "geo_blocked").then_some(None),
+    }
+}
+
+/// Gives a geo-blocked item one more run through the configured proxy when
+/// that proxy is switched off. Returns whether the item was re-queued.
+async fn requeue_through_proxy(
+    app: &tauri::AppHandle,
+    queue: &Arc<tokio::sync::Mutex<DownloadQueue>>,
+    item_id: u64,
+) -> bool {
+    if crate::core::http_client::geo_retry_proxy().is_none() {
+        return false;
+    }
+    let state = {
+        let mut q = queue.lock().await;
+        if !q.requeue_through_proxy(item_id) {
+            return false;
+        }
+        q.get_state()
+    };
+    tracing::info!("[queue] {} is geo-blocked; retrying through proxy", item_id);
+    emit_queue_state_from_state(app, state);
+    try_start_next(app.clone(), queue.clone()).await;
+    true
+}
+
+/// Free space kept on top of a download's expected size, for temporary
+/// fragments and the remux into the final container.
+const DISK_SPACE_MARGIN_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Fails fast when the item is over `max_filesize_mb` (0 = no limit) or
+/// `output_dir`'s volume can't hold it plus a margin. Items without a size
+/// estimate always pass.
+fn check_output_space(
+    info: &MediaInfo,
+    quality: Option<&str>,
+    output_dir: &std::path::Path,
+    max_filesize_mb: u64,
+) -> Result<(), omniget_core::core::errors::DownloadError> {
+    let Some(size) = info.estimated_size_bytes(quality) else {
+        return Ok(());
+    };
+    space_verdict(
+        size,
+        crate::core::disk_space::available_bytes(output_dir),
+        max_filesize_mb,
+    )
+}
+
+fn space_verdict(
+    size: u64,
+    available: Option<u64>,
+    max_filesize_mb: u64,
+) -> Result<(), omniget_core::core::errors::DownloadError> {
+    use omniget_core::core::errors::DownloadError;
+    if max_filesize_mb > 0 && size > max_filesize_mb.saturating_mul(1024 * 1024) {
+        return Err(DownloadError::TooLarge {
+            size,
+            limit_mb: max_filesize_mb,
+        });
+    }
+    let needed = size
+        .saturating_add(size / 10)
+        .saturating_add(DISK_SPACE_MARGIN_BYTES);
+    match available {
+        Some(available) if available < needed => {
+            Err(DownloadError::InsufficientSpace { needed, available })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Transient failures worth another attempt. Private, missing, geo-blocked
+/// and auth failures would fail the same way again.
 fn is_retryable_category(category: &str) -> bool {
-    matches!(category, "unknown" | "rate_limited")
+    matches!(category, "unknown" | "rate_limited" | "network")
+}
+
+/// Longest wait between automatic retries.
+const MAX_RETRY_DELAY_SECS: u64 = 300;
+
+/// Backoff before retry number `attempt` (1-based): `base`, `2 * base`,
+/// `4 * base`, ... capped at `MAX_RETRY_DELAY_SECS`.
+fn retry_delay_secs(base: u64, attempt: u32) -> u64 {
+    let factor = 1u64 << attempt.saturating_sub(1).min(16);
+    base.max(1).saturating_mul(factor).min(MAX_RETRY_DELAY_SECS)
 }
 
 const OUTPUT_MISSING_ERROR: &str =
@@ -1841,6 +2790,12 @@ pub async fn try_get_cached_info(url: &str) -> Option<MediaInfo> {
         .map(|entry| entry.info.clone())
 }
 
+/// Drops any cached info for `url` so the next download re-runs
+/// `get_media_info` and gets fresh media URLs.
+pub async fn evict_cached_info(url: &str) {
+    info_cache().lock().await.remove(url);
+}
+
 pub async fn prefetch_info(
     url: &str,
     downloader: &dyn PlatformDownloader,
@@ -1894,10 +2849,115 @@ pub async fn prefetch_info_with_emit(
     }
 }
 
+/// Info lookups allowed ahead of the download stage at once.
+const PREFETCH_CONCURRENCY: usize = 3;
+
+struct PrefetchJob {
+    id: u64,
+    url: String,
+    platform: String,
+    downloader: Arc<dyn PlatformDownloader>,
+    ytdlp_path: Option<PathBuf>,
+    cookie_slug: Option<String>,
+}
+
+/// Resolves info for queued items before they reach the front, a few at a
+/// time, so large batches show titles and thumbnails while they wait. Each
+/// finished lookup claims the next item, until none are left.
+pub fn prefetch_queued_info(app: tauri::AppHandle, queue: Arc<tokio::sync::Mutex<DownloadQueue>>) {
+    tokio::spawn(async move {
+        let jobs = queue.lock().await.claim_prefetch();
+        for job in jobs {
+            let app = app.clone();
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                let info = prefetch_job_info(&job).await;
+                let state = {
+                    let mut q = queue.lock().await;
+                    q.finish_prefetch(job.id, info).then(|| q.get_state())
+                };
+                if let Some(state) = state {
+                    emit_queue_state_from_state(&app, state);
+                }
+                prefetch_queued_info(app, queue);
+            });
+        }
+    });
+}
+
+async fn prefetch_job_info(job: &PrefetchJob) -> Option<MediaInfo> {
+    let host_key = crate::core::host_limiter::host_key_for_url(&job.url);
+    let _host_lease = crate::core::host_limiter::acquire(&host_key).await;
+    let info_future = fetch_and_cache_info(
+        &job.url,
+        &*job.downloader,
+        &job.platform,
+        job.ytdlp_path.as_deref(),
+    );
+    let scoped = omniget_core::core::log_hook::CURRENT_COOKIE_SLUG
+        .scope(job.cookie_slug.clone(), info_future);
+    let timeout = std::time::Duration::from_secs(
+        omniget_core::core::ytdlp::DEFAULT_VIDEO_INFO_TOTAL_TIMEOUT_SECS,
+    );
+    match tokio::time::timeout(timeout, scoped).await {
+        Ok(Ok(info)) => Some(info),
+        Ok(Err(e)) => {
+            tracing::debug!("[queue] prefetch for {} failed: {}", job.id, e);
+            None
+        }
+        Err(_) => {
+            tracing::debug!("[queue] prefetch for {} timed out", job.id);
+            None
+        }
+    }
+}
+
+/// Decodes a finished download with ffmpeg and flags the item as suspect
+/// when that finds errors. Stops when the item is removed.
+async fn verify_finished(
+    app: tauri::AppHandle,
+    queue: Arc<tokio::sync::Mutex<DownloadQueue>>,
+    item_id: u64,
+    path: std::path::PathBuf,
+    cancel_token: CancellationToken,
+) {
+    if !ffmpeg::is_ffmpeg_available().await {
+        return;
+    }
+    match ffmpeg::verify_media(&path, &cancel_token).await {
+        Ok(health) if !health.ok => {
+            tracing::warn!("[queue] verification found decode errors in {:?}", path);
+            append_download_log(&app, item_id, "[omniget] verification found decode errors:");
+            for line in health.errors {
+                append_download_log(&app, item_id, format!("  {}", line));
+            }
+            let state = {
+                let mut q = queue.lock().await;
+                q.set_suspect(item_id, true).then(|| q.get_state())
+            };
+            if let Some(state) = state {
+                emit_queue_state_from_state(&app, state);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("[queue] verification failed to run: {}", e),
+    }
+}
+
 pub async fn try_start_next(app: tauri::AppHandle, queue: Arc<tokio::sync::Mutex<DownloadQueue>>) {
     let _timer_start = std::time::Instant::now();
-    let (next_ids, stagger, state_to_emit) = {
+    let (next_ids, stagger, state_to_emit, wake_in_ms) = {
         let mut q = queue.lock().await;
+        let now = now_ms();
+        let total_429 = crate::core::stats::session().snapshot().rate_limit_429_count;
+        if q.rate_breaker.observe(now, total_429) {
+            let _ = app.emit(
+                "queue-rate-limited",
+                &RateLimitedPayload {
+                    active: q.rate_breaker.is_tripped(),
+                },
+            );
+        }
         let ids = q.next_queued_ids();
         for nid in &ids {
             q.mark_active(*nid);
@@ -1907,13 +2967,28 @@ pub async fn try_start_next(app: tauri::AppHandle, queue: Arc<tokio::sync::Mutex
         } else {
             None
         };
-        (ids, q.stagger_delay_ms, state)
+        let cooldown = q.rate_breaker.cooldown_left_ms(now);
+        let waiting = q.items.iter().any(|i| i.status == QueueStatus::Queued);
+        let wake = (cooldown > 0 && waiting && q.rate_breaker.schedule_wake()).then_some(cooldown);
+        (ids, q.stagger_delay_ms, state, wake)
     };
 
     if let Some(state) = state_to_emit {
         emit_queue_state_from_state(&app, state);
     }
 
+    // Slowed down by the rate breaker: come back when the cooldown since the
+    // last start is over instead of holding up the caller.
+    if let Some(delay_ms) = wake_in_ms {
+        let app_c = app.clone();
+        let queue_c = queue.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            queue_c.lock().await.rate_breaker.wake_done();
+            try_start_next(app_c, queue_c).await;
+        });
+    }
+
     let batch_size = next_ids.len();
     for (i, nid) in next_ids.into_iter().enumerate() {
         let _ = app.emit(
@@ -1961,15 +3036,22 @@ pub async fn try_start_next(app: tauri::AppHandle, queue: Arc<tokio::sync::Mutex
 
 // Periodic tick so a future-scheduled download still starts when its time
 // arrives even if the queue is otherwise idle, and so a download with a
-// stop time is cancelled when that time passes.
+// stop time is cancelled when that time passes. It also picks up the
+// `allowed_hours` setting and starts the queue when that window opens.
 pub fn start_scheduler(app: tauri::AppHandle) {
     tauri::async_runtime::spawn(async move {
         tokio::time::sleep(std::time::Duration::from_secs(15)).await;
+        let mut was_open = true;
         loop {
             let state = app.state::<crate::AppState>();
             let queue = state.download_queue.clone();
+            let allowed_hours = config::load_settings(&app).advanced.allowed_hours;
+            let is_open = allowed_hours.is_none_or(|w| w.is_open_now());
+            let window_opened = is_open && !was_open;
+            was_open = is_open;
             let (has_due, stopped_any) = {
-                let q = queue.lock().await;
+                let mut q = queue.lock().await;
+                q.allowed_hours = allowed_hours;
                 let now = now_ms();
                 let mut stopped = false;
                 for item in &q.items {
@@ -1982,13 +3064,16 @@ pub fn start_scheduler(app: tauri::AppHandle) {
                         }
                     }
                 }
+                // Items queued during the rate breaker's cooldown by a path
+                // that doesn't schedule its wake-up are picked up here too.
                 let due = q.items.iter().any(|i| {
                     i.status == QueueStatus::Queued
-                        && i.scheduled_at_ms.map(|t| now >= t).unwrap_or(false)
+                        && (i.scheduled_at_ms.map(|t| now >= t).unwrap_or(false)
+                            || q.rate_breaker.is_tripped())
                 });
                 (due, stopped)
             };
-            if has_due || stopped_any {
+            if has_due || stopped_any || window_opened {
                 try_start_next(app.clone(), queue.clone()).await;
             }
             tokio::time::sleep(std::time::Duration::from_secs(15)).await;
@@ -2008,6 +3093,463 @@ fn is_generic_title(title: &str) -> bool {
         || t.starts_with("media [media]")
 }
 
+/// Picks up to `slots` candidate ids in order, skipping any whose host
+/// already has `max_per_host` downloads running or admitted.
+fn admit_within_host_limit(
+    active_hosts: impl Iterator<Item = Option<String>>,
+    candidates: impl Iterator<Item = (u64, Option<String>)>,
+    slots: usize,
+    max_per_host: u32,
+) -> Vec<u64> {
+    let max_per_host = max_per_host.max(1);
+    let mut per_host: HashMap<String, u32> = HashMap::new();
+    for host in active_hosts.flatten() {
+        *per_host.entry(host).or_default() += 1;
+    }
+
+    let mut admitted = Vec::new();
+    for (id, host) in candidates {
+        if admitted.len() >= slots {
+            break;
+        }
+        if let Some(host) = host {
+            let running = per_host.entry(host).or_default();
+            if *running >= max_per_host {
+                continue;
+            }
+            *running += 1;
+        }
+        admitted.push(id);
+    }
+    admitted
+}
+
+#[cfg(test)]
+mod test_support {
+    use super::{QueueItem, QueueStatus};
+    use std::sync::Arc;
+    use tokio_util::sync::CancellationToken;
+
+    /// A bare queue item with every optional field unset.
+    pub(super) fn item(id: u64, status: QueueStatus) -> QueueItem {
+        QueueItem {
+            id,
+            url: format!("https://example.com/{}", id),
+            platform: "generic".to_string(),
+            title: format!("Item {}", id),
+            status,
+            cancel_token: CancellationToken::new(),
+            output_dir: "out".to_string(),
+            download_mode: None,
+            quality: None,
+            format_id: None,
+            referer: None,
+            extra_headers: None,
+            page_url: None,
+            user_agent: None,
+            percent: 0.0,
+            speed_bytes_per_sec: 0.0,
+            downloaded_bytes: 0,
+            total_bytes: None,
+            file_path: None,
+            file_size_bytes: None,
+            file_count: None,
+            media_info: None,
+            downloader: Arc::new(crate::platforms::noop::NoopDownloader::new()),
+            ytdlp_path: None,
+            from_hotkey: false,
+            torrent_id: None,
+            kind: None,
+            external: false,
+            thumbnail_url_override: None,
+            retry_count: 0,
+            max_retries: 0,
+            retry_base_delay_secs: 0,
+            retry_at_ms: None,
+            resume_state: None,
+            concurrent_segments: None,
+            segment_size_bytes: None,
+            eta_seconds: None,
+            cookie_slug: None,
+            custom_ytdlp_args: None,
+            torrent_files: None,
+            scheduled_at_ms: None,
+            stop_at_ms: None,
+            priority: 0,
+            proxy_retried: false,
+            playlist_position: None,
+            selected_indices: None,
+            section: None,
+            archive: None,
+            tags: Vec::new(),
+            suspect: false,
+            extra_files: Vec::new(),
+            phase: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod order_tests {
+    use super::test_support::item;
+    use super::{DownloadQueue, QueueStatus};
+
+    fn queue(items: &[(u64, QueueStatus, i32)]) -> DownloadQueue {
+        let mut q = DownloadQueue::new(2);
+        q.items = items
+            .iter()
+            .map(|(id, status, priority)| {
+                let mut item = item(*id, status.clone());
+                item.priority = *priority;
+                item
+            })
+            .collect();
+        q
+    }
+
+    fn ids(q: &DownloadQueue) -> Vec<u64> {
+        q.items.iter().map(|i| i.id).collect()
+    }
+
+    #[test]
+    fn sort_keeps_ties_in_place_and_active_items_put() {
+        let mut q = queue(&[
+            (1, QueueStatus::Queued, 0),
+            (2, QueueStatus::Active, 9),
+            (3, QueueStatus::Queued, 5),
+            (4, QueueStatus::Queued, 5),
+        ]);
+        q.sort_queued_by_priority();
+        assert_eq!(ids(&q), vec![3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn moved_items_keep_their_place_through_later_sorts() {
+        let mut q = queue(&[
+            (1, QueueStatus::Active, 0),
+            (2, QueueStatus::Queued, 3),
+            (3, QueueStatus::Queued, 0),
+            (4, QueueStatus::Queued, 0),
+        ]);
+        assert!(q.move_to_front(4));
+        assert_eq!(ids(&q), vec![1, 4, 2, 3]);
+        assert!(q.move_to_back(2));
+        assert_eq!(ids(&q), vec![1, 4, 3, 2]);
+        q.sort_queued_by_priority();
+        assert_eq!(ids(&q), vec![1, 4, 3, 2]);
+        assert_eq!(q.items[1].priority, 4);
+        assert_eq!(q.items[3].priority, -1);
+    }
+
+    #[test]
+    fn only_queued_items_can_be_moved_or_reprioritized() {
+        let mut q = queue(&[(1, QueueStatus::Active, 0), (2, QueueStatus::Queued, 0)]);
+        assert!(!q.move_to_front(1));
+        assert!(!q.move_to_back(1));
+        assert!(!q.set_priority(1, 7));
+        assert!(!q.move_to_front(9));
+        assert_eq!(ids(&q), vec![1, 2]);
+        assert_eq!(q.items[0].priority, 0);
+    }
+
+    #[test]
+    fn set_priority_reorders_the_queued_items() {
+        let mut q = queue(&[
+            (1, QueueStatus::Queued, 0),
+            (2, QueueStatus::Active, 0),
+            (3, QueueStatus::Queued, 0),
+        ]);
+        assert!(q.set_priority(3, 2));
+        assert_eq!(ids(&q), vec![3, 2, 1]);
+        assert!(q.set_priority(3, -2));
+        assert_eq!(ids(&q), vec![1, 2, 3]);
+    }
+}
+
+#[cfg(test)]
+mod tag_tests {
+    use super::test_support::item;
+    use super::{batch_tag_for, DownloadQueue, QueueStatus};
+
+    fn queue(ids: &[u64]) -> DownloadQueue {
+        let mut q = DownloadQueue::new(2);
+        q.items = ids
+            .iter()
+            .map(|id| item(*id, QueueStatus::Queued))
+            .collect();
+        q
+    }
+
+    fn tagged_ids(q: &DownloadQueue, tag: &str) -> Vec<u64> {
+        q.get_state_tagged(tag).iter().map(|i| i.id).collect()
+    }
+
+    #[test]
+    fn tags_are_trimmed_and_case_insensitive() {
+        let mut q = queue(&[1]);
+        assert!(q.add_tag(1, " Music "));
+        assert!(!q.add_tag(1, "music"));
+        assert!(!q.add_tag(1, "  "));
+        assert!(!q.add_tag(9, "music"));
+        assert_eq!(q.items[0].tags, vec!["Music".to_string()]);
+        assert!(q.remove_tag(1, "MUSIC"));
+        assert!(!q.remove_tag(1, "music"));
+        assert!(q.items[0].tags.is_empty());
+    }
+
+    #[test]
+    fn state_can_be_narrowed_to_a_tag() {
+        let mut q = queue(&[1, 2, 3]);
+        q.add_tag(1, "work");
+        q.add_tag(3, "Work");
+        q.add_tag(3, "later");
+        assert_eq!(tagged_ids(&q, "work"), vec![1, 3]);
+        assert_eq!(tagged_ids(&q, "later"), vec![3]);
+        assert!(tagged_ids(&q, "missing").is_empty());
+        assert_eq!(q.get_state().len(), 3);
+    }
+
+    #[test]
+    fn collection_items_share_a_batch_tag() {
+        let playlist = "https://www.youtube.com/playlist?list=PL123&si=x";
+        assert_eq!(batch_tag_for(playlist), "PL123");
+        assert_eq!(
+            batch_tag_for("https://soundcloud.com/artist/sets/road-trip/"),
+            "road-trip"
+        );
+        assert_eq!(batch_tag_for("https://example.com/"), "example.com");
+
+        let mut q = queue(&[1, 2, 3]);
+        let tag = batch_tag_for(playlist);
+        for id in [1, 3] {
+            assert!(q.add_tag(id, &tag));
+        }
+        assert_eq!(tagged_ids(&q, "pl123"), vec![1, 3]);
+    }
+}
+
+#[cfg(test)]
+mod prefetch_tests {
+    use super::test_support::item;
+    use super::{DownloadQueue, QueueStatus, PREFETCH_CONCURRENCY};
+    use crate::models::media::{MediaInfo, MediaType};
+
+    fn queue(items: Vec<super::QueueItem>) -> DownloadQueue {
+        let mut q = DownloadQueue::new(2);
+        q.items = items;
+        q
+    }
+
+    fn claimed(q: &mut DownloadQueue) -> Vec<u64> {
+        q.claim_prefetch().iter().map(|j| j.id).collect()
+    }
+
+    fn info(title: &str) -> MediaInfo {
+        MediaInfo {
+            title: title.to_string(),
+            media_type: MediaType::Video,
+            file_size_bytes: Some(42),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn claims_waiting_items_without_info_up_to_the_slots() {
+        let mut with_info = item(2, QueueStatus::Queued);
+        with_info.media_info = Some(info("Known"));
+        let mut q = queue(vec![
+            item(1, QueueStatus::Active),
+            with_info,
+            item(3, QueueStatus::Queued),
+            item(4, QueueStatus::Queued),
+            item(5, QueueStatus::Queued),
+            item(6, QueueStatus::Queued),
+        ]);
+        assert_eq!(PREFETCH_CONCURRENCY, 3);
+        assert_eq!(claimed(&mut q), vec![3, 4, 5]);
+        assert!(claimed(&mut q).is_empty());
+        q.finish_prefetch(3, None);
+        assert_eq!(claimed(&mut q), vec![6]);
+    }
+
+    #[test]
+    fn finished_prefetch_fills_in_items_still_waiting() {
+        let mut q = queue(vec![
+            item(1, QueueStatus::Queued),
+            item(2, QueueStatus::Queued),
+        ]);
+        claimed(&mut q);
+        assert!(q.finish_prefetch(1, Some(info("Real title"))));
+        assert_eq!(q.items[0].title, "Real title");
+        assert_eq!(q.items[0].total_bytes, Some(42));
+        assert!(q.items[0].media_info.is_some());
+
+        q.items[1].status = QueueStatus::Active;
+        assert!(!q.finish_prefetch(2, Some(info("Late"))));
+        assert!(q.items[1].media_info.is_none());
+        assert!(!q.finish_prefetch(9, Some(info("Gone"))));
+    }
+
+    #[test]
+    fn generic_titles_do_not_replace_the_queued_one() {
+        let mut q = queue(vec![item(1, QueueStatus::Queued)]);
+        assert!(q.finish_prefetch(1, Some(info("Video"))));
+        assert_eq!(q.items[0].title, "Item 1");
+    }
+
+    #[test]
+    fn removed_items_are_forgotten() {
+        let mut q = queue(vec![
+            item(1, QueueStatus::Queued),
+            item(2, QueueStatus::Queued),
+        ]);
+        assert_eq!(claimed(&mut q), vec![1, 2]);
+        q.finish_prefetch(1, None);
+        q.remove_inner(1);
+        q.remove_inner(2);
+        assert!(q.prefetched.is_empty());
+        assert!(!q.finish_prefetch(2, None));
+        assert!(q.prefetched.is_empty());
+        assert!(q.prefetching.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod host_limit_tests {
+    use super::admit_within_host_limit;
+
+    fn host(h: &str) -> Option<String> {
+        Some(h.to_string())
+    }
+
+    #[test]
+    fn busy_host_is_skipped_for_other_hosts() {
+        let active = vec![host("instagram"), host("instagram")];
+        let candidates = vec![(1, host("instagram")), (2, host("youtube")), (3, None)];
+        assert_eq!(
+            admit_within_host_limit(active.into_iter(), candidates.into_iter(), 4, 2),
+            vec![2, 3]
+        );
+    }
+
+    #[test]
+    fn admitted_items_count_against_their_host() {
+        let candidates = vec![
+            (1, host("instagram")),
+            (2, host("instagram")),
+            (3, host("instagram")),
+            (4, host("reddit")),
+        ];
+        assert_eq!(
+            admit_within_host_limit(std::iter::empty(), candidates.into_iter(), 3, 2),
+            vec![1, 2, 4]
+        );
+    }
+}
+
+#[cfg(test)]
+mod playlist_template_tests {
+    use super::{playlist_template, PlaylistPosition};
+    use crate::models::media::{DownloadOptions, MediaInfo};
+
+    const POSITION: PlaylistPosition = PlaylistPosition {
+        index: 7,
+        count: 12,
+    };
+
+    fn render(template: &str) -> String {
+        let opts = DownloadOptions {
+            filename_template: Some(playlist_template(template.to_string(), Some(POSITION))),
+            ..DownloadOptions::new(std::path::PathBuf::from("out"))
+        };
+        let info = MediaInfo {
+            title: "Title".to_string(),
+            ..Default::default()
+        };
+        let path = opts
+            .output_path(&opts.media_filename(&info, "mp4"))
+            .unwrap();
+        path.file_name().unwrap().to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn items_are_numbered_once() {
+        assert_eq!(
+            render("%(playlist_index)s - %(title)s.%(ext)s"),
+            "07 - Title.mp4"
+        );
+        assert_eq!(render("%(title)s.%(ext)s"), "07 - Title.mp4");
+    }
+
+    #[test]
+    fn number_goes_in_front_of_the_file_name() {
+        assert_eq!(
+            playlist_template("%(uploader)s/%(title)s.%(ext)s".to_string(), Some(POSITION)),
+            "%(uploader)s/07 - %(title)s.%(ext)s"
+        );
+        assert_eq!(
+            playlist_template("%(title)s.%(ext)s".to_string(), None),
+            "%(title)s.%(ext)s"
+        );
+    }
+}
+
+#[cfg(test)]
+mod space_tests {
+    use super::space_verdict;
+    use omniget_core::core::errors::DownloadError;
+
+    const MIB: u64 = 1024 * 1024;
+
+    #[test]
+    fn oversized_items_fail_before_the_space_check() {
+        assert_eq!(
+            space_verdict(600 * MIB, Some(u64::MAX), 500),
+            Err(DownloadError::TooLarge {
+                size: 600 * MIB,
+                limit_mb: 500
+            })
+        );
+        assert!(space_verdict(600 * MIB, Some(u64::MAX), 0).is_ok());
+    }
+
+    #[test]
+    fn space_check_includes_the_margin() {
+        assert!(space_verdict(1000 * MIB, Some(1300 * MIB), 0).is_ok());
+        assert!(matches!(
+            space_verdict(1000 * MIB, Some(1100 * MIB), 0),
+            Err(DownloadError::InsufficientSpace { .. })
+        ));
+        assert!(space_verdict(1000 * MIB, None, 0).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod retry_tests {
+    use super::{is_retryable_category, is_retryable_error_message, retry_delay_secs};
+
+    #[test]
+    fn delay_doubles_up_to_the_cap() {
+        assert_eq!(retry_delay_secs(2, 1), 2);
+        assert_eq!(retry_delay_secs(2, 2), 4);
+        assert_eq!(retry_delay_secs(2, 3), 8);
+        assert_eq!(retry_delay_secs(0, 1), 1);
+        assert_eq!(retry_delay_secs(10, 20), super::MAX_RETRY_DELAY_SECS);
+    }
+
+    #[test]
+    fn only_transient_failures_are_retried() {
+        assert!(is_retryable_category("network"));
+        assert!(is_retryable_category("rate_limited"));
+        assert!(!is_retryable_category("restricted"));
+        assert!(!is_retryable_category("not_found"));
+        assert!(!is_retryable_category("auth_required"));
+        assert!(is_retryable_error_message("operation timed out"));
+        assert!(!is_retryable_error_message("HTTP Error 404: Not Found"));
+        assert!(!is_retryable_error_message("Download cancelled"));
+    }
+}
+
 #[cfg(test)]
 mod kind_tests {
     use super::{kind_from_platform, QueueKind};
@@ -1,13 +1,15 @@
 use std::path::PathBuf;
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 
 use crate::core::db;
 use crate::core::queue::QueueKind;
 
 const HISTORY_FILE: &str = "download-history.json";
-const MAX_HISTORY_ENTRIES: usize = 200;
+const MAX_HISTORY_ENTRIES: usize = 2000;
+/// How many recent entries are restored into the queue on startup.
+const QUEUE_RESTORE_LIMIT: usize = 200;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
@@ -29,6 +31,29 @@ pub struct HistoryEntry {
     pub thumbnail_url: Option<String>,
     #[serde(default)]
     pub kind: Option<QueueKind>,
+    /// Page the media was found on when `url` is a direct media link.
+    #[serde(default)]
+    pub page_url: Option<String>,
+    /// Files saved besides `file_path`, such as the rest of a carousel.
+    #[serde(default)]
+    pub extra_files: Vec<String>,
+}
+
+impl HistoryEntry {
+    /// The user-facing URL to fetch again. Direct media links are often
+    /// signed and expire, the page they came from doesn't.
+    pub fn source_url(&self) -> &str {
+        self.page_url.as_deref().unwrap_or(&self.url)
+    }
+}
+
+/// Narrows `history_list`; unset fields match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryFilter {
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub success: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -51,20 +76,35 @@ fn schema(conn: &Connection) -> rusqlite::Result<()> {
             error TEXT,
             completed_at INTEGER NOT NULL,
             thumbnail_url TEXT,
-            kind TEXT
+            kind TEXT,
+            dismissed INTEGER NOT NULL DEFAULT 0,
+            page_url TEXT,
+            extra_files TEXT
         );
         CREATE INDEX IF NOT EXISTS idx_history_completed
             ON history (completed_at DESC, id DESC);",
-    )
+    )?;
+    // Older databases lack the later columns; the statements fail
+    // harmlessly everywhere else.
+    let _ = conn.execute(
+        "ALTER TABLE history ADD COLUMN dismissed INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute("ALTER TABLE history ADD COLUMN page_url TEXT", []);
+    let _ = conn.execute("ALTER TABLE history ADD COLUMN extra_files TEXT", []);
+    Ok(())
 }
 
 fn db_upsert(conn: &Connection, e: &HistoryEntry) -> rusqlite::Result<()> {
     let kind = e.kind.as_ref().and_then(|k| serde_json::to_string(k).ok());
+    let extra_files = (!e.extra_files.is_empty())
+        .then(|| serde_json::to_string(&e.extra_files).ok())
+        .flatten();
     conn.execute(
         "INSERT OR REPLACE INTO history
             (id, url, platform, title, file_path, file_size_bytes, total_bytes,
-             success, error, completed_at, thumbnail_url, kind)
-         VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12)",
+             success, error, completed_at, thumbnail_url, kind, page_url, extra_files)
+         VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14)",
         params![
             e.id as i64,
             e.url,
@@ -78,6 +118,8 @@ fn db_upsert(conn: &Connection, e: &HistoryEntry) -> rusqlite::Result<()> {
             e.completed_at,
             e.thumbnail_url,
             kind,
+            e.page_url,
+            extra_files,
         ],
     )?;
     conn.execute(
@@ -94,6 +136,7 @@ fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
     let total: Option<i64> = row.get(6)?;
     let success: i64 = row.get(7)?;
     let kind_text: Option<String> = row.get(11)?;
+    let extra_text: Option<String> = row.get(13)?;
     Ok(HistoryEntry {
         id: id as u64,
         url: row.get(1)?,
@@ -107,16 +150,66 @@ fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
         completed_at: row.get(9)?,
         thumbnail_url: row.get(10)?,
         kind: kind_text.and_then(|t| serde_json::from_str(&t).ok()),
+        page_url: row.get(12)?,
+        extra_files: extra_text
+            .and_then(|t| serde_json::from_str(&t).ok())
+            .unwrap_or_default(),
     })
 }
 
 fn db_list(conn: &Connection) -> rusqlite::Result<Vec<HistoryEntry>> {
     let mut stmt = conn.prepare(
         "SELECT id, url, platform, title, file_path, file_size_bytes, total_bytes,
-                success, error, completed_at, thumbnail_url, kind
-         FROM history ORDER BY completed_at DESC, id DESC",
+                success, error, completed_at, thumbnail_url, kind, page_url,
+                extra_files
+         FROM history WHERE dismissed = 0
+         ORDER BY completed_at DESC, id DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map(params![QUEUE_RESTORE_LIMIT as i64], row_to_entry)?;
+    rows.collect()
+}
+
+/// Escapes `LIKE` wildcards so a search matches `query` literally.
+fn like_pattern(query: &str) -> String {
+    let escaped = query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+fn db_query(
+    conn: &Connection,
+    filter: &HistoryFilter,
+    search: Option<&str>,
+    limit: u32,
+    offset: u32,
+) -> rusqlite::Result<Vec<HistoryEntry>> {
+    let pattern = search
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(like_pattern);
+    let mut stmt = conn.prepare(
+        "SELECT id, url, platform, title, file_path, file_size_bytes, total_bytes,
+                success, error, completed_at, thumbnail_url, kind, page_url,
+                extra_files
+         FROM history
+         WHERE (?1 IS NULL OR platform = ?1)
+           AND (?2 IS NULL OR success = ?2)
+           AND (?3 IS NULL OR title LIKE ?3 ESCAPE '\\' OR url LIKE ?3 ESCAPE '\\'
+                OR file_path LIKE ?3 ESCAPE '\\')
+         ORDER BY completed_at DESC, id DESC LIMIT ?4 OFFSET ?5",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            filter.platform,
+            filter.success.map(|s| s as i64),
+            pattern,
+            limit as i64,
+            offset as i64,
+        ],
+        row_to_entry,
     )?;
-    let rows = stmt.query_map([], row_to_entry)?;
     rows.collect()
 }
 
@@ -156,13 +249,43 @@ pub fn record(entry: HistoryEntry) {
     db::with_conn(|c| db_upsert(c, &entry));
 }
 
+/// Recent entries still shown in the queue, newest first.
 pub fn list() -> Vec<HistoryEntry> {
     db::with_conn(db_list).unwrap_or_default()
 }
 
-pub fn remove(id: u64) {
+pub fn get(id: u64) -> Option<HistoryEntry> {
     db::with_conn(|c| {
-        c.execute("DELETE FROM history WHERE id = ?1", params![id as i64])?;
+        c.query_row(
+            "SELECT id, url, platform, title, file_path, file_size_bytes, total_bytes,
+                    success, error, completed_at, thumbnail_url, kind, page_url,
+                    extra_files
+             FROM history WHERE id = ?1",
+            params![id as i64],
+            row_to_entry,
+        )
+        .optional()
+    })
+    .flatten()
+}
+
+/// A page of the full history, including entries removed from the queue.
+pub fn query(
+    filter: &HistoryFilter,
+    search: Option<&str>,
+    limit: u32,
+    offset: u32,
+) -> Vec<HistoryEntry> {
+    db::with_conn(|c| db_query(c, filter, search, limit, offset)).unwrap_or_default()
+}
+
+/// Keeps the entry in the history but stops restoring it into the queue.
+pub fn dismiss(id: u64) {
+    db::with_conn(|c| {
+        c.execute(
+            "UPDATE history SET dismissed = 1 WHERE id = ?1",
+            params![id as i64],
+        )?;
         Ok(())
     });
 }
@@ -199,6 +322,8 @@ mod tests {
             completed_at,
             thumbnail_url: None,
             kind: Some(QueueKind::Video),
+            page_url: None,
+            extra_files: Vec::new(),
         }
     }
 
@@ -220,6 +345,30 @@ mod tests {
         assert_eq!(list[0].kind, Some(QueueKind::Video));
     }
 
+    #[test]
+    fn source_url_prefers_the_page() {
+        let c = conn();
+        let mut e = mk(1, 100);
+        e.url = "https://cdn.test/v.m3u8?sig=abc".into();
+        e.page_url = Some("https://site.test/watch/1".into());
+        db_upsert(&c, &e).unwrap();
+        let stored = db_list(&c).unwrap().remove(0);
+        assert_eq!(stored.source_url(), "https://site.test/watch/1");
+        assert_eq!(mk(2, 200).source_url(), "https://x.test/2");
+    }
+
+    #[test]
+    fn extra_files_round_trip() {
+        let c = conn();
+        let mut e = mk(1, 100);
+        e.extra_files = vec!["/tmp/1-2.jpg".into(), "/tmp/1.en.vtt".into()];
+        db_upsert(&c, &e).unwrap();
+        db_upsert(&c, &mk(2, 200)).unwrap();
+        let list = db_list(&c).unwrap();
+        assert!(list[0].extra_files.is_empty());
+        assert_eq!(list[1].extra_files, e.extra_files);
+    }
+
     #[test]
     fn upsert_replaces_same_id() {
         let c = conn();
@@ -238,11 +387,63 @@ mod tests {
         for i in 0..(MAX_HISTORY_ENTRIES as u64 + 25) {
             db_upsert(&c, &mk(i + 1, i as i64)).unwrap();
         }
+        let count: i64 = c
+            .query_row("SELECT COUNT(*) FROM history", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count as usize, MAX_HISTORY_ENTRIES);
         let list = db_list(&c).unwrap();
-        assert_eq!(list.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(list.len(), QUEUE_RESTORE_LIMIT);
         assert_eq!(list[0].id, MAX_HISTORY_ENTRIES as u64 + 25);
     }
 
+    #[test]
+    fn query_filters_searches_and_keeps_dismissed() {
+        let c = conn();
+        db_upsert(&c, &mk(1, 100)).unwrap();
+        let mut failed = mk(2, 200);
+        failed.success = false;
+        failed.platform = "vimeo".into();
+        db_upsert(&c, &failed).unwrap();
+        let mut odd = mk(3, 300);
+        odd.title = "100% real_clip".into();
+        db_upsert(&c, &odd).unwrap();
+        c.execute("UPDATE history SET dismissed = 1 WHERE id = 1", [])
+            .unwrap();
+
+        assert_eq!(db_list(&c).unwrap().len(), 2);
+        let all = db_query(&c, &HistoryFilter::default(), None, 10, 0).unwrap();
+        assert_eq!(all.len(), 3);
+
+        let ok = HistoryFilter {
+            success: Some(true),
+            ..Default::default()
+        };
+        let ids: Vec<u64> = db_query(&c, &ok, None, 10, 0)
+            .unwrap()
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec![3, 1]);
+
+        let vimeo = HistoryFilter {
+            platform: Some("vimeo".into()),
+            ..Default::default()
+        };
+        assert_eq!(db_query(&c, &vimeo, None, 10, 0).unwrap()[0].id, 2);
+
+        let hits = db_query(&c, &HistoryFilter::default(), Some("0% real_"), 10, 0).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, 3);
+        assert!(
+            db_query(&c, &HistoryFilter::default(), Some("0%_real"), 10, 0)
+                .unwrap()
+                .is_empty()
+        );
+
+        let page = db_query(&c, &HistoryFilter::default(), None, 1, 1).unwrap();
+        assert_eq!(page[0].id, 2);
+    }
+
     #[test]
     fn import_legacy_json_round_trips() {
         let c = conn();
@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+
+/// 429s within `WINDOW_MS` that trip the breaker.
+const THRESHOLD: u64 = 5;
+const WINDOW_MS: u64 = 5 * 60 * 1000;
+/// How long without a new 429 before the queue goes back to normal.
+const QUIET_MS: u64 = 10 * 60 * 1000;
+/// Gap between starts while tripped.
+pub const COOLDOWN_MS: u64 = 30 * 1000;
+
+/// Slows the whole queue down when servers keep answering 429: one
+/// download at a time, with a cooldown before each start. It watches the
+/// session's running 429 total, so every backend that reports to
+/// `stats::session()` counts.
+#[derive(Debug, Default)]
+pub struct RateBreaker {
+    /// When new 429s were seen, and how many.
+    recent: VecDeque<(u64, u64)>,
+    last_total: u64,
+    last_429_ms: u64,
+    tripped: bool,
+    /// When the queue last started a download.
+    last_start_ms: u64,
+    /// Whether a wake-up for the end of the cooldown is already scheduled.
+    wake_pending: bool,
+}
+
+impl RateBreaker {
+    /// Takes the session's 429 total at `now_ms` and returns whether the
+    /// breaker tripped or recovered because of it.
+    pub fn observe(&mut self, now_ms: u64, total_429: u64) -> bool {
+        if total_429 > self.last_total {
+            self.recent.push_back((now_ms, total_429 - self.last_total));
+            self.last_429_ms = now_ms;
+        }
+        self.last_total = total_429;
+        while self
+            .recent
+            .front()
+            .is_some_and(|(at, _)| now_ms.saturating_sub(*at) > WINDOW_MS)
+        {
+            self.recent.pop_front();
+        }
+
+        if !self.tripped && self.recent.iter().map(|(_, n)| n).sum::<u64>() >= THRESHOLD {
+            self.tripped = true;
+            tracing::warn!("[queue] sustained 429s, slowing down to one download at a time");
+            return true;
+        }
+        if self.tripped && now_ms.saturating_sub(self.last_429_ms) >= QUIET_MS {
+            self.tripped = false;
+            self.recent.clear();
+            tracing::info!("[queue] no 429s for a while, back to normal concurrency");
+            return true;
+        }
+        false
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped
+    }
+
+    pub fn record_start(&mut self, now_ms: u64) {
+        self.last_start_ms = now_ms;
+    }
+
+    /// How long the next start still has to wait: what is left of the
+    /// cooldown since the last start while tripped, else zero. A queue that
+    /// hasn't started anything for that long starts right away.
+    pub fn cooldown_left_ms(&self, now_ms: u64) -> u64 {
+        if !self.tripped {
+            return 0;
+        }
+        COOLDOWN_MS.saturating_sub(now_ms.saturating_sub(self.last_start_ms))
+    }
+
+    /// Claims the wake-up for the end of the cooldown. False when one is
+    /// already scheduled.
+    pub fn schedule_wake(&mut self) -> bool {
+        !std::mem::replace(&mut self.wake_pending, true)
+    }
+
+    pub fn wake_done(&mut self) {
+        self.wake_pending = false;
+    }
+
+    /// `max_concurrent` as it applies right now.
+    pub fn limit(&self, max_concurrent: u32) -> u32 {
+        if self.tripped {
+            1
+        } else {
+            max_concurrent
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trips_on_a_burst_and_recovers_after_quiet() {
+        let mut breaker = RateBreaker::default();
+        assert!(!breaker.observe(0, 2));
+        assert_eq!(breaker.limit(4), 4);
+        assert!(breaker.observe(60_000, 5));
+        assert!(breaker.is_tripped());
+        assert_eq!(breaker.limit(4), 1);
+
+        assert!(!breaker.observe(60_000 + QUIET_MS - 1, 5));
+        assert!(breaker.observe(60_000 + QUIET_MS, 5));
+        assert_eq!(breaker.limit(4), 4);
+    }
+
+    #[test]
+    fn cooldown_only_follows_a_recent_start_while_tripped() {
+        let mut breaker = RateBreaker::default();
+        breaker.record_start(1_000);
+        assert_eq!(breaker.cooldown_left_ms(2_000), 0);
+
+        breaker.observe(1_000, THRESHOLD);
+        assert!(breaker.is_tripped());
+        assert_eq!(breaker.cooldown_left_ms(11_000), COOLDOWN_MS - 10_000);
+        assert_eq!(breaker.cooldown_left_ms(1_000 + COOLDOWN_MS), 0);
+
+        assert!(breaker.schedule_wake());
+        assert!(!breaker.schedule_wake());
+        breaker.wake_done();
+        assert!(breaker.schedule_wake());
+    }
+
+    #[test]
+    fn spread_out_429s_do_not_trip() {
+        let mut breaker = RateBreaker::default();
+        for i in 1..=10 {
+            breaker.observe(i * (WINDOW_MS / 2 + 1), i);
+        }
+        assert!(!breaker.is_tripped());
+    }
+}
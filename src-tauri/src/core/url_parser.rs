@@ -43,6 +43,10 @@ pub fn parse_url(url_str: &str) -> Option<ParsedUrl> {
         Platform::Vimeo => parse_vimeo(&segments),
         Platform::Udemy => parse_udemy(&segments),
         Platform::Bilibili => parse_bilibili(&segments),
+        Platform::Kick => parse_kick(&parsed, &segments),
+        Platform::Dailymotion => parse_dailymotion(&parsed, &segments),
+        Platform::SoundCloud => parse_soundcloud(&segments),
+        Platform::Facebook => parse_facebook(&parsed, &segments),
         Platform::Other(ref name) => match name.as_str() {
             "douyin" => parse_douyin(&segments),
             "tencentvideo" => parse_tencent(&segments),
@@ -282,6 +286,72 @@ fn parse_bilibili(segments: &[&str]) -> (Option<String>, ParsedContentType) {
     (None, ParsedContentType::Unknown)
 }
 
+fn parse_kick(parsed: &url::Url, segments: &[&str]) -> (Option<String>, ParsedContentType) {
+    // kick.com/channel?clip=clip_01H...
+    if let Some(clip) = parsed.query_pairs().find(|(k, _)| k == "clip") {
+        return (Some(clip.1.to_string()), ParsedContentType::Clip);
+    }
+
+    match segments {
+        ["video", id, ..] | [_, "videos", id, ..] => {
+            (Some(id.to_string()), ParsedContentType::Video)
+        }
+        [_, "clips", id, ..] => (Some(id.to_string()), ParsedContentType::Clip),
+        [channel] => (Some(channel.to_string()), ParsedContentType::Profile),
+        _ => (None, ParsedContentType::Unknown),
+    }
+}
+
+fn parse_dailymotion(parsed: &url::Url, segments: &[&str]) -> (Option<String>, ParsedContentType) {
+    if parsed.host_str() == Some("dai.ly") {
+        let id = segments.first().map(|s| s.to_string());
+        return (id, ParsedContentType::Video);
+    }
+
+    match segments {
+        // Older links append the slug: /video/x7tgad0_title
+        ["video", id, ..] => {
+            let id = id.split('_').next().unwrap_or(id);
+            (Some(id.to_string()), ParsedContentType::Video)
+        }
+        ["playlist", id, ..] => (Some(id.to_string()), ParsedContentType::Playlist),
+        _ => (None, ParsedContentType::Unknown),
+    }
+}
+
+fn parse_soundcloud(segments: &[&str]) -> (Option<String>, ParsedContentType) {
+    match segments {
+        [user, "sets", set, ..] => (
+            Some(format!("{}/sets/{}", user, set)),
+            ParsedContentType::Playlist,
+        ),
+        [user, track, ..] if !["tracks", "albums", "likes", "reposts"].contains(track) => (
+            Some(format!("{}/{}", user, track)),
+            ParsedContentType::Audio,
+        ),
+        [user, ..] => (Some(user.to_string()), ParsedContentType::Profile),
+        _ => (None, ParsedContentType::Unknown),
+    }
+}
+
+fn parse_facebook(parsed: &url::Url, segments: &[&str]) -> (Option<String>, ParsedContentType) {
+    if let Some((_, v)) = parsed.query_pairs().find(|(k, _)| k == "v") {
+        return (Some(v.to_string()), ParsedContentType::Video);
+    }
+
+    match segments {
+        ["reel", id, ..] => (Some(id.to_string()), ParsedContentType::Reel),
+        ["watch", "live", ..] | ["watch"] => (None, ParsedContentType::Video),
+        [.., "videos", id] | [.., "videos", _, id] => {
+            (Some(id.to_string()), ParsedContentType::Video)
+        }
+        [page] if parsed.host_str() != Some("fb.watch") => {
+            (Some(page.to_string()), ParsedContentType::Profile)
+        }
+        _ => (None, ParsedContentType::Unknown),
+    }
+}
+
 fn parse_telegram(segments: &[&str]) -> (Option<String>, ParsedContentType) {
     if segments.len() >= 2 {
         let channel = segments[0].to_string();
@@ -92,24 +92,67 @@ pub async fn queue_url_with_defaults(
     url: String,
     from_hotkey: bool,
     download_mode: Option<String>,
+) -> Result<QueueUrlOutcome, String> {
+    queue_url_at(app, url, from_hotkey, download_mode, None, None).await
+}
+
+/// `queue_url_with_defaults` for one URL, remembering where it sits in the
+/// collection it was expanded from and tagging it with the collection.
+async fn queue_url_at(
+    app: &AppHandle,
+    url: String,
+    from_hotkey: bool,
+    download_mode: Option<String>,
+    playlist_position: Option<queue::PlaylistPosition>,
+    batch_tag: Option<String>,
 ) -> Result<QueueUrlOutcome, String> {
     let state = app.state::<AppState>();
     let settings = config::load_settings(app);
     let download_queue = state.download_queue.clone();
+    let (url, downloader) = state.registry.find_platform_resolved(&url).await;
 
     {
         let mut q = download_queue.lock().await;
         q.max_concurrent = settings.advanced.max_concurrent_downloads.max(1);
         q.stagger_delay_ms = settings.advanced.stagger_delay_ms;
+        q.max_per_host = settings.advanced.max_downloads_per_host.max(1);
+        q.allowed_hours = settings.advanced.allowed_hours;
+        q.default_max_retries = settings.advanced.max_retries;
+        q.default_retry_base_delay_secs = settings.advanced.retry_base_delay_secs;
+        q.overwrite_policy = queue::overwrite_policy_for(&settings.download);
         if q.has_url(&url) {
             return Ok(QueueUrlOutcome::AlreadyQueued);
         }
     }
 
-    let downloader = state
-        .registry
-        .find_platform(&url)
-        .ok_or_else(|| "No downloader available for this URL".to_string())?;
+    let downloader =
+        downloader.ok_or_else(|| "No downloader available for this URL".to_string())?;
+
+    if let Some(item_urls) = downloader.expand(&url).await.map_err(|e| e.to_string())? {
+        let mut outcome = QueueUrlOutcome::AlreadyQueued;
+        let count = item_urls.len() as u32;
+        let batch_tag = queue::batch_tag_for(&url);
+        for (i, item_url) in item_urls.into_iter().enumerate() {
+            match Box::pin(queue_url_at(
+                app,
+                item_url,
+                from_hotkey,
+                download_mode.clone(),
+                Some(queue::PlaylistPosition {
+                    index: i as u32 + 1,
+                    count,
+                }),
+                Some(batch_tag.clone()),
+            ))
+            .await
+            {
+                Ok(QueueUrlOutcome::Queued) => outcome = QueueUrlOutcome::Queued,
+                Ok(QueueUrlOutcome::AlreadyQueued) => {}
+                Err(e) => tracing::warn!("[external] skipping collection item: {}", e),
+            }
+        }
+        return Ok(outcome);
+    }
 
     let platform = Platform::from_url(&url);
     let platform_name = platform
@@ -201,13 +244,14 @@ pub async fn queue_url_with_defaults(
                 height: 0,
                 url: url.clone(),
                 format: format.to_string(),
+                ..Default::default()
             }],
             media_type: if format == "direct_audio" {
                 crate::models::media::MediaType::Audio
             } else {
                 crate::models::media::MediaType::Video
             },
-            file_size_bytes: None,
+            ..Default::default()
         })
     });
 
@@ -230,16 +274,16 @@ pub async fn queue_url_with_defaults(
             thumbnail_url: ext_thumbnail.clone(),
             available_qualities: Vec::new(),
             media_type: crate::models::media::MediaType::Video,
-            file_size_bytes: None,
+            ..Default::default()
         })
     });
 
     let queue_title = ext_title.clone().unwrap_or_else(|| url.clone());
 
-    {
+    let status = {
         let mut q = download_queue.lock().await;
         download_id = q.next_available_id(download_id);
-        q.enqueue(
+        let status = q.enqueue(
             download_id,
             url.clone(),
             platform_name,
@@ -264,6 +308,12 @@ pub async fn queue_url_with_defaults(
             None,
             None,
         );
+        if let Some(position) = playlist_position {
+            q.set_playlist_position(download_id, position);
+        }
+        if let Some(tag) = &batch_tag {
+            q.add_tag(download_id, tag);
+        }
 
         let next_ids = q.next_queued_ids();
         for nid in &next_ids {
@@ -272,7 +322,12 @@ pub async fn queue_url_with_defaults(
         let state = q.get_state();
         drop(q);
         emit_queue_state_from_state(app, state);
+        status
+    };
+    if matches!(status, queue::QueueStatus::DuplicateSkipped { .. }) {
+        return Ok(QueueUrlOutcome::AlreadyQueued);
     }
+    queue::prefetch_queued_info(app.clone(), download_queue.clone());
 
     let q_clone = download_queue.clone();
     let app_clone = app.clone();
@@ -1,6 +1,7 @@
 use tauri::Emitter;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use tauri_plugin_notification::NotificationExt;
 
 use crate::storage::config;
 
@@ -69,12 +70,12 @@ pub fn on_hotkey_pressed(app: &tauri::AppHandle, shortcut: &Shortcut) {
     }
 
     if settings.download.music_hotkey_enabled && music_match {
-        handle_music_clipboard(app);
+        handle_clipboard(app, Some("audio".to_string()));
         return;
     }
 
     if settings.download.hotkey_enabled && download_match {
-        handle_download_clipboard(app);
+        handle_clipboard(app, None);
     }
 }
 
@@ -85,62 +86,58 @@ fn matches_binding(pressed: &Shortcut, binding: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn handle_download_clipboard(app: &tauri::AppHandle) {
-    let text = match app.clipboard().read_text() {
-        Ok(t) => t,
-        Err(_) => return,
-    };
-
-    let text = text.trim().to_string();
-    if text.is_empty() || (!text.starts_with("http://") && !text.starts_with("https://")) {
-        return;
-    }
-
-    if url::Url::parse(&text).is_err() {
+/// Queues the URL on the clipboard with the platform defaults, or as
+/// audio for the music hotkey, and reports the outcome as a notification
+/// since the window is usually hidden when a global hotkey fires.
+fn handle_clipboard(app: &tauri::AppHandle, download_mode: Option<String>) {
+    let language = config::load_settings(app).appearance.language;
+    let text = app.clipboard().read_text().unwrap_or_default();
+    let Some(url) = clipboard_url(&text) else {
+        notify(
+            app,
+            localized(&language, "hotkey_no_link"),
+            localized(&language, "hotkey_no_link_body"),
+        );
         return;
-    }
+    };
 
     let app = app.clone();
     tauri::async_runtime::spawn(async move {
-        enqueue_from_clipboard(&app, text).await;
+        match crate::external_url::queue_url_with_defaults(&app, url.clone(), true, download_mode)
+            .await
+        {
+            Ok(crate::external_url::QueueUrlOutcome::Queued) => {
+                let _ = app.emit("hotkey-download-queued", serde_json::json!({ "url": url }));
+                notify(&app, localized(&language, "hotkey_queued"), &url);
+            }
+            Ok(crate::external_url::QueueUrlOutcome::AlreadyQueued) => {
+                notify(&app, localized(&language, "hotkey_already_queued"), &url);
+            }
+            Err(e) => {
+                tracing::warn!("[hotkey] could not queue {}: {}", url, e);
+                notify(
+                    &app,
+                    localized(&language, "hotkey_failed"),
+                    &format!("{}\n{}", url, e),
+                );
+            }
+        }
     });
 }
 
-async fn enqueue_from_clipboard(app: &tauri::AppHandle, url: String) {
-    if matches!(
-        crate::external_url::queue_url_with_defaults(app, url.clone(), true, None).await,
-        Ok(crate::external_url::QueueUrlOutcome::Queued)
-    ) {
-        let _ = app.emit("hotkey-download-queued", serde_json::json!({ "url": url }));
-    }
+/// The clipboard text as an http(s) URL, if that's what it is.
+fn clipboard_url(text: &str) -> Option<String> {
+    let text = text.trim();
+    let parsed = url::Url::parse(text).ok()?;
+    matches!(parsed.scheme(), "http" | "https").then(|| text.to_string())
 }
 
-fn handle_music_clipboard(app: &tauri::AppHandle) {
-    let text = match app.clipboard().read_text() {
-        Ok(t) => t,
-        Err(_) => return,
-    };
-    let text = text.trim().to_string();
-    if text.is_empty() || (!text.starts_with("http://") && !text.starts_with("https://")) {
-        return;
-    }
-    if url::Url::parse(&text).is_err() {
-        return;
-    }
+fn localized(language: &str, key: &str) -> &'static str {
+    crate::core::i18n::notification_text(language, key).unwrap_or_default()
+}
 
-    let app = app.clone();
-    tauri::async_runtime::spawn(async move {
-        if matches!(
-            crate::external_url::queue_url_with_defaults(
-                &app,
-                text.clone(),
-                true,
-                Some("audio".to_string()),
-            )
-            .await,
-            Ok(crate::external_url::QueueUrlOutcome::Queued)
-        ) {
-            let _ = app.emit("hotkey-download-queued", serde_json::json!({ "url": text }));
-        }
-    });
+fn notify(app: &tauri::AppHandle, title: &str, body: &str) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        tracing::warn!("[hotkey] notification failed: {}", e);
+    }
 }
@@ -17,6 +17,7 @@ pub mod core;
 pub mod extension_storage;
 pub mod external_url;
 pub mod hotkey;
+pub mod local_api;
 pub mod local_bridge;
 pub mod models;
 pub mod platforms;
@@ -33,13 +34,27 @@ pub struct AppState {
     pub download_queue: Arc<tokio::sync::Mutex<core::queue::DownloadQueue>>,
     pub torrent_session: Arc<tokio::sync::Mutex<Option<Arc<librqbit::Session>>>>,
     pub active_p2p_sends: ActiveP2pSends,
+    /// Cancellation tokens of running conversion batches, by batch id.
+    pub active_conversions: Arc<tokio::sync::Mutex<HashMap<u64, CancellationToken>>>,
     pub frontend_ready: Arc<tokio::sync::Mutex<bool>>,
     pub pending_external_events: Arc<tokio::sync::Mutex<Vec<external_url::ExternalUrlEvent>>>,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tracing_subscriber::fmt::init();
+    let _log_guard = core::logging::init();
+
+    // Platform downloaders build their HTTP clients up front, so the proxy,
+    // timeouts and header profiles have to be known before the registry is
+    // populated.
+    let startup_settings = storage::config::load_settings_standalone();
+    core::http_client::init_proxy(startup_settings.proxy);
+    core::http_client::set_timeouts(
+        startup_settings.download.http_timeout_secs,
+        startup_settings.download.http_connect_timeout_secs,
+    );
+    core::http_client::init_header_profiles(startup_settings.advanced.header_profiles);
+    core::paths::ensure_default_output_dir(&startup_settings.download.default_output_dir);
 
     let mut registry = core::registry::PlatformRegistry::new();
     registry.register(Arc::new(platforms::instagram::InstagramDownloader::new()));
@@ -53,6 +68,12 @@ pub fn run() {
     registry.register(Arc::new(platforms::vimeo::VimeoDownloader::new()));
     registry.register(Arc::new(platforms::bilibili::BilibiliDownloader::new()));
     registry.register(Arc::new(platforms::douyin::DouyinDownloader::new()));
+    registry.register(Arc::new(platforms::soundcloud::SoundCloudDownloader::new()));
+    registry.register(Arc::new(platforms::facebook::FacebookDownloader::new()));
+    registry.register(Arc::new(platforms::ytdlp_site::YtdlpSiteDownloader::kick()));
+    registry.register(Arc::new(
+        platforms::ytdlp_site::YtdlpSiteDownloader::dailymotion(),
+    ));
     let torrent_session: Arc<tokio::sync::Mutex<Option<Arc<librqbit::Session>>>> =
         Arc::new(tokio::sync::Mutex::new(None));
     registry.register(Arc::new(platforms::magnet::MagnetDownloader::new(
@@ -72,6 +93,7 @@ pub fn run() {
         download_queue: Arc::new(tokio::sync::Mutex::new(core::queue::DownloadQueue::new(2))),
         torrent_session,
         active_p2p_sends: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+        active_conversions: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
         frontend_ready: Arc::new(tokio::sync::Mutex::new(false)),
         pending_external_events: Arc::new(tokio::sync::Mutex::new(Vec::new())),
     };
@@ -139,9 +161,25 @@ pub fn run() {
             }
             let settings = storage::config::load_settings(app.handle());
             core::http_client::init_proxy(settings.proxy.clone());
+            omniget_core::platforms::set_backend_preferences(
+                settings.download.platform_backend.clone(),
+            );
             core::http_fetcher::set_global_max_concurrent_segments(
                 settings.advanced.max_concurrent_segments as usize,
             );
+            core::direct_downloader::set_global_speed_limit_kbps(
+                settings.download.effective_speed_limit_kbps(),
+            );
+            core::direct_downloader::set_global_use_aria2c(settings.advanced.native_aria2c);
+            core::http_client::set_timeouts(
+                settings.download.http_timeout_secs,
+                settings.download.http_connect_timeout_secs,
+            );
+            core::direct_downloader::set_global_max_retries(settings.download.http_max_retries);
+            core::direct_downloader::set_global_download_timeout_mins(
+                settings.download.download_timeout_mins,
+            );
+            core::temp_dir::set_global_temp_dir(settings.download.temp_dir.clone());
             core::ytdlp::set_per_domain_cookie_fn(|url| {
                 let parsed = url::Url::parse(url).ok()?;
                 let host = parsed.host_str()?;
@@ -203,6 +241,11 @@ pub fn run() {
             core::ytdlp::set_keep_vtt_fn(|| {
                 storage::config::load_settings_standalone().download.keep_vtt
             });
+            core::ytdlp::set_convert_keep_original_fn(|| {
+                storage::config::load_settings_standalone()
+                    .download
+                    .convert_keep_original
+            });
             core::ytdlp::set_translate_metadata_fn(|| {
                 let s = storage::config::load_settings_standalone();
                 if s.download.translate_metadata {
@@ -247,26 +290,31 @@ pub fn run() {
                     .embed_thumbnail
             });
             core::ytdlp::set_speed_limit_fn(|| {
-                let v = storage::config::load_settings_standalone()
+                storage::config::load_settings_standalone()
                     .download
-                    .speed_limit;
-                let t = v.trim();
-                if t.is_empty() {
-                    None
-                } else {
-                    Some(t.to_string())
-                }
+                    .effective_speed_limit_kbps()
+                    .map(|kbps| format!("{}K", kbps))
             });
             core::ytdlp::set_live_from_start_fn(|| {
                 storage::config::load_settings_standalone()
                     .download
                     .live_from_start
             });
+            core::ytdlp::set_record_live_fn(|| {
+                storage::config::load_settings_standalone()
+                    .download
+                    .record_live
+            });
             core::ytdlp::set_concurrent_fragments_fn(|| {
                 storage::config::load_settings_standalone()
                     .advanced
                     .concurrent_fragments
             });
+            core::ytdlp::set_ffmpeg_missing_policy_fn(|| {
+                storage::config::load_settings_standalone()
+                    .download
+                    .ffmpeg_missing_policy
+            });
             core::ytdlp::set_user_agent_fn(|| {
                 let v = storage::config::load_settings_standalone()
                     .advanced
@@ -291,10 +339,34 @@ pub fn run() {
                     }
                 }));
             }
+            {
+                // One consumer applies phases in the order they were
+                // reported, so a late `Extracting` can't overwrite `Muxing`.
+                let app_handle = app.handle().clone();
+                let (phase_tx, mut phase_rx) = tokio::sync::mpsc::unbounded_channel();
+                omniget_core::core::log_hook::set_phase_sink(std::sync::Arc::new(
+                    move |id, phase| {
+                        let _ = phase_tx.send((id, phase));
+                    },
+                ));
+                tauri::async_runtime::spawn(async move {
+                    while let Some((id, phase)) = phase_rx.recv().await {
+                        let state = app_handle.state::<AppState>();
+                        let snapshot = {
+                            let mut q = state.download_queue.lock().await;
+                            q.set_phase(id, phase).then(|| q.get_state())
+                        };
+                        if let Some(snapshot) = snapshot {
+                            core::queue::emit_queue_state_from_state(&app_handle, snapshot);
+                        }
+                    }
+                });
+            }
             core::recovery::init_from_disk();
             core::queue_history::init_from_disk();
             core::channels::init_from_disk();
             core::channel_poller::start(app.handle().clone());
+            core::clipboard_watch::start(app.handle().clone());
             core::queue::start_scheduler(app.handle().clone());
             {
                 let app_handle = app.handle().clone();
@@ -404,6 +476,12 @@ pub fn run() {
                     local_bridge::spawn(app_handle).await;
                 });
             }
+            {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    local_api::spawn(app_handle).await;
+                });
+            }
             {
                 let plugins_dir = core::paths::app_data_dir()
                     .unwrap_or_else(|| std::path::PathBuf::from("."))
@@ -523,12 +601,24 @@ pub fn run() {
             cookies::commands::cookies_test,
             commands::clip::clip_video,
             commands::reencode::reencode_video,
+            commands::extract_audio::list_audio_presets,
+            commands::extract_audio::extract_audio,
+            commands::convert_batch::convert_batch,
+            commands::convert_batch::cancel_conversion_batch,
+            commands::trim::trim_file,
+            commands::trim::cancel_trim,
+            commands::probe::probe_file,
+            commands::probe::verify_media_file,
             commands::diagnostics::get_hwaccel_info,
+            commands::diagnostics::run_diagnostics,
+            commands::diagnostics::get_recent_logs,
             commands::downloads::detect_platform,
             commands::downloads::check_cookie_error,
             commands::downloads::validate_output_path,
             commands::downloads::get_media_formats,
             commands::downloads::prefetch_media_info,
+            commands::downloads::get_platform_capabilities,
+            commands::downloads::list_supported_platforms,
             commands::downloads::download_from_url,
             commands::downloads::playlist_entries,
             commands::downloads::torrent_contents,
@@ -550,6 +640,9 @@ pub fn run() {
             commands::video_ops::video_op_run,
             commands::video_ops::detect_shot_changes,
             commands::video_ops::waveform_peaks,
+            commands::video_ops::generate_contact_sheet,
+            commands::video_ops::convert_to_gif,
+            commands::video_ops::convert_gif_to_mp4,
             commands::subtitle_ws::subtitle_load,
             commands::subtitle_ws::subtitle_save,
             commands::subtitle_ws::subtitle_translate,
@@ -557,6 +650,7 @@ pub fn run() {
             commands::downloads::metadata_fetch,
             commands::downloads::thumbnails_list,
             commands::downloads::thumbnail_save,
+            commands::downloads::download_thumbnail,
             commands::downloads::subtitles_list,
             commands::downloads::subtitles_save,
             commands::downloads::subtitles_merge,
@@ -572,10 +666,20 @@ pub fn run() {
             commands::downloads::pause_download,
             commands::downloads::resume_download,
             commands::downloads::pause_all_downloads,
+            commands::downloads::cancel_all_downloads,
             commands::downloads::resume_all_downloads,
             commands::downloads::reorder_queue,
+            commands::downloads::move_to_front,
+            commands::downloads::move_to_back,
+            commands::downloads::set_priority,
+            commands::downloads::schedule_download,
+            commands::downloads::get_queue_state,
+            commands::downloads::add_tag,
+            commands::downloads::remove_tag,
+            commands::downloads::repair_download,
             commands::downloads::retry_download,
             commands::downloads::remove_download,
+            commands::downloads::get_stats,
             commands::downloads::update_max_concurrent,
             commands::downloads::clear_finished_downloads,
             commands::downloads::get_download_log,
@@ -583,9 +687,12 @@ pub fn run() {
             commands::downloads::get_recovery_items,
             commands::downloads::discard_recovery,
             commands::downloads::restore_recovery,
-            commands::downloads::get_download_history,
-            commands::downloads::clear_download_history,
+            commands::downloads::history_list,
+            commands::downloads::history_search,
+            commands::downloads::history_redownload,
+            commands::downloads::history_clear,
             commands::downloads::reveal_file,
+            commands::downloads::open_file,
             commands::downloads::open_path_default,
             commands::host_queue::host_queue_enqueue_external,
             commands::host_queue::host_queue_report_progress,
@@ -594,6 +701,9 @@ pub fn run() {
             commands::settings::get_settings,
             commands::settings::update_settings,
             commands::settings::reset_settings,
+            commands::settings::export_state,
+            commands::settings::import_state,
+            commands::settings::get_recent_dirs,
             commands::settings::mark_onboarding_complete,
             commands::settings::mark_legal_acknowledged,
             commands::rpc::rpc_test_connection,
@@ -605,11 +715,14 @@ pub fn run() {
             commands::settings::bridge_open_pairing,
             commands::dependencies::check_dependencies,
             commands::dependencies::check_ytdlp_available,
+            commands::dependencies::ytdlp_version,
+            commands::dependencies::ytdlp_update,
             commands::dependencies::install_dependency,
             commands::dependencies::dependency_variants,
             commands::dependencies::dependency_install_dir,
             commands::dependencies::set_dependency_path,
             commands::search::search_videos,
+            commands::search::search,
             commands::plugins::list_plugins,
             commands::plugins::get_plugin_frontend_path,
             commands::plugins::set_plugin_enabled,
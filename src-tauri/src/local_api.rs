@@ -0,0 +1,258 @@
+//! Optional localhost HTTP API for scripting OmniGet from other tools.
+//!
+//! Off unless `settings.local_api.port` is set. Listens on `127.0.0.1` only
+//! and requires `Authorization: Bearer <settings.local_api.token>` on every
+//! request. Handlers call straight into the Tauri commands, so a scripted
+//! download behaves exactly like one started from the UI.
+//!
+//! Endpoints:
+//!
+//! * `POST /download` — `{ url, opts }`; queues the URL. `opts` mirrors the
+//!   optional arguments of `download_from_url`.
+//! * `GET /queue` — current queue items, as sent to the UI.
+//! * `POST /cancel/{id}` — cancels one queued or running download.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Request, State},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::local_bridge::{bind_with_reuse, check_bearer, generate_token};
+use crate::storage::config;
+use crate::AppState;
+
+#[derive(Clone)]
+struct ApiState {
+    app: AppHandle,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DownloadOpts {
+    /// Defaults to the configured download folder.
+    #[serde(default)]
+    output_dir: Option<String>,
+    #[serde(default)]
+    download_mode: Option<String>,
+    #[serde(default)]
+    quality: Option<String>,
+    #[serde(default)]
+    format_id: Option<String>,
+    #[serde(default)]
+    referer: Option<String>,
+    #[serde(default)]
+    cookie_slug: Option<String>,
+    #[serde(default)]
+    time_range: Option<String>,
+    /// Skip channel or playlist items already downloaded from this source.
+    #[serde(default)]
+    download_archive: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct DownloadRequest {
+    url: String,
+    #[serde(default)]
+    opts: DownloadOpts,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+/// Starts the API when a port is configured, generating and persisting a
+/// token first if there is none. Bind failures are logged; the app keeps
+/// running without the API.
+pub async fn spawn(app: AppHandle) {
+    let mut settings = config::load_settings(&app);
+    let Some(port) = settings.local_api.port else {
+        return;
+    };
+
+    if settings.local_api.token.trim().is_empty() {
+        settings.local_api.token = generate_token();
+        if let Err(error) = config::save_settings(&app, &settings) {
+            tracing::warn!("failed to persist local API token: {}", error);
+            return;
+        }
+    }
+
+    let listener = match bind_with_reuse(port) {
+        Ok(listener) => listener,
+        Err(error) => {
+            tracing::warn!("failed to bind local API on 127.0.0.1:{}: {}", port, error);
+            return;
+        }
+    };
+
+    let router = Router::new()
+        .route("/download", post(download))
+        .route("/queue", get(queue))
+        .route("/cancel/{id}", post(cancel))
+        .with_state(ApiState { app });
+    let router = with_auth(router, Arc::new(settings.local_api.token));
+
+    tracing::info!("local API listening on http://127.0.0.1:{port}");
+
+    tokio::spawn(async move {
+        if let Err(error) = axum::serve(listener, router).await {
+            tracing::warn!("local API stopped: {error}");
+        }
+    });
+}
+
+fn reply(status: StatusCode, message: Option<String>) -> Response {
+    let body = Json(ApiResponse {
+        ok: status.is_success(),
+        message,
+    });
+    (status, body).into_response()
+}
+
+/// Rejects every request without the bearer token before any handler or
+/// body extractor runs.
+fn with_auth(router: Router, token: Arc<String>) -> Router {
+    router.layer(middleware::from_fn_with_state(token, require_bearer))
+}
+
+async fn require_bearer(
+    State(token): State<Arc<String>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !check_bearer(request.headers(), &token) {
+        return reply(
+            StatusCode::UNAUTHORIZED,
+            Some("Invalid or missing bearer token".to_string()),
+        );
+    }
+    next.run(request).await
+}
+
+async fn download(State(state): State<ApiState>, Json(request): Json<DownloadRequest>) -> Response {
+    if !crate::external_url::is_external_url(&request.url) {
+        return reply(
+            StatusCode::BAD_REQUEST,
+            Some("The requested URL is invalid".to_string()),
+        );
+    }
+
+    let opts = request.opts;
+    let output_dir = opts.output_dir.unwrap_or_else(|| {
+        config::load_settings(&state.app)
+            .download
+            .default_output_dir
+            .to_string_lossy()
+            .into_owned()
+    });
+
+    let app_state = state.app.state::<AppState>();
+    match crate::commands::downloads::download_from_url(
+        state.app.clone(),
+        app_state,
+        request.url,
+        output_dir,
+        opts.download_mode,
+        opts.quality,
+        opts.format_id,
+        opts.referer,
+        opts.cookie_slug,
+        opts.time_range,
+        None,
+        None,
+        None,
+        None,
+        Some(opts.download_archive),
+    )
+    .await
+    {
+        Ok(started) => (StatusCode::OK, Json(started)).into_response(),
+        Err(message) => reply(StatusCode::BAD_REQUEST, Some(message)),
+    }
+}
+
+async fn queue(State(state): State<ApiState>) -> Response {
+    let app_state = state.app.state::<AppState>();
+    let items = app_state.download_queue.lock().await.get_state();
+    (StatusCode::OK, Json(items)).into_response()
+}
+
+async fn cancel(State(state): State<ApiState>, Path(id): Path<u64>) -> Response {
+    let app_state = state.app.state::<AppState>();
+    match crate::commands::downloads::cancel_generic_download(state.app.clone(), app_state, id)
+        .await
+    {
+        Ok(_) => reply(StatusCode::OK, None),
+        Err(message) => reply(StatusCode::NOT_FOUND, Some(message)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn serve_stub(token: &str) -> std::net::SocketAddr {
+        let router = Router::new().route("/queue", get(|| async { "ok" }));
+        let router = with_auth(router, Arc::new(token.to_string()));
+        let listener = bind_with_reuse(0).expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn listens_on_loopback_only() {
+        let listener = bind_with_reuse(0).expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        assert_eq!(addr.ip(), std::net::Ipv4Addr::LOCALHOST);
+    }
+
+    #[tokio::test]
+    async fn requests_without_the_token_are_rejected() {
+        let addr = serve_stub("secret").await;
+        let url = format!("http://{}/queue", addr);
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+        let missing = client.get(&url).send().await.unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let wrong = client.get(&url).bearer_auth("guess").send().await.unwrap();
+        assert_eq!(wrong.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let body: serde_json::Value = wrong.json().await.unwrap();
+        assert_eq!(body["ok"], false);
+
+        let right = client.get(&url).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(right.status(), reqwest::StatusCode::OK);
+    }
+
+    #[test]
+    fn download_request_parses_with_and_without_opts() {
+        let request: DownloadRequest = serde_json::from_str(
+            r#"{"url":"https://example.com/v","opts":{"quality":"720p","download_archive":true}}"#,
+        )
+        .unwrap();
+        assert_eq!(request.url, "https://example.com/v");
+        assert_eq!(request.opts.quality.as_deref(), Some("720p"));
+        assert!(request.opts.download_archive);
+        assert!(request.opts.output_dir.is_none());
+
+        let request: DownloadRequest =
+            serde_json::from_str(r#"{"url":"https://example.com/v"}"#).unwrap();
+        assert!(request.opts.quality.is_none());
+        assert!(!request.opts.download_archive);
+
+        assert!(serde_json::from_str::<DownloadRequest>(r#"{"opts":{}}"#).is_err());
+    }
+}
@@ -105,7 +105,7 @@ pub fn generate_token() -> String {
 /// the port can be re-bound immediately across app restarts (the previous
 /// socket stays in `TIME_WAIT` for ~60 s otherwise, which would otherwise
 /// kick us off our persisted port and force re-pairing).
-fn bind_with_reuse(port: u16) -> std::io::Result<TcpListener> {
+pub(crate) fn bind_with_reuse(port: u16) -> std::io::Result<TcpListener> {
     let addr: SocketAddr = ([127, 0, 0, 1], port).into();
     let socket = Socket::new(Domain::IPV4, Type::STREAM, Some(Protocol::TCP))?;
     socket.set_reuse_address(true)?;
@@ -293,7 +293,7 @@ fn unauthorized() -> Response {
     (StatusCode::UNAUTHORIZED, body).into_response()
 }
 
-fn check_bearer(headers: &HeaderMap, expected: &str) -> bool {
+pub(crate) fn check_bearer(headers: &HeaderMap, expected: &str) -> bool {
     let header = match headers.get("authorization") {
         Some(v) => v,
         None => return false,
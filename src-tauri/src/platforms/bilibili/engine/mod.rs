@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use omniget_core::core::{ffmpeg, temp_dir};
 use omniget_core::models::progress::ProgressUpdate;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
@@ -35,6 +36,8 @@ pub struct EngineOptions {
 pub struct EngineResult {
     pub final_path: PathBuf,
     pub bytes: u64,
+    /// Audio saved next to `final_path` because ffmpeg was missing.
+    pub separate_audio: Option<PathBuf>,
 }
 
 pub async fn run_single(
@@ -208,7 +211,10 @@ pub async fn run_single(
         std::fs::create_dir_all(parent).ok();
     }
 
-    if audio_result.is_some() {
+    // Without ffmpeg the streams are kept as `<name>.mp4` and
+    // `<name>_audio.m4a` instead of failing the download.
+    let mut separate_audio = None;
+    let final_path = if audio_result.is_some() && ffmpeg::is_ffmpeg_available().await {
         mux::mux(mux::MuxInputs {
             video: &temp_video,
             audio: &temp_audio,
@@ -216,10 +222,22 @@ pub async fn run_single(
             output: &final_path,
             container: opts.container,
         })
-        .await?;
+        .await?
+    } else if audio_result.is_some() {
+        let video_path = final_path.with_extension("mp4");
+        let audio_path = opts.output_dir.join(format!("{}_audio.m4a", opts.filename));
+        temp_dir::move_file(&temp_video, &video_path)
+            .await
+            .map_err(|_| BilibiliError::ContentUnavailable)?;
+        temp_dir::move_file(&temp_audio, &audio_path)
+            .await
+            .map_err(|_| BilibiliError::ContentUnavailable)?;
+        separate_audio = Some(audio_path);
+        video_path
     } else {
         std::fs::rename(&temp_video, &final_path).map_err(|_| BilibiliError::ContentUnavailable)?;
-    }
+        final_path
+    };
 
     if !opts.keep_streams {
         cleanup(&[&temp_video, &temp_audio, &temp_cover]);
@@ -297,7 +315,11 @@ pub async fn run_single(
     let _ = progress.send(ProgressUpdate::percent(100.0)).await;
 
     let _ = video_result;
-    Ok(EngineResult { final_path, bytes })
+    Ok(EngineResult {
+        final_path,
+        bytes,
+        separate_audio,
+    })
 }
 
 pub async fn run_parsed_content(
@@ -401,6 +423,7 @@ async fn run_progressive(
     Ok(EngineResult {
         final_path,
         bytes: result.bytes_written,
+        separate_audio: None,
     })
 }
 
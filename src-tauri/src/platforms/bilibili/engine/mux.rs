@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use omniget_core::core::process;
+use omniget_core::core::{process, temp_dir};
 
 use super::super::api::{BilibiliError, Result};
 
@@ -27,10 +27,13 @@ pub struct MuxInputs<'a> {
     pub container: Container,
 }
 
+/// Writes to a staging file and moves it to `inputs.output` only once
+/// ffmpeg has finished, so a cut-off mux never looks like a finished file.
 pub async fn mux(inputs: MuxInputs<'_>) -> Result<PathBuf> {
     if let Some(parent) = inputs.output.parent() {
         std::fs::create_dir_all(parent).ok();
     }
+    let staging = temp_dir::staging_for(inputs.output);
 
     let mut args: Vec<String> = Vec::new();
     args.push("-y".into());
@@ -73,18 +76,22 @@ pub async fn mux(inputs: MuxInputs<'_>) -> Result<PathBuf> {
         args.push("+faststart".into());
     }
 
-    args.push(inputs.output.to_string_lossy().into_owned());
+    args.push(staging.to_string_lossy().into_owned());
 
     let status = process::command("ffmpeg")
         .args(args.iter().map(|s| s.as_str()))
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
         .status()
-        .await
-        .map_err(|_| BilibiliError::ContentUnavailable)?;
+        .await;
 
-    if !status.success() {
+    let produced = std::fs::metadata(&staging).is_ok_and(|m| m.len() > 0);
+    if !status.is_ok_and(|s| s.success()) || !produced {
+        let _ = std::fs::remove_file(&staging);
         return Err(BilibiliError::ContentUnavailable);
     }
+    temp_dir::move_file(&staging, inputs.output)
+        .await
+        .map_err(|_| BilibiliError::ContentUnavailable)?;
     Ok(inputs.output.to_path_buf())
 }
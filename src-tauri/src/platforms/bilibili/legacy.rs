@@ -53,6 +53,7 @@ pub async fn get_media_info(url: &str) -> anyhow::Result<MediaInfo> {
                 height: 0,
                 url: e.url.clone(),
                 format: "mp4".to_string(),
+                ..Default::default()
             })
             .collect();
 
@@ -64,7 +65,7 @@ pub async fn get_media_info(url: &str) -> anyhow::Result<MediaInfo> {
             thumbnail_url: None,
             available_qualities: qualities,
             media_type: MediaType::Playlist,
-            file_size_bytes: None,
+            ..Default::default()
         });
     }
 
@@ -125,6 +126,7 @@ pub async fn get_media_info(url: &str) -> anyhow::Result<MediaInfo> {
             height,
             url: url.to_string(),
             format: "mp4".to_string(),
+            ..Default::default()
         });
     }
 
@@ -137,6 +139,7 @@ pub async fn get_media_info(url: &str) -> anyhow::Result<MediaInfo> {
             height: 0,
             url: url.to_string(),
             format: "mp4".to_string(),
+            ..Default::default()
         });
     }
 
@@ -156,7 +159,7 @@ pub async fn get_media_info(url: &str) -> anyhow::Result<MediaInfo> {
         } else {
             MediaType::Audio
         },
-        file_size_bytes: None,
+        ..Default::default()
     })
 }
 
@@ -211,6 +214,8 @@ pub async fn download(
         opts.download_subtitles,
         &extra,
         opts.audio_format.as_deref(),
+        &opts.quality_preference,
+        &opts.extra_ytdlp_args,
     )
     .await
 }
@@ -221,15 +226,27 @@ async fn download_playlist(
     progress: mpsc::Sender<ProgressUpdate>,
     ytdlp_path: &std::path::Path,
 ) -> anyhow::Result<DownloadResult> {
-    let total = info.available_qualities.len().max(1);
-    let mut last_result = DownloadResult {
-        file_path: opts.output_dir.clone(),
-        file_size_bytes: 0,
-        duration_seconds: 0.0,
-        torrent_id: None,
+    // In "only new" mode entries already in the archive are skipped, and
+    // each finished entry is recorded, since the queue only records the
+    // playlist URL.
+    let archive = opts.download_archive.as_deref();
+    let entries: Vec<&MediaVideoQuality> = match archive {
+        Some(archive) => {
+            let seen = crate::core::download_archive::load(archive);
+            info.available_qualities
+                .iter()
+                .filter(|e| !seen.contains(&crate::core::download_archive::entry_for(&e.url)))
+                .collect()
+        }
+        None => info.available_qualities.iter().collect(),
     };
+    if entries.is_empty() && archive.is_some() {
+        return Err(anyhow!("No new videos in this playlist"));
+    }
+    let total = entries.len().max(1);
+    let mut last_result = DownloadResult::new(opts.output_dir.clone(), 0);
 
-    for (i, quality) in info.available_qualities.iter().enumerate() {
+    for (i, quality) in entries.into_iter().enumerate() {
         if opts.cancel_token.is_cancelled() {
             return Err(anyhow!("Download cancelled"));
         }
@@ -266,13 +283,29 @@ async fn download_playlist(
             opts.download_subtitles,
             &extra,
             opts.audio_format.as_deref(),
+            &opts.quality_preference,
+            &opts.extra_ytdlp_args,
         )
         .await
         {
             Ok(result) => {
+                if let Some(archive) = archive {
+                    let entry = crate::core::download_archive::entry_for(&quality.url);
+                    if let Err(e) = crate::core::download_archive::record(archive, &entry) {
+                        tracing::warn!(
+                            "[bilibili] failed to record {} in the archive: {}",
+                            entry,
+                            e
+                        );
+                    }
+                }
                 last_result.file_size_bytes += result.file_size_bytes;
                 last_result.duration_seconds += result.duration_seconds;
-                last_result.file_path = result.file_path;
+                let previous = std::mem::replace(&mut last_result.file_path, result.file_path);
+                if previous != opts.output_dir {
+                    last_result.extra_files.push(previous);
+                }
+                last_result.extra_files.extend(result.extra_files);
             }
             Err(e) => {
                 tracing::error!("[bilibili] playlist item {} failed: {}", i + 1, e);
@@ -6,8 +6,10 @@ use anyhow::anyhow;
 use async_trait::async_trait;
 use tokio::sync::mpsc;
 
-use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType};
+use crate::core::errors::DownloadError;
+use crate::models::media::{DownloadNote, DownloadOptions, DownloadResult, MediaInfo, MediaType};
 use crate::platforms::traits::PlatformDownloader;
+use crate::platforms::url_matches_hosts;
 
 pub mod api;
 pub mod auth;
@@ -122,7 +124,7 @@ async fn api_engine_download(
         .map_err(|e| anyhow!("Failed to detect URL kind: {}", e.i18n_key()))?;
     let parsed = parser::parse(&client, &kind)
         .await
-        .map_err(|e| anyhow!("Failed to parse content: {}", e.i18n_key()))?;
+        .map_err(|e| api_error("Failed to parse content", e))?;
 
     let settings = crate::storage::config::load_settings_standalone();
     let container = mux::container_from_setting(&settings.download.bilibili_container);
@@ -182,20 +184,37 @@ async fn api_engine_download(
 
     let result = engine::run_parsed_content(&client, &parsed, &kind, &engine_opts, progress)
         .await
-        .map_err(|e| anyhow!("Engine failed: {}", e.i18n_key()))?;
+        .map_err(|e| api_error("Engine failed", e))?;
+    let audio_bytes = result
+        .separate_audio
+        .as_ref()
+        .and_then(|p| std::fs::metadata(p).ok())
+        .map_or(0, |m| m.len());
 
     Ok(DownloadResult {
-        file_path: result.final_path,
-        file_size_bytes: result.bytes,
         duration_seconds: parsed
             .items
             .first()
             .and_then(|i| i.duration_seconds)
             .unwrap_or(0.0),
-        torrent_id: None,
+        note: result
+            .separate_audio
+            .is_some()
+            .then_some(DownloadNote::FfmpegMissingSeparateTracks),
+        extra_files: result.separate_audio.into_iter().collect(),
+        ..DownloadResult::new(result.final_path, result.bytes + audio_bytes)
     })
 }
 
+/// Region blocks become `DownloadError::GeoBlocked` so the queue can retry
+/// through a proxy; other errors keep their i18n key.
+fn api_error(context: &str, e: api::BilibiliError) -> anyhow::Error {
+    match e {
+        api::BilibiliError::GeoBlocked => DownloadError::GeoBlocked { country_hint: None }.into(),
+        e => anyhow!("{}: {}", context, e.i18n_key()),
+    }
+}
+
 fn build_api_client(
     slug: Option<&str>,
     user_agent: Option<&str>,
@@ -247,15 +266,11 @@ impl PlatformDownloader for BilibiliDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host.contains("bilibili.com")
-                    || host.contains("bilibili.tv")
-                    || host == "b23.tv";
-            }
-        }
-        false
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["bilibili.com", "bilibili.tv", "b23.tv"]
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
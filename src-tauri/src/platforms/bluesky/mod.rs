@@ -4,9 +4,13 @@ use omniget_core::models::progress::ProgressUpdate;
 use tokio::sync::mpsc;
 
 use crate::core::direct_downloader;
+use crate::core::errors::DownloadError;
+use crate::core::ffmpeg;
+use crate::core::filename;
 use crate::core::hls_downloader::HlsDownloader;
 use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality};
-use crate::platforms::traits::PlatformDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
 
 const API_BASE: &str = "https://public.api.bsky.app/xrpc/app.bsky.feed.getPostThread";
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
@@ -23,6 +27,7 @@ impl Default for BlueskyDownloader {
 
 impl BlueskyDownloader {
     async fn fallback_ytdlp(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        omniget_core::platforms::check_ytdlp_fallback("bluesky")?;
         let ytdlp_path = crate::core::ytdlp::ensure_ytdlp().await?;
         let json = crate::core::ytdlp::get_video_info(&ytdlp_path, url, &[]).await?;
         crate::platforms::generic_ytdlp::GenericYtdlpDownloader::parse_video_info(&json)
@@ -44,6 +49,7 @@ impl BlueskyDownloader {
 
         match media {
             BlueskyMedia::Video { hls_url } => Ok(MediaInfo {
+                id: Some(post_id.clone()),
                 title: filename_base,
                 author: user,
                 platform: "bluesky".to_string(),
@@ -55,9 +61,10 @@ impl BlueskyDownloader {
                     height: 0,
                     url: hls_url,
                     format: "hls".to_string(),
+                    ..Default::default()
                 }],
                 media_type: MediaType::Video,
-                file_size_bytes: None,
+                ..Default::default()
             }),
             BlueskyMedia::Images { urls } => {
                 let media_type = if urls.len() == 1 {
@@ -74,9 +81,11 @@ impl BlueskyDownloader {
                         height: 0,
                         url: u.clone(),
                         format: "jpg".to_string(),
+                        ..Default::default()
                     })
                     .collect();
                 Ok(MediaInfo {
+                    id: Some(post_id.clone()),
                     title: filename_base,
                     author: user,
                     platform: "bluesky".to_string(),
@@ -84,10 +93,11 @@ impl BlueskyDownloader {
                     thumbnail_url: None,
                     available_qualities: qualities,
                     media_type,
-                    file_size_bytes: None,
+                    ..Default::default()
                 })
             }
             BlueskyMedia::Gif { url: gif_url } => Ok(MediaInfo {
+                id: Some(post_id.clone()),
                 title: filename_base,
                 author: user,
                 platform: "bluesky".to_string(),
@@ -99,18 +109,16 @@ impl BlueskyDownloader {
                     height: 0,
                     url: gif_url,
                     format: "gif".to_string(),
+                    ..Default::default()
                 }],
                 media_type: MediaType::Gif,
-                file_size_bytes: None,
+                ..Default::default()
             }),
         }
     }
 
     pub fn new() -> Self {
-        let client = crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
-            .user_agent(USER_AGENT)
-            .timeout(std::time::Duration::from_secs(120))
-            .connect_timeout(std::time::Duration::from_secs(15))
+        let client = crate::core::http_client::platform_client_builder("bluesky", USER_AGENT)
             .build()
             .unwrap_or_default();
         Self { client }
@@ -136,16 +144,18 @@ impl BlueskyDownloader {
         let response = self.client.get(&url).send().await?;
 
         if !response.status().is_success() {
-            return Err(anyhow!("Bluesky API retornou HTTP {}", response.status()));
+            return Err(DownloadError::from_status("Bluesky API", response.status()).into());
         }
 
         let json: serde_json::Value = response.json().await?;
 
         if let Some(error) = json.get("error").and_then(|e| e.as_str()) {
             return match error {
-                "NotFound" | "InternalServerError" => Err(anyhow!("Post not available")),
-                "InvalidRequest" => Err(anyhow!("Unsupported link")),
-                _ => Err(anyhow!("Erro da API: {}", error)),
+                "NotFound" | "InternalServerError" => Err(DownloadError::NotFound.into()),
+                "InvalidRequest" => {
+                    Err(DownloadError::Unsupported("Unsupported link".to_string()).into())
+                }
+                _ => Err(anyhow!("Bluesky API error: {}", error)),
             };
         }
 
@@ -220,13 +230,18 @@ impl PlatformDownloader for BlueskyDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host == "bsky.app" || host.ends_with(".bsky.app");
-            }
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["bsky.app"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_audio_only: true,
+            ..Capabilities::default()
         }
-        false
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
@@ -267,6 +282,8 @@ impl PlatformDownloader for BlueskyDownloader {
                     false,
                     &[],
                     opts.audio_format.as_deref(),
+                    &opts.quality_preference,
+                    &opts.extra_ytdlp_args,
                 )
                 .await;
             }
@@ -280,8 +297,11 @@ impl PlatformDownloader for BlueskyDownloader {
                     .ok_or_else(|| anyhow!("No HLS URL available"))?
                     .url;
 
-                let filename = format!("{}.mp4", sanitize_filename::sanitize(&info.title));
-                let output_path = opts.output_dir.join(&filename);
+                let filename = opts.media_filename(info, "mp4");
+                let output_path = match opts.output_path(&filename) {
+                    Ok(path) => path,
+                    Err(skipped) => return Ok(skipped),
+                };
                 let output_str = output_path.to_string_lossy().to_string();
 
                 let downloader =
@@ -295,38 +315,58 @@ impl PlatformDownloader for BlueskyDownloader {
                         "https://bsky.app",
                         None,
                         opts.cancel_token.clone(),
-                        20,
-                        3,
+                        opts.hls_max_concurrent_segments,
+                        opts.hls_max_retries,
                     )
                     .await?;
 
+                let (file_path, file_size, note) = if opts.is_mute() {
+                    let (size, note) =
+                        ffmpeg::mute_downloaded(&result.path, result.file_size).await?;
+                    (result.path, size, note)
+                } else if opts.is_audio() {
+                    ffmpeg::audio_from_downloaded(
+                        &result.path,
+                        result.file_size,
+                        opts.audio_format.as_deref(),
+                        opts.cancel_token.clone(),
+                    )
+                    .await?
+                } else {
+                    (result.path, result.file_size, None)
+                };
+
                 let _ = progress.send(ProgressUpdate::percent(100.0)).await;
 
                 Ok(DownloadResult {
-                    file_path: result.path,
-                    file_size_bytes: result.file_size,
-                    duration_seconds: 0.0,
-                    torrent_id: None,
+                    note,
+                    ..DownloadResult::new(file_path, file_size)
                 })
             }
             MediaType::Photo | MediaType::Carousel => {
                 let mut total_bytes = 0u64;
                 let count = info.available_qualities.len();
-                let mut last_path = opts.output_dir.clone();
+                let mut saved = Vec::new();
+                let mut already_saved = None;
 
                 for (i, quality) in info.available_qualities.iter().enumerate() {
+                    if !opts.is_selected(i) {
+                        continue;
+                    }
                     let ext = &quality.format;
+                    let filename = opts.media_filename(info, ext);
                     let filename = if count == 1 {
-                        format!("{}.{}", sanitize_filename::sanitize(&info.title), ext)
+                        filename
                     } else {
-                        format!(
-                            "{}_{}.{}",
-                            sanitize_filename::sanitize(&info.title),
-                            i + 1,
-                            ext
-                        )
+                        filename::with_quality_suffix(&filename, &(i + 1).to_string())
+                    };
+                    let output = match opts.output_path(&filename) {
+                        Ok(path) => path,
+                        Err(existing) => {
+                            already_saved.get_or_insert(existing);
+                            continue;
+                        }
                     };
-                    let output = opts.output_dir.join(&filename);
                     let (tx, _rx) = mpsc::channel(8);
                     let bytes = direct_downloader::download_direct(
                         &self.client,
@@ -337,17 +377,20 @@ impl PlatformDownloader for BlueskyDownloader {
                     )
                     .await?;
                     total_bytes += bytes;
-                    last_path = output;
+                    saved.push(output);
 
                     let percent = ((i + 1) as f64 / count as f64) * 100.0;
                     let _ = progress.send(ProgressUpdate::percent(percent)).await;
                 }
 
+                if let Some(existing) = already_saved.filter(|_| saved.is_empty()) {
+                    return Ok(existing);
+                }
+                let file_path = saved.pop().unwrap_or_else(|| opts.output_dir.clone());
+
                 Ok(DownloadResult {
-                    file_path: last_path,
-                    file_size_bytes: total_bytes,
-                    duration_seconds: 0.0,
-                    torrent_id: None,
+                    extra_files: saved,
+                    ..DownloadResult::new(file_path, total_bytes)
                 })
             }
             MediaType::Gif => {
@@ -357,8 +400,11 @@ impl PlatformDownloader for BlueskyDownloader {
                     .ok_or_else(|| anyhow!("No GIF URL available"))?
                     .url;
 
-                let filename = format!("{}.gif", sanitize_filename::sanitize(&info.title));
-                let output = opts.output_dir.join(&filename);
+                let filename = opts.media_filename(info, "gif");
+                let output = match opts.output_path(&filename) {
+                    Ok(path) => path,
+                    Err(skipped) => return Ok(skipped),
+                };
 
                 let bytes = direct_downloader::download_direct(
                     &self.client,
@@ -369,12 +415,7 @@ impl PlatformDownloader for BlueskyDownloader {
                 )
                 .await?;
 
-                Ok(DownloadResult {
-                    file_path: output,
-                    file_size_bytes: bytes,
-                    duration_seconds: 0.0,
-                    torrent_id: None,
-                })
+                Ok(DownloadResult::new(output, bytes))
             }
             _ => Err(anyhow!("Unsupported media type for download")),
         }
@@ -82,9 +82,11 @@ impl PlatformDownloader for DirectFileDownloader {
                 height: 0,
                 url: url.to_string(),
                 format: "direct_file".to_string(),
+                ..Default::default()
             }],
             media_type: MediaType::File,
             file_size_bytes,
+            ..Default::default()
         })
     }
 
@@ -109,7 +111,10 @@ impl PlatformDownloader for DirectFileDownloader {
         } else {
             filename
         };
-        let output_path = opts.output_dir.join(&filename);
+        let output_path = match opts.output_path(&filename) {
+            Ok(path) => path,
+            Err(skipped) => return Ok(skipped),
+        };
 
         let mut builder = http_client::apply_global_proxy(reqwest::Client::builder())
             .connect_timeout(std::time::Duration::from_secs(30));
@@ -159,11 +164,6 @@ impl PlatformDownloader for DirectFileDownloader {
         )
         .await?;
 
-        Ok(DownloadResult {
-            file_path: output_path,
-            file_size_bytes: bytes,
-            duration_seconds: 0.0,
-            torrent_id: None,
-        })
+        Ok(DownloadResult::new(output_path, bytes))
     }
 }
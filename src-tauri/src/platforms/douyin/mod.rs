@@ -4,14 +4,17 @@ use std::time::Duration;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
+use omniget_core::core::log_hook;
 use tokio::sync::mpsc;
 
 use crate::core::http_client;
 use crate::core::ytdlp;
 use crate::models::media::{
-    DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality as MediaVideoQuality,
+    DownloadOptions, DownloadPhase, DownloadResult, MediaInfo, MediaType,
+    VideoQuality as MediaVideoQuality,
 };
-use crate::platforms::traits::PlatformDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
 
 const DOUYIN_UA: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/139.0.0.0 Safari/537.36";
 const DOUYIN_REFERER: &str = "https://www.douyin.com/?recommend=1";
@@ -34,7 +37,7 @@ impl DouyinDownloader {
             "--referer".to_string(),
             DOUYIN_REFERER.to_string(),
             "--user-agent".to_string(),
-            DOUYIN_UA.to_string(),
+            http_client::platform_user_agent("douyin", DOUYIN_UA),
         ]
     }
 
@@ -101,21 +104,23 @@ impl DouyinDownloader {
         if !Self::is_short_link(url) {
             return Self::canonicalize(url);
         }
-        let builder = reqwest::Client::builder()
+        let builder = http_client::platform_client_builder("douyin", DOUYIN_UA)
             .timeout(Duration::from_secs(10))
-            .redirect(reqwest::redirect::Policy::limited(10))
-            .user_agent(DOUYIN_UA);
-        let client = match http_client::apply_global_proxy(builder).build() {
+            .redirect(reqwest::redirect::Policy::limited(10));
+        let client = match builder.build() {
             Ok(c) => c,
             Err(_) => return Self::canonicalize(url),
         };
-        match client.get(url).send().await {
+        log_hook::report_phase(DownloadPhase::Resolving);
+        let resolved = match client.get(url).send().await {
             Ok(resp) => Self::canonicalize(resp.url().as_str()),
             Err(e) => {
                 tracing::warn!("[douyin] short-link resolve failed: {}", e);
                 Self::canonicalize(url)
             }
-        }
+        };
+        log_hook::report_phase(DownloadPhase::Extracting);
+        resolved
     }
 
     fn build_qualities(formats: &[serde_json::Value], url: &str) -> Vec<MediaVideoQuality> {
@@ -149,6 +154,7 @@ impl DouyinDownloader {
                 height,
                 url: url.to_string(),
                 format: "mp4".to_string(),
+                ..Default::default()
             });
         }
 
@@ -161,6 +167,7 @@ impl DouyinDownloader {
                 height: 0,
                 url: url.to_string(),
                 format: "mp4".to_string(),
+                ..Default::default()
             });
         }
 
@@ -175,12 +182,19 @@ impl PlatformDownloader for DouyinDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                return Self::host_matches(host);
-            }
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["douyin.com", "iesdouyin.com", "amemv.com"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_audio_only: true,
+            supports_subtitles: true,
+            ..Capabilities::default()
         }
-        false
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
@@ -236,7 +250,7 @@ impl PlatformDownloader for DouyinDownloader {
             } else {
                 MediaType::Audio
             },
-            file_size_bytes: None,
+            ..Default::default()
         })
     }
 
@@ -288,6 +302,8 @@ impl PlatformDownloader for DouyinDownloader {
             opts.download_subtitles,
             &extra,
             opts.audio_format.as_deref(),
+            &opts.quality_preference,
+            &opts.extra_ytdlp_args,
         )
         .await
     }
@@ -0,0 +1,394 @@
+use omniget_core::models::progress::ProgressUpdate;
+use std::sync::LazyLock;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use regex::Regex;
+use tokio::sync::mpsc;
+
+use crate::core::direct_downloader;
+use crate::core::errors::DownloadError;
+use crate::core::redirect;
+use crate::core::ytdlp;
+use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality};
+use crate::platforms::generic_ytdlp::GenericYtdlpDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
+
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+
+/// Keys the page's embedded JSON uses for the progressive MP4 renditions,
+/// newest first. Older layouts still show up on some pages.
+const HD_KEYS: &[&str] = &["browser_native_hd_url", "playable_url_quality_hd", "hd_src"];
+const SD_KEYS: &[&str] = &["browser_native_sd_url", "playable_url", "sd_src"];
+
+/// A string value stored under one of the keys `extract_json_string` is
+/// asked for; group 1 is the key, group 2 the still-escaped value.
+static JSON_STRING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    let keys: Vec<String> = HD_KEYS
+        .iter()
+        .chain(SD_KEYS)
+        .chain(&["video_id"])
+        .map(|k| regex::escape(k))
+        .collect();
+    Regex::new(&format!(
+        r#""?({})"?\s*:\s*"((?:[^"\\]|\\.)+)""#,
+        keys.join("|")
+    ))
+    .expect("valid JSON_STRING_RE")
+});
+
+static META_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<meta\s+property="([^"]+)"\s+content="([^"]*)""#).expect("valid META_RE")
+});
+
+/// What Facebook renders in place of a video that was deleted or that the
+/// viewer isn't allowed to see.
+const UNAVAILABLE_MARKERS: &[&str] = &[
+    "This content isn't available",
+    "This video isn't available",
+    "\"is_content_unavailable\":true",
+];
+
+pub struct FacebookDownloader {
+    client: reqwest::Client,
+}
+
+impl Default for FacebookDownloader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FacebookDownloader {
+    pub fn new() -> Self {
+        let mut builder = crate::core::http_client::platform_client_builder("facebook", USER_AGENT);
+
+        if let Some(jar) =
+            crate::core::cookie_parser::load_extension_cookies_for_domain("facebook.com")
+        {
+            builder = builder.cookie_provider(jar);
+        }
+
+        let client = builder.build().unwrap_or_default();
+        Self { client }
+    }
+
+    fn extract_video_id(url: &str) -> Option<String> {
+        let parsed = url::Url::parse(url).ok()?;
+        if let Some((_, v)) = parsed.query_pairs().find(|(k, _)| k == "v") {
+            return Some(v.into_owned());
+        }
+        let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+        match segments.as_slice() {
+            ["reel", id, ..] => Some(id.to_string()),
+            [.., "videos", id] | [.., "videos", _, id] => {
+                Some(id.to_string()).filter(|id| id.chars().all(|c| c.is_ascii_digit()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The first string value stored under one of `keys`, unescaped. The
+    /// values sit in JSON blobs embedded in the page, so they come with
+    /// `\/` and `\u0026` escapes.
+    fn extract_json_string(keys: &[&str], html: &str) -> Option<String> {
+        let mut found: Vec<(&str, &str)> = Vec::new();
+        for caps in JSON_STRING_RE.captures_iter(html) {
+            let key = caps.get(1).map_or("", |m| m.as_str());
+            if !found.iter().any(|(k, _)| *k == key) {
+                found.push((key, caps.get(2).map_or("", |m| m.as_str())));
+            }
+        }
+        keys.iter().find_map(|key| {
+            let (_, raw) = found.iter().find(|(k, _)| k == key)?;
+            serde_json::from_str::<String>(&format!("\"{}\"", raw)).ok()
+        })
+    }
+
+    fn extract_meta(property: &str, html: &str) -> Option<String> {
+        let raw = META_RE
+            .captures_iter(html)
+            .find(|caps| caps.get(1).is_some_and(|m| m.as_str() == property))?
+            .get(2)?
+            .as_str();
+        Some(
+            raw.replace("&quot;", "\"")
+                .replace("&#039;", "'")
+                .replace("&#x27;", "'")
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&amp;", "&"),
+        )
+        .filter(|s| !s.trim().is_empty())
+    }
+
+    fn is_login_wall(final_url: &str, html: &str) -> bool {
+        let path = url::Url::parse(final_url)
+            .map(|u| u.path().to_string())
+            .unwrap_or_default();
+        path.starts_with("/login")
+            || path.starts_with("/checkpoint")
+            || html.contains("id=\"login_form\"")
+    }
+
+    fn is_unavailable(html: &str) -> bool {
+        UNAVAILABLE_MARKERS.iter().any(|m| html.contains(m))
+    }
+
+    async fn fetch_page(&self, url: &str) -> anyhow::Result<(String, String)> {
+        let response = self
+            .client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml")
+            .header("Accept-Language", "en-US,en;q=0.9")
+            .header("Sec-Fetch-Mode", "navigate")
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(DownloadError::NotFound.into());
+        }
+        if !status.is_success() {
+            return Err(anyhow!("HTTP {} fetching {}", status, url));
+        }
+
+        let final_url = response.url().to_string();
+        let html = response.text().await?;
+        Ok((final_url, html))
+    }
+
+    async fn native_get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        let url = if redirect::is_short_link(url) {
+            redirect::expand_short_link(url).await?
+        } else {
+            url.to_string()
+        };
+
+        let (final_url, html) = self.fetch_page(&url).await?;
+
+        let hd = Self::extract_json_string(HD_KEYS, &html);
+        let sd = Self::extract_json_string(SD_KEYS, &html);
+
+        if hd.is_none() && sd.is_none() {
+            if Self::is_login_wall(&final_url, &html) {
+                return Err(DownloadError::LoginRequired.into());
+            }
+            if Self::is_unavailable(&html) {
+                return Err(DownloadError::Private.into());
+            }
+            return Err(anyhow!("No video found on the Facebook page"));
+        }
+
+        let mut qualities = Vec::new();
+        for (label, source) in [("hd", hd), ("sd", sd)] {
+            let Some(source) = source else {
+                continue;
+            };
+            if qualities.iter().any(|q: &VideoQuality| q.url == source) {
+                continue;
+            }
+            qualities.push(VideoQuality {
+                label: label.to_string(),
+                width: 0,
+                height: 0,
+                url: source,
+                format: "mp4".to_string(),
+                ..Default::default()
+            });
+        }
+
+        let id = Self::extract_video_id(&final_url)
+            .or_else(|| Self::extract_video_id(&url))
+            .or_else(|| Self::extract_json_string(&["video_id"], &html));
+        let title = Self::extract_meta("og:title", &html)
+            .or_else(|| id.as_ref().map(|id| format!("facebook_{}", id)))
+            .unwrap_or_else(|| "facebook".to_string());
+
+        Ok(MediaInfo {
+            id,
+            title,
+            author: String::new(),
+            platform: "facebook".to_string(),
+            duration_seconds: None,
+            thumbnail_url: Self::extract_meta("og:image", &html),
+            available_qualities: qualities,
+            media_type: MediaType::Video,
+            ..Default::default()
+        })
+    }
+
+    async fn fallback_ytdlp(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        omniget_core::platforms::check_ytdlp_fallback("facebook")?;
+        let ytdlp_path = ytdlp::ensure_ytdlp().await?;
+        let json = ytdlp::get_video_info(&ytdlp_path, url, &[]).await?;
+        let mut info = GenericYtdlpDownloader::parse_video_info(&json)?;
+        info.platform = "facebook".to_string();
+        Ok(info)
+    }
+}
+
+#[async_trait]
+impl PlatformDownloader for FacebookDownloader {
+    fn name(&self) -> &str {
+        "facebook"
+    }
+
+    fn can_handle(&self, url: &str) -> bool {
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["facebook.com", "fb.com", "fb.watch"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_quality_selection: true,
+            ..Capabilities::default()
+        }
+    }
+
+    async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        match self.native_get_media_info(url).await {
+            Ok(mut info) => {
+                direct_downloader::fill_file_size(&self.client, &mut info, None).await;
+                Ok(info)
+            }
+            Err(native_err) => {
+                tracing::warn!(
+                    "[facebook] native failed: {}, trying yt-dlp fallback",
+                    native_err
+                );
+                // yt-dlp may have browser cookies the native client lacks,
+                // but if it fails too the login/private error is the one
+                // worth showing.
+                self.fallback_ytdlp(url).await.map_err(|_| native_err)
+            }
+        }
+    }
+
+    async fn download(
+        &self,
+        info: &MediaInfo,
+        opts: &DownloadOptions,
+        progress: mpsc::Sender<ProgressUpdate>,
+    ) -> anyhow::Result<DownloadResult> {
+        let first = info
+            .available_qualities
+            .first()
+            .ok_or_else(|| anyhow!("No media URL available"))?;
+
+        if first.format == "ytdlp" {
+            let ytdlp_path = ytdlp::ensure_ytdlp().await?;
+            return ytdlp::download_video(
+                &ytdlp_path,
+                &first.url,
+                &opts.output_dir,
+                None,
+                progress,
+                opts.download_mode.as_deref(),
+                opts.format_id.as_deref(),
+                opts.filename_template.as_deref(),
+                opts.referer
+                    .as_deref()
+                    .or(Some("https://www.facebook.com/")),
+                opts.cancel_token.clone(),
+                None,
+                opts.concurrent_fragments,
+                false,
+                &[],
+                opts.audio_format.as_deref(),
+                &opts.quality_preference,
+                &opts.extra_ytdlp_args,
+            )
+            .await;
+        }
+
+        let selected = opts
+            .quality
+            .as_deref()
+            .and_then(|wanted| {
+                info.available_qualities
+                    .iter()
+                    .find(|q| q.matches_label(wanted))
+            })
+            .unwrap_or(first);
+
+        let filename = opts.media_filename(info, &selected.format);
+        let output_path = match opts.output_path(&filename) {
+            Ok(path) => path,
+            Err(skipped) => return Ok(skipped),
+        };
+
+        let total_bytes = direct_downloader::download_direct(
+            &self.client,
+            &selected.url,
+            &output_path,
+            progress,
+            Some(&opts.cancel_token),
+        )
+        .await?;
+
+        Ok(DownloadResult {
+            duration_seconds: info.duration_seconds.unwrap_or(0.0),
+            ..DownloadResult::new(output_path, total_bytes)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_escaped_video_urls() {
+        let html = r#"{"video":{"browser_native_sd_url":"https:\/\/video.xx.fbcdn.net\/v\/t42\/sd.mp4?_nc_ht=x&oh=1","browser_native_hd_url":null,"playable_url_quality_hd":"https:\/\/video.xx.fbcdn.net\/v\/t42\/hd.mp4?efg=e%3D"}}"#;
+        assert_eq!(
+            FacebookDownloader::extract_json_string(HD_KEYS, html).as_deref(),
+            Some("https://video.xx.fbcdn.net/v/t42/hd.mp4?efg=e%3D")
+        );
+        assert_eq!(
+            FacebookDownloader::extract_json_string(SD_KEYS, html).as_deref(),
+            Some("https://video.xx.fbcdn.net/v/t42/sd.mp4?_nc_ht=x&oh=1")
+        );
+    }
+
+    #[test]
+    fn video_ids_from_link_shapes() {
+        for (url, id) in [
+            ("https://www.facebook.com/watch/?v=123456", Some("123456")),
+            ("https://www.facebook.com/reel/987654", Some("987654")),
+            ("https://www.facebook.com/page/videos/555", Some("555")),
+            (
+                "https://www.facebook.com/page/videos/some-title/777",
+                Some("777"),
+            ),
+            ("https://www.facebook.com/page", None),
+        ] {
+            assert_eq!(
+                FacebookDownloader::extract_video_id(url).as_deref(),
+                id,
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn detects_login_walls() {
+        assert!(FacebookDownloader::is_login_wall(
+            "https://www.facebook.com/login/?next=x",
+            ""
+        ));
+        assert!(FacebookDownloader::is_login_wall(
+            "https://www.facebook.com/watch/?v=1",
+            r#"<form id="login_form" action="/login/">"#
+        ));
+        assert!(!FacebookDownloader::is_login_wall(
+            "https://www.facebook.com/watch/?v=1",
+            "<html></html>"
+        ));
+    }
+}
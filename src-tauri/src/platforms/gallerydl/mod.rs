@@ -117,9 +117,10 @@ impl PlatformDownloader for GalleryDlDownloader {
                 height: 0,
                 url: url.to_string(),
                 format: "gallery".to_string(),
+                ..Default::default()
             }],
             media_type: MediaType::Carousel,
-            file_size_bytes: None,
+            ..Default::default()
         })
     }
 
@@ -255,12 +256,7 @@ impl PlatformDownloader for GalleryDlDownloader {
 
         let _ = progress.send(ProgressUpdate::percent(100.0)).await;
 
-        Ok(DownloadResult {
-            file_path,
-            file_size_bytes: total_bytes,
-            duration_seconds: 0.0,
-            torrent_id: None,
-        })
+        Ok(DownloadResult::new(file_path, total_bytes))
     }
 }
 
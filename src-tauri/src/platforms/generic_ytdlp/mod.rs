@@ -11,7 +11,7 @@ use crate::core::ytdlp;
 use crate::models::media::{
     DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality as MediaVideoQuality,
 };
-use crate::platforms::traits::PlatformDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
 
 pub struct GenericYtdlpDownloader;
 
@@ -116,6 +116,7 @@ impl GenericYtdlpDownloader {
                             height,
                             url: webpage_url.clone(),
                             format: "ytdlp".to_string(),
+                            ..Default::default()
                         });
                     }
                 }
@@ -131,10 +132,12 @@ impl GenericYtdlpDownloader {
                 height: 0,
                 url: webpage_url,
                 format: "ytdlp".to_string(),
+                ..Default::default()
             });
         }
 
         Ok(MediaInfo {
+            id: json.get("id").and_then(|v| v.as_str()).map(String::from),
             title,
             author,
             platform,
@@ -142,7 +145,8 @@ impl GenericYtdlpDownloader {
             thumbnail_url: thumbnail,
             available_qualities: qualities,
             media_type,
-            file_size_bytes: None,
+            is_live: ytdlp::is_live_info(json),
+            ..Default::default()
         })
     }
 }
@@ -220,9 +224,10 @@ fn build_direct_media_info(url: &str, media_type_hint: &str) -> MediaInfo {
             height: 0,
             url: url.to_string(),
             format,
+            ..Default::default()
         }],
         media_type,
-        file_size_bytes: None,
+        ..Default::default()
     }
 }
 
@@ -240,6 +245,15 @@ impl PlatformDownloader for GenericYtdlpDownloader {
         false
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_quality_selection: true,
+            supports_audio_only: true,
+            supports_subtitles: true,
+            ..Capabilities::default()
+        }
+    }
+
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
         if let Some(media_type) = is_direct_media_url(url) {
             return Ok(build_direct_media_info(url, media_type));
@@ -299,7 +313,10 @@ impl PlatformDownloader for GenericYtdlpDownloader {
             } else {
                 format!("{}.mp4", title)
             };
-            let output_path = opts.output_dir.join(&filename);
+            let output_path = match opts.output_path(&filename) {
+                Ok(path) => path,
+                Err(skipped) => return Ok(skipped),
+            };
             let output_str = output_path.to_string_lossy().to_string();
 
             let referer = opts
@@ -353,24 +370,22 @@ impl PlatformDownloader for GenericYtdlpDownloader {
                     referer,
                     None,
                     opts.cancel_token.clone(),
-                    20,
-                    3,
+                    opts.hls_max_concurrent_segments,
+                    opts.hls_max_retries,
                 )
                 .await?;
 
             let _ = progress.send(ProgressUpdate::percent(100.0)).await;
 
-            return Ok(DownloadResult {
-                file_path: result.path,
-                file_size_bytes: result.file_size,
-                duration_seconds: 0.0,
-                torrent_id: None,
-            });
+            return Ok(DownloadResult::new(result.path, result.file_size));
         }
 
         if selected.format == "direct_video" || selected.format == "direct_audio" {
             let title = sanitize_filename::sanitize(&info.title);
-            let output_path = opts.output_dir.join(&title);
+            let output_path = match opts.output_path(&title) {
+                Ok(path) => path,
+                Err(skipped) => return Ok(skipped),
+            };
 
             let mut builder =
                 crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
@@ -420,12 +435,7 @@ impl PlatformDownloader for GenericYtdlpDownloader {
             )
             .await?;
 
-            return Ok(DownloadResult {
-                file_path: output_path,
-                file_size_bytes: bytes,
-                duration_seconds: 0.0,
-                torrent_id: None,
-            });
+            return Ok(DownloadResult::new(output_path, bytes));
         }
 
         let ytdlp_path = if let Some(ref p) = opts.ytdlp_path {
@@ -450,11 +460,21 @@ impl PlatformDownloader for GenericYtdlpDownloader {
         };
 
         let mut last_err: Option<anyhow::Error> = None;
-        let extra_flags_owned: Vec<String> = opts
+        let mut extra_flags_owned: Vec<String> = opts
             .custom_ytdlp_args
             .as_deref()
             .map(|v| v.to_vec())
             .unwrap_or_default();
+        extra_flags_owned.extend(ytdlp::section_args(opts.section_start, opts.section_end).await);
+        // A playlist handed to yt-dlp in "only new" mode records its own
+        // items; the queue only records the URL it was given.
+        if let Some(archive) = opts.download_archive.as_deref() {
+            if let Some(parent) = archive.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            extra_flags_owned.push("--download-archive".to_string());
+            extra_flags_owned.push(archive.to_string_lossy().to_string());
+        }
         for (idx, override_format) in format_fallbacks.iter().enumerate() {
             let effective_format = override_format.or(opts.format_id.as_deref());
             let attempt_progress = progress.clone();
@@ -474,6 +494,8 @@ impl PlatformDownloader for GenericYtdlpDownloader {
                 opts.download_subtitles,
                 &extra_flags_owned,
                 opts.audio_format.as_deref(),
+                &opts.quality_preference,
+                &opts.extra_ytdlp_args,
             )
             .await;
 
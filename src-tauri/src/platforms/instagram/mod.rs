@@ -6,8 +6,14 @@ use regex::Regex;
 use tokio::sync::mpsc;
 
 use crate::core::direct_downloader::download_direct_with_headers;
-use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality};
-use crate::platforms::traits::PlatformDownloader;
+use crate::core::errors::DownloadError;
+use crate::core::ffmpeg;
+use crate::core::filename;
+use crate::models::media::{
+    json_count, DownloadOptions, DownloadResult, MediaInfo, MediaType, PostMetadata, VideoQuality,
+};
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
 
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
 const IG_APP_ID: &str = "936619743392459";
@@ -15,7 +21,6 @@ const GQL_DOC_ID: &str = "8845758582119845";
 
 pub struct InstagramDownloader {
     client: reqwest::Client,
-    redirect_client: reqwest::Client,
 }
 
 enum InstagramMedia {
@@ -53,10 +58,8 @@ impl Default for InstagramDownloader {
 
 impl InstagramDownloader {
     pub fn new() -> Self {
-        let mut builder = crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
-            .user_agent(USER_AGENT)
-            .timeout(std::time::Duration::from_secs(120))
-            .connect_timeout(std::time::Duration::from_secs(15));
+        let mut builder =
+            crate::core::http_client::platform_client_builder("instagram", USER_AGENT);
 
         if let Some(jar) =
             crate::core::cookie_parser::load_extension_cookies_for_domain("instagram.com")
@@ -66,18 +69,7 @@ impl InstagramDownloader {
 
         let client = builder.build().unwrap_or_default();
 
-        let redirect_client =
-            crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
-                .user_agent("curl/7.88.1")
-                .timeout(std::time::Duration::from_secs(120))
-                .connect_timeout(std::time::Duration::from_secs(15))
-                .build()
-                .unwrap_or_default();
-
-        Self {
-            client,
-            redirect_client,
-        }
+        Self { client }
     }
 
     fn extract_post_id(url: &str) -> Option<String> {
@@ -114,8 +106,7 @@ impl InstagramDownloader {
     async fn resolve_share_link(&self, share_id: &str) -> anyhow::Result<String> {
         let url = format!("https://www.instagram.com/share/{}/", share_id);
 
-        let response = self.redirect_client.get(&url).send().await?;
-        let final_url = response.url().to_string();
+        let final_url = crate::core::redirect::expand_short_link(&url).await?;
 
         if final_url.contains("/share/") || final_url == url {
             return Err(anyhow!("Could not resolve share link"));
@@ -412,14 +403,14 @@ impl InstagramDownloader {
             .await?;
 
         if !response.status().is_success() {
-            return Err(anyhow!("Instagram GQL retornou HTTP {}", response.status()));
+            return Err(DownloadError::from_status("Instagram GQL", response.status()).into());
         }
 
         let json: serde_json::Value = response.json().await?;
 
         let data = json
             .get("data")
-            .ok_or_else(|| anyhow!("Resposta GQL sem data"))?;
+            .ok_or_else(|| anyhow!("GQL response has no data"))?;
 
         let media = data
             .get("xdt_shortcode_media")
@@ -472,6 +463,7 @@ impl InstagramDownloader {
     }
 
     async fn fallback_ytdlp(&self, url: &str, post_id: &str) -> anyhow::Result<MediaInfo> {
+        omniget_core::platforms::check_ytdlp_fallback("instagram")?;
         let ytdlp_path = crate::core::ytdlp::ensure_ytdlp().await?;
         let json = crate::core::ytdlp::get_video_info(&ytdlp_path, url, &[]).await?;
         let mut info =
@@ -604,10 +596,62 @@ impl InstagramDownloader {
             false,
             &[],
             opts.audio_format.as_deref(),
+            &opts.quality_preference,
+            &opts.extra_ytdlp_args,
         )
         .await
     }
 
+    /// The poster's handle from embed or GraphQL data: `owner.username` of
+    /// the shortcode media, or the embed context's `username`.
+    fn extract_author(data: &serde_json::Value) -> String {
+        [
+            "/owner/username",
+            "/gql_data/shortcode_media/owner/username",
+            "/gql_data/xdt_shortcode_media/owner/username",
+            "/media/owner/username",
+            "/context/username",
+        ]
+        .iter()
+        .find_map(|pointer| data.pointer(pointer).and_then(|v| v.as_str()))
+        .unwrap_or_default()
+        .to_string()
+    }
+
+    /// Caption, date and counts of the shortcode media, whether `data` is
+    /// the media itself (GraphQL) or an embed that wraps it in `gql_data`.
+    fn extract_metadata(data: &serde_json::Value) -> Option<PostMetadata> {
+        let media = data
+            .get("gql_data")
+            .and_then(|g| {
+                g.get("shortcode_media")
+                    .or_else(|| g.get("xdt_shortcode_media"))
+            })
+            .unwrap_or(data);
+        let timestamp = media.get("taken_at_timestamp").and_then(|v| v.as_i64());
+        let description = media
+            .pointer("/edge_media_to_caption/edges/0/node/text")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        if timestamp.is_none() && description.is_none() {
+            return None;
+        }
+        Some(PostMetadata {
+            tags: description
+                .as_deref()
+                .map(PostMetadata::hashtags_in)
+                .unwrap_or_default(),
+            description,
+            timestamp,
+            view_count: json_count(media.get("video_view_count")),
+            like_count: json_count(media.pointer("/edge_media_preview_like/count"))
+                .or_else(|| json_count(media.pointer("/edge_liked_by/count"))),
+            comment_count: json_count(media.pointer("/edge_media_to_parent_comment/count"))
+                .or_else(|| json_count(media.pointer("/edge_media_to_comment/count"))),
+            share_count: None,
+        })
+    }
+
     fn extract_media_from_embed(data: &serde_json::Value) -> anyhow::Result<InstagramMedia> {
         if let Some(video_url) = data.get("gql_data").and_then(|g| {
             g.get("shortcode_media")
@@ -651,16 +695,18 @@ impl PlatformDownloader for InstagramDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host == "instagram.com"
-                    || host.ends_with(".instagram.com")
-                    || host == "ddinstagram.com"
-                    || host.ends_with(".ddinstagram.com");
-            }
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["instagram.com", "ddinstagram.com"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_audio_only: true,
+            ..Capabilities::default()
         }
-        false
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
@@ -680,10 +726,18 @@ impl PlatformDownloader for InstagramDownloader {
         let filename_base = format!("instagram_{}", post_id);
 
         let embed_result = self.request_embed(&post_id).await;
-        let media = match embed_result {
-            Ok(data) => Self::extract_media_from_embed(&data),
+        let (media, author, metadata) = match embed_result {
+            Ok(data) => (
+                Self::extract_media_from_embed(&data),
+                Self::extract_author(&data),
+                Self::extract_metadata(&data),
+            ),
             Err(_embed_err) => match self.request_gql(&post_id).await {
-                Ok(data) => Self::extract_media_from_gql(&data),
+                Ok(data) => (
+                    Self::extract_media_from_gql(&data),
+                    Self::extract_author(&data),
+                    Self::extract_metadata(&data),
+                ),
                 Err(_gql_err) => {
                     return self.fallback_ytdlp(url, &post_id).await;
                 }
@@ -697,7 +751,7 @@ impl PlatformDownloader for InstagramDownloader {
             }
         };
 
-        match media {
+        let mut info = match media {
             InstagramMedia::Single { url, is_video } => {
                 let (media_type, format) = if is_video {
                     (MediaType::Video, "mp4")
@@ -705,9 +759,10 @@ impl PlatformDownloader for InstagramDownloader {
                     (MediaType::Photo, "jpg")
                 };
 
-                Ok(MediaInfo {
+                MediaInfo {
+                    id: Some(post_id.clone()),
                     title: filename_base,
-                    author: String::new(),
+                    author,
                     platform: "instagram".to_string(),
                     duration_seconds: None,
                     thumbnail_url: None,
@@ -717,10 +772,12 @@ impl PlatformDownloader for InstagramDownloader {
                         height: 0,
                         url,
                         format: format.to_string(),
+                        ..Default::default()
                     }],
                     media_type,
-                    file_size_bytes: None,
-                })
+                    metadata,
+                    ..Default::default()
+                }
             }
             InstagramMedia::Carousel { items } => {
                 let qualities: Vec<VideoQuality> = items
@@ -734,22 +791,30 @@ impl PlatformDownloader for InstagramDownloader {
                             height: 0,
                             url: item.url.clone(),
                             format: format.to_string(),
+                            ..Default::default()
                         }
                     })
                     .collect();
 
-                Ok(MediaInfo {
+                MediaInfo {
+                    id: Some(post_id.clone()),
                     title: filename_base,
-                    author: String::new(),
+                    author,
                     platform: "instagram".to_string(),
                     duration_seconds: None,
                     thumbnail_url: None,
                     available_qualities: qualities,
                     media_type: MediaType::Carousel,
-                    file_size_bytes: None,
-                })
+                    metadata,
+                    ..Default::default()
+                }
             }
-        }
+        };
+
+        let headers = Self::instagram_headers();
+        crate::core::direct_downloader::fill_file_size(&self.client, &mut info, Some(&headers))
+            .await;
+        Ok(info)
     }
 
     async fn download(
@@ -767,12 +832,11 @@ impl PlatformDownloader for InstagramDownloader {
                 return Self::ytdlp_download_post(&quality.url, opts, progress).await;
             }
 
-            let filename = format!(
-                "{}.{}",
-                sanitize_filename::sanitize(&info.title),
-                quality.format
-            );
-            let output = opts.output_dir.join(&filename);
+            let filename = opts.media_filename(info, &quality.format);
+            let output = match opts.output_path(&filename) {
+                Ok(path) => path,
+                Err(skipped) => return Ok(skipped),
+            };
 
             let mut hdr_map = Self::instagram_headers();
             crate::core::http_client::inject_ua_header(&mut hdr_map, opts.user_agent.as_deref());
@@ -789,11 +853,25 @@ impl PlatformDownloader for InstagramDownloader {
             .await
             {
                 Ok(bytes) => {
+                    let bytes = crate::core::image::strip_downloaded_image(
+                        &output,
+                        bytes,
+                        opts.strip_metadata,
+                    );
+                    let (output, bytes, note) = if opts.is_audio() && quality.format == "mp4" {
+                        ffmpeg::audio_from_downloaded(
+                            &output,
+                            bytes,
+                            opts.audio_format.as_deref(),
+                            opts.cancel_token.clone(),
+                        )
+                        .await?
+                    } else {
+                        (output, bytes, None)
+                    };
                     return Ok(DownloadResult {
-                        file_path: output,
-                        file_size_bytes: bytes,
-                        duration_seconds: 0.0,
-                        torrent_id: None,
+                        note,
+                        ..DownloadResult::new(output, bytes)
                     });
                 }
                 Err(e) => {
@@ -812,16 +890,25 @@ impl PlatformDownloader for InstagramDownloader {
         }
 
         let mut total_bytes = 0u64;
-        let mut last_path = opts.output_dir.clone();
+        let mut saved = Vec::new();
+        let mut already_saved = None;
+        let mut note = None;
 
         for (i, quality) in info.available_qualities.iter().enumerate() {
-            let filename = format!(
-                "{}_{}.{}",
-                sanitize_filename::sanitize(&info.title),
-                i + 1,
-                quality.format,
+            if !opts.is_selected(i) {
+                continue;
+            }
+            let filename = filename::with_quality_suffix(
+                &opts.media_filename(info, &quality.format),
+                &(i + 1).to_string(),
             );
-            let output = opts.output_dir.join(&filename);
+            let output = match opts.output_path(&filename) {
+                Ok(path) => path,
+                Err(existing) => {
+                    already_saved.get_or_insert(existing);
+                    continue;
+                }
+            };
             let (tx, _rx) = mpsc::channel(8);
 
             let mut hdr_map = Self::instagram_headers();
@@ -839,8 +926,26 @@ impl PlatformDownloader for InstagramDownloader {
             .await
             {
                 Ok(bytes) => {
+                    let bytes = crate::core::image::strip_downloaded_image(
+                        &output,
+                        bytes,
+                        opts.strip_metadata,
+                    );
+                    let (output, bytes) = if opts.is_audio() && quality.format == "mp4" {
+                        let (audio, bytes, kept_video) = ffmpeg::audio_from_downloaded(
+                            &output,
+                            bytes,
+                            opts.audio_format.as_deref(),
+                            opts.cancel_token.clone(),
+                        )
+                        .await?;
+                        note = note.or(kept_video);
+                        (audio, bytes)
+                    } else {
+                        (output, bytes)
+                    };
                     total_bytes += bytes;
-                    last_path = output;
+                    saved.push(output);
 
                     let percent = ((i + 1) as f64 / count as f64) * 100.0;
                     let _ = progress.send(ProgressUpdate::percent(percent)).await;
@@ -862,11 +967,15 @@ impl PlatformDownloader for InstagramDownloader {
             }
         }
 
+        if let Some(existing) = already_saved.filter(|_| saved.is_empty()) {
+            return Ok(existing);
+        }
+        let file_path = saved.pop().unwrap_or_else(|| opts.output_dir.clone());
+
         Ok(DownloadResult {
-            file_path: last_path,
-            file_size_bytes: total_bytes,
-            duration_seconds: 0.0,
-            torrent_id: None,
+            note,
+            extra_files: saved,
+            ..DownloadResult::new(file_path, total_bytes)
         })
     }
 }
@@ -875,31 +984,11 @@ impl PlatformDownloader for InstagramDownloader {
 mod tests {
     use super::*;
     use std::path::PathBuf;
-    use tokio_util::sync::CancellationToken;
 
     fn make_opts(page_url: Option<&str>) -> DownloadOptions {
         DownloadOptions {
-            quality: None,
-            output_dir: PathBuf::from("."),
-            filename_template: None,
-            download_subtitles: false,
-            include_auto_subtitles: false,
-            download_mode: None,
-            audio_format: None,
-            format_id: None,
-            referer: None,
-            extra_headers: None,
             page_url: page_url.map(String::from),
-            user_agent: None,
-            cancel_token: CancellationToken::new(),
-            concurrent_fragments: 1,
-            ytdlp_path: None,
-            torrent_listen_port: None,
-            torrent_id_slot: None,
-            custom_ytdlp_args: None,
-            torrent_files: None,
-            torrent_auto_trackers: false,
-            torrent_upnp: false,
+            ..DownloadOptions::new(PathBuf::from("."))
         }
     }
 
@@ -912,10 +1001,45 @@ mod tests {
             thumbnail_url: None,
             available_qualities: vec![],
             media_type: MediaType::Video,
-            file_size_bytes: None,
+            ..Default::default()
         }
     }
 
+    #[test]
+    fn author_comes_from_owner_or_embed_context() {
+        let gql = serde_json::json!({ "owner": { "username": "nasa" }, "display_url": "x" });
+        assert_eq!(InstagramDownloader::extract_author(&gql), "nasa");
+
+        let embed = serde_json::json!({
+            "gql_data": { "xdt_shortcode_media": { "owner": { "username": "natgeo" } } }
+        });
+        assert_eq!(InstagramDownloader::extract_author(&embed), "natgeo");
+
+        let context = serde_json::json!({ "context": { "username": "bbc" } });
+        assert_eq!(InstagramDownloader::extract_author(&context), "bbc");
+        assert_eq!(
+            InstagramDownloader::extract_author(&serde_json::json!({})),
+            ""
+        );
+    }
+
+    #[test]
+    fn metadata_comes_from_the_shortcode_media() {
+        let embed = serde_json::json!({ "gql_data": { "shortcode_media": {
+            "taken_at_timestamp": 1700000000,
+            "edge_media_to_caption": { "edges": [{ "node": { "text": "sunset #beach #beach" } }] },
+            "edge_media_preview_like": { "count": 40 },
+            "edge_media_to_parent_comment": { "count": 2 }
+        } } });
+        let meta = InstagramDownloader::extract_metadata(&embed).unwrap();
+        assert_eq!(meta.description.as_deref(), Some("sunset #beach #beach"));
+        assert_eq!(meta.timestamp, Some(1_700_000_000));
+        assert_eq!(meta.like_count, Some(40));
+        assert_eq!(meta.comment_count, Some(2));
+        assert_eq!(meta.tags, vec!["beach".to_string()]);
+        assert!(InstagramDownloader::extract_metadata(&serde_json::json!({})).is_none());
+    }
+
     #[test]
     fn post_url_from_title_accepts_prefixed_title() {
         assert_eq!(
@@ -78,9 +78,10 @@ impl PlatformDownloader for MagnetDownloader {
                 height: 0,
                 url: url.to_string(),
                 format: "torrent".to_string(),
+                ..Default::default()
             }],
             media_type: MediaType::Video,
-            file_size_bytes: None,
+            ..Default::default()
         })
     }
 
@@ -301,10 +302,8 @@ impl PlatformDownloader for MagnetDownloader {
         };
 
         Ok(DownloadResult {
-            file_path,
-            file_size_bytes: total_size,
-            duration_seconds: 0.0,
             torrent_id: Some(torrent_id),
+            ..DownloadResult::new(file_path, total_size)
         })
     }
 }
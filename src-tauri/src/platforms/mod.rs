@@ -1,5 +1,7 @@
 pub use omniget_core::platforms::traits;
 pub use omniget_core::platforms::Platform;
+pub use omniget_core::platforms::PlatformDescriptor;
+pub use omniget_core::platforms::url_matches_hosts;
 
 pub mod bluesky;
 pub mod direct_file;
@@ -14,6 +16,8 @@ pub mod bilibili;
 #[cfg(not(target_os = "android"))]
 pub mod douyin;
 #[cfg(not(target_os = "android"))]
+pub mod facebook;
+#[cfg(not(target_os = "android"))]
 pub mod gallerydl;
 #[cfg(not(target_os = "android"))]
 pub mod generic_ytdlp;
@@ -24,6 +28,39 @@ pub mod p2p;
 #[cfg(not(target_os = "android"))]
 pub mod reddit;
 #[cfg(not(target_os = "android"))]
+pub mod soundcloud;
+#[cfg(not(target_os = "android"))]
 pub mod vimeo;
 #[cfg(not(target_os = "android"))]
 pub mod youtube;
+#[cfg(not(target_os = "android"))]
+pub mod ytdlp_site;
+
+use std::sync::Arc;
+
+use traits::PlatformDownloader;
+
+/// A new instance of the downloader registered as `name`, for platforms
+/// that build their HTTP client once in `new()`. Built inside
+/// `http_client::with_proxy_override`, its client goes through the
+/// override. `None` for downloaders that create clients or yt-dlp runs per
+/// download, which pick the override up on their own.
+pub fn rebuild_with_fresh_client(name: &str) -> Option<Arc<dyn PlatformDownloader>> {
+    let downloader: Arc<dyn PlatformDownloader> = match name {
+        "bluesky" => Arc::new(bluesky::BlueskyDownloader::new()),
+        "pinterest" => Arc::new(pinterest::PinterestDownloader::new()),
+        "tiktok" => Arc::new(tiktok::TikTokDownloader::new()),
+        "twitch" => Arc::new(twitch::TwitchClipsDownloader::new()),
+        "twitter" => Arc::new(twitter::TwitterDownloader::new()),
+        #[cfg(not(target_os = "android"))]
+        "facebook" => Arc::new(facebook::FacebookDownloader::new()),
+        #[cfg(not(target_os = "android"))]
+        "instagram" => Arc::new(instagram::InstagramDownloader::new()),
+        #[cfg(not(target_os = "android"))]
+        "reddit" => Arc::new(reddit::RedditDownloader::new()),
+        #[cfg(not(target_os = "android"))]
+        "soundcloud" => Arc::new(soundcloud::SoundCloudDownloader::new()),
+        _ => return None,
+    };
+    Some(downloader)
+}
@@ -1,7 +1,7 @@
 pub mod words;
 
 use omniget_core::models::progress::ProgressUpdate;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::anyhow;
@@ -51,6 +51,43 @@ fn check_relay_error(line: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Copies up to `file_size` bytes from the relay into `part_path`, returning
+/// how many arrived.
+async fn receive_into(
+    reader: &mut BufReader<tokio::io::ReadHalf<TcpStream>>,
+    part_path: &Path,
+    file_size: u64,
+    opts: &DownloadOptions,
+    progress: &mpsc::Sender<ProgressUpdate>,
+) -> anyhow::Result<u64> {
+    let mut file = File::create(part_path).await?;
+    let mut received: u64 = 0;
+    let mut buf = vec![0u8; CHUNK_SIZE];
+
+    while received < file_size {
+        if opts.cancel_token.is_cancelled() {
+            anyhow::bail!("Download cancelled");
+        }
+
+        let to_read = ((file_size - received) as usize).min(CHUNK_SIZE);
+        let n = reader.read(&mut buf[..to_read]).await?;
+        if n == 0 {
+            break;
+        }
+
+        file.write_all(&buf[..n]).await?;
+        received += n as u64;
+
+        if file_size > 0 {
+            let pct = (received as f64 / file_size as f64) * 100.0;
+            let _ = progress.send(ProgressUpdate::percent(pct)).await;
+        }
+    }
+
+    file.flush().await?;
+    Ok(received)
+}
+
 pub struct P2pDownloader;
 
 impl P2pDownloader {
@@ -95,9 +132,10 @@ impl PlatformDownloader for P2pDownloader {
                 height: 0,
                 url: url.to_string(),
                 format: "p2p".to_string(),
+                ..Default::default()
             }],
             media_type: MediaType::Video,
-            file_size_bytes: None,
+            ..Default::default()
         })
     }
 
@@ -156,44 +194,30 @@ impl PlatformDownloader for P2pDownloader {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        let mut file = File::create(&output_path).await?;
-        let mut received: u64 = 0;
-        let mut buf = vec![0u8; CHUNK_SIZE];
-
-        while received < file_size {
-            if opts.cancel_token.is_cancelled() {
-                let _ = tokio::fs::remove_file(&output_path).await;
-                anyhow::bail!("Download cancelled");
-            }
-
-            let to_read = ((file_size - received) as usize).min(CHUNK_SIZE);
-            let n = reader.read(&mut buf[..to_read]).await?;
-            if n == 0 {
-                break;
+        let part_path = crate::core::temp_dir::part_path_for(&output_path);
+        let received = match receive_into(&mut reader, &part_path, file_size, opts, &progress).await
+        {
+            Ok(received) if received == file_size => received,
+            Ok(received) => {
+                let _ = tokio::fs::remove_file(&part_path).await;
+                anyhow::bail!(
+                    "Transfer ended early: got {} of {} bytes",
+                    received,
+                    file_size
+                );
             }
-
-            file.write_all(&buf[..n]).await?;
-            received += n as u64;
-
-            if file_size > 0 {
-                let pct = (received as f64 / file_size as f64) * 100.0;
-                let _ = progress.send(ProgressUpdate::percent(pct)).await;
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&part_path).await;
+                return Err(e);
             }
-        }
-
-        file.flush().await?;
-        drop(file);
+        };
+        crate::core::temp_dir::move_file(&part_path, &output_path).await?;
 
         let _ = progress.send(ProgressUpdate::percent(100.0)).await;
 
         tracing::info!("[p2p] download complete: {}", output_path.display());
 
-        Ok(DownloadResult {
-            file_path: output_path,
-            file_size_bytes: received,
-            duration_seconds: 0.0,
-            torrent_id: None,
-        })
+        Ok(DownloadResult::new(output_path, received))
     }
 }
 
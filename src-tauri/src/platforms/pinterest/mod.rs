@@ -7,11 +7,37 @@ use regex::Regex;
 use tokio::sync::mpsc;
 
 use crate::core::direct_downloader;
+use crate::core::errors::DownloadError;
+use crate::core::ffmpeg;
+use crate::core::hls_downloader::HlsDownloader;
 use crate::core::redirect;
 use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality};
-use crate::platforms::traits::PlatformDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
 
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+/// Most pins taken from one board, so a huge board does not flood the queue.
+const MAX_BOARD_PINS: usize = 500;
+const BOARD_PAGE_SIZE: u32 = 100;
+/// Most feed pages requested for one board, in case the feed keeps
+/// handing out bookmarks without pins.
+const MAX_BOARD_PAGES: usize = 20;
+/// First path segments that are Pinterest pages, not a user's boards.
+const NON_USER_SEGMENTS: &[&str] = &[
+    "pin",
+    "url_shortener",
+    "search",
+    "ideas",
+    "today",
+    "explore",
+    "resource",
+    "business",
+    "settings",
+    "categories",
+    "topics",
+    "videos",
+    "news_hub",
+];
 
 static PIN_NOT_FOUND_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r#""__typename"\s*:\s*"PinNotFound""#).expect("valid PIN_NOT_FOUND_RE")
@@ -27,6 +53,25 @@ static IMAGE_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
 
 pub struct PinterestDownloader {
     client: reqwest::Client,
+    /// Browser cookies were found; without them secret boards look missing.
+    logged_in: bool,
+}
+
+/// A board, or a section of one, as named in its URL.
+#[derive(Debug, PartialEq, Eq)]
+struct BoardRef {
+    username: String,
+    slug: String,
+    section: Option<String>,
+}
+
+impl BoardRef {
+    fn source_url(&self) -> String {
+        match &self.section {
+            Some(section) => format!("/{}/{}/{}/", self.username, self.slug, section),
+            None => format!("/{}/{}/", self.username, self.slug),
+        }
+    }
 }
 
 impl Default for PinterestDownloader {
@@ -37,19 +82,184 @@ impl Default for PinterestDownloader {
 
 impl PinterestDownloader {
     pub fn new() -> Self {
-        let mut builder = crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
-            .user_agent(USER_AGENT)
-            .timeout(std::time::Duration::from_secs(120))
-            .connect_timeout(std::time::Duration::from_secs(15));
+        let mut builder =
+            crate::core::http_client::platform_client_builder("pinterest", USER_AGENT);
 
-        if let Some(jar) =
-            crate::core::cookie_parser::load_extension_cookies_for_domain("pinterest.com")
-        {
+        let jar = crate::core::cookie_parser::load_extension_cookies_for_domain("pinterest.com");
+        let logged_in = jar.is_some();
+        if let Some(jar) = jar {
             builder = builder.cookie_provider(jar);
         }
 
         let client = builder.build().unwrap_or_default();
-        Self { client }
+        Self { client, logged_in }
+    }
+
+    /// `pinterest.com/<user>/<board>/` or `.../<board>/<section>/`. Profile
+    /// tabs such as `/<user>/_saved/` start with an underscore.
+    fn parse_board_url(url: &str) -> Option<BoardRef> {
+        let parsed = url::Url::parse(url).ok()?;
+        let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+        let (username, slug, section) = match segments.as_slice() {
+            [user, board] => (*user, *board, None),
+            [user, board, section] => (*user, *board, Some(*section)),
+            _ => return None,
+        };
+        if NON_USER_SEGMENTS.contains(&username)
+            || slug.starts_with('_')
+            || section.is_some_and(|s| s.starts_with('_'))
+        {
+            return None;
+        }
+        Some(BoardRef {
+            username: username.to_string(),
+            slug: slug.to_string(),
+            section: section.map(str::to_string),
+        })
+    }
+
+    /// Calls one of the `/resource/<Name>Resource/get/` endpoints the web
+    /// app uses and returns its `resource_response`.
+    async fn fetch_resource(
+        &self,
+        resource: &str,
+        source_url: &str,
+        options: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        let data = serde_json::json!({ "options": options, "context": {} }).to_string();
+        let response = self
+            .client
+            .get(format!(
+                "https://www.pinterest.com/resource/{}Resource/get/",
+                resource
+            ))
+            .query(&[("source_url", source_url), ("data", data.as_str())])
+            .header("Accept", "application/json")
+            .header("X-Requested-With", "XMLHttpRequest")
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.as_u16() == 404 && !self.logged_in {
+            // Secret boards answer 404 to anyone but their collaborators.
+            return Err(DownloadError::LoginRequired.into());
+        }
+        if !status.is_success() {
+            return Err(DownloadError::from_status("Pinterest", status).into());
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        json.get("resource_response")
+            .cloned()
+            .ok_or_else(|| anyhow!("Pinterest returned no resource_response"))
+    }
+
+    /// Pin URLs on one feed page, plus the bookmark of the next page.
+    fn parse_pin_feed(response: &serde_json::Value) -> (Vec<String>, Option<String>) {
+        let pins = response
+            .get("data")
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter(|item| item.get("type").and_then(|v| v.as_str()) == Some("pin"))
+                    .filter_map(|item| item.get("id").and_then(|v| v.as_str()))
+                    .map(|id| format!("https://www.pinterest.com/pin/{}/", id))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let bookmark = response
+            .get("bookmark")
+            .and_then(|v| v.as_str())
+            .filter(|b| !b.is_empty() && *b != "-end-")
+            .map(str::to_string);
+        (pins, bookmark)
+    }
+
+    async fn expand_board(&self, board: &BoardRef) -> anyhow::Result<Vec<String>> {
+        let source_url = board.source_url();
+        let board_data = self
+            .fetch_resource(
+                "Board",
+                &source_url,
+                serde_json::json!({
+                    "username": board.username,
+                    "slug": board.slug,
+                    "field_set_key": "detailed",
+                }),
+            )
+            .await?;
+        let board_data = board_data.get("data").unwrap_or(&serde_json::Value::Null);
+        let board_id = board_data
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or(DownloadError::NotFound)?
+            .to_string();
+        let secret = board_data.get("privacy").and_then(|v| v.as_str()) == Some("secret");
+
+        let (resource, mut options) = match &board.section {
+            Some(section) => {
+                let section_data = self
+                    .fetch_resource(
+                        "BoardSection",
+                        &source_url,
+                        serde_json::json!({
+                            "username": board.username,
+                            "board_slug": board.slug,
+                            "section_slug": section,
+                        }),
+                    )
+                    .await?;
+                let section_id = section_data
+                    .pointer("/data/id")
+                    .and_then(|v| v.as_str())
+                    .ok_or(DownloadError::NotFound)?
+                    .to_string();
+                (
+                    "BoardSectionPins",
+                    serde_json::json!({ "section_id": section_id, "page_size": BOARD_PAGE_SIZE }),
+                )
+            }
+            None => (
+                "BoardFeed",
+                serde_json::json!({ "board_id": board_id, "page_size": BOARD_PAGE_SIZE }),
+            ),
+        };
+
+        let mut pins: Vec<String> = Vec::new();
+        let mut last_bookmark: Option<String> = None;
+        for _ in 0..MAX_BOARD_PAGES {
+            let page = self
+                .fetch_resource(resource, &source_url, options.clone())
+                .await?;
+            let (page_pins, bookmark) = Self::parse_pin_feed(&page);
+            let before = pins.len();
+            for pin in page_pins {
+                if !pins.contains(&pin) {
+                    pins.push(pin);
+                }
+            }
+            if pins.len() == before {
+                break;
+            }
+            let Some(bookmark) = bookmark
+                .filter(|b| pins.len() < MAX_BOARD_PINS && last_bookmark.as_ref() != Some(b))
+            else {
+                break;
+            };
+            options["bookmarks"] = serde_json::json!([bookmark]);
+            last_bookmark = Some(bookmark);
+        }
+        pins.truncate(MAX_BOARD_PINS);
+
+        if pins.is_empty() {
+            return Err(if secret {
+                DownloadError::Private.into()
+            } else {
+                DownloadError::NotFound.into()
+            });
+        }
+        Ok(pins)
     }
 
     fn extract_pin_id(url: &str) -> Option<String> {
@@ -77,19 +287,9 @@ impl PinterestDownloader {
         })
     }
 
-    fn is_short_link(url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                return host == "pin.it";
-            }
-        }
-        false
-    }
-
     async fn resolve_pin_url(&self, url: &str) -> anyhow::Result<String> {
-        if Self::is_short_link(url) {
-            let canonical = redirect::resolve_redirect(&self.client, url).await?;
-            return Ok(canonical);
+        if redirect::is_short_link(url) {
+            return redirect::expand_short_link(url).await;
         }
         Ok(url.to_string())
     }
@@ -120,6 +320,82 @@ impl PinterestDownloader {
         PIN_NOT_FOUND_RE.is_match(html)
     }
 
+    /// The first JSON value stored under `key` in the page's embedded data,
+    /// read on its own so the rest of the script does not need to parse.
+    fn embedded_value(html: &str, key: &str) -> Option<serde_json::Value> {
+        let marker = format!("\"{}\":", key);
+        html.match_indices(&marker)
+            .filter_map(|(start, _)| Self::json_at(&html[start + marker.len()..]))
+            .find(|value| !value.is_null())
+    }
+
+    /// The JSON value `text` starts with, ignoring whatever follows it.
+    fn json_at(text: &str) -> Option<serde_json::Value> {
+        serde_json::Deserializer::from_str(text.trim_start())
+            .into_iter::<serde_json::Value>()
+            .next()?
+            .ok()
+    }
+
+    /// Who made the pin: its `native_creator`, else the `pinner` who saved
+    /// it. Read from the pin's own entry, keyed by its id, since the page
+    /// also embeds related pins with their own creators.
+    fn extract_author(html: &str, pin_id: &str) -> String {
+        let marker = format!("\"{}\":", pin_id);
+        html.match_indices(&marker)
+            .filter_map(|(start, _)| Self::json_at(&html[start + marker.len()..]))
+            .find_map(|pin| {
+                ["native_creator", "pinner"]
+                    .iter()
+                    .find_map(|key| pin.get(key)?.get("username")?.as_str().map(str::to_string))
+            })
+            .unwrap_or_default()
+    }
+
+    /// Renditions from the first `video_list` in the pin's embedded JSON,
+    /// best first. `V_HLSV4` and the other playlists are only kept when
+    /// the pin has no MP4, since those download without remuxing.
+    fn extract_video_renditions(html: &str) -> Vec<VideoQuality> {
+        let Some(serde_json::Value::Object(list)) = Self::embedded_value(html, "video_list") else {
+            return Vec::new();
+        };
+
+        let mut mp4 = Vec::new();
+        let mut hls = Vec::new();
+        for (key, entry) in &list {
+            let Some(url) = entry.get("url").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let width = entry.get("width").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+            let height = entry.get("height").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+            // Most pins are portrait, so name the rendition by its short side.
+            let label = match width.min(height) {
+                0 => key.trim_start_matches("V_").to_lowercase(),
+                side => format!("{}p", side),
+            };
+            let is_hls = url.contains(".m3u8");
+            let quality = VideoQuality {
+                id: Some(key.to_lowercase()),
+                label,
+                width,
+                height,
+                url: url.to_string(),
+                format: if is_hls { "m3u8" } else { "mp4" }.to_string(),
+                ..Default::default()
+            };
+            if is_hls {
+                hls.push(quality);
+            } else if url.ends_with(".mp4") {
+                mp4.push(quality);
+            }
+        }
+
+        let mut renditions = if mp4.is_empty() { hls } else { mp4 };
+        renditions.sort_by(|a, b| b.height.cmp(&a.height));
+        renditions.dedup_by(|a, b| a.url == b.url || a.label == b.label);
+        renditions
+    }
+
     fn extract_video_url(html: &str) -> Option<String> {
         VIDEO_URL_RE
             .captures_iter(html)
@@ -151,42 +427,36 @@ impl PlatformDownloader for PinterestDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host == "pin.it"
-                    || host.contains("pinterest.com")
-                    || host.contains("pinterest.ca")
-                    || host.contains("pinterest.co.uk")
-                    || host.contains("pinterest.fr")
-                    || host.contains("pinterest.de")
-                    || host.contains("pinterest.es")
-                    || host.contains("pinterest.it")
-                    || host.contains("pinterest.pt")
-                    || host.contains("pinterest.jp")
-                    || host.contains("pinterest.kr")
-                    || host.contains("pinterest.com.br")
-                    || host.contains("pinterest.com.mx")
-                    || host.contains("pinterest.co.kr")
-                    || host.contains("pinterest.cl")
-                    || host.contains("pinterest.at")
-                    || host.contains("pinterest.ch")
-                    || host.contains("pinterest.com.au")
-                    || host.contains("pinterest.co.in")
-                    || host.contains("pinterest.nz")
-                    || host.contains("pinterest.ph")
-                    || host.contains("pinterest.ru")
-                    || host.contains("pinterest.se")
-                    || host.contains("pinterest.dk")
-                    || host.contains("pinterest.");
-            }
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["pin.it", "pinterest.*"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_audio_only: true,
+            supports_expansion: true,
+            ..Capabilities::default()
         }
-        false
+    }
+
+    async fn expand(&self, url: &str) -> anyhow::Result<Option<Vec<String>>> {
+        let url = self.resolve_pin_url(url).await?;
+        let Some(board) = Self::parse_board_url(&url) else {
+            return Ok(None);
+        };
+        tracing::info!("[pinterest] expanding board {}", board.source_url());
+        self.expand_board(&board).await.map(Some)
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
         match self.native_get_media_info(url).await {
-            Ok(info) => Ok(info),
+            Ok(mut info) => {
+                crate::core::direct_downloader::fill_file_size(&self.client, &mut info, None).await;
+                Ok(info)
+            }
             Err(native_err) => {
                 tracing::warn!(
                     "[pinterest] native failed: {}, trying yt-dlp fallback",
@@ -224,20 +494,67 @@ impl PlatformDownloader for PinterestDownloader {
                     false,
                     &[],
                     opts.audio_format.as_deref(),
+                    &opts.quality_preference,
+                    &opts.extra_ytdlp_args,
                 )
                 .await;
             }
         }
 
-        let quality = info
-            .available_qualities
-            .first()
+        let quality = opts
+            .pick_quality(&info.available_qualities)
             .ok_or_else(|| anyhow!("No media URL available"))?;
 
+        if quality.format == "m3u8" {
+            let filename = opts.media_filename(info, "mp4");
+            let output_path = match opts.output_path(&filename) {
+                Ok(path) => path,
+                Err(skipped) => return Ok(skipped),
+            };
+            let output_str = output_path.to_string_lossy().to_string();
+
+            let downloader = HlsDownloader::with_client(self.client.clone())
+                .with_user_agent_override(opts.user_agent.clone());
+            let _ = progress.send(ProgressUpdate::percent(0.0)).await;
+
+            let result = downloader
+                .download(
+                    &quality.url,
+                    &output_str,
+                    "https://www.pinterest.com/",
+                    None,
+                    opts.cancel_token.clone(),
+                    opts.hls_max_concurrent_segments,
+                    opts.hls_max_retries,
+                )
+                .await?;
+
+            let (file_path, file_size, note) = if opts.is_audio() {
+                ffmpeg::audio_from_downloaded(
+                    &result.path,
+                    result.file_size,
+                    opts.audio_format.as_deref(),
+                    opts.cancel_token.clone(),
+                )
+                .await?
+            } else {
+                (result.path, result.file_size, None)
+            };
+
+            let _ = progress.send(ProgressUpdate::percent(100.0)).await;
+
+            return Ok(DownloadResult {
+                note,
+                ..DownloadResult::new(file_path, file_size)
+            });
+        }
+
         let extension = &quality.format;
-        let filename = format!("{}.{}", info.title, extension);
-        let safe_filename = sanitize_filename::sanitize(&filename);
-        let output_path = opts.output_dir.join(&safe_filename);
+        let filename = opts.media_filename(info, extension);
+        let output_path = match opts.output_path(&filename) {
+            Ok(path) => path,
+            Err(skipped) => return Ok(skipped),
+        };
 
         let total_bytes = direct_downloader::download_direct(
             &self.client,
@@ -247,18 +564,33 @@ impl PlatformDownloader for PinterestDownloader {
             Some(&opts.cancel_token),
         )
         .await?;
+        let total_bytes = crate::core::image::strip_downloaded_image(
+            &output_path,
+            total_bytes,
+            opts.strip_metadata,
+        );
+        let (output_path, total_bytes, note) = if opts.is_audio() && extension == "mp4" {
+            ffmpeg::audio_from_downloaded(
+                &output_path,
+                total_bytes,
+                opts.audio_format.as_deref(),
+                opts.cancel_token.clone(),
+            )
+            .await?
+        } else {
+            (output_path, total_bytes, None)
+        };
 
         Ok(DownloadResult {
-            file_path: output_path,
-            file_size_bytes: total_bytes,
-            duration_seconds: 0.0,
-            torrent_id: None,
+            note,
+            ..DownloadResult::new(output_path, total_bytes)
         })
     }
 }
 
 impl PinterestDownloader {
     async fn fallback_ytdlp(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        omniget_core::platforms::check_ytdlp_fallback("pinterest")?;
         let ytdlp_path = crate::core::ytdlp::ensure_ytdlp().await?;
         let json = crate::core::ytdlp::get_video_info(&ytdlp_path, url, &[]).await?;
         crate::platforms::generic_ytdlp::GenericYtdlpDownloader::parse_video_info(&json)
@@ -273,25 +605,35 @@ impl PinterestDownloader {
         let html = self.fetch_pin_html(&pin_id).await?;
 
         if Self::check_pin_not_found(&html) {
-            return Err(anyhow!("Pin not found"));
+            return Err(DownloadError::NotFound.into());
         }
 
-        if let Some(video_url) = Self::extract_video_url(&html) {
-            return Ok(MediaInfo {
-                title: format!("pinterest_{}", pin_id),
-                author: String::new(),
-                platform: "pinterest".to_string(),
-                duration_seconds: None,
-                thumbnail_url: None,
-                available_qualities: vec![VideoQuality {
+        let author = Self::extract_author(&html, &pin_id);
+        let mut renditions = Self::extract_video_renditions(&html);
+        if renditions.is_empty() {
+            if let Some(video_url) = Self::extract_video_url(&html) {
+                renditions.push(VideoQuality {
                     label: "original".to_string(),
                     width: 0,
                     height: 0,
                     url: video_url,
                     format: "mp4".to_string(),
-                }],
+                    ..Default::default()
+                });
+            }
+        }
+
+        if !renditions.is_empty() {
+            return Ok(MediaInfo {
+                id: Some(pin_id.clone()),
+                title: format!("pinterest_{}", pin_id),
+                author,
+                platform: "pinterest".to_string(),
+                duration_seconds: None,
+                thumbnail_url: None,
+                available_qualities: renditions,
                 media_type: MediaType::Video,
-                file_size_bytes: None,
+                ..Default::default()
             });
         }
 
@@ -304,8 +646,9 @@ impl PinterestDownloader {
             let format = if is_gif { "gif" } else { "jpg" };
 
             return Ok(MediaInfo {
+                id: Some(pin_id.clone()),
                 title: format!("pinterest_{}", pin_id),
-                author: String::new(),
+                author,
                 platform: "pinterest".to_string(),
                 duration_seconds: None,
                 thumbnail_url: None,
@@ -315,12 +658,97 @@ impl PinterestDownloader {
                     height: 0,
                     url: image_url,
                     format: format.to_string(),
+                    ..Default::default()
                 }],
                 media_type,
-                file_size_bytes: None,
+                ..Default::default()
             });
         }
 
         Err(anyhow!("No media found in pin {}", pin_id))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_board_and_section_urls() {
+        assert_eq!(
+            PinterestDownloader::parse_board_url("https://www.pinterest.com/someone/recipes/"),
+            Some(BoardRef {
+                username: "someone".into(),
+                slug: "recipes".into(),
+                section: None,
+            })
+        );
+        let section =
+            PinterestDownloader::parse_board_url("https://br.pinterest.com/someone/recipes/soups/")
+                .unwrap();
+        assert_eq!(section.section.as_deref(), Some("soups"));
+        assert_eq!(section.source_url(), "/someone/recipes/soups/");
+
+        for url in [
+            "https://www.pinterest.com/pin/123456/",
+            "https://www.pinterest.com/someone/",
+            "https://www.pinterest.com/someone/_saved/",
+            "https://www.pinterest.com/search/pins/",
+            "https://www.pinterest.com/ideas/cats/9201/",
+        ] {
+            assert_eq!(PinterestDownloader::parse_board_url(url), None, "{}", url);
+        }
+    }
+
+    #[test]
+    fn renditions_prefer_mp4_and_sort_best_first() {
+        let html = r#"<script>{"videos":{"video_list":{"V_HLSV4":{"url":"https://v1.pinimg.com/videos/hls/ab.m3u8","width":720,"height":1280},"V_480P":{"url":"https://v1.pinimg.com/videos/480p/ab.mp4","width":480,"height":854},"V_720P":{"url":"https://v1.pinimg.com/videos/720p/ab.mp4","width":720,"height":1280}}}}</script>"#;
+        let renditions = PinterestDownloader::extract_video_renditions(html);
+        let labels: Vec<&str> = renditions.iter().map(|q| q.label.as_str()).collect();
+        assert_eq!(labels, ["720p", "480p"]);
+        assert_eq!(renditions[0].id.as_deref(), Some("v_720p"));
+
+        let hls_only = r#"{"video_list":{"V_HLSV4":{"url":"https://v1.pinimg.com/videos/hls/ab.m3u8","width":1080,"height":1920}}}"#;
+        let renditions = PinterestDownloader::extract_video_renditions(hls_only);
+        assert_eq!(renditions.len(), 1);
+        assert_eq!(renditions[0].format, "m3u8");
+        assert_eq!(renditions[0].label, "1080p");
+    }
+
+    #[test]
+    fn author_is_read_from_the_pin_itself() {
+        let html = r#"{"pins":{"42":{"id":"42","native_creator":null,"pinner":{"username":"saver"}},"99":{"id":"99","native_creator":{"username":"related"}}}}"#;
+        assert_eq!(PinterestDownloader::extract_author(html, "42"), "saver");
+        let made = r#"{"pins":{"42":{"native_creator":{"username":"maker"},"pinner":{"username":"saver"}}}}"#;
+        assert_eq!(PinterestDownloader::extract_author(made, "42"), "maker");
+        assert_eq!(PinterestDownloader::extract_author(html, "7"), "");
+        assert_eq!(
+            PinterestDownloader::extract_author("<html></html>", "42"),
+            ""
+        );
+    }
+
+    #[test]
+    fn pin_feed_keeps_pins_and_stops_at_end() {
+        let page = serde_json::json!({
+            "data": [
+                { "type": "pin", "id": "111" },
+                { "type": "story", "id": "222" },
+                { "type": "pin", "id": "333" },
+            ],
+            "bookmark": "Y2JVSG81V2sxcmNHRlpWM1J"
+        });
+        let (pins, bookmark) = PinterestDownloader::parse_pin_feed(&page);
+        assert_eq!(
+            pins,
+            [
+                "https://www.pinterest.com/pin/111/",
+                "https://www.pinterest.com/pin/333/"
+            ]
+        );
+        assert!(bookmark.is_some());
+
+        let last = serde_json::json!({ "data": [], "bookmark": "-end-" });
+        assert_eq!(PinterestDownloader::parse_pin_feed(&last), (vec![], None));
+    }
+}
@@ -1,24 +1,50 @@
+use std::sync::Arc;
+
 use anyhow::anyhow;
 use async_trait::async_trait;
-use omniget_core::models::progress::ProgressUpdate;
+use omniget_core::models::progress::{forward_part, ProgressUpdate};
 use tokio::sync::mpsc;
 
+use crate::core::cookie_parser;
 use crate::core::direct_downloader;
+use crate::core::errors::DownloadError;
 use crate::core::ffmpeg;
+use crate::core::filename;
 use crate::core::redirect;
-use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality};
-use crate::platforms::traits::PlatformDownloader;
+use crate::core::temp_dir;
+use crate::models::media::{
+    json_count, DownloadNote, DownloadOptions, DownloadResult, MediaInfo, MediaType, PostMetadata,
+    VideoQuality,
+};
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
 
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/133.0.0.0 Safari/537.36";
+/// Most posts taken from one feed, so a busy subreddit or a long user
+/// history does not flood the queue.
+const MAX_LISTING_ITEMS: usize = 100;
+/// Largest page Reddit's listing endpoints return.
+const LISTING_PAGE_SIZE: &str = "100";
+const LISTING_SORTS: &[&str] = &["hot", "new", "top", "rising", "controversial"];
 
 pub struct RedditDownloader {
     client: reqwest::Client,
+    /// A `reddit_session` cookie was loaded, so gated posts should be
+    /// visible.
+    logged_in: bool,
+    /// `token_v2` from the cookies; post lookups then go through
+    /// `oauth.reddit.com` the way the web app does.
+    oauth_token: Option<String>,
 }
 
 enum RedditMedia {
     Video {
         video_url: String,
         duration: Option<f64>,
+        width: u32,
+        height: u32,
+        /// Bits per second.
+        bitrate: Option<u64>,
     },
     Gif {
         url: String,
@@ -44,19 +70,66 @@ impl Default for RedditDownloader {
 
 impl RedditDownloader {
     pub fn new() -> Self {
-        let mut builder = crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
-            .user_agent(USER_AGENT)
-            .timeout(std::time::Duration::from_secs(120))
-            .connect_timeout(std::time::Duration::from_secs(15));
-
-        if let Some(jar) =
-            crate::core::cookie_parser::load_extension_cookies_for_domain("reddit.com")
-        {
+        let mut builder = crate::core::http_client::platform_client_builder("reddit", USER_AGENT);
+
+        let mut logged_in = false;
+        let mut oauth_token = None;
+        if let Some(jar) = Self::load_cookies() {
+            use reqwest::cookie::CookieStore;
+            let site = url::Url::parse("https://www.reddit.com/").expect("static url");
+            if let Some(header) = jar.cookies(&site) {
+                (logged_in, oauth_token) =
+                    Self::session_from_cookies(header.to_str().unwrap_or(""));
+            }
             builder = builder.cookie_provider(jar);
         }
 
         let client = builder.build().unwrap_or_default();
-        Self { client }
+        Self {
+            client,
+            logged_in,
+            oauth_token,
+        }
+    }
+
+    /// The cookie manager's file or the global cookie file, falling back to
+    /// the browser extension's cookies.
+    fn load_cookies() -> Option<Arc<reqwest::cookie::Jar>> {
+        crate::core::ytdlp::cookie_source_for_url("https://www.reddit.com/")
+            .and_then(|path| cookie_parser::load_cookie_file_for_domain(&path, "reddit.com"))
+            .or_else(|| cookie_parser::load_extension_cookies_for_domain("reddit.com"))
+    }
+
+    /// Whether `reddit_session` is set, and the `token_v2` bearer token, from
+    /// a `Cookie` header value.
+    fn session_from_cookies(header: &str) -> (bool, Option<String>) {
+        let mut logged_in = false;
+        let mut token = None;
+        for pair in header.split(';') {
+            let Some((name, value)) = pair.trim().split_once('=') else {
+                continue;
+            };
+            match name {
+                "reddit_session" => logged_in = !value.is_empty(),
+                "token_v2" if !value.is_empty() => token = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        (logged_in, token)
+    }
+
+    /// NSFW and quarantined posts come back with their media stripped when
+    /// fetched without an account.
+    fn is_gated(data: &serde_json::Value) -> bool {
+        let flag = |key: &str| data.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        flag("over_18") || flag("quarantine")
+    }
+
+    fn status_error(&self, status: reqwest::StatusCode) -> DownloadError {
+        match status.as_u16() {
+            401 | 403 if !self.logged_in => DownloadError::LoginRequired,
+            _ => DownloadError::from_status("Reddit", status),
+        }
     }
 
     fn extract_post_id(url: &str) -> Option<String> {
@@ -87,61 +160,126 @@ impl RedditDownloader {
         None
     }
 
-    fn is_short_link(url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host == "v.redd.it" || host == "redd.it";
+    /// `.json` listing endpoint behind a feed URL: a user's submissions, a
+    /// subreddit (optionally sorted) or a multireddit. `None` for posts and
+    /// anything else that is not a feed.
+    fn listing_endpoint(url: &str) -> Option<url::Url> {
+        let parsed = url::Url::parse(url).ok()?;
+        let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+        let path = match segments.as_slice() {
+            ["user" | "u", name] | ["user" | "u", name, "submitted"] => {
+                format!("user/{}/submitted", name)
+            }
+            ["user" | "u", name, "m", multi] => format!("user/{}/m/{}", name, multi),
+            ["user" | "u", name, "m", multi, sort] if LISTING_SORTS.contains(sort) => {
+                format!("user/{}/m/{}/{}", name, multi, sort)
+            }
+            ["r", sub] => format!("r/{}", sub),
+            ["r", sub, sort] if LISTING_SORTS.contains(sort) => format!("r/{}/{}", sub, sort),
+            _ => return None,
+        };
+
+        let mut endpoint =
+            url::Url::parse(&format!("https://www.reddit.com/{}.json", path)).ok()?;
+        {
+            let mut query = endpoint.query_pairs_mut();
+            query.append_pair("limit", LISTING_PAGE_SIZE);
+            query.append_pair("raw_json", "1");
+            // `t` is the time window of top/controversial sorts.
+            if let Some((_, window)) = parsed.query_pairs().find(|(k, _)| k == "t") {
+                query.append_pair("t", &window);
             }
         }
-        false
+        Some(endpoint)
     }
 
-    fn is_share_link(url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
-            return segments.len() >= 4 && segments[0] == "r" && segments[2] == "s";
-        }
-        false
+    /// Permalinks of the media posts on one listing page, plus the `after`
+    /// cursor of the next page. Listing children carry the same post data
+    /// as `fetch_post_data`, so `parse_media` decides what is downloadable
+    /// without a request per post; text and link posts are skipped.
+    fn parse_listing(json: &serde_json::Value) -> (Vec<String>, Option<String>) {
+        let posts = json
+            .pointer("/data/children")
+            .and_then(|v| v.as_array())
+            .map(|children| {
+                children
+                    .iter()
+                    .filter(|child| child.get("kind").and_then(|v| v.as_str()) == Some("t3"))
+                    .filter_map(|child| child.get("data"))
+                    .filter(|data| Self::parse_media(data).is_some())
+                    .filter_map(|data| data.get("permalink").and_then(|v| v.as_str()))
+                    .map(|permalink| format!("https://www.reddit.com{}", permalink))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let after = json
+            .pointer("/data/after")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+        (posts, after)
     }
 
-    async fn resolve_to_canonical(&self, url: &str) -> anyhow::Result<String> {
-        if Self::is_short_link(url) {
-            return redirect::resolve_redirect(&self.client, url).await;
-        }
+    async fn fetch_listing(&self, endpoint: &url::Url) -> anyhow::Result<serde_json::Value> {
+        let response = self
+            .client
+            .get(endpoint.as_str())
+            .header("Accept", "application/json")
+            .send()
+            .await?;
 
-        if Self::is_share_link(url) {
-            return redirect::resolve_redirect(&self.client, url).await;
+        if !response.status().is_success() {
+            return Err(self.status_error(response.status()).into());
         }
 
+        Ok(response.json().await?)
+    }
+
+    async fn resolve_to_canonical(&self, url: &str) -> anyhow::Result<String> {
+        if redirect::is_short_link(url) {
+            return redirect::expand_short_link(url).await;
+        }
         Ok(url.to_string())
     }
 
     async fn fetch_post_data(&self, post_id: &str) -> anyhow::Result<serde_json::Value> {
+        if let Some(ref token) = self.oauth_token {
+            let url = format!("https://oauth.reddit.com/comments/{}.json", post_id);
+            match self.fetch_post_json(&url, Some(token)).await {
+                Ok(data) => return Ok(data),
+                Err(e) => tracing::warn!("[reddit] oauth lookup failed: {}, retrying www", e),
+            }
+        }
         let url = format!("https://www.reddit.com/comments/{}.json", post_id);
+        self.fetch_post_json(&url, None).await
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Accept", "application/json")
-            .send()
-            .await?;
+    async fn fetch_post_json(
+        &self,
+        url: &str,
+        bearer: Option<&str>,
+    ) -> anyhow::Result<serde_json::Value> {
+        let mut request = self.client.get(url).header("Accept", "application/json");
+        if let Some(token) = bearer {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
 
         if !response.status().is_success() {
-            return Err(anyhow!("Reddit retornou HTTP {}", response.status()));
+            return Err(self.status_error(response.status()).into());
         }
 
         let json: serde_json::Value = response.json().await?;
 
         if !json.is_array() {
-            return Err(anyhow!("Post not found"));
+            return Err(DownloadError::NotFound.into());
         }
 
         json.as_array()
             .and_then(|arr| arr.first())
             .and_then(|listing| listing.pointer("/data/children/0/data"))
             .cloned()
-            .ok_or_else(|| anyhow!("Post not found"))
+            .ok_or_else(|| DownloadError::NotFound.into())
     }
 
     fn construct_audio_url(fallback_url: &str) -> Vec<String> {
@@ -202,6 +340,171 @@ impl RedditDownloader {
         variants
     }
 
+    /// The DASH renditions of `video_url` as qualities labelled by height,
+    /// with `dash-<height>` ids for `format_id`.
+    fn resolution_qualities(video_url: &str) -> Vec<VideoQuality> {
+        Self::get_resolution_variants(video_url)
+            .into_iter()
+            .filter_map(|url| {
+                let height: u32 = url
+                    .rsplit("DASH_")
+                    .next()?
+                    .split(['.', '?'])
+                    .next()?
+                    .parse()
+                    .ok()?;
+                Some(VideoQuality {
+                    id: Some(format!("dash-{}", height)),
+                    label: format!("{}p", height),
+                    width: 0,
+                    height,
+                    url,
+                    format: "mp4".to_string(),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Saves every selected DASH rendition as `<title>_<height>p.mp4`. The
+    /// audio track is fetched once and muxed into each file when FFmpeg is
+    /// available, or saved next to them as `<title>_audio.mp4` when it
+    /// isn't; renditions the CDN doesn't serve are skipped. Muted downloads
+    /// get no audio track and have any audio in the renditions stripped.
+    async fn download_qualities(
+        &self,
+        info: &MediaInfo,
+        video_url: &str,
+        audio_url: Option<&str>,
+        opts: &DownloadOptions,
+        progress: mpsc::Sender<ProgressUpdate>,
+    ) -> anyhow::Result<DownloadResult> {
+        let variants = Self::resolution_qualities(video_url);
+        let qualities = opts.quality_selection.select(&variants);
+        if qualities.is_empty() {
+            return Err(anyhow!("None of the requested qualities are available"));
+        }
+
+        let name = opts.media_filename(info, "mp4");
+        let title = name.strip_suffix(".mp4").unwrap_or(&name);
+        let has_ffmpeg = ffmpeg::is_ffmpeg_available().await;
+        let work_dir = temp_dir::for_output(&opts.output_dir);
+        let audio_target = match audio_url {
+            Some(_) if has_ffmpeg => Some(work_dir.join(format!("{}_audio_tmp.mp4", title))),
+            Some(_) => opts.output_path(&format!("{}_audio.mp4", title)).ok(),
+            None => None,
+        };
+        let audio_url = audio_url.filter(|_| audio_target.is_some());
+        let parts = qualities.len() + usize::from(audio_url.is_some());
+
+        let audio_ok = match (audio_url, &audio_target) {
+            (Some(url), Some(target)) => {
+                let (part_tx, forwarder) = forward_part(progress.clone(), 0, parts);
+                let ok = direct_downloader::download_direct(
+                    &self.client,
+                    url,
+                    target,
+                    part_tx,
+                    Some(&opts.cancel_token),
+                )
+                .await
+                .is_ok();
+                let _ = forwarder.await;
+                ok
+            }
+            _ => false,
+        };
+        let muxed_audio = audio_target.as_deref().filter(|_| audio_ok && has_ffmpeg);
+
+        let mut results = Vec::new();
+        let mut last_err = None;
+        for (i, quality) in qualities.iter().enumerate() {
+            if opts.cancel_token.is_cancelled() {
+                if let Some(target) = &audio_target {
+                    let _ = tokio::fs::remove_file(target).await;
+                }
+                return Err(anyhow!("Download cancelled"));
+            }
+            let filename = format!("{}_{}.mp4", title, quality.short_label());
+            let output = match opts.output_path(&filename) {
+                Ok(path) => path,
+                Err(existing) => {
+                    results.push(existing);
+                    continue;
+                }
+            };
+            let video_target = if muxed_audio.is_some() {
+                work_dir.join(format!("{}_{}_video_tmp.mp4", title, quality.short_label()))
+            } else {
+                output.clone()
+            };
+
+            let slot = i + parts - qualities.len();
+            let (part_tx, forwarder) = forward_part(progress.clone(), slot, parts);
+            let downloaded = direct_downloader::download_direct(
+                &self.client,
+                &quality.url,
+                &video_target,
+                part_tx,
+                Some(&opts.cancel_token),
+            )
+            .await;
+            let _ = forwarder.await;
+
+            let saved = match (downloaded, muxed_audio) {
+                (Ok(_), Some(audio)) => {
+                    let muxed = ffmpeg::mux_video_audio(&video_target, audio, &output).await;
+                    let _ = tokio::fs::remove_file(&video_target).await;
+                    muxed
+                }
+                (Ok(_), None) => Ok(()),
+                (Err(e), _) => {
+                    let _ = tokio::fs::remove_file(&video_target).await;
+                    Err(e)
+                }
+            };
+            let saved = match saved {
+                Ok(()) => tokio::fs::metadata(&output)
+                    .await
+                    .map(|m| m.len())
+                    .map_err(anyhow::Error::from),
+                Err(e) => Err(e),
+            };
+            let saved = match saved {
+                Ok(size) if opts.is_mute() => ffmpeg::mute_downloaded(&output, size).await,
+                Ok(size) => Ok((size, None)),
+                Err(e) => Err(e),
+            };
+            match saved {
+                Ok((size, note)) => results.push(DownloadResult {
+                    duration_seconds: info.duration_seconds.unwrap_or(0.0),
+                    note,
+                    ..DownloadResult::new(output, size)
+                }),
+                Err(e) => {
+                    tracing::warn!("[reddit] {} rendition failed: {}", quality.label, e);
+                    last_err = Some(e);
+                }
+            }
+        }
+
+        let _ = progress.send(ProgressUpdate::percent(100.0)).await;
+        let mut combined = DownloadResult::combine(results)
+            .ok_or_else(|| last_err.unwrap_or_else(|| anyhow!("No quality was downloaded")));
+        match (&mut combined, audio_target) {
+            (Ok(result), Some(audio)) if audio_ok && !has_ffmpeg && !result.skipped => {
+                result.file_size_bytes += tokio::fs::metadata(&audio).await.map_or(0, |m| m.len());
+                result.extra_files.push(audio);
+                result.note = Some(DownloadNote::FfmpegMissingSeparateTracks);
+            }
+            (_, Some(audio)) => {
+                let _ = tokio::fs::remove_file(audio).await;
+            }
+            _ => {}
+        }
+        combined
+    }
+
     async fn download_video_with_fallback(
         &self,
         video_url: &str,
@@ -261,10 +564,19 @@ impl RedditDownloader {
             let fallback = reddit_video.get("fallback_url").and_then(|v| v.as_str())?;
             let duration = reddit_video.get("duration").and_then(|v| v.as_f64());
             let video_url = fallback.split('?').next().unwrap_or(fallback).to_string();
+            let dimension =
+                |key: &str| reddit_video.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+            let bitrate = reddit_video
+                .get("bitrate_kbps")
+                .and_then(|v| v.as_u64())
+                .map(|kbps| kbps * 1000);
 
             return Some(RedditMedia::Video {
                 video_url,
                 duration,
+                width: dimension("width"),
+                height: dimension("height"),
+                bitrate,
             });
         }
 
@@ -288,6 +600,31 @@ impl RedditDownloader {
         None
     }
 
+    /// Title and self text, creation time, score and comment count of a
+    /// post. Reddit reports `created_utc` as a float.
+    fn extract_metadata(data: &serde_json::Value) -> PostMetadata {
+        let text = |key: &str| {
+            data.get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+        };
+        let description = match (text("title"), text("selftext")) {
+            (Some(title), Some(body)) => Some(format!("{}\n\n{}", title, body)),
+            (title, body) => title.or(body).map(String::from),
+        };
+        PostMetadata {
+            description,
+            timestamp: data
+                .get("created_utc")
+                .and_then(|v| v.as_f64())
+                .map(|t| t as i64),
+            like_count: json_count(data.get("score")),
+            comment_count: json_count(data.get("num_comments")),
+            ..Default::default()
+        }
+    }
+
     fn parse_gallery(data: &serde_json::Value) -> Option<RedditMedia> {
         let gallery_data = data.get("gallery_data")?.get("items")?.as_array()?;
         let media_metadata = data.get("media_metadata")?;
@@ -342,21 +679,74 @@ impl PlatformDownloader for RedditDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host == "reddit.com"
-                    || host.ends_with(".reddit.com")
-                    || host == "v.redd.it"
-                    || host == "redd.it";
+        // Bare `redd.it` short links only; `i.redd.it` and
+        // `preview.redd.it` are plain image files.
+        url_matches_hosts(url, self.supported_hosts())
+            || url::Url::parse(url).is_ok_and(|u| u.host_str() == Some("redd.it"))
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["reddit.com", "v.redd.it"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_quality_selection: true,
+            supports_audio_only: true,
+            native_formats: true,
+            supports_expansion: true,
+            ..Capabilities::default()
+        }
+    }
+
+    async fn expand(&self, url: &str) -> anyhow::Result<Option<Vec<String>>> {
+        let Some(endpoint) = Self::listing_endpoint(url) else {
+            return Ok(None);
+        };
+
+        let mut permalinks: Vec<String> = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let mut page = endpoint.clone();
+            if let Some(ref cursor) = after {
+                page.query_pairs_mut().append_pair("after", cursor);
+            }
+            let json = self.fetch_listing(&page).await?;
+            let (posts, next) = Self::parse_listing(&json);
+            permalinks.extend(posts);
+            if permalinks.len() >= MAX_LISTING_ITEMS || next.is_none() {
+                break;
             }
+            after = next;
         }
-        false
+        permalinks.truncate(MAX_LISTING_ITEMS);
+
+        if permalinks.is_empty() {
+            return Err(DownloadError::NotFound.into());
+        }
+        Ok(Some(permalinks))
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
         match self.native_get_media_info(url).await {
-            Ok(info) => Ok(info),
+            Ok(mut info) => {
+                if info.media_type == MediaType::Video {
+                    // Video and audio are fetched separately and muxed. The
+                    // DASH renditions are alternatives to the default video,
+                    // so they don't add to its size.
+                    let urls: Vec<&str> = info
+                        .available_qualities
+                        .iter()
+                        .filter(|q| q.label == "video" || q.label == "audio")
+                        .map(|q| q.url.as_str())
+                        .collect();
+                    info.file_size_bytes =
+                        direct_downloader::probe_total_size(&self.client, &urls, None).await;
+                } else {
+                    direct_downloader::fill_file_size(&self.client, &mut info, None).await;
+                }
+                Ok(info)
+            }
             Err(native_err) => {
                 tracing::warn!(
                     "[reddit] native failed: {}, trying yt-dlp fallback",
@@ -392,6 +782,8 @@ impl PlatformDownloader for RedditDownloader {
                     false,
                     &[],
                     opts.audio_format.as_deref(),
+                    &opts.quality_preference,
+                    &opts.extra_ytdlp_args,
                 )
                 .await;
             }
@@ -403,6 +795,7 @@ impl PlatformDownloader for RedditDownloader {
 
 impl RedditDownloader {
     async fn fallback_ytdlp(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        omniget_core::platforms::check_ytdlp_fallback("reddit")?;
         let ytdlp_path = crate::core::ytdlp::ensure_ytdlp().await?;
         let json = crate::core::ytdlp::get_video_info(&ytdlp_path, url, &[]).await?;
         crate::platforms::generic_ytdlp::GenericYtdlpDownloader::parse_video_info(&json)
@@ -418,7 +811,13 @@ impl RedditDownloader {
 
         let data = self.fetch_post_data(&post_id).await?;
 
-        let media = Self::parse_media(&data).ok_or_else(|| anyhow!("No media found in post"))?;
+        let media = match Self::parse_media(&data) {
+            Some(media) => media,
+            None if !self.logged_in && Self::is_gated(&data) => {
+                return Err(DownloadError::LoginRequired.into());
+            }
+            None => return Err(anyhow!("No media found in post")),
+        };
 
         let source_id = if subreddit.is_empty() {
             post_id.clone()
@@ -427,19 +826,26 @@ impl RedditDownloader {
         };
 
         let title = format!("reddit_{}", source_id);
+        let metadata = Some(Self::extract_metadata(&data));
 
         match media {
             RedditMedia::Video {
                 video_url,
                 duration,
+                width,
+                height,
+                bitrate,
             } => {
                 let audio = self.find_audio_url(&video_url).await;
+                let renditions = Self::resolution_qualities(&video_url);
                 let mut qualities = vec![VideoQuality {
                     label: "video".to_string(),
-                    width: 0,
-                    height: 0,
+                    width,
+                    height,
                     url: video_url,
                     format: "mp4".to_string(),
+                    bitrate,
+                    ..Default::default()
                 }];
 
                 if let Some(audio_url) = audio {
@@ -449,10 +855,13 @@ impl RedditDownloader {
                         height: 0,
                         url: audio_url,
                         format: "mp4_audio".to_string(),
+                        ..Default::default()
                     });
                 }
+                qualities.extend(renditions);
 
                 Ok(MediaInfo {
+                    id: Some(post_id.clone()),
                     title,
                     author: subreddit,
                     platform: "reddit".to_string(),
@@ -460,10 +869,12 @@ impl RedditDownloader {
                     thumbnail_url: None,
                     available_qualities: qualities,
                     media_type: MediaType::Video,
-                    file_size_bytes: None,
+                    metadata,
+                    ..Default::default()
                 })
             }
             RedditMedia::Gif { url: gif_url } => Ok(MediaInfo {
+                id: Some(post_id.clone()),
                 title,
                 author: subreddit,
                 platform: "reddit".to_string(),
@@ -475,9 +886,11 @@ impl RedditDownloader {
                     height: 0,
                     url: gif_url,
                     format: "gif".to_string(),
+                    ..Default::default()
                 }],
                 media_type: MediaType::Gif,
-                file_size_bytes: None,
+                metadata,
+                ..Default::default()
             }),
             RedditMedia::Image { url: image_url } => {
                 let ext = if image_url.ends_with(".png") {
@@ -486,6 +899,7 @@ impl RedditDownloader {
                     "jpg"
                 };
                 Ok(MediaInfo {
+                    id: Some(post_id.clone()),
                     title,
                     author: subreddit,
                     platform: "reddit".to_string(),
@@ -497,9 +911,11 @@ impl RedditDownloader {
                         height: 0,
                         url: image_url,
                         format: ext.to_string(),
+                        ..Default::default()
                     }],
                     media_type: MediaType::Photo,
-                    file_size_bytes: None,
+                    metadata,
+                    ..Default::default()
                 })
             }
             RedditMedia::Gallery { items } => {
@@ -512,10 +928,12 @@ impl RedditDownloader {
                         height: 0,
                         url: item.url,
                         format: item.ext,
+                        ..Default::default()
                     })
                     .collect();
 
                 Ok(MediaInfo {
+                    id: Some(post_id.clone()),
                     title,
                     author: subreddit,
                     platform: "reddit".to_string(),
@@ -523,7 +941,8 @@ impl RedditDownloader {
                     thumbnail_url: None,
                     available_qualities: qualities,
                     media_type: MediaType::Carousel,
-                    file_size_bytes: None,
+                    metadata,
+                    ..Default::default()
                 })
             }
         }
@@ -542,28 +961,58 @@ impl RedditDownloader {
                     .iter()
                     .find(|q| q.label == "video")
                     .ok_or_else(|| anyhow!("No video URL"))?;
+                // A rendition picked by `format_id` or label is tried first;
+                // the CDN may still not serve it, so the others follow.
+                let video_url = VideoQuality::find_requested(
+                    &info.available_qualities,
+                    opts.format_id.as_deref(),
+                    opts.quality.as_deref(),
+                )
+                .filter(|q| q.id.is_some())
+                .map_or(video_quality.url.as_str(), |q| q.url.as_str());
+
+                // Reddit serves the tracks apart, so muting is just not fetching the audio.
+                let audio_quality = info
+                    .available_qualities
+                    .iter()
+                    .find(|q| q.label == "audio")
+                    .filter(|_| !opts.is_mute());
 
-                let audio_quality = info.available_qualities.iter().find(|q| q.label == "audio");
+                if opts.quality_selection.is_multi() {
+                    return self
+                        .download_qualities(
+                            info,
+                            &video_quality.url,
+                            audio_quality.map(|q| q.url.as_str()),
+                            opts,
+                            progress,
+                        )
+                        .await;
+                }
 
                 let has_audio = audio_quality.is_some();
                 let ffmpeg_available = ffmpeg::is_ffmpeg_available().await;
 
+                // Reddit has no stream with both tracks, so without ffmpeg they are
+                // saved side by side whatever `ffmpeg_missing_policy` says.
                 if has_audio && !ffmpeg_available {
-                    tracing::warn!("[reddit] Video has separate audio but FFmpeg is not installed — downloading video without audio");
+                    tracing::warn!("[reddit] Video has separate audio but FFmpeg is not installed — saving video and audio separately");
                 }
 
                 if has_audio {
-                    let video_tmp = opts.output_dir.join(format!(
+                    let work_dir = temp_dir::for_output(&opts.output_dir);
+                    let video_tmp = work_dir.join(format!(
                         "{}_video_tmp.mp4",
                         sanitize_filename::sanitize(&info.title)
                     ));
-                    let audio_tmp = opts.output_dir.join(format!(
+                    let audio_tmp = work_dir.join(format!(
                         "{}_audio_tmp.mp4",
                         sanitize_filename::sanitize(&info.title)
                     ));
-                    let output = opts
-                        .output_dir
-                        .join(format!("{}.mp4", sanitize_filename::sanitize(&info.title)));
+                    let output = match opts.output_path(&opts.media_filename(info, "mp4")) {
+                        Ok(path) => path,
+                        Err(skipped) => return Ok(skipped),
+                    };
 
                     let _ = progress.send(ProgressUpdate::percent(0.0)).await;
 
@@ -580,7 +1029,7 @@ impl RedditDownloader {
 
                     let video_bytes = self
                         .download_video_with_fallback(
-                            &video_quality.url,
+                            video_url,
                             &video_tmp,
                             vtx,
                             Some(&opts.cancel_token),
@@ -621,25 +1070,40 @@ impl RedditDownloader {
 
                         let file_size = tokio::fs::metadata(&output).await?.len();
                         Ok(DownloadResult {
-                            file_path: output,
-                            file_size_bytes: file_size,
                             duration_seconds: info.duration_seconds.unwrap_or(0.0),
-                            torrent_id: None,
+                            ..DownloadResult::new(output, file_size)
                         })
                     } else {
-                        let video_final = opts.output_dir.join(format!(
+                        let video_final = match opts.output_path(&format!(
                             "{}{}.mp4",
                             sanitize_filename::sanitize(&info.title),
                             if !audio_ok { "" } else { "_noaudio" }
-                        ));
-                        let _ = tokio::fs::rename(&video_tmp, &video_final).await;
-
+                        )) {
+                            Ok(path) => path,
+                            Err(skipped) => {
+                                let _ = tokio::fs::remove_file(&video_tmp).await;
+                                let _ = tokio::fs::remove_file(&audio_tmp).await;
+                                return Ok(skipped);
+                            }
+                        };
+                        let _ = temp_dir::move_file(&video_tmp, &video_final).await;
+
+                        let mut extra_files = Vec::new();
                         if audio_ok {
-                            let audio_final = opts.output_dir.join(format!(
+                            let audio_final = opts.output_path(&format!(
                                 "{}_audio.mp4",
                                 sanitize_filename::sanitize(&info.title)
                             ));
-                            let _ = tokio::fs::rename(&audio_tmp, &audio_final).await;
+                            match audio_final {
+                                Ok(path) => {
+                                    if temp_dir::move_file(&audio_tmp, &path).await.is_ok() {
+                                        extra_files.push(path);
+                                    }
+                                }
+                                Err(_) => {
+                                    let _ = tokio::fs::remove_file(&audio_tmp).await;
+                                }
+                            }
                         } else {
                             let _ = tokio::fs::remove_file(&audio_tmp).await;
                         }
@@ -647,19 +1111,20 @@ impl RedditDownloader {
                         let _ = progress.send(ProgressUpdate::percent(100.0)).await;
 
                         Ok(DownloadResult {
-                            file_path: video_final,
-                            file_size_bytes: video_bytes,
                             duration_seconds: info.duration_seconds.unwrap_or(0.0),
-                            torrent_id: None,
+                            note: audio_ok.then_some(DownloadNote::FfmpegMissingSeparateTracks),
+                            extra_files,
+                            ..DownloadResult::new(video_final, video_bytes)
                         })
                     }
                 } else {
-                    let output = opts
-                        .output_dir
-                        .join(format!("{}.mp4", sanitize_filename::sanitize(&info.title)));
+                    let output = match opts.output_path(&opts.media_filename(info, "mp4")) {
+                        Ok(path) => path,
+                        Err(skipped) => return Ok(skipped),
+                    };
                     let bytes = self
                         .download_video_with_fallback(
-                            &video_quality.url,
+                            video_url,
                             &output,
                             progress,
                             Some(&opts.cancel_token),
@@ -667,10 +1132,8 @@ impl RedditDownloader {
                         .await?;
 
                     Ok(DownloadResult {
-                        file_path: output,
-                        file_size_bytes: bytes,
                         duration_seconds: info.duration_seconds.unwrap_or(0.0),
-                        torrent_id: None,
+                        ..DownloadResult::new(output, bytes)
                     })
                 }
             }
@@ -678,11 +1141,12 @@ impl RedditDownloader {
                 let url = &info
                     .available_qualities
                     .first()
-                    .ok_or_else(|| anyhow!("Nenhum URL GIF"))?
+                    .ok_or_else(|| anyhow!("No GIF URL"))?
                     .url;
-                let output = opts
-                    .output_dir
-                    .join(format!("{}.gif", sanitize_filename::sanitize(&info.title)));
+                let output = match opts.output_path(&opts.media_filename(info, "gif")) {
+                    Ok(path) => path,
+                    Err(skipped) => return Ok(skipped),
+                };
                 let bytes = direct_downloader::download_direct(
                     &self.client,
                     url,
@@ -692,24 +1156,19 @@ impl RedditDownloader {
                 )
                 .await?;
 
-                Ok(DownloadResult {
-                    file_path: output,
-                    file_size_bytes: bytes,
-                    duration_seconds: 0.0,
-                    torrent_id: None,
-                })
+                Ok(DownloadResult::new(output, bytes))
             }
             MediaType::Photo => {
                 let quality = info
                     .available_qualities
                     .first()
-                    .ok_or_else(|| anyhow!("Nenhum URL de imagem"))?;
+                    .ok_or_else(|| anyhow!("No image URL"))?;
                 let ext = &quality.format;
-                let output = opts.output_dir.join(format!(
-                    "{}.{}",
-                    sanitize_filename::sanitize(&info.title),
-                    ext
-                ));
+                let filename = opts.media_filename(info, ext);
+                let output = match opts.output_path(&filename) {
+                    Ok(path) => path,
+                    Err(skipped) => return Ok(skipped),
+                };
                 let bytes = direct_downloader::download_direct(
                     &self.client,
                     &quality.url,
@@ -718,27 +1177,32 @@ impl RedditDownloader {
                     Some(&opts.cancel_token),
                 )
                 .await?;
+                let bytes =
+                    crate::core::image::strip_downloaded_image(&output, bytes, opts.strip_metadata);
 
-                Ok(DownloadResult {
-                    file_path: output,
-                    file_size_bytes: bytes,
-                    duration_seconds: 0.0,
-                    torrent_id: None,
-                })
+                Ok(DownloadResult::new(output, bytes))
             }
             MediaType::Carousel => {
                 let count = info.available_qualities.len();
                 let mut total_bytes = 0u64;
-                let mut last_path = opts.output_dir.clone();
+                let mut saved = Vec::new();
+                let mut already_saved = None;
 
                 for (i, quality) in info.available_qualities.iter().enumerate() {
-                    let filename = format!(
-                        "{}_{}.{}",
-                        sanitize_filename::sanitize(&info.title),
-                        i + 1,
-                        quality.format,
+                    if !opts.is_selected(i) {
+                        continue;
+                    }
+                    let filename = filename::with_quality_suffix(
+                        &opts.media_filename(info, &quality.format),
+                        &(i + 1).to_string(),
                     );
-                    let output = opts.output_dir.join(&filename);
+                    let output = match opts.output_path(&filename) {
+                        Ok(path) => path,
+                        Err(existing) => {
+                            already_saved.get_or_insert(existing);
+                            continue;
+                        }
+                    };
                     let (tx, _rx) = mpsc::channel(8);
 
                     let bytes = direct_downloader::download_direct(
@@ -750,21 +1214,147 @@ impl RedditDownloader {
                     )
                     .await?;
 
-                    total_bytes += bytes;
-                    last_path = output;
+                    total_bytes += crate::core::image::strip_downloaded_image(
+                        &output,
+                        bytes,
+                        opts.strip_metadata,
+                    );
+                    saved.push(output);
 
                     let percent = ((i + 1) as f64 / count as f64) * 100.0;
                     let _ = progress.send(ProgressUpdate::percent(percent)).await;
                 }
 
+                if let Some(existing) = already_saved.filter(|_| saved.is_empty()) {
+                    return Ok(existing);
+                }
+                let file_path = saved.pop().unwrap_or_else(|| opts.output_dir.clone());
+
                 Ok(DownloadResult {
-                    file_path: last_path,
-                    file_size_bytes: total_bytes,
-                    duration_seconds: 0.0,
-                    torrent_id: None,
+                    extra_files: saved,
+                    ..DownloadResult::new(file_path, total_bytes)
                 })
             }
             _ => Err(anyhow!("Unsupported media type")),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_reddit_video_and_short_links_but_not_image_hosts() {
+        let downloader = RedditDownloader::new();
+        assert!(downloader.can_handle("https://www.reddit.com/r/rust/comments/abc/x/"));
+        assert!(downloader.can_handle("https://v.redd.it/abc123"));
+        assert!(downloader.can_handle("https://redd.it/abc123"));
+        assert!(!downloader.can_handle("https://i.redd.it/cat.jpg"));
+        assert!(!downloader.can_handle("https://preview.redd.it/cat.jpg?width=640"));
+    }
+
+    #[test]
+    fn maps_feed_urls_to_listing_endpoints() {
+        let endpoint = |url: &str| {
+            RedditDownloader::listing_endpoint(url).map(|u| {
+                let mut u = u;
+                u.set_query(None);
+                u.to_string()
+            })
+        };
+        assert_eq!(
+            endpoint("https://www.reddit.com/user/spez/submitted/").as_deref(),
+            Some("https://www.reddit.com/user/spez/submitted.json")
+        );
+        assert_eq!(
+            endpoint("https://old.reddit.com/r/videos/top/?t=week").as_deref(),
+            Some("https://www.reddit.com/r/videos/top.json")
+        );
+        assert_eq!(
+            endpoint("https://www.reddit.com/user/someone/m/cats/new").as_deref(),
+            Some("https://www.reddit.com/user/someone/m/cats/new.json")
+        );
+        assert_eq!(
+            endpoint("https://www.reddit.com/r/videos/comments/abc123/title/"),
+            None
+        );
+        assert_eq!(endpoint("https://www.reddit.com/r/videos/wiki"), None);
+
+        let top =
+            RedditDownloader::listing_endpoint("https://www.reddit.com/r/pics/top?t=all").unwrap();
+        assert!(top.query_pairs().any(|(k, v)| k == "t" && v == "all"));
+    }
+
+    #[test]
+    fn reads_session_cookies() {
+        assert_eq!(
+            RedditDownloader::session_from_cookies("loid=1; reddit_session=abc; token_v2=jwt"),
+            (true, Some("jwt".to_string()))
+        );
+        assert_eq!(
+            RedditDownloader::session_from_cookies("loid=1; token_v2="),
+            (false, None)
+        );
+    }
+
+    #[test]
+    fn nsfw_and_quarantined_posts_are_gated() {
+        assert!(RedditDownloader::is_gated(
+            &serde_json::json!({ "over_18": true })
+        ));
+        assert!(RedditDownloader::is_gated(
+            &serde_json::json!({ "quarantine": true })
+        ));
+        assert!(!RedditDownloader::is_gated(
+            &serde_json::json!({ "over_18": false })
+        ));
+    }
+
+    #[test]
+    fn metadata_joins_title_and_self_text() {
+        let meta = RedditDownloader::extract_metadata(&serde_json::json!({
+            "title": "My cat",
+            "selftext": "",
+            "created_utc": 1700000000.0,
+            "score": 321,
+            "num_comments": 12
+        }));
+        assert_eq!(meta.description.as_deref(), Some("My cat"));
+        assert_eq!(meta.timestamp, Some(1_700_000_000));
+        assert_eq!(meta.like_count, Some(321));
+        assert_eq!(meta.comment_count, Some(12));
+
+        let meta = RedditDownloader::extract_metadata(&serde_json::json!({
+            "title": "Question",
+            "selftext": "Details"
+        }));
+        assert_eq!(meta.description.as_deref(), Some("Question\n\nDetails"));
+    }
+
+    #[test]
+    fn listing_keeps_only_media_posts() {
+        let json = serde_json::json!({
+            "data": {
+                "after": "t3_next",
+                "children": [
+                    { "kind": "t3", "data": {
+                        "permalink": "/r/pics/comments/a1/cat/",
+                        "url": "https://i.redd.it/cat.jpg"
+                    }},
+                    { "kind": "t3", "data": {
+                        "permalink": "/r/pics/comments/a2/question/",
+                        "url": "https://www.reddit.com/r/pics/comments/a2/question/",
+                        "is_self": true
+                    }}
+                ]
+            }
+        });
+        let (posts, after) = RedditDownloader::parse_listing(&json);
+        assert_eq!(
+            posts,
+            vec!["https://www.reddit.com/r/pics/comments/a1/cat/".to_string()]
+        );
+        assert_eq!(after.as_deref(), Some("t3_next"));
+    }
+}
@@ -0,0 +1,509 @@
+use omniget_core::models::progress::ProgressUpdate;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use regex::Regex;
+use tokio::sync::{mpsc, RwLock};
+
+use crate::core::direct_downloader;
+use crate::core::errors::DownloadError;
+use crate::core::hls_downloader::HlsDownloader;
+use crate::core::redirect;
+use crate::core::ytdlp;
+use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality};
+use crate::platforms::generic_ytdlp::GenericYtdlpDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
+
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+const API_BASE: &str = "https://api-v2.soundcloud.com";
+/// `/tracks?ids=` takes at most this many ids per request.
+const TRACK_BATCH: usize = 50;
+
+static SCRIPT_SRC_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<script[^>]+src="(https://a-v2\.sndcdn\.com/assets/[^"]+\.js)""#)
+        .expect("valid SCRIPT_SRC_RE")
+});
+
+static CLIENT_ID_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"client_id\s*[:=]\s*"?([0-9A-Za-z]{32})\b"#).expect("valid CLIENT_ID_RE")
+});
+
+pub struct SoundCloudDownloader {
+    client: reqwest::Client,
+    /// The web player's public client id, scraped once and reused until the
+    /// API rejects it.
+    client_id: RwLock<Option<String>>,
+}
+
+impl Default for SoundCloudDownloader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoundCloudDownloader {
+    pub fn new() -> Self {
+        let client = crate::core::http_client::platform_client_builder("soundcloud", USER_AGENT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            client_id: RwLock::new(None),
+        }
+    }
+
+    fn extract_client_id(script: &str) -> Option<String> {
+        CLIENT_ID_RE
+            .captures(script)
+            .and_then(|cap| cap.get(1))
+            .map(|m| m.as_str().to_string())
+    }
+
+    /// The id sits in one of the asset bundles the homepage loads, usually
+    /// one of the last, so they are scanned from the end.
+    async fn discover_client_id(&self) -> anyhow::Result<String> {
+        let html = self
+            .client
+            .get("https://soundcloud.com/")
+            .header("Accept", "text/html")
+            .send()
+            .await?
+            .text()
+            .await?;
+
+        let scripts: Vec<String> = SCRIPT_SRC_RE
+            .captures_iter(&html)
+            .filter_map(|cap| cap.get(1).map(|m| m.as_str().to_string()))
+            .collect();
+
+        for script_url in scripts.iter().rev() {
+            let Ok(response) = self.client.get(script_url).send().await else {
+                continue;
+            };
+            let Ok(body) = response.text().await else {
+                continue;
+            };
+            if let Some(id) = Self::extract_client_id(&body) {
+                return Ok(id);
+            }
+        }
+
+        Err(anyhow!("Could not find a SoundCloud client_id"))
+    }
+
+    async fn client_id(&self) -> anyhow::Result<String> {
+        if let Some(id) = self.client_id.read().await.clone() {
+            return Ok(id);
+        }
+        let id = self.discover_client_id().await?;
+        *self.client_id.write().await = Some(id.clone());
+        Ok(id)
+    }
+
+    /// GETs an API endpoint with the client id, scraping a fresh id once if
+    /// the cached one has been rotated out.
+    async fn api_get(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+    ) -> anyhow::Result<serde_json::Value> {
+        for attempt in 0..2 {
+            let client_id = self.client_id().await?;
+            let response = self
+                .client
+                .get(url)
+                .query(query)
+                .query(&[("client_id", client_id.as_str())])
+                .send()
+                .await?;
+
+            let status = response.status();
+            if (status == reqwest::StatusCode::UNAUTHORIZED
+                || status == reqwest::StatusCode::FORBIDDEN)
+                && attempt == 0
+            {
+                *self.client_id.write().await = None;
+                continue;
+            }
+            if status == reqwest::StatusCode::NOT_FOUND {
+                return Err(DownloadError::NotFound.into());
+            }
+            if !status.is_success() {
+                return Err(anyhow!("HTTP {} from SoundCloud API", status));
+            }
+            return response.json().await.map_err(Into::into);
+        }
+        Err(anyhow!("SoundCloud rejected the client_id"))
+    }
+
+    async fn resolve(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+        let url = if redirect::is_short_link(url) {
+            redirect::expand_short_link(url).await?
+        } else {
+            url.to_string()
+        };
+        self.api_get(&format!("{}/resolve", API_BASE), &[("url", url.as_str())])
+            .await
+    }
+
+    /// The MP3 transcoding, progressive before HLS, with the track's
+    /// authorization appended the way the web player requests it. Other
+    /// codecs come as fMP4 or Ogg segments, which the HLS downloader can't
+    /// join into a playable file.
+    fn pick_transcoding(track: &serde_json::Value) -> Option<String> {
+        let transcodings = track.pointer("/media/transcodings")?.as_array()?;
+        let mp3 = |protocol: &str| {
+            transcodings.iter().find(|t| {
+                t.pointer("/format/protocol").and_then(|v| v.as_str()) == Some(protocol)
+                    && t.pointer("/format/mime_type")
+                        .and_then(|v| v.as_str())
+                        .is_some_and(|mime| mime.starts_with("audio/mpeg"))
+            })
+        };
+        let endpoint = mp3("progressive")
+            .or_else(|| mp3("hls"))?
+            .get("url")?
+            .as_str()?;
+
+        match track.get("track_authorization").and_then(|v| v.as_str()) {
+            Some(auth) => Some(format!("{}?track_authorization={}", endpoint, auth)),
+            None => Some(endpoint.to_string()),
+        }
+    }
+
+    fn is_set_url(url: &str) -> bool {
+        let Ok(parsed) = url::Url::parse(url) else {
+            return false;
+        };
+        let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+        matches!(segments.as_slice(), [_, "sets", _, ..])
+    }
+
+    async fn native_get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        let track = self.resolve(url).await?;
+        if track.get("kind").and_then(|v| v.as_str()) != Some("track") {
+            return Err(anyhow!("SoundCloud link is not a track"));
+        }
+
+        let endpoint = Self::pick_transcoding(&track)
+            .ok_or_else(|| anyhow!("No downloadable SoundCloud stream"))?;
+
+        let str_field = |pointer: &str| {
+            track
+                .pointer(pointer)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        };
+
+        Ok(MediaInfo {
+            id: track
+                .get("id")
+                .and_then(|v| v.as_u64())
+                .map(|id| id.to_string()),
+            title: str_field("/title").unwrap_or_else(|| "soundcloud".to_string()),
+            author: str_field("/user/username").unwrap_or_default(),
+            platform: "soundcloud".to_string(),
+            duration_seconds: track
+                .get("duration")
+                .and_then(|v| v.as_f64())
+                .map(|ms| ms / 1000.0),
+            // The API hands out the 100x100 rendition.
+            thumbnail_url: str_field("/artwork_url")
+                .or_else(|| str_field("/user/avatar_url"))
+                .map(|art| art.replace("-large.", "-t500x500.")),
+            available_qualities: vec![VideoQuality {
+                label: "mp3".to_string(),
+                width: 0,
+                height: 0,
+                url: endpoint,
+                format: "mp3".to_string(),
+                ..Default::default()
+            }],
+            media_type: MediaType::Audio,
+            ..Default::default()
+        })
+    }
+
+    /// Playlist responses carry the first few tracks in full and the rest as
+    /// bare ids, which are looked up in batches.
+    async fn native_expand(&self, url: &str) -> anyhow::Result<Vec<String>> {
+        let playlist = self.resolve(url).await?;
+        let tracks = playlist
+            .get("tracks")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow!("SoundCloud link is not a playlist"))?;
+
+        let permalink = |track: &serde_json::Value| {
+            track
+                .get("permalink_url")
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        };
+
+        let missing: Vec<String> = tracks
+            .iter()
+            .filter(|t| permalink(t).is_none())
+            .filter_map(|t| t.get("id").and_then(|v| v.as_u64()))
+            .map(|id| id.to_string())
+            .collect();
+
+        let mut looked_up: HashMap<u64, String> = HashMap::new();
+        for chunk in missing.chunks(TRACK_BATCH) {
+            let ids = chunk.join(",");
+            let found = self
+                .api_get(&format!("{}/tracks", API_BASE), &[("ids", ids.as_str())])
+                .await?;
+            for track in found.as_array().into_iter().flatten() {
+                if let (Some(id), Some(url)) =
+                    (track.get("id").and_then(|v| v.as_u64()), permalink(track))
+                {
+                    looked_up.insert(id, url);
+                }
+            }
+        }
+
+        let urls: Vec<String> = tracks
+            .iter()
+            .filter_map(|t| {
+                permalink(t).or_else(|| {
+                    t.get("id")
+                        .and_then(|v| v.as_u64())
+                        .and_then(|id| looked_up.get(&id).cloned())
+                })
+            })
+            .collect();
+
+        if urls.is_empty() {
+            return Err(DownloadError::NotFound.into());
+        }
+        Ok(urls)
+    }
+
+    async fn fallback_ytdlp(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        omniget_core::platforms::check_ytdlp_fallback("soundcloud")?;
+        let ytdlp_path = ytdlp::ensure_ytdlp().await?;
+        let json = ytdlp::get_video_info(&ytdlp_path, url, &[]).await?;
+        let mut info = GenericYtdlpDownloader::parse_video_info(&json)?;
+        info.platform = "soundcloud".to_string();
+        info.media_type = MediaType::Audio;
+        Ok(info)
+    }
+
+    async fn fallback_expand(&self, url: &str) -> anyhow::Result<Vec<String>> {
+        omniget_core::platforms::check_ytdlp_fallback("soundcloud")?;
+        let ytdlp_path = ytdlp::ensure_ytdlp().await?;
+        let (_title, entries) = ytdlp::get_playlist_info(&ytdlp_path, url, &[]).await?;
+        if entries.is_empty() {
+            return Err(DownloadError::NotFound.into());
+        }
+        Ok(entries.into_iter().map(|e| e.url).collect())
+    }
+
+    /// Trades a transcoding endpoint for the signed, short-lived media URL.
+    async fn stream_url(&self, endpoint: &str) -> anyhow::Result<String> {
+        let json = self.api_get(endpoint, &[]).await?;
+        json.get("url")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .ok_or_else(|| anyhow!("SoundCloud returned no stream URL"))
+    }
+}
+
+#[async_trait]
+impl PlatformDownloader for SoundCloudDownloader {
+    fn name(&self) -> &str {
+        "soundcloud"
+    }
+
+    fn can_handle(&self, url: &str) -> bool {
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["soundcloud.com", "snd.sc"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_audio_only: true,
+            supports_expansion: true,
+            ..Capabilities::default()
+        }
+    }
+
+    async fn expand(&self, url: &str) -> anyhow::Result<Option<Vec<String>>> {
+        let url = if redirect::is_short_link(url) {
+            redirect::expand_short_link(url).await?
+        } else {
+            url.to_string()
+        };
+        if !Self::is_set_url(&url) {
+            return Ok(None);
+        }
+        match self.native_expand(&url).await {
+            Ok(urls) => Ok(Some(urls)),
+            Err(native_err) => {
+                tracing::warn!(
+                    "[soundcloud] native expand failed: {}, trying yt-dlp fallback",
+                    native_err
+                );
+                self.fallback_expand(&url)
+                    .await
+                    .map(Some)
+                    .map_err(|_| native_err)
+            }
+        }
+    }
+
+    async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        match self.native_get_media_info(url).await {
+            Ok(info) => Ok(info),
+            Err(native_err) => {
+                tracing::warn!(
+                    "[soundcloud] native failed: {}, trying yt-dlp fallback",
+                    native_err
+                );
+                self.fallback_ytdlp(url).await.map_err(|_| native_err)
+            }
+        }
+    }
+
+    async fn download(
+        &self,
+        info: &MediaInfo,
+        opts: &DownloadOptions,
+        progress: mpsc::Sender<ProgressUpdate>,
+    ) -> anyhow::Result<DownloadResult> {
+        let quality = info
+            .available_qualities
+            .first()
+            .ok_or_else(|| anyhow!("No media URL available"))?;
+
+        if quality.format == "ytdlp" {
+            let ytdlp_path = ytdlp::ensure_ytdlp().await?;
+            return ytdlp::download_video(
+                &ytdlp_path,
+                &quality.url,
+                &opts.output_dir,
+                None,
+                progress,
+                opts.download_mode.as_deref().or(Some("audio")),
+                opts.format_id.as_deref(),
+                opts.filename_template.as_deref(),
+                opts.referer.as_deref().or(Some("https://soundcloud.com/")),
+                opts.cancel_token.clone(),
+                None,
+                opts.concurrent_fragments,
+                false,
+                &[],
+                opts.audio_format.as_deref(),
+                &opts.quality_preference,
+                &opts.extra_ytdlp_args,
+            )
+            .await;
+        }
+
+        let filename = opts.media_filename(info, "mp3");
+        let output_path = match opts.output_path(&filename) {
+            Ok(path) => path,
+            Err(skipped) => return Ok(skipped),
+        };
+        let stream = self.stream_url(&quality.url).await?;
+        let duration_seconds = info.duration_seconds.unwrap_or(0.0);
+
+        if quality.url.contains("/stream/hls") {
+            let downloader = HlsDownloader::new().with_user_agent_override(opts.user_agent.clone());
+            let _ = progress.send(ProgressUpdate::percent(0.0)).await;
+
+            let result = downloader
+                .download(
+                    &stream,
+                    &output_path.to_string_lossy(),
+                    "https://soundcloud.com/",
+                    None,
+                    opts.cancel_token.clone(),
+                    opts.hls_max_concurrent_segments,
+                    opts.hls_max_retries,
+                )
+                .await?;
+
+            let _ = progress.send(ProgressUpdate::percent(100.0)).await;
+
+            return Ok(DownloadResult {
+                duration_seconds,
+                ..DownloadResult::new(result.path, result.file_size)
+            });
+        }
+
+        let total_bytes = direct_downloader::download_direct(
+            &self.client,
+            &stream,
+            &output_path,
+            progress,
+            Some(&opts.cancel_token),
+        )
+        .await?;
+
+        Ok(DownloadResult {
+            duration_seconds,
+            ..DownloadResult::new(output_path, total_bytes)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn finds_client_id_in_bundle() {
+        let bundle = r#"n.a=function(){return{client_id:"AbCdEfGhIjKlMnOpQrStUvWxYz012345",env:"production"}}"#;
+        assert_eq!(
+            SoundCloudDownloader::extract_client_id(bundle).as_deref(),
+            Some("AbCdEfGhIjKlMnOpQrStUvWxYz012345")
+        );
+        assert_eq!(
+            SoundCloudDownloader::extract_client_id("client_id:null"),
+            None
+        );
+    }
+
+    #[test]
+    fn prefers_progressive_mp3() {
+        let track = json!({
+            "track_authorization": "tok",
+            "media": { "transcodings": [
+                { "url": "https://api-v2.soundcloud.com/media/1/a/stream/hls",
+                  "format": { "protocol": "hls", "mime_type": "audio/mpeg" } },
+                { "url": "https://api-v2.soundcloud.com/media/1/b/stream/hls",
+                  "format": { "protocol": "hls", "mime_type": "audio/ogg; codecs=\"opus\"" } },
+                { "url": "https://api-v2.soundcloud.com/media/1/c/stream/progressive",
+                  "format": { "protocol": "progressive", "mime_type": "audio/mpeg" } }
+            ]}
+        });
+        assert_eq!(
+            SoundCloudDownloader::pick_transcoding(&track).as_deref(),
+            Some("https://api-v2.soundcloud.com/media/1/c/stream/progressive?track_authorization=tok")
+        );
+
+        let opus_only = json!({ "media": { "transcodings": [
+            { "url": "https://api-v2.soundcloud.com/media/1/b/stream/hls",
+              "format": { "protocol": "hls", "mime_type": "audio/ogg; codecs=\"opus\"" } }
+        ]}});
+        assert_eq!(SoundCloudDownloader::pick_transcoding(&opus_only), None);
+    }
+
+    #[test]
+    fn only_sets_are_expanded() {
+        assert!(SoundCloudDownloader::is_set_url(
+            "https://soundcloud.com/artist/sets/playlist-name"
+        ));
+        assert!(!SoundCloudDownloader::is_set_url(
+            "https://soundcloud.com/artist/track-name"
+        ));
+    }
+}
@@ -1,18 +1,37 @@
 use omniget_core::models::progress::ProgressUpdate;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 
 use anyhow::anyhow;
 use async_trait::async_trait;
+use regex::Regex;
 use reqwest::header::{HeaderMap, HeaderValue, COOKIE, REFERER};
 use tokio::sync::mpsc;
 
 use crate::core::direct_downloader;
-use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality};
-use crate::platforms::traits::PlatformDownloader;
+use crate::core::errors::DownloadError;
+use crate::core::ffmpeg;
+use crate::core::filename;
+use crate::models::media::{
+    json_count, DownloadOptions, DownloadResult, MediaInfo, MediaType, PostMetadata, VideoQuality,
+};
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
 
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/138.0.0.0 Safari/537.36";
-const SHORT_LINK_UA: &str =
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko)";
+const WATERMARKED_LABEL: &str = "watermarked";
+const REHYDRATION_SCRIPT: &str = "__UNIVERSAL_DATA_FOR_REHYDRATION__";
+const SIGI_SCRIPT: &str = "SIGI_STATE";
+
+/// Opening tag of a page-data script, whatever order its attributes are in.
+static STATE_SCRIPT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<script[^>]*\bid="(__UNIVERSAL_DATA_FOR_REHYDRATION__|SIGI_STATE)"[^>]*>"#)
+        .expect("valid STATE_SCRIPT_RE")
+});
+
+struct VideoCandidate {
+    url: String,
+    watermarked: bool,
+}
 
 pub struct TikTokDownloader {
     client: reqwest::Client,
@@ -27,9 +46,7 @@ impl Default for TikTokDownloader {
 
 impl TikTokDownloader {
     pub fn new() -> Self {
-        let mut builder = crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
-            .user_agent(USER_AGENT)
-            .timeout(std::time::Duration::from_secs(30));
+        let mut builder = crate::core::http_client::platform_client_builder("tiktok", USER_AGENT);
 
         if let Some(jar) =
             crate::core::cookie_parser::load_extension_cookies_for_domain("tiktok.com")
@@ -79,38 +96,6 @@ impl TikTokDownloader {
         None
     }
 
-    async fn resolve_short_link(&self, url: &str) -> anyhow::Result<String> {
-        let redirect_client =
-            crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
-                .user_agent(SHORT_LINK_UA)
-                .redirect(reqwest::redirect::Policy::none())
-                .build()
-                .unwrap_or_default();
-
-        let response = redirect_client.get(url).send().await?;
-
-        if let Some(location) = response
-            .headers()
-            .get("location")
-            .and_then(|v| v.to_str().ok())
-        {
-            let clean = location.split('?').next().unwrap_or(location).to_string();
-            return Ok(clean);
-        }
-
-        let html = response.text().await?;
-
-        if html.starts_with("<a href=\"https://") {
-            if let Some(url_part) = html.split("<a href=\"").nth(1) {
-                let full_url = url_part.split('"').next().unwrap_or(url_part);
-                let clean = full_url.split('?').next().unwrap_or(full_url).to_string();
-                return Ok(clean);
-            }
-        }
-
-        Err(anyhow!("Could not resolve short link"))
-    }
-
     fn is_captcha_page(html: &str) -> bool {
         html.contains("verify-bar-close")
             || html.contains("captcha_verify")
@@ -141,7 +126,7 @@ impl TikTokDownloader {
         let status = response.status();
 
         if !status.is_success() && status.as_u16() != 302 {
-            return Err(anyhow!("TikTok retornou HTTP {}", status));
+            return Err(DownloadError::from_status("TikTok", status).into());
         }
 
         let mut cookie_parts = Vec::new();
@@ -156,25 +141,98 @@ impl TikTokDownloader {
         let html = response.text().await?;
 
         if Self::is_captcha_page(&html) {
-            return Err(anyhow!(
-                "TikTok is blocking requests. Try again in a few minutes."
-            ));
+            return Err(DownloadError::RateLimited.into());
         }
 
-        let json_str = html
-            .split("<script id=\"__UNIVERSAL_DATA_FOR_REHYDRATION__\" type=\"application/json\">")
+        let rehydration = Self::page_state(&html, REHYDRATION_SCRIPT);
+        let video_detail = rehydration
+            .as_ref()
+            .and_then(|data| data.pointer("/__DEFAULT_SCOPE__/webapp.video-detail"));
+
+        let detail = if let Some(video_detail) = video_detail {
+            Self::check_detail_status(video_detail)?;
+            video_detail
+                .pointer("/itemInfo/itemStruct")
+                .ok_or_else(|| anyhow!("Video data not found in TikTok response"))?
+                .clone()
+        } else if let Some(item) =
+            Self::page_state(&html, SIGI_SCRIPT).and_then(|data| Self::sigi_item(&data, post_id))
+        {
+            item
+        } else {
+            // The page changed shape or came without data; the item API
+            // still answers with the same `itemStruct`.
+            tracing::debug!(
+                "[tiktok] no video detail in page data (rehydration={}), trying item API",
+                rehydration.is_some()
+            );
+            match self.fetch_detail_api(post_id).await {
+                Ok(detail) => detail,
+                Err(e) if rehydration.is_none() => {
+                    tracing::debug!("[tiktok] item API failed: {}", e);
+                    return Err(DownloadError::RateLimited.into());
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        if detail
+            .get("isContentClassified")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            return Err(DownloadError::AgeRestricted.into());
+        }
+
+        if detail.get("author").is_none() {
+            return Err(DownloadError::NotFound.into());
+        }
+
+        Ok(detail)
+    }
+
+    /// JSON of the page-data script `id`. Cutting at `</script>` is tried
+    /// first; when that doesn't parse, the tag is found by regex and one
+    /// balanced JSON value is read from there, which survives reordered
+    /// attributes and `</script>` inside strings.
+    fn page_state(html: &str, id: &str) -> Option<serde_json::Value> {
+        let open_tag = format!("<script id=\"{}\" type=\"application/json\">", id);
+        let split = html
+            .split(open_tag.as_str())
             .nth(1)
             .and_then(|s| s.split("</script>").next())
-            .ok_or_else(|| anyhow!("TikTok is blocking requests. Try again in a few minutes."))?;
+            .and_then(|json| serde_json::from_str(json).ok());
+        if split.is_some() {
+            return split;
+        }
 
-        let data: serde_json::Value = serde_json::from_str(json_str)
-            .map_err(|_| anyhow!("Erro ao processar resposta do TikTok"))?;
+        STATE_SCRIPT_RE
+            .captures_iter(html)
+            .filter(|cap| &cap[1] == id)
+            .find_map(|cap| {
+                let rest = &html[cap.get(0)?.end()..];
+                serde_json::Deserializer::from_str(rest.trim_start())
+                    .into_iter::<serde_json::Value>()
+                    .next()?
+                    .ok()
+            })
+    }
 
-        let video_detail = data
-            .get("__DEFAULT_SCOPE__")
-            .and_then(|s| s.get("webapp.video-detail"))
-            .ok_or_else(|| anyhow!("Video data not found in TikTok response"))?;
+    /// `SIGI_STATE` keeps the post under `ItemModule`, with `author` as a
+    /// bare handle instead of the object `itemStruct` has.
+    fn sigi_item(data: &serde_json::Value, post_id: &str) -> Option<serde_json::Value> {
+        let mut item = data.pointer(&format!("/ItemModule/{}", post_id))?.clone();
+        if let Some(handle) = item
+            .get("author")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+        {
+            item["author"] = serde_json::json!({ "uniqueId": handle });
+        }
+        Some(item)
+    }
 
+    fn check_detail_status(video_detail: &serde_json::Value) -> anyhow::Result<()> {
         if let Some(status_msg) = video_detail
             .get("statusMsg")
             .and_then(|v| v.as_str())
@@ -188,25 +246,39 @@ impl TikTokDownloader {
                 return Err(anyhow!("Post not available (status {})", status_code));
             }
         }
+        Ok(())
+    }
 
-        let detail = video_detail
-            .pointer("/itemInfo/itemStruct")
-            .ok_or_else(|| anyhow!("Video data not found in TikTok response"))?
-            .clone();
-
-        if detail
-            .get("isContentClassified")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false)
-        {
-            return Err(anyhow!("Age-restricted content"));
-        }
+    /// The web app's item endpoint, for pages whose embedded data has no
+    /// video detail. Sends the cookies the page request set.
+    async fn fetch_detail_api(&self, post_id: &str) -> anyhow::Result<serde_json::Value> {
+        let cookies = self.captured_cookies.lock().await.clone();
+        let response = self
+            .client
+            .get("https://www.tiktok.com/api/item/detail/")
+            .query(&[
+                ("itemId", post_id),
+                ("aid", "1988"),
+                ("app_name", "tiktok_web"),
+                ("device_platform", "web_pc"),
+            ])
+            .headers(self.download_headers(&cookies))
+            .send()
+            .await?;
 
-        if detail.get("author").is_none() {
-            return Err(anyhow!("Post not available"));
+        let status = response.status();
+        if !status.is_success() {
+            return Err(DownloadError::from_status("TikTok", status).into());
         }
 
-        Ok(detail)
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|_| anyhow!("Failed to parse TikTok item API response"))?;
+        Self::check_detail_status(&json)?;
+        json.pointer("/itemInfo/itemStruct")
+            .cloned()
+            .ok_or_else(|| anyhow!("Video data not found in TikTok item API response"))
     }
 
     fn extract_author(detail: &serde_json::Value) -> String {
@@ -218,56 +290,86 @@ impl TikTokDownloader {
             .to_string()
     }
 
-    fn extract_video_url(detail: &serde_json::Value) -> Option<String> {
-        if let Some(play_addr) = detail.pointer("/video/playAddr") {
-            if let Some(url) = play_addr.as_str() {
-                if Self::is_valid_play_addr(url) {
-                    return Some(url.to_string());
-                }
-            }
+    fn first_valid_url<'a>(
+        urls: impl IntoIterator<Item = &'a serde_json::Value>,
+    ) -> Option<String> {
+        urls.into_iter()
+            .filter_map(|u| u.as_str())
+            .find(|u| Self::is_valid_play_addr(u))
+            .map(|u| u.to_string())
+    }
+
+    /// Collects one URL per known source, in TikTok's preference order.
+    /// `downloadAddr` is the "save video" export and carries the burned-in
+    /// watermark; the play addresses and bitrate renditions are clean.
+    fn extract_video_candidates(detail: &serde_json::Value) -> Vec<VideoCandidate> {
+        let mut candidates = Vec::new();
+
+        if let Some(url) = detail
+            .pointer("/video/playAddr")
+            .and_then(|v| Self::first_valid_url(std::iter::once(v)))
+        {
+            candidates.push(VideoCandidate {
+                url,
+                watermarked: false,
+            });
         }
 
-        if let Some(play_addr) = detail.pointer("/video/play_addr/url_list") {
-            if let Some(urls) = play_addr.as_array() {
-                for u in urls {
-                    if let Some(url) = u.as_str() {
-                        if Self::is_valid_play_addr(url) {
-                            return Some(url.to_string());
-                        }
-                    }
-                }
-            }
+        if let Some(url) = detail
+            .pointer("/video/play_addr/url_list")
+            .and_then(|v| v.as_array())
+            .and_then(Self::first_valid_url)
+        {
+            candidates.push(VideoCandidate {
+                url,
+                watermarked: false,
+            });
         }
 
-        if let Some(download_addr) = detail.pointer("/video/downloadAddr") {
-            if let Some(url) = download_addr.as_str() {
-                if Self::is_valid_play_addr(url) {
-                    return Some(url.to_string());
-                }
-            }
+        if let Some(url) = detail
+            .pointer("/video/downloadAddr")
+            .and_then(|v| Self::first_valid_url(std::iter::once(v)))
+        {
+            candidates.push(VideoCandidate {
+                url,
+                watermarked: true,
+            });
         }
 
-        if let Some(bitrate_info) = detail
+        if let Some(url) = detail
             .pointer("/video/bitrateInfo")
             .and_then(|v| v.as_array())
+            .and_then(|bitrates| {
+                bitrates.iter().find_map(|bitrate| {
+                    bitrate
+                        .pointer("/PlayAddr/UrlList")
+                        .and_then(|v| v.as_array())
+                        .and_then(Self::first_valid_url)
+                })
+            })
         {
-            for bitrate in bitrate_info {
-                if let Some(url_list) = bitrate
-                    .pointer("/PlayAddr/UrlList")
-                    .and_then(|v| v.as_array())
-                {
-                    for u in url_list {
-                        if let Some(url) = u.as_str() {
-                            if Self::is_valid_play_addr(url) {
-                                return Some(url.to_string());
-                            }
-                        }
-                    }
-                }
-            }
+            candidates.push(VideoCandidate {
+                url,
+                watermarked: false,
+            });
         }
 
-        None
+        candidates
+    }
+
+    fn select_video_quality(
+        qualities: &[VideoQuality],
+        prefer_no_watermark: bool,
+    ) -> Option<&VideoQuality> {
+        if !prefer_no_watermark {
+            return qualities.first();
+        }
+        if let Some(clean) = qualities.iter().find(|q| q.label != WATERMARKED_LABEL) {
+            return Some(clean);
+        }
+        let fallback = qualities.first()?;
+        tracing::warn!("[tiktok] only a watermarked stream is available");
+        Some(fallback)
     }
 
     fn extract_image_urls(detail: &serde_json::Value) -> Option<Vec<String>> {
@@ -295,6 +397,7 @@ impl TikTokDownloader {
     }
 
     async fn fallback_ytdlp(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        omniget_core::platforms::check_ytdlp_fallback("tiktok")?;
         let ytdlp_path = crate::core::ytdlp::ensure_ytdlp().await?;
         let extra_flags = vec![
             "--referer".to_string(),
@@ -347,6 +450,7 @@ impl TikTokDownloader {
             .map(String::from);
 
         Ok(MediaInfo {
+            id: Some(post_id.to_string()),
             title,
             author,
             platform: "tiktok".to_string(),
@@ -358,9 +462,10 @@ impl TikTokDownloader {
                 height: 0,
                 url: url.to_string(),
                 format: "ytdlp".to_string(),
+                ..Default::default()
             }],
             media_type: MediaType::Video,
-            file_size_bytes: None,
+            ..Default::default()
         })
     }
 
@@ -375,6 +480,47 @@ impl TikTokDownloader {
     fn extract_duration(detail: &serde_json::Value) -> Option<f64> {
         detail.pointer("/video/duration").and_then(|v| v.as_f64())
     }
+
+    /// Caption, date and stats of a post. Newer payloads put the counts
+    /// as strings under `statsV2`.
+    fn extract_metadata(detail: &serde_json::Value) -> PostMetadata {
+        let stat = |key: &str| {
+            json_count(detail.pointer(&format!("/stats/{}", key)))
+                .or_else(|| json_count(detail.pointer(&format!("/statsV2/{}", key))))
+        };
+        let mut tags: Vec<String> = detail
+            .get("textExtra")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|t| t.get("hashtagName").and_then(|v| v.as_str()))
+            .filter(|t| !t.is_empty())
+            .map(String::from)
+            .collect();
+        if tags.is_empty() {
+            tags = detail
+                .get("challenges")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|c| c.get("title").and_then(|v| v.as_str()))
+                .map(String::from)
+                .collect();
+        }
+        PostMetadata {
+            description: detail
+                .get("desc")
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(String::from),
+            timestamp: json_count(detail.get("createTime")).map(|t| t as i64),
+            tags,
+            view_count: stat("playCount"),
+            like_count: stat("diggCount"),
+            comment_count: stat("commentCount"),
+            share_count: stat("shareCount"),
+        }
+    }
 }
 
 #[async_trait]
@@ -384,13 +530,18 @@ impl PlatformDownloader for TikTokDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host == "tiktok.com" || host.ends_with(".tiktok.com");
-            }
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["tiktok.com"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_audio_only: true,
+            ..Capabilities::default()
         }
-        false
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
@@ -399,10 +550,7 @@ impl PlatformDownloader for TikTokDownloader {
         let post_id = match Self::extract_post_id(url) {
             Some(id) => id,
             None => {
-                let canonical = self
-                    .resolve_short_link(url)
-                    .await
-                    .unwrap_or_else(|_| url.to_string());
+                let canonical = crate::core::redirect::canonicalize(url).await;
                 Self::extract_post_id(&canonical).unwrap_or_else(|| "unknown".to_string())
             }
         };
@@ -419,6 +567,7 @@ impl PlatformDownloader for TikTokDownloader {
         };
 
         let author = Self::extract_author(&detail);
+        let metadata = Some(Self::extract_metadata(&detail));
         let filename_base = format!(
             "tiktok_{}_{}",
             sanitize_filename::sanitize(&author),
@@ -441,10 +590,12 @@ impl PlatformDownloader for TikTokDownloader {
                     height: 0,
                     url: u.clone(),
                     format: "jpg".to_string(),
+                    ..Default::default()
                 })
                 .collect();
 
             return Ok(MediaInfo {
+                id: Some(post_id.clone()),
                 title: filename_base,
                 author,
                 platform: "tiktok".to_string(),
@@ -452,31 +603,52 @@ impl PlatformDownloader for TikTokDownloader {
                 thumbnail_url: None,
                 available_qualities: qualities,
                 media_type,
-                file_size_bytes: None,
+                metadata,
+                ..Default::default()
             });
         }
 
-        if let Some(video_url) = Self::extract_video_url(&detail) {
+        let candidates = Self::extract_video_candidates(&detail);
+        if !candidates.is_empty() {
+            let qualities: Vec<VideoQuality> = candidates
+                .into_iter()
+                .map(|c| VideoQuality {
+                    label: if c.watermarked {
+                        WATERMARKED_LABEL.to_string()
+                    } else {
+                        "best".to_string()
+                    },
+                    width: 0,
+                    height: 0,
+                    url: c.url,
+                    format: "tiktok_direct".to_string(),
+                    ..Default::default()
+                })
+                .collect();
+            // Whether the stream `download` will pick carries the watermark.
+            let prefer_no_watermark = crate::storage::config::load_settings_standalone()
+                .download
+                .tiktok_no_watermark;
+            let watermarked = Self::select_video_quality(&qualities, prefer_no_watermark)
+                .is_some_and(|q| q.label == WATERMARKED_LABEL);
             return Ok(MediaInfo {
+                id: Some(post_id.clone()),
                 title: filename_base,
                 author,
                 platform: "tiktok".to_string(),
                 duration_seconds: Self::extract_duration(&detail),
                 thumbnail_url: None,
-                available_qualities: vec![VideoQuality {
-                    label: "best".to_string(),
-                    width: 0,
-                    height: 0,
-                    url: video_url,
-                    format: "tiktok_direct".to_string(),
-                }],
+                available_qualities: qualities,
                 media_type: MediaType::Video,
-                file_size_bytes: None,
+                watermarked: Some(watermarked),
+                metadata,
+                ..Default::default()
             });
         }
 
         if let Some(music_url) = Self::extract_music_url(&detail) {
             return Ok(MediaInfo {
+                id: Some(post_id.clone()),
                 title: filename_base,
                 author,
                 platform: "tiktok".to_string(),
@@ -488,9 +660,11 @@ impl PlatformDownloader for TikTokDownloader {
                     height: 0,
                     url: music_url,
                     format: "mp3".to_string(),
+                    ..Default::default()
                 }],
                 media_type: MediaType::Audio,
-                file_size_bytes: None,
+                metadata,
+                ..Default::default()
             });
         }
 
@@ -523,6 +697,8 @@ impl PlatformDownloader for TikTokDownloader {
                     false,
                     &[],
                     opts.audio_format.as_deref(),
+                    &opts.quality_preference,
+                    &opts.extra_ytdlp_args,
                 )
                 .await;
             }
@@ -534,14 +710,16 @@ impl PlatformDownloader for TikTokDownloader {
 
         match info.media_type {
             MediaType::Video => {
-                let quality = info
-                    .available_qualities
-                    .first()
-                    .ok_or_else(|| anyhow!("No video URL available"))?;
+                let quality =
+                    Self::select_video_quality(&info.available_qualities, opts.prefer_no_watermark)
+                        .ok_or_else(|| anyhow!("No video URL available"))?;
 
                 if quality.format == "tiktok_direct" {
-                    let filename = format!("{}.mp4", sanitize_filename::sanitize(&info.title));
-                    let output = opts.output_dir.join(&filename);
+                    let filename = opts.media_filename(info, "mp4");
+                    let output = match opts.output_path(&filename) {
+                        Ok(path) => path,
+                        Err(skipped) => return Ok(skipped),
+                    };
 
                     let result = direct_downloader::download_direct_with_headers(
                         &self.client,
@@ -555,11 +733,15 @@ impl PlatformDownloader for TikTokDownloader {
 
                     match result {
                         Ok(bytes) => {
+                            let (bytes, note) = if opts.is_mute() {
+                                ffmpeg::mute_downloaded(&output, bytes).await?
+                            } else {
+                                (bytes, None)
+                            };
                             return Ok(DownloadResult {
-                                file_path: output,
-                                file_size_bytes: bytes,
                                 duration_seconds: info.duration_seconds.unwrap_or(0.0),
-                                torrent_id: None,
+                                note,
+                                ..DownloadResult::new(output, bytes)
                             });
                         }
                         Err(e) => {
@@ -595,25 +777,34 @@ impl PlatformDownloader for TikTokDownloader {
                     false,
                     &[],
                     opts.audio_format.as_deref(),
+                    &opts.quality_preference,
+                    &opts.extra_ytdlp_args,
                 )
                 .await
             }
             MediaType::Photo | MediaType::Carousel => {
                 let mut total_bytes = 0u64;
                 let count = info.available_qualities.len();
-                let mut last_path = opts.output_dir.clone();
+                let mut saved = Vec::new();
+                let mut already_saved = None;
 
                 for (i, quality) in info.available_qualities.iter().enumerate() {
+                    if !opts.is_selected(i) {
+                        continue;
+                    }
+                    let filename = opts.media_filename(info, "jpg");
                     let filename = if count == 1 {
-                        format!("{}.jpg", sanitize_filename::sanitize(&info.title))
+                        filename
                     } else {
-                        format!(
-                            "{}_photo_{}.jpg",
-                            sanitize_filename::sanitize(&info.title),
-                            i + 1
-                        )
+                        filename::with_quality_suffix(&filename, &format!("photo_{}", i + 1))
+                    };
+                    let output = match opts.output_path(&filename) {
+                        Ok(path) => path,
+                        Err(existing) => {
+                            already_saved.get_or_insert(existing);
+                            continue;
+                        }
                     };
-                    let output = opts.output_dir.join(&filename);
                     let (tx, _rx) = mpsc::channel(8);
 
                     let bytes = direct_downloader::download_direct_with_headers(
@@ -627,17 +818,20 @@ impl PlatformDownloader for TikTokDownloader {
                     .await?;
 
                     total_bytes += bytes;
-                    last_path = output;
+                    saved.push(output);
 
                     let percent = ((i + 1) as f64 / count as f64) * 100.0;
                     let _ = progress.send(ProgressUpdate::percent(percent)).await;
                 }
 
+                if let Some(existing) = already_saved.filter(|_| saved.is_empty()) {
+                    return Ok(existing);
+                }
+                let file_path = saved.pop().unwrap_or_else(|| opts.output_dir.clone());
+
                 Ok(DownloadResult {
-                    file_path: last_path,
-                    file_size_bytes: total_bytes,
-                    duration_seconds: 0.0,
-                    torrent_id: None,
+                    extra_files: saved,
+                    ..DownloadResult::new(file_path, total_bytes)
                 })
             }
             MediaType::Audio => {
@@ -646,8 +840,11 @@ impl PlatformDownloader for TikTokDownloader {
                     .first()
                     .ok_or_else(|| anyhow!("No audio URL available"))?;
 
-                let filename = format!("{}.mp3", sanitize_filename::sanitize(&info.title));
-                let output = opts.output_dir.join(&filename);
+                let filename = opts.media_filename(info, "mp3");
+                let output = match opts.output_path(&filename) {
+                    Ok(path) => path,
+                    Err(skipped) => return Ok(skipped),
+                };
 
                 let bytes = direct_downloader::download_direct_with_headers(
                     &self.client,
@@ -659,14 +856,103 @@ impl PlatformDownloader for TikTokDownloader {
                 )
                 .await?;
 
-                Ok(DownloadResult {
-                    file_path: output,
-                    file_size_bytes: bytes,
-                    duration_seconds: 0.0,
-                    torrent_id: None,
-                })
+                Ok(DownloadResult::new(output, bytes))
             }
             _ => Err(anyhow!("Unsupported media type for download")),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quality(label: &str, url: &str) -> VideoQuality {
+        VideoQuality {
+            label: label.to_string(),
+            width: 0,
+            height: 0,
+            url: url.to_string(),
+            format: "tiktok_direct".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn page_state_reads_either_script_shape() {
+        let split = r#"<script id="__UNIVERSAL_DATA_FOR_REHYDRATION__" type="application/json">{"__DEFAULT_SCOPE__":{"webapp.video-detail":{"statusCode":0}}}</script>"#;
+        let data = TikTokDownloader::page_state(split, REHYDRATION_SCRIPT).unwrap();
+        assert!(data
+            .pointer("/__DEFAULT_SCOPE__/webapp.video-detail")
+            .is_some());
+
+        let reordered = r#"<script type="application/json" id="__UNIVERSAL_DATA_FOR_REHYDRATION__">{"desc":"a </script> b","n":1}</script><script>x</script>"#;
+        let data = TikTokDownloader::page_state(reordered, REHYDRATION_SCRIPT).unwrap();
+        assert_eq!(data["desc"], "a </script> b");
+        assert_eq!(data["n"], 1);
+
+        assert!(TikTokDownloader::page_state("<html></html>", REHYDRATION_SCRIPT).is_none());
+    }
+
+    #[test]
+    fn sigi_item_turns_the_author_handle_into_an_object() {
+        let html = r#"<script id="SIGI_STATE" type="application/json">{"ItemModule":{"123":{"id":"123","author":"someone","video":{}}}}</script>"#;
+        let data = TikTokDownloader::page_state(html, SIGI_SCRIPT).unwrap();
+        let item = TikTokDownloader::sigi_item(&data, "123").unwrap();
+        assert_eq!(TikTokDownloader::extract_author(&item), "someone");
+        assert!(TikTokDownloader::sigi_item(&data, "456").is_none());
+    }
+
+    #[test]
+    fn metadata_reads_string_stats_and_hashtags() {
+        let detail = serde_json::json!({
+            "desc": "dance #fyp",
+            "createTime": "1700000000",
+            "stats": { "diggCount": 10 },
+            "statsV2": { "playCount": "2500", "diggCount": "10" },
+            "textExtra": [{ "hashtagName": "fyp" }, { "hashtagName": "" }],
+        });
+        let meta = TikTokDownloader::extract_metadata(&detail);
+        assert_eq!(meta.description.as_deref(), Some("dance #fyp"));
+        assert_eq!(meta.timestamp, Some(1_700_000_000));
+        assert_eq!(meta.view_count, Some(2500));
+        assert_eq!(meta.like_count, Some(10));
+        assert_eq!(meta.tags, vec!["fyp".to_string()]);
+    }
+
+    #[test]
+    fn download_addr_is_marked_watermarked() {
+        let detail = serde_json::json!({
+            "video": {
+                "downloadAddr": "https://v16.tiktokcdn.com/wm.mp4",
+                "bitrateInfo": [
+                    { "PlayAddr": { "UrlList": ["https://v16.tiktokcdn.com/clean.mp4"] } }
+                ]
+            }
+        });
+        let candidates = TikTokDownloader::extract_video_candidates(&detail);
+        assert_eq!(candidates.len(), 2);
+        assert!(candidates[0].watermarked);
+        assert!(!candidates[1].watermarked);
+        assert_eq!(candidates[1].url, "https://v16.tiktokcdn.com/clean.mp4");
+    }
+
+    #[test]
+    fn prefer_no_watermark_skips_download_addr() {
+        let qualities = vec![
+            quality(WATERMARKED_LABEL, "https://a/wm.mp4"),
+            quality("best", "https://a/clean.mp4"),
+        ];
+        let picked = TikTokDownloader::select_video_quality(&qualities, true).unwrap();
+        assert_eq!(picked.url, "https://a/clean.mp4");
+        let picked = TikTokDownloader::select_video_quality(&qualities, false).unwrap();
+        assert_eq!(picked.url, "https://a/wm.mp4");
+    }
+
+    #[test]
+    fn watermarked_stream_is_used_when_nothing_else_exists() {
+        let qualities = vec![quality(WATERMARKED_LABEL, "https://a/wm.mp4")];
+        let picked = TikTokDownloader::select_video_quality(&qualities, true).unwrap();
+        assert_eq!(picked.url, "https://a/wm.mp4");
+    }
+}
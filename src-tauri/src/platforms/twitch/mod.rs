@@ -1,11 +1,14 @@
 use anyhow::anyhow;
 use async_trait::async_trait;
-use omniget_core::models::progress::ProgressUpdate;
+use omniget_core::models::progress::{forward_part, ProgressUpdate};
 use tokio::sync::mpsc;
 
 use crate::core::direct_downloader;
+use crate::core::errors::DownloadError;
+use crate::core::filename;
 use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality};
-use crate::platforms::traits::PlatformDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
 
 const GQL_URL: &str = "https://gql.twitch.tv/gql";
 const CLIENT_ID: &str = "kimne78kx3ncx6brgo4mv6wki5h1ko";
@@ -59,7 +62,7 @@ impl TwitchClipsDownloader {
         let broadcaster = clip
             .broadcaster_login
             .as_deref()
-            .ok_or_else(|| anyhow!("Dados do clip incompletos"))?;
+            .ok_or_else(|| anyhow!("Incomplete clip data"))?;
 
         let token = self.fetch_access_token(&slug).await?;
 
@@ -72,16 +75,19 @@ impl TwitchClipsDownloader {
                 let height: u32 = q.quality.parse().unwrap_or(0);
                 let authenticated_url = Self::build_authenticated_url(&q.source_url, &token);
                 VideoQuality {
+                    id: Some(format!("clip-{}", q.quality)),
                     label: format!("{}p", q.quality),
                     width: 0,
                     height,
                     url: authenticated_url,
                     format: "mp4".to_string(),
+                    ..Default::default()
                 }
             })
             .collect();
 
         Ok(MediaInfo {
+            id: Some(slug.clone()),
             title: sanitize_filename::sanitize(&clip_title),
             author: broadcaster.to_string(),
             platform: "twitch".to_string(),
@@ -89,16 +95,16 @@ impl TwitchClipsDownloader {
             thumbnail_url: clip.thumbnail_url,
             available_qualities,
             media_type: MediaType::Video,
-            file_size_bytes: None,
+            ..Default::default()
         })
     }
 
     pub fn new() -> Self {
-        let client = crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
-            .timeout(std::time::Duration::from_secs(120))
-            .connect_timeout(std::time::Duration::from_secs(15))
-            .build()
-            .unwrap_or_default();
+        let client = crate::core::http_client::apply_timeouts(
+            crate::core::http_client::apply_global_proxy(reqwest::Client::builder()),
+        )
+        .build()
+        .unwrap_or_default();
 
         Self { client }
     }
@@ -136,7 +142,7 @@ impl TwitchClipsDownloader {
             .await?;
 
         if !response.status().is_success() {
-            return Err(anyhow!("Twitch GQL retornou HTTP {}", response.status()));
+            return Err(DownloadError::from_status("Twitch GQL", response.status()).into());
         }
 
         let json: serde_json::Value = response.json().await?;
@@ -217,10 +223,7 @@ impl TwitchClipsDownloader {
             .await?;
 
         if !response.status().is_success() {
-            return Err(anyhow!(
-                "Twitch GQL token retornou HTTP {}",
-                response.status()
-            ));
+            return Err(DownloadError::from_status("Twitch GQL token", response.status()).into());
         }
 
         let json: serde_json::Value = response.json().await?;
@@ -234,13 +237,13 @@ impl TwitchClipsDownloader {
         let signature = token_obj
             .get("signature")
             .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow!("Token sem signature"))?
+            .ok_or_else(|| anyhow!("Token has no signature"))?
             .to_string();
 
         let value = token_obj
             .get("value")
             .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow!("Token sem value"))?
+            .ok_or_else(|| anyhow!("Token has no value"))?
             .to_string();
 
         Ok(AccessToken { signature, value })
@@ -263,24 +266,28 @@ impl PlatformDownloader for TwitchClipsDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                let is_twitch = host == "twitch.tv" || host.ends_with(".twitch.tv");
+        url_matches_hosts(url, self.supported_hosts()) && Self::extract_clip_slug(url).is_some()
+    }
 
-                if !is_twitch {
-                    return false;
-                }
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["twitch.tv"]
+    }
 
-                return Self::extract_clip_slug(url).is_some();
-            }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_quality_selection: true,
+            supports_audio_only: true,
+            native_formats: true,
+            ..Capabilities::default()
         }
-        false
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
         match self.native_get_media_info(url).await {
-            Ok(info) => Ok(info),
+            Ok(mut info) => {
+                crate::core::direct_downloader::fill_file_size(&self.client, &mut info, None).await;
+                Ok(info)
+            }
             Err(native_err) => {
                 tracing::warn!(
                     "[twitch] native failed: {}, trying yt-dlp fallback",
@@ -316,35 +323,47 @@ impl PlatformDownloader for TwitchClipsDownloader {
                     false,
                     &[],
                     opts.audio_format.as_deref(),
+                    &opts.quality_preference,
+                    &opts.extra_ytdlp_args,
                 )
                 .await;
             }
         }
 
-        let first = info
-            .available_qualities
-            .first()
+        if opts.quality_selection.is_multi() {
+            let qualities = opts.quality_selection.select(&info.available_qualities);
+            return self
+                .download_qualities(info, &qualities, opts, progress)
+                .await;
+        }
+
+        let selected = opts
+            .pick_quality(&info.available_qualities)
             .ok_or_else(|| anyhow!("No media URL available"))?;
 
-        let selected = if let Some(ref wanted) = opts.quality {
-            info.available_qualities
-                .iter()
-                .find(|q| q.label == *wanted)
-                .unwrap_or(first)
-        } else {
-            first
-        };
+        self.download_clip_quality(info, selected, opts, progress)
+            .await
+    }
+}
 
-        let filename = format!(
-            "{}_{}.mp4",
-            sanitize_filename::sanitize(&info.title),
-            selected.label
-        );
-        let output_path = opts.output_dir.join(&filename);
+impl TwitchClipsDownloader {
+    async fn download_clip_quality(
+        &self,
+        info: &MediaInfo,
+        quality: &VideoQuality,
+        opts: &DownloadOptions,
+        progress: mpsc::Sender<ProgressUpdate>,
+    ) -> anyhow::Result<DownloadResult> {
+        let filename =
+            filename::with_quality_suffix(&opts.media_filename(info, "mp4"), &quality.label);
+        let output_path = match opts.output_path(&filename) {
+            Ok(path) => path,
+            Err(skipped) => return Ok(skipped),
+        };
 
         let total_bytes = direct_downloader::download_direct(
             &self.client,
-            &selected.url,
+            &quality.url,
             &output_path,
             progress,
             Some(&opts.cancel_token),
@@ -352,10 +371,44 @@ impl PlatformDownloader for TwitchClipsDownloader {
         .await?;
 
         Ok(DownloadResult {
-            file_path: output_path,
-            file_size_bytes: total_bytes,
             duration_seconds: info.duration_seconds.unwrap_or(0.0),
-            torrent_id: None,
+            ..DownloadResult::new(output_path, total_bytes)
         })
     }
+
+    /// Clip qualities are separate MP4 URLs, and the label is already part
+    /// of the filename, so each one is saved as its own file.
+    async fn download_qualities(
+        &self,
+        info: &MediaInfo,
+        qualities: &[&VideoQuality],
+        opts: &DownloadOptions,
+        progress: mpsc::Sender<ProgressUpdate>,
+    ) -> anyhow::Result<DownloadResult> {
+        if qualities.is_empty() {
+            return Err(anyhow!("None of the requested qualities are available"));
+        }
+        let mut results = Vec::new();
+        let mut last_err = None;
+        for (i, quality) in qualities.iter().enumerate() {
+            if opts.cancel_token.is_cancelled() {
+                anyhow::bail!("Download cancelled");
+            }
+            let (part_tx, forwarder) = forward_part(progress.clone(), i, qualities.len());
+            match self
+                .download_clip_quality(info, quality, opts, part_tx)
+                .await
+            {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    tracing::warn!("[twitch] clip quality {} failed: {}", quality.label, e);
+                    last_err = Some(e);
+                }
+            }
+            let _ = forwarder.await;
+        }
+        let _ = progress.send(ProgressUpdate::percent(100.0)).await;
+        DownloadResult::combine(results)
+            .ok_or_else(|| last_err.unwrap_or_else(|| anyhow!("No quality was downloaded")))
+    }
 }
@@ -1,27 +1,78 @@
-use omniget_core::models::progress::ProgressUpdate;
+use omniget_core::models::progress::{forward_part, ProgressUpdate};
 use regex::Regex;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
 use async_trait::async_trait;
 use tokio::sync::{mpsc, Mutex};
 
 use crate::core::direct_downloader;
-use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality};
-use crate::platforms::traits::PlatformDownloader;
+use crate::core::errors::DownloadError;
+use crate::core::ffmpeg;
+use crate::core::filename;
+use crate::core::hls_downloader::HlsDownloader;
+use crate::models::media::{
+    json_count, DownloadNote, DownloadOptions, DownloadResult, MediaInfo, MediaType, PostMetadata,
+    VideoQuality,
+};
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
 
 const GRAPHQL_URL: &str = "https://api.x.com/graphql/4Siu98E55GquhG52zHdY5w/TweetDetail";
 const TOKEN_URL: &str = "https://api.x.com/1.1/guest/activate.json";
+const VXTWITTER_API: &str = "https://api.vxtwitter.com/Status";
+const AUDIO_SPACE_URL: &str = "https://x.com/i/api/graphql/Uv5R_-Chxbn1FEkyUkSW2w/AudioSpaceById";
+const LIVE_STREAM_STATUS_URL: &str = "https://x.com/i/api/1.1/live_video_stream/status";
+/// Guest tokens stop working after a few hours. Refreshing a bit earlier
+/// saves the failed request that would otherwise reveal it.
+const GUEST_TOKEN_TTL: Duration = Duration::from_secs(150 * 60);
 const BEARER: &str = "Bearer AAAAAAAAAAAAAAAAAAAAANRILgAAAAAAnNwIzUejRCOuH5E6I8xnZz4puTs%3D1Zv7ttfk8LF81IUq16cHjhLTvJu4FA33AGWWjCpTnA";
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
 
 const TWEET_FEATURES: &str = r#"{"rweb_video_screen_enabled":false,"payments_enabled":false,"rweb_xchat_enabled":false,"profile_label_improvements_pcf_label_in_post_enabled":true,"rweb_tipjar_consumption_enabled":true,"verified_phone_label_enabled":false,"creator_subscriptions_tweet_preview_api_enabled":true,"responsive_web_graphql_timeline_navigation_enabled":true,"responsive_web_graphql_skip_user_profile_image_extensions_enabled":false,"premium_content_api_read_enabled":false,"communities_web_enable_tweet_community_results_fetch":true,"c9s_tweet_anatomy_moderator_badge_enabled":true,"responsive_web_grok_analyze_button_fetch_trends_enabled":false,"responsive_web_grok_analyze_post_followups_enabled":true,"responsive_web_jetfuel_frame":true,"responsive_web_grok_share_attachment_enabled":true,"articles_preview_enabled":true,"responsive_web_edit_tweet_api_enabled":true,"graphql_is_translatable_rweb_tweet_is_translatable_enabled":true,"view_counts_everywhere_api_enabled":true,"longform_notetweets_consumption_enabled":true,"responsive_web_twitter_article_tweet_consumption_enabled":true,"tweet_awards_web_tipping_enabled":false,"responsive_web_grok_show_grok_translated_post":false,"responsive_web_grok_analysis_button_from_backend":true,"creator_subscriptions_quote_tweet_preview_enabled":false,"freedom_of_speech_not_reach_fetch_enabled":true,"standardized_nudges_misinfo":true,"tweet_with_visibility_results_prefer_gql_limited_actions_policy_enabled":true,"longform_notetweets_rich_text_read_enabled":true,"longform_notetweets_inline_media_enabled":true,"responsive_web_grok_image_annotation_enabled":true,"responsive_web_grok_imagine_annotation_enabled":true,"responsive_web_grok_community_note_auto_translation_is_enabled":false,"responsive_web_enhance_cards_enabled":false}"#;
 
+const SPACE_FEATURES: &str = r#"{"spaces_2022_h2_spaces_communities":true,"spaces_2022_h2_clipping":true,"creator_subscriptions_tweet_preview_api_enabled":true,"profile_label_improvements_pcf_label_in_post_enabled":true,"rweb_tipjar_consumption_enabled":true,"verified_phone_label_enabled":false,"premium_content_api_read_enabled":false,"communities_web_enable_tweet_community_results_fetch":true,"c9s_tweet_anatomy_moderator_badge_enabled":true,"responsive_web_graphql_exclude_directive_enabled":true,"responsive_web_graphql_skip_user_profile_image_extensions_enabled":false,"responsive_web_graphql_timeline_navigation_enabled":true,"articles_preview_enabled":true,"responsive_web_edit_tweet_api_enabled":true,"graphql_is_translatable_rweb_tweet_is_translatable_enabled":true,"view_counts_everywhere_api_enabled":true,"longform_notetweets_consumption_enabled":true,"responsive_web_twitter_article_tweet_consumption_enabled":true,"tweet_awards_web_tipping_enabled":false,"freedom_of_speech_not_reach_fetch_enabled":true,"standardized_nudges_misinfo":true,"tweet_with_visibility_results_prefer_gql_limited_actions_policy_enabled":true,"longform_notetweets_rich_text_read_enabled":true,"longform_notetweets_inline_media_enabled":true,"responsive_web_enhance_cards_enabled":false}"#;
+
 const TWEET_FIELD_TOGGLES: &str = r#"{"withArticleRichContentState":true,"withArticlePlainText":false,"withGrokAnalyze":false,"withDisallowedReplyControls":false}"#;
 
+/// The parts of an `AudioSpaceById` response a download needs.
+struct AudioSpace {
+    media_key: String,
+    /// `Running` while on air, `Ended` afterwards; `NotStarted` and
+    /// `Canceled` for scheduled Spaces.
+    state: String,
+    replayable: bool,
+    title: Option<String>,
+    creator: Option<String>,
+    started_at_ms: Option<i64>,
+    ended_at_ms: Option<i64>,
+}
+
+/// What `try_graphql` reads from a `TweetDetail` response.
+struct GraphqlTweet {
+    media: Vec<serde_json::Value>,
+    author: Option<String>,
+    metadata: Option<PostMetadata>,
+}
+
+/// The registry holds a single `TwitterDownloader` in `AppState`, so this
+/// cache is shared by every download.
 pub struct TwitterDownloader {
     client: reqwest::Client,
-    guest_token: Arc<Mutex<Option<String>>>,
+    guest_token: Arc<Mutex<Option<GuestToken>>>,
+}
+
+struct GuestToken {
+    value: String,
+    fetched_at: Instant,
+}
+
+impl GuestToken {
+    /// Still young enough, and not the token a caller just saw rejected.
+    fn usable(&self, rejected: Option<&str>) -> bool {
+        self.fetched_at.elapsed() < GUEST_TOKEN_TTL && rejected != Some(self.value.as_str())
+    }
 }
 
 enum TwitterMedia {
@@ -33,6 +84,8 @@ struct TwitterMediaItem {
     media_type: TwitterMediaType,
     url: String,
     extension: String,
+    /// Bits per second of the chosen mp4 variant, when known.
+    bitrate: Option<u64>,
 }
 
 enum TwitterMediaType {
@@ -41,6 +94,14 @@ enum TwitterMediaType {
     AnimatedGif,
 }
 
+impl TwitterMediaItem {
+    /// `"none"`, yt-dlp's spelling for no audio, for GIFs, which X serves
+    /// as silent mp4s.
+    fn acodec(&self) -> Option<String> {
+        matches!(self.media_type, TwitterMediaType::AnimatedGif).then(|| "none".to_string())
+    }
+}
+
 impl Default for TwitterDownloader {
     fn default() -> Self {
         Self::new()
@@ -241,10 +302,7 @@ impl TwitterDownloader {
     }
 
     pub fn new() -> Self {
-        let mut builder = crate::core::http_client::apply_global_proxy(reqwest::Client::builder())
-            .user_agent(USER_AGENT)
-            .timeout(std::time::Duration::from_secs(120))
-            .connect_timeout(std::time::Duration::from_secs(15));
+        let mut builder = crate::core::http_client::platform_client_builder("twitter", USER_AGENT);
 
         if let Some(jar) = crate::core::cookie_parser::load_extension_cookies_for_domain("x.com") {
             builder = builder.cookie_provider(jar);
@@ -271,12 +329,26 @@ impl TwitterDownloader {
         None
     }
 
-    async fn get_guest_token(&self, force: bool) -> anyhow::Result<String> {
-        if !force {
-            let cached = self.guest_token.lock().await;
-            if let Some(ref token) = *cached {
-                return Ok(token.clone());
+    fn extract_space_id(url: &str) -> Option<String> {
+        let parsed = url::Url::parse(url).ok()?;
+        let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+
+        match segments.as_slice() {
+            ["i", "spaces", id, ..] if id.chars().all(|c| c.is_ascii_alphanumeric()) => {
+                Some(id.to_string())
             }
+            _ => None,
+        }
+    }
+
+    /// Cached guest token, activating a new one when it is missing, old, or
+    /// the one in `rejected`. The lock is held while activating so parallel
+    /// downloads wait for one activation instead of each starting a guest
+    /// session, which X rate-limits.
+    async fn get_guest_token(&self, rejected: Option<&str>) -> anyhow::Result<String> {
+        let mut cached = self.guest_token.lock().await;
+        if let Some(token) = cached.as_ref().filter(|t| t.usable(rejected)) {
+            return Ok(token.value.clone());
         }
 
         let response = self
@@ -300,14 +372,42 @@ impl TwitterDownloader {
         let token = json
             .get("guest_token")
             .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow!("Guest token ausente na resposta"))?
+            .ok_or_else(|| anyhow!("Guest token missing from response"))?
             .to_string();
 
-        let mut cached = self.guest_token.lock().await;
-        *cached = Some(token.clone());
+        *cached = Some(GuestToken {
+            value: token.clone(),
+            fetched_at: Instant::now(),
+        });
         Ok(token)
     }
 
+    /// A GET to X's web API with the guest token, plus the session
+    /// headers when the user's cookies are logged in.
+    fn api_request(&self, url: &str, guest_token: &str) -> reqwest::RequestBuilder {
+        let cookie_val = Self::request_cookie_header(guest_token);
+        let ct0 = Self::cookie_value(&cookie_val, "ct0");
+        let has_auth_token = Self::cookie_value(&cookie_val, "auth_token").is_some();
+
+        let mut request = self
+            .client
+            .get(url)
+            .header("Authorization", BEARER)
+            .header("x-guest-token", guest_token)
+            .header("x-twitter-client-language", "en")
+            .header("x-twitter-active-user", "yes")
+            .header("Accept-Language", "en")
+            .header("Content-Type", "application/json")
+            .header("Cookie", &cookie_val);
+        if has_auth_token {
+            request = request.header("x-twitter-auth-type", "OAuth2Session");
+        }
+        if let Some(ct0) = ct0 {
+            request = request.header("x-csrf-token", ct0);
+        }
+        request
+    }
+
     async fn request_tweet(
         &self,
         tweet_id: &str,
@@ -332,47 +432,173 @@ impl TwitterDownloader {
             urlencoding::encode(TWEET_FIELD_TOGGLES),
         );
 
-        let cookie_val = Self::request_cookie_header(guest_token);
-        let ct0 = Self::cookie_value(&cookie_val, "ct0");
-        let has_auth_token = Self::cookie_value(&cookie_val, "auth_token").is_some();
+        let response = self.api_request(&url, guest_token).send().await?;
 
-        let mut request = self
-            .client
-            .get(&url)
-            .header("Authorization", BEARER)
-            .header("x-guest-token", guest_token)
-            .header("x-twitter-client-language", "en")
-            .header("x-twitter-active-user", "yes")
-            .header("Accept-Language", "en")
-            .header("Content-Type", "application/json")
-            .header("Cookie", &cookie_val);
-        if has_auth_token {
-            request = request.header("x-twitter-auth-type", "OAuth2Session");
+        let status = response.status();
+        tracing::debug!("[twitter] graphql tweet_id={} status={}", tweet_id, status);
+
+        if status == reqwest::StatusCode::FORBIDDEN
+            || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        {
+            return Err(DownloadError::TokenExpired.into());
         }
-        if let Some(ct0) = ct0 {
-            request = request.header("x-csrf-token", ct0);
+
+        if !status.is_success() {
+            return Err(DownloadError::from_status("Twitter API", status).into());
         }
 
-        let response = request.send().await?;
+        response.json().await.map_err(Into::into)
+    }
+
+    async fn request_audio_space(
+        &self,
+        space_id: &str,
+        guest_token: &str,
+    ) -> anyhow::Result<serde_json::Value> {
+        let variables = serde_json::json!({
+            "id": space_id,
+            "isMetatagsQuery": false,
+            "withReplays": true,
+            "withListeners": false
+        });
+        let url = format!(
+            "{}?variables={}&features={}",
+            AUDIO_SPACE_URL,
+            urlencoding::encode(&variables.to_string()),
+            urlencoding::encode(SPACE_FEATURES),
+        );
 
+        let response = self.api_request(&url, guest_token).send().await?;
         let status = response.status();
-        tracing::debug!("[twitter] graphql tweet_id={} status={}", tweet_id, status);
+        tracing::debug!("[twitter] audio space id={} status={}", space_id, status);
 
         if status == reqwest::StatusCode::FORBIDDEN
             || status == reqwest::StatusCode::TOO_MANY_REQUESTS
         {
-            return Err(anyhow!("token_expired"));
+            return Err(DownloadError::TokenExpired.into());
         }
-
-        if status == reqwest::StatusCode::NOT_FOUND {
-            return Err(anyhow!("Post not available"));
+        if !status.is_success() {
+            return Err(DownloadError::from_status("Twitter API", status).into());
         }
 
+        response.json().await.map_err(Into::into)
+    }
+
+    fn parse_audio_space(json: &serde_json::Value) -> Option<AudioSpace> {
+        let metadata = json.pointer("/data/audioSpace/metadata")?;
+        let text = |pointer: &str| {
+            metadata
+                .pointer(pointer)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        let creator = metadata.pointer("/creator_results/result");
+        Some(AudioSpace {
+            media_key: text("/media_key")?,
+            state: text("/state").unwrap_or_default(),
+            replayable: metadata
+                .get("is_space_available_for_replay")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            title: text("/title"),
+            creator: creator
+                .and_then(|c| {
+                    c.pointer("/core/screen_name")
+                        .or_else(|| c.pointer("/legacy/screen_name"))
+                })
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            started_at_ms: metadata.get("started_at").and_then(|v| v.as_i64()),
+            ended_at_ms: metadata
+                .get("ended_at")
+                .and_then(|v| v.as_i64().or_else(|| v.as_str()?.parse().ok())),
+        })
+    }
+
+    /// The HLS playlist of a Space's recording, from the stream status of
+    /// its media key.
+    async fn space_stream_url(&self, media_key: &str, guest_token: &str) -> anyhow::Result<String> {
+        let url = format!(
+            "{}/{}?client=web&use_syndication_guest_id=false&cookie_set_host=x.com",
+            LIVE_STREAM_STATUS_URL, media_key
+        );
+        let response = self.api_request(&url, guest_token).send().await?;
+        let status = response.status();
         if !status.is_success() {
-            return Err(anyhow!("Twitter API retornou HTTP {}", status));
+            return Err(DownloadError::from_status("Twitter API", status).into());
         }
+        let json: serde_json::Value = response.json().await?;
+        json.pointer("/source/location")
+            .or_else(|| json.pointer("/source/noRedirectPlaybackUrl"))
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Space stream has no playlist"))
+    }
 
-        response.json().await.map_err(Into::into)
+    /// An ended, recorded Space as a single audio rendition. Spaces that are
+    /// still on air, scheduled or unrecorded have nothing to download.
+    async fn space_media_info(&self, space_id: &str) -> anyhow::Result<MediaInfo> {
+        let token = self.get_guest_token(None).await?;
+        let (json, token) = match self.request_audio_space(space_id, &token).await {
+            Ok(json) => (json, token),
+            Err(e) if e.downcast_ref() == Some(&DownloadError::TokenExpired) => {
+                let new_token = self.get_guest_token(Some(&token)).await?;
+                (
+                    self.request_audio_space(space_id, &new_token).await?,
+                    new_token,
+                )
+            }
+            Err(e) => return Err(e),
+        };
+        let space = Self::parse_audio_space(&json).ok_or(DownloadError::NotFound)?;
+
+        match space.state.as_str() {
+            "Running" => return Err(DownloadError::LiveStream.into()),
+            "Ended" if space.replayable => {}
+            "Ended" => {
+                return Err(DownloadError::Unsupported(
+                    "This Space ended without a recording".to_string(),
+                )
+                .into())
+            }
+            _ => {
+                return Err(
+                    DownloadError::Unsupported("This Space hasn't aired yet".to_string()).into(),
+                )
+            }
+        }
+
+        let playlist = self.space_stream_url(&space.media_key, &token).await?;
+        let duration_seconds = match (space.started_at_ms, space.ended_at_ms) {
+            (Some(start), Some(end)) if end > start => Some((end - start) as f64 / 1000.0),
+            _ => None,
+        };
+
+        Ok(MediaInfo {
+            id: Some(space_id.to_string()),
+            title: format!("twitter_space_{}", space_id),
+            author: space.creator.unwrap_or_default(),
+            platform: "twitter".to_string(),
+            duration_seconds,
+            thumbnail_url: None,
+            available_qualities: vec![VideoQuality {
+                label: "audio".to_string(),
+                width: 0,
+                height: 0,
+                url: playlist,
+                format: "m3u8".to_string(),
+                ..Default::default()
+            }],
+            media_type: MediaType::Audio,
+            metadata: Some(PostMetadata {
+                description: space.title,
+                timestamp: space.started_at_ms.map(|ms| ms / 1000),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
     }
 
     fn calculate_syndication_token(id: &str) -> String {
@@ -457,40 +683,197 @@ impl TwitterDownloader {
         );
 
         if !response.status().is_success() {
-            return Err(anyhow!(
-                "Syndication API retornou HTTP {}",
-                response.status()
-            ));
+            return Err(DownloadError::from_status("Syndication API", response.status()).into());
         }
 
         response.json().await.map_err(Into::into)
     }
 
+    /// The tweet itself when it's wrapped in `TweetWithVisibilityResults`.
+    fn unwrap_tweet(tweet_result: &serde_json::Value) -> &serde_json::Value {
+        tweet_result.get("tweet").unwrap_or(tweet_result)
+    }
+
+    /// The focal tweet's result in a `TweetDetail` response, unwrapped.
+    fn graphql_focal<'a>(
+        json: &'a serde_json::Value,
+        tweet_id: &str,
+    ) -> Option<&'a serde_json::Value> {
+        let entry_id = format!("tweet-{}", tweet_id);
+        let tweet = json
+            .pointer("/data/threaded_conversation_with_injections_v2/instructions")?
+            .as_array()?
+            .iter()
+            .filter_map(|i| i.get("entries").and_then(|v| v.as_array()))
+            .flatten()
+            .find(|e| e.get("entryId").and_then(|v| v.as_str()) == Some(&entry_id))?
+            .pointer("/content/itemContent/tweet_results/result")?;
+        Some(Self::unwrap_tweet(tweet))
+    }
+
+    /// The poster's `screen_name` from the focal tweet's `core/user_results`.
+    /// Newer responses move it from `legacy` to `core`.
+    fn graphql_author(json: &serde_json::Value, tweet_id: &str) -> Option<String> {
+        let user = Self::graphql_focal(json, tweet_id)?.pointer("/core/user_results/result")?;
+        user.pointer("/core/screen_name")
+            .or_else(|| user.pointer("/legacy/screen_name"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    }
+
+    /// Text, date and counts of the focal tweet. Long posts keep their
+    /// full text in `note_tweet`; `legacy.full_text` is cut short.
+    fn graphql_metadata(json: &serde_json::Value, tweet_id: &str) -> Option<PostMetadata> {
+        let tweet = Self::graphql_focal(json, tweet_id)?;
+        let legacy = tweet.get("legacy")?;
+        let description = tweet
+            .pointer("/note_tweet/note_tweet_results/result/text")
+            .or_else(|| legacy.get("full_text"))
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        let tags = legacy
+            .pointer("/entities/hashtags")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|h| h.get("text").and_then(|v| v.as_str()))
+            .map(String::from)
+            .collect();
+        Some(PostMetadata {
+            description,
+            timestamp: legacy
+                .get("created_at")
+                .and_then(|v| v.as_str())
+                .and_then(|s| chrono::DateTime::parse_from_str(s, "%a %b %d %H:%M:%S %z %Y").ok())
+                .map(|t| t.timestamp()),
+            tags,
+            view_count: json_count(tweet.pointer("/views/count")),
+            like_count: json_count(legacy.get("favorite_count")),
+            comment_count: json_count(legacy.get("reply_count")),
+            share_count: json_count(legacy.get("retweet_count")),
+        })
+    }
+
+    /// The syndication endpoint only has text, date, likes and replies.
+    fn syndication_metadata(json: &serde_json::Value) -> PostMetadata {
+        let description = json.get("text").and_then(|v| v.as_str()).map(String::from);
+        PostMetadata {
+            tags: description
+                .as_deref()
+                .map(PostMetadata::hashtags_in)
+                .unwrap_or_default(),
+            description,
+            timestamp: json
+                .get("created_at")
+                .and_then(|v| v.as_str())
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                .map(|t| t.timestamp()),
+            like_count: json_count(json.get("favorite_count")),
+            comment_count: json_count(json.get("conversation_count")),
+            ..Default::default()
+        }
+    }
+
+    /// Every tweet in the timeline, in order: single entries and the
+    /// `conversationthread-*` modules that hold replies.
+    fn entry_tweet_results(entries: &[serde_json::Value]) -> Vec<&serde_json::Value> {
+        let mut results = Vec::new();
+        for entry in entries {
+            if let Some(result) = entry.pointer("/content/itemContent/tweet_results/result") {
+                results.push(result);
+            }
+            let items = entry.pointer("/content/items").and_then(|v| v.as_array());
+            for item in items.into_iter().flatten() {
+                if let Some(result) = item.pointer("/item/itemContent/tweet_results/result") {
+                    results.push(result);
+                }
+            }
+        }
+        results
+    }
+
+    /// Media from every tweet of the focal tweet's self-thread: tweets by
+    /// the same author in the same conversation, in timeline order. The
+    /// focal tweet keeps `focal_media`, which may come from a quote or
+    /// retweet; other tweets only contribute their own media.
+    fn thread_media(
+        entries: &[serde_json::Value],
+        focal: &serde_json::Value,
+        focal_media: Vec<serde_json::Value>,
+    ) -> Vec<serde_json::Value> {
+        let focal = Self::unwrap_tweet(focal);
+        let author = focal
+            .pointer("/legacy/user_id_str")
+            .and_then(|v| v.as_str());
+        let conversation = focal
+            .pointer("/legacy/conversation_id_str")
+            .and_then(|v| v.as_str());
+        let (Some(author), Some(conversation)) = (author, conversation) else {
+            return focal_media;
+        };
+        let focal_id = focal.get("rest_id").and_then(|v| v.as_str());
+
+        let mut seen = std::collections::HashSet::new();
+        let mut focal_media = Some(focal_media);
+        let mut media = Vec::new();
+        for result in Self::entry_tweet_results(entries) {
+            let tweet = Self::unwrap_tweet(result);
+            let Some(id) = tweet.get("rest_id").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            if tweet
+                .pointer("/legacy/user_id_str")
+                .and_then(|v| v.as_str())
+                != Some(author)
+                || tweet
+                    .pointer("/legacy/conversation_id_str")
+                    .and_then(|v| v.as_str())
+                    != Some(conversation)
+                || !seen.insert(id)
+            {
+                continue;
+            }
+            if Some(id) == focal_id {
+                media.extend(focal_media.take().unwrap_or_default());
+            } else if let Some(items) = tweet
+                .pointer("/legacy/extended_entities/media")
+                .and_then(Self::clone_media_array)
+            {
+                media.extend(items);
+            }
+        }
+        if let Some(focal_media) = focal_media {
+            media.splice(0..0, focal_media);
+        }
+        media
+    }
+
     fn extract_graphql_media(
         json: &serde_json::Value,
         tweet_id: &str,
+        full_thread: bool,
     ) -> anyhow::Result<Vec<serde_json::Value>> {
         let instructions = json
             .pointer("/data/threaded_conversation_with_injections_v2/instructions")
             .and_then(|v| v.as_array())
-            .ok_or_else(|| anyhow!("Post not available"))?;
+            .ok_or(DownloadError::NotFound)?;
 
         let add_insn = instructions
             .iter()
             .find(|i| i.get("type").and_then(|v| v.as_str()) == Some("TimelineAddEntries"))
-            .ok_or_else(|| anyhow!("Post not available"))?;
+            .ok_or(DownloadError::NotFound)?;
 
         let entry_id = format!("tweet-{}", tweet_id);
         let entries = add_insn
             .get("entries")
             .and_then(|v| v.as_array())
-            .ok_or_else(|| anyhow!("Post not available"))?;
+            .ok_or(DownloadError::NotFound)?;
 
         let tweet_result = entries
             .iter()
             .find(|e| e.get("entryId").and_then(|v| v.as_str()) == Some(&entry_id))
             .and_then(|e| e.pointer("/content/itemContent/tweet_results/result"))
-            .ok_or_else(|| anyhow!("Post not available"))?;
+            .ok_or(DownloadError::NotFound)?;
 
         let typename = tweet_result
             .get("__typename")
@@ -511,7 +894,7 @@ impl TwitterDownloader {
                     .unwrap_or("");
 
                 if reason == "Protected" {
-                    return Err(anyhow!("Post privado"));
+                    return Err(DownloadError::Private.into());
                 }
 
                 let tombstone_text = tweet_result
@@ -527,14 +910,20 @@ impl TwitterDownloader {
                 );
 
                 if reason == "NsfwLoggedOut" || tombstone_text.starts_with("Age-restricted") {
-                    return Err(anyhow!("Age-restricted content"));
+                    return Err(DownloadError::AgeRestricted.into());
                 }
 
-                Err(anyhow!("Post not available"))
+                Err(DownloadError::NotFound.into())
             }
             "Tweet" | "TweetWithVisibilityResults" => {
-                let media = Self::media_arrays_from_tweet_result(tweet_result)
-                    .ok_or_else(|| anyhow!("No media found in tweet"))?;
+                let mut media =
+                    Self::media_arrays_from_tweet_result(tweet_result).unwrap_or_default();
+                if full_thread {
+                    media = Self::thread_media(entries, tweet_result, media);
+                }
+                if media.is_empty() {
+                    return Err(anyhow!("No media found in tweet"));
+                }
                 tracing::debug!(
                     "[twitter] graphql extracted {} media entries for tweet_id={}",
                     media.len(),
@@ -542,7 +931,7 @@ impl TwitterDownloader {
                 );
                 Ok(media)
             }
-            _ => Err(anyhow!("Post not available")),
+            _ => Err(DownloadError::NotFound.into()),
         }
     }
 
@@ -555,7 +944,7 @@ impl TwitterDownloader {
             .unwrap_or("");
         if typename == "TweetTombstone" || typename == "TweetUnavailable" {
             tracing::warn!("[twitter] syndication tombstone typename={}", typename);
-            return Err(anyhow!("Post not available"));
+            return Err(DownloadError::NotFound.into());
         }
 
         let media = json
@@ -573,7 +962,9 @@ impl TwitterDownloader {
         Ok(media)
     }
 
-    fn best_video_url(media_item: &serde_json::Value) -> Option<String> {
+    /// Picks the highest-bitrate mp4 variant, falling back to an HLS
+    /// playlist. Returns the URL and the variant's bitrate if it has one.
+    fn best_video_url(media_item: &serde_json::Value) -> Option<(String, Option<u64>)> {
         let variants = media_item
             .pointer("/video_info/variants")
             .or_else(|| media_item.pointer("/video/variants"))
@@ -583,8 +974,10 @@ impl TwitterDownloader {
             .iter()
             .filter(|v| v.get("content_type").and_then(|c| c.as_str()) == Some("video/mp4"))
             .max_by_key(|v| v.get("bitrate").and_then(|b| b.as_u64()).unwrap_or(0))
-            .and_then(|v| v.get("url").and_then(|u| u.as_str()))
-            .map(|s| s.to_string());
+            .and_then(|v| {
+                let url = v.get("url").and_then(|u| u.as_str())?;
+                Some((url.to_string(), v.get("bitrate").and_then(|b| b.as_u64())))
+            });
 
         if best_mp4.is_some() {
             return best_mp4;
@@ -600,7 +993,7 @@ impl TwitterDownloader {
                     .filter_map(|v| v.get("url").and_then(|u| u.as_str()))
                     .next()
             })
-            .map(|s| s.to_string())
+            .map(|s| (s.to_string(), None))
     }
 
     fn best_photo_url(media_item: &serde_json::Value) -> Option<(String, String)> {
@@ -675,6 +1068,7 @@ impl TwitterDownloader {
                         media_type: TwitterMediaType::Photo,
                         url,
                         extension,
+                        bitrate: None,
                     });
                 }
             }
@@ -692,10 +1086,11 @@ impl TwitterDownloader {
                         media_type: TwitterMediaType::Photo,
                         url,
                         extension: ext,
+                        bitrate: None,
                     })
                 }
                 TwitterMediaType::Video => {
-                    let url = Self::best_video_url(m)?;
+                    let (url, bitrate) = Self::best_video_url(m)?;
                     let extension = if url.contains(".m3u8") || url.contains("mpegurl") {
                         "ytdlp"
                     } else {
@@ -705,14 +1100,16 @@ impl TwitterDownloader {
                         media_type: TwitterMediaType::Video,
                         url,
                         extension: extension.to_string(),
+                        bitrate,
                     })
                 }
                 TwitterMediaType::AnimatedGif => {
-                    let url = Self::best_video_url(m)?;
+                    let (url, bitrate) = Self::best_video_url(m)?;
                     Some(TwitterMediaItem {
                         media_type: TwitterMediaType::AnimatedGif,
                         url,
                         extension: "mp4".to_string(),
+                        bitrate,
                     })
                 }
             })
@@ -745,6 +1142,7 @@ impl TwitterDownloader {
             TwitterMedia::Single(item) => {
                 let media_type = Self::media_type_for_item(&item);
                 MediaInfo {
+                    id: filename_base.strip_prefix("twitter_").map(String::from),
                     title: filename_base,
                     author: String::new(),
                     platform: "twitter".to_string(),
@@ -754,11 +1152,14 @@ impl TwitterDownloader {
                         label: "original".to_string(),
                         width: 0,
                         height: 0,
+                        acodec: item.acodec(),
                         url: item.url,
                         format: item.extension,
+                        bitrate: item.bitrate,
+                        ..Default::default()
                     }],
                     media_type,
-                    file_size_bytes: None,
+                    ..Default::default()
                 }
             }
             TwitterMedia::Multiple(items) => {
@@ -771,10 +1172,14 @@ impl TwitterDownloader {
                         height: 0,
                         url: item.url.clone(),
                         format: item.extension.clone(),
+                        acodec: item.acodec(),
+                        bitrate: item.bitrate,
+                        ..Default::default()
                     })
                     .collect();
 
                 MediaInfo {
+                    id: filename_base.strip_prefix("twitter_").map(String::from),
                     title: filename_base,
                     author: String::new(),
                     platform: "twitter".to_string(),
@@ -782,7 +1187,7 @@ impl TwitterDownloader {
                     thumbnail_url: None,
                     available_qualities: qualities,
                     media_type: MediaType::Carousel,
-                    file_size_bytes: None,
+                    ..Default::default()
                 }
             }
         }
@@ -796,25 +1201,34 @@ impl PlatformDownloader for TwitterDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host == "twitter.com"
-                    || host.ends_with(".twitter.com")
-                    || host == "x.com"
-                    || host.ends_with(".x.com")
-                    || host == "vxtwitter.com"
-                    || host.ends_with(".vxtwitter.com")
-                    || host == "fixvx.com"
-                    || host.ends_with(".fixvx.com");
-            }
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["twitter.com", "x.com", "vxtwitter.com", "fixvx.com"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_audio_only: true,
+            ..Capabilities::default()
         }
-        false
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
         match self.native_get_media_info(url).await {
-            Ok(info) => Ok(info),
+            Ok(mut info) => {
+                crate::core::direct_downloader::fill_file_size(&self.client, &mut info, None).await;
+                Ok(info)
+            }
+            Err(native_err)
+                if matches!(
+                    native_err.downcast_ref::<DownloadError>(),
+                    Some(DownloadError::LiveStream | DownloadError::Unsupported(_))
+                ) =>
+            {
+                Err(native_err)
+            }
             Err(native_err) => {
                 tracing::warn!(
                     "[twitter] native failed: {}, trying yt-dlp fallback",
@@ -868,11 +1282,48 @@ impl PlatformDownloader for TwitterDownloader {
                     false,
                     &extra_flags,
                     opts.audio_format.as_deref(),
+                    &opts.quality_preference,
+                    &opts.extra_ytdlp_args,
                 )
                 .await;
             }
         }
 
+        if let Some(quality) = info
+            .available_qualities
+            .first()
+            .filter(|q| q.format == "m3u8")
+        {
+            let filename = opts.media_filename(info, "aac");
+            let output_path = match opts.output_path(&filename) {
+                Ok(path) => path,
+                Err(skipped) => return Ok(skipped),
+            };
+
+            let downloader = HlsDownloader::with_client(self.client.clone())
+                .with_user_agent_override(opts.user_agent.clone());
+            let _ = progress.send(ProgressUpdate::percent(0.0)).await;
+
+            let result = downloader
+                .download(
+                    &quality.url,
+                    &output_path.to_string_lossy(),
+                    "https://x.com/",
+                    None,
+                    opts.cancel_token.clone(),
+                    opts.hls_max_concurrent_segments,
+                    opts.hls_max_retries,
+                )
+                .await?;
+
+            let _ = progress.send(ProgressUpdate::percent(100.0)).await;
+
+            return Ok(DownloadResult {
+                duration_seconds: info.duration_seconds.unwrap_or(0.0),
+                ..DownloadResult::new(result.path, result.file_size)
+            });
+        }
+
         let count = info.available_qualities.len();
 
         if count == 0 {
@@ -883,12 +1334,11 @@ impl PlatformDownloader for TwitterDownloader {
 
         if count == 1 {
             let quality = info.available_qualities.first().unwrap();
-            let filename = format!(
-                "{}.{}",
-                sanitize_filename::sanitize(&info.title),
-                quality.format
-            );
-            let output = opts.output_dir.join(&filename);
+            let filename = opts.media_filename(info, &quality.format);
+            let output = match opts.output_path(&filename) {
+                Ok(path) => path,
+                Err(skipped) => return Ok(skipped),
+            };
 
             let bytes = direct_downloader::download_direct(
                 &self.client,
@@ -898,55 +1348,122 @@ impl PlatformDownloader for TwitterDownloader {
                 Some(&opts.cancel_token),
             )
             .await?;
+            let bytes =
+                crate::core::image::strip_downloaded_image(&output, bytes, opts.strip_metadata);
+            let (output, bytes, note) = if opts.is_mute() && quality.format == "mp4" {
+                let (bytes, note) = ffmpeg::mute_downloaded(&output, bytes).await?;
+                (output, bytes, note)
+            } else if opts.is_audio() && quality.format == "mp4" {
+                Self::audio_from_saved(&output, bytes, quality, opts).await?
+            } else {
+                (output, bytes, None)
+            };
 
             return Ok(DownloadResult {
-                file_path: output,
-                file_size_bytes: bytes,
-                duration_seconds: 0.0,
-                torrent_id: None,
+                note,
+                ..DownloadResult::new(output, bytes)
             });
         }
 
         let mut total_bytes = 0u64;
-        let mut last_path = opts.output_dir.clone();
+        let mut saved = Vec::new();
+        let mut already_saved = None;
+        let mut note = None;
 
         for (i, quality) in info.available_qualities.iter().enumerate() {
-            let filename = format!(
-                "{}_{}.{}",
-                sanitize_filename::sanitize(&info.title),
-                i + 1,
-                quality.format
+            if !opts.is_selected(i) {
+                continue;
+            }
+            let filename = filename::with_quality_suffix(
+                &opts.media_filename(info, &quality.format),
+                &(i + 1).to_string(),
             );
-            let output = opts.output_dir.join(&filename);
-            let (tx, _rx) = mpsc::channel(8);
-
+            let output = match opts.output_path(&filename) {
+                Ok(path) => path,
+                Err(existing) => {
+                    already_saved.get_or_insert(existing);
+                    continue;
+                }
+            };
+            let (part_tx, forwarder) = forward_part(progress.clone(), i, count);
             let bytes = direct_downloader::download_direct(
                 &self.client,
                 &quality.url,
                 &output,
-                tx,
+                part_tx,
                 Some(&opts.cancel_token),
             )
-            .await?;
-
-            total_bytes += bytes;
-            last_path = output;
+            .await;
+            let _ = forwarder.await;
+            let bytes = bytes?;
+
+            let bytes =
+                crate::core::image::strip_downloaded_image(&output, bytes, opts.strip_metadata);
+            let output = if opts.is_mute() && quality.format == "mp4" {
+                let (muted, kept_audio) = ffmpeg::mute_downloaded(&output, bytes).await?;
+                total_bytes += muted;
+                note = note.or(kept_audio);
+                output
+            } else if opts.is_audio() && quality.format == "mp4" {
+                let (audio, bytes, kept_video) =
+                    Self::audio_from_saved(&output, bytes, quality, opts).await?;
+                total_bytes += bytes;
+                note = note.or(kept_video);
+                audio
+            } else {
+                total_bytes += bytes;
+                output
+            };
+            saved.push(output);
 
             let percent = ((i + 1) as f64 / count as f64) * 100.0;
             let _ = progress.send(ProgressUpdate::percent(percent)).await;
         }
 
+        if let Some(existing) = already_saved.filter(|_| saved.is_empty()) {
+            return Ok(existing);
+        }
+        let file_path = saved.pop().unwrap_or_else(|| opts.output_dir.clone());
+
         Ok(DownloadResult {
-            file_path: last_path,
-            file_size_bytes: total_bytes,
-            duration_seconds: 0.0,
-            torrent_id: None,
+            note,
+            extra_files: saved,
+            ..DownloadResult::new(file_path, total_bytes)
         })
     }
 }
 
 impl TwitterDownloader {
+    /// `download_mode: "audio"` for a saved mp4. GIFs have no audio to
+    /// extract, so they are kept as they are with a note.
+    async fn audio_from_saved(
+        output: &std::path::Path,
+        bytes: u64,
+        quality: &VideoQuality,
+        opts: &DownloadOptions,
+    ) -> anyhow::Result<(std::path::PathBuf, u64, Option<DownloadNote>)> {
+        if quality.acodec.as_deref() == Some("none") {
+            tracing::info!(
+                "[twitter] {} has no audio track; keeping it",
+                output.display()
+            );
+            return Ok((
+                output.to_path_buf(),
+                bytes,
+                Some(DownloadNote::NoAudioKeptVideo),
+            ));
+        }
+        ffmpeg::audio_from_downloaded(
+            output,
+            bytes,
+            opts.audio_format.as_deref(),
+            opts.cancel_token.clone(),
+        )
+        .await
+    }
+
     async fn fallback_ytdlp(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        omniget_core::platforms::check_ytdlp_fallback("twitter")?;
         let ytdlp_path = crate::core::ytdlp::ensure_ytdlp().await?;
         let mut extra_flags = vec![
             "--referer".to_string(),
@@ -963,6 +1480,10 @@ impl TwitterDownloader {
     }
 
     async fn native_get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        if let Some(space_id) = Self::extract_space_id(url) {
+            return self.space_media_info(&space_id).await;
+        }
+
         let tweet_id =
             Self::extract_tweet_id(url).ok_or_else(|| anyhow!("Could not extract tweet ID"))?;
         tracing::debug!(
@@ -972,9 +1493,18 @@ impl TwitterDownloader {
         );
 
         let filename_base = format!("twitter_{}", tweet_id);
-
-        let media_items = match self.try_graphql(&tweet_id).await {
-            Ok(items) => items,
+        let full_thread = crate::storage::config::load_settings_standalone()
+            .download
+            .twitter_full_thread;
+
+        let mut author = None;
+        let mut metadata = None;
+        let media_items = match self.try_graphql(&tweet_id, full_thread).await {
+            Ok(tweet) => {
+                author = tweet.author;
+                metadata = tweet.metadata;
+                tweet.media
+            }
             Err(graphql_err) => {
                 tracing::warn!(
                     "[twitter] graphql lookup failed for tweet_id={}: {}",
@@ -983,7 +1513,14 @@ impl TwitterDownloader {
                 );
                 match self.request_syndication(&tweet_id).await {
                     Ok(syndication) => match Self::extract_syndication_media(&syndication) {
-                        Ok(items) => items,
+                        Ok(items) => {
+                            author = syndication
+                                .pointer("/user/screen_name")
+                                .and_then(|v| v.as_str())
+                                .map(str::to_string);
+                            metadata = Some(Self::syndication_metadata(&syndication));
+                            items
+                        }
                         Err(syndication_extract_err) => {
                             tracing::warn!(
                                 "[twitter] syndication media extraction failed for tweet_id={}: {}",
@@ -993,12 +1530,12 @@ impl TwitterDownloader {
                             match self.request_html_media(url).await {
                                 Ok(items) => items,
                                 Err(html_err) => {
-                                    return Err(anyhow!(
+                                    let summary = format!(
                                         "Post not available; graphql='{}'; syndication_extract='{}'; html='{}'",
-                                        graphql_err,
-                                        syndication_extract_err,
-                                        html_err
-                                    ));
+                                        graphql_err, syndication_extract_err, html_err
+                                    );
+                                    self.last_resort_vxtwitter(&tweet_id, graphql_err, summary)
+                                        .await?
                                 }
                             }
                         }
@@ -1012,12 +1549,12 @@ impl TwitterDownloader {
                         match self.request_html_media(url).await {
                             Ok(items) => items,
                             Err(html_err) => {
-                                return Err(anyhow!(
+                                let summary = format!(
                                     "Post not available; graphql='{}'; syndication='{}'; html='{}'",
-                                    graphql_err,
-                                    syndication_err,
-                                    html_err
-                                ));
+                                    graphql_err, syndication_err, html_err
+                                );
+                                self.last_resort_vxtwitter(&tweet_id, graphql_err, summary)
+                                    .await?
                             }
                         }
                     }
@@ -1027,17 +1564,16 @@ impl TwitterDownloader {
 
         let twitter_media = Self::parse_media_items(&media_items)?;
 
-        Ok(Self::media_info_from_twitter_media(
-            filename_base,
-            twitter_media,
-        ))
+        let mut info = Self::media_info_from_twitter_media(filename_base, twitter_media);
+        info.author = author.unwrap_or_default();
+        info.metadata = metadata;
+        Ok(info)
     }
 
     async fn request_html_media(&self, url: &str) -> anyhow::Result<Vec<serde_json::Value>> {
         let mut request = self
             .client
             .get(url)
-            .header("User-Agent", USER_AGENT)
             .header("Accept-Language", "en")
             .header("Referer", "https://x.com/");
         if let Some(cookie) = Self::auth_cookie_string() {
@@ -1064,17 +1600,285 @@ impl TwitterDownloader {
             .collect())
     }
 
-    async fn try_graphql(&self, tweet_id: &str) -> anyhow::Result<Vec<serde_json::Value>> {
-        let token = self.get_guest_token(false).await?;
+    /// Asks the vxtwitter mirror, which fetches the post with its own
+    /// accounts, once every x.com path has failed. On failure the GraphQL
+    /// error is kept as the cause so its `DownloadError` still drives hints.
+    async fn last_resort_vxtwitter(
+        &self,
+        tweet_id: &str,
+        graphql_err: anyhow::Error,
+        summary: String,
+    ) -> anyhow::Result<Vec<serde_json::Value>> {
+        match self.request_vxtwitter(tweet_id).await {
+            Ok(items) => {
+                tracing::info!(
+                    "[twitter] vxtwitter fallback succeeded for tweet_id={}",
+                    tweet_id
+                );
+                Ok(items)
+            }
+            Err(vx_err) => Err(graphql_err.context(format!("{}; vxtwitter='{}'", summary, vx_err))),
+        }
+    }
+
+    async fn request_vxtwitter(&self, tweet_id: &str) -> anyhow::Result<Vec<serde_json::Value>> {
+        let url = format!("{}/{}", VXTWITTER_API, tweet_id);
+        let response = self
+            .client
+            .get(&url)
+            .header("Accept", "application/json")
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(DownloadError::from_status("vxtwitter", response.status()).into());
+        }
+        let json: serde_json::Value = response.json().await?;
+        Self::extract_vxtwitter_media(&json)
+    }
+
+    /// Rewrites vxtwitter's `media_extended` entries into the GraphQL media
+    /// shape so `parse_media_items` can build the qualities.
+    fn extract_vxtwitter_media(json: &serde_json::Value) -> anyhow::Result<Vec<serde_json::Value>> {
+        let media = json
+            .get("media_extended")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow!("No media found in tweet"))?;
+
+        let items: Vec<serde_json::Value> = media
+            .iter()
+            .filter_map(|m| {
+                let url = m.get("url").and_then(|v| v.as_str())?;
+                match m.get("type").and_then(|v| v.as_str())? {
+                    "image" => Some(serde_json::json!({
+                        "type": "photo",
+                        "media_url_https": url,
+                    })),
+                    kind @ ("video" | "gif") => Some(serde_json::json!({
+                        "type": if kind == "gif" { "animated_gif" } else { "video" },
+                        "video_info": {
+                            "variants": [{ "content_type": "video/mp4", "url": url }],
+                        },
+                    })),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        if items.is_empty() {
+            return Err(anyhow!("No media found in tweet"));
+        }
+        tracing::debug!(
+            "[twitter] vxtwitter extracted {} media entries",
+            items.len()
+        );
+        Ok(items)
+    }
+
+    async fn try_graphql(&self, tweet_id: &str, full_thread: bool) -> anyhow::Result<GraphqlTweet> {
+        let token = self.get_guest_token(None).await?;
 
-        match self.request_tweet(tweet_id, &token).await {
-            Ok(json) => Self::extract_graphql_media(&json, tweet_id),
-            Err(e) if e.to_string() == "token_expired" => {
-                let new_token = self.get_guest_token(true).await?;
-                let json = self.request_tweet(tweet_id, &new_token).await?;
-                Self::extract_graphql_media(&json, tweet_id)
+        let json = match self.request_tweet(tweet_id, &token).await {
+            Ok(json) => json,
+            Err(e) if e.downcast_ref() == Some(&DownloadError::TokenExpired) => {
+                let new_token = self.get_guest_token(Some(&token)).await?;
+                self.request_tweet(tweet_id, &new_token).await?
             }
-            Err(e) => Err(e),
+            Err(e) => return Err(e),
+        };
+        Ok(GraphqlTweet {
+            media: Self::extract_graphql_media(&json, tweet_id, full_thread)?,
+            author: Self::graphql_author(&json, tweet_id),
+            metadata: Self::graphql_metadata(&json, tweet_id),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guest_token_expires_and_can_be_rejected() {
+        let fresh = GuestToken {
+            value: "abc".to_string(),
+            fetched_at: Instant::now(),
+        };
+        assert!(fresh.usable(None));
+        assert!(fresh.usable(Some("older")));
+        assert!(!fresh.usable(Some("abc")));
+
+        if let Some(fetched_at) = Instant::now().checked_sub(GUEST_TOKEN_TTL) {
+            let stale = GuestToken {
+                value: "abc".to_string(),
+                fetched_at,
+            };
+            assert!(!stale.usable(None));
         }
     }
+
+    #[test]
+    fn vxtwitter_media_maps_to_qualities() {
+        let json = serde_json::json!({
+            "media_extended": [
+                { "type": "video", "url": "https://video.twimg.com/ext_tw_video/1/vid/720x1280/a.mp4" },
+                { "type": "image", "url": "https://pbs.twimg.com/media/abc.jpg" },
+                { "type": "unknown", "url": "https://example.com/x" }
+            ]
+        });
+        let items = TwitterDownloader::extract_vxtwitter_media(&json).unwrap();
+        assert_eq!(items.len(), 2);
+
+        let info = TwitterDownloader::media_info_from_twitter_media(
+            "twitter_1".to_string(),
+            TwitterDownloader::parse_media_items(&items).unwrap(),
+        );
+        assert_eq!(info.media_type, MediaType::Carousel);
+        assert_eq!(info.available_qualities[0].format, "mp4");
+        assert_eq!(
+            info.available_qualities[1].url,
+            "https://pbs.twimg.com/media/abc.jpg?name=orig"
+        );
+
+        assert!(TwitterDownloader::extract_vxtwitter_media(&serde_json::json!({})).is_err());
+    }
+
+    fn thread_tweet(id: &str, author: &str, photo: &str) -> serde_json::Value {
+        serde_json::json!({
+            "__typename": "Tweet",
+            "rest_id": id,
+            "legacy": {
+                "user_id_str": author,
+                "conversation_id_str": "1",
+                "extended_entities": { "media": [
+                    { "type": "photo", "media_url_https": photo }
+                ] }
+            }
+        })
+    }
+
+    #[test]
+    fn full_thread_collects_media_from_the_authors_tweets() {
+        let json = serde_json::json!({
+            "data": { "threaded_conversation_with_injections_v2": { "instructions": [{
+                "type": "TimelineAddEntries",
+                "entries": [
+                    { "entryId": "tweet-1", "content": { "itemContent": {
+                        "tweet_results": { "result": thread_tweet("1", "42", "a.jpg") }
+                    } } },
+                    { "entryId": "tweet-2", "content": { "itemContent": {
+                        "tweet_results": { "result": thread_tweet("2", "42", "b.jpg") }
+                    } } },
+                    { "entryId": "conversationthread-3", "content": { "items": [
+                        { "item": { "itemContent": {
+                            "tweet_results": { "result": thread_tweet("3", "42", "c.jpg") }
+                        } } },
+                        { "item": { "itemContent": {
+                            "tweet_results": { "result": thread_tweet("4", "99", "d.jpg") }
+                        } } }
+                    ] } }
+                ]
+            }] } }
+        });
+        let urls = |full_thread| {
+            TwitterDownloader::extract_graphql_media(&json, "2", full_thread)
+                .unwrap()
+                .iter()
+                .map(|m| m["media_url_https"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(urls(false), ["b.jpg"]);
+        assert_eq!(urls(true), ["a.jpg", "b.jpg", "c.jpg"]);
+    }
+
+    #[test]
+    fn graphql_author_reads_the_focal_screen_name() {
+        let entry = |user: serde_json::Value| {
+            serde_json::json!({ "data": { "threaded_conversation_with_injections_v2": {
+                "instructions": [{ "type": "TimelineAddEntries", "entries": [{
+                    "entryId": "tweet-7",
+                    "content": { "itemContent": { "tweet_results": { "result": {
+                        "__typename": "TweetWithVisibilityResults",
+                        "tweet": { "core": { "user_results": { "result": user } } }
+                    } } } }
+                }] }]
+            } } })
+        };
+        let legacy = entry(serde_json::json!({ "legacy": { "screen_name": "jack" } }));
+        assert_eq!(
+            TwitterDownloader::graphql_author(&legacy, "7").as_deref(),
+            Some("jack")
+        );
+        let current = entry(serde_json::json!({ "core": { "screen_name": "NASA" } }));
+        assert_eq!(
+            TwitterDownloader::graphql_author(&current, "7").as_deref(),
+            Some("NASA")
+        );
+        assert_eq!(TwitterDownloader::graphql_author(&current, "8"), None);
+    }
+
+    #[test]
+    fn space_urls_are_recognized() {
+        assert_eq!(
+            TwitterDownloader::extract_space_id("https://x.com/i/spaces/1vOxwjaWEbdJB/peek")
+                .as_deref(),
+            Some("1vOxwjaWEbdJB")
+        );
+        assert_eq!(
+            TwitterDownloader::extract_space_id("https://twitter.com/i/spaces/1OdKrBnaEPXKX"),
+            Some("1OdKrBnaEPXKX".to_string())
+        );
+        assert_eq!(
+            TwitterDownloader::extract_space_id("https://x.com/jack/status/20"),
+            None
+        );
+    }
+
+    #[test]
+    fn audio_space_reads_state_and_creator() {
+        let json = serde_json::json!({ "data": { "audioSpace": { "metadata": {
+            "media_key": "28_1234",
+            "state": "Ended",
+            "title": "Weekly sync",
+            "is_space_available_for_replay": true,
+            "started_at": 1700000000000_i64,
+            "ended_at": "1700003600000",
+            "creator_results": { "result": { "legacy": { "screen_name": "host" } } }
+        } } } });
+        let space = TwitterDownloader::parse_audio_space(&json).unwrap();
+        assert_eq!(space.media_key, "28_1234");
+        assert_eq!(space.state, "Ended");
+        assert!(space.replayable);
+        assert_eq!(space.title.as_deref(), Some("Weekly sync"));
+        assert_eq!(space.creator.as_deref(), Some("host"));
+        assert_eq!(space.ended_at_ms, Some(1_700_003_600_000));
+        assert!(TwitterDownloader::parse_audio_space(&serde_json::json!({})).is_none());
+    }
+
+    #[test]
+    fn graphql_metadata_prefers_note_tweet_text() {
+        let json = serde_json::json!({ "data": { "threaded_conversation_with_injections_v2": {
+            "instructions": [{ "type": "TimelineAddEntries", "entries": [{
+                "entryId": "tweet-7",
+                "content": { "itemContent": { "tweet_results": { "result": {
+                    "views": { "count": "1500" },
+                    "note_tweet": { "note_tweet_results": { "result": { "text": "long text #rust" } } },
+                    "legacy": {
+                        "full_text": "long te…",
+                        "created_at": "Wed Oct 10 20:19:24 +0000 2018",
+                        "favorite_count": 12,
+                        "retweet_count": 3,
+                        "reply_count": 1,
+                        "entities": { "hashtags": [{ "text": "rust" }] }
+                    }
+                } } } }
+            }] }]
+        } } });
+        let meta = TwitterDownloader::graphql_metadata(&json, "7").unwrap();
+        assert_eq!(meta.description.as_deref(), Some("long text #rust"));
+        assert_eq!(meta.timestamp, Some(1_539_202_764));
+        assert_eq!(meta.view_count, Some(1500));
+        assert_eq!(meta.like_count, Some(12));
+        assert_eq!(meta.share_count, Some(3));
+        assert_eq!(meta.tags, vec!["rust".to_string()]);
+    }
 }
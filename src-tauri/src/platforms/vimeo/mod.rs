@@ -1,15 +1,17 @@
 use omniget_core::models::progress::ProgressUpdate;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::anyhow;
 use async_trait::async_trait;
 use tokio::sync::mpsc;
 
+use crate::core::errors::DownloadError;
 use crate::core::ytdlp;
 use crate::models::media::{
     DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality as MediaVideoQuality,
 };
-use crate::platforms::traits::PlatformDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
 
 pub struct VimeoDownloader;
 
@@ -24,6 +26,23 @@ impl VimeoDownloader {
         Self
     }
 
+    /// Showcases (`/showcase/ID`), albums (`/album/ID`) and channel pages
+    /// (`/channels/NAME`) list several videos. `/channels/NAME/ID` is a
+    /// single video shown inside a channel.
+    fn is_collection_url(url: &str) -> bool {
+        let Ok(parsed) = url::Url::parse(url) else {
+            return false;
+        };
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|s| s.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        matches!(
+            segments.as_slice(),
+            ["showcase" | "album", _, ..] | ["channels", _]
+        )
+    }
+
     fn extract_quality_height(quality_str: &str) -> Option<u32> {
         let s = quality_str.trim().to_lowercase();
         if s == "best" || s == "highest" {
@@ -48,6 +67,11 @@ impl VimeoDownloader {
 
         let duration = json.get("duration").and_then(|v| v.as_f64());
 
+        let file_size = json
+            .get("filesize")
+            .or_else(|| json.get("filesize_approx"))
+            .and_then(|v| v.as_u64());
+
         let thumbnail = json
             .get("thumbnail")
             .and_then(|v| v.as_str())
@@ -61,6 +85,7 @@ impl VimeoDownloader {
 
         let mut qualities: Vec<MediaVideoQuality> = Vec::new();
         let mut seen_heights: HashSet<u32> = HashSet::new();
+        let mut best_tbr: HashMap<u32, f64> = HashMap::new();
 
         if let Some(formats) = json.get("formats").and_then(|v| v.as_array()) {
             for f in formats {
@@ -74,13 +99,27 @@ impl VimeoDownloader {
 
                 if seen_heights.insert(height) {
                     qualities.push(MediaVideoQuality {
+                        id: f
+                            .get("format_id")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
                         label: format!("{}p", height),
                         width,
                         height,
                         url: webpage_url.clone(),
                         format: "ytdlp".to_string(),
+                        ..Default::default()
                     });
                 }
+                // The details are those of the format a download of this
+                // height gets, not a mix of every format that has it.
+                let tbr = ytdlp::format_tbr(f);
+                if best_tbr.get(&height).is_none_or(|&best| tbr > best) {
+                    best_tbr.insert(height, tbr);
+                    if let Some(quality) = qualities.iter_mut().find(|q| q.height == height) {
+                        ytdlp::apply_format_details(quality, f);
+                    }
+                }
             }
         }
 
@@ -93,10 +132,12 @@ impl VimeoDownloader {
                 height: 0,
                 url: webpage_url,
                 format: "ytdlp".to_string(),
+                ..Default::default()
             });
         }
 
         Ok(MediaInfo {
+            id: json.get("id").and_then(|v| v.as_str()).map(String::from),
             title,
             author,
             platform: "vimeo".to_string(),
@@ -104,7 +145,9 @@ impl VimeoDownloader {
             thumbnail_url: thumbnail,
             available_qualities: qualities,
             media_type: MediaType::Video,
-            file_size_bytes: None,
+            file_size_bytes: file_size,
+            is_live: ytdlp::is_live_info(json),
+            ..Default::default()
         })
     }
 }
@@ -116,13 +159,40 @@ impl PlatformDownloader for VimeoDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host == "vimeo.com" || host.ends_with(".vimeo.com");
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &["vimeo.com"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_quality_selection: true,
+            supports_audio_only: true,
+            supports_expansion: true,
+            ..Capabilities::default()
+        }
+    }
+
+    async fn expand(&self, url: &str) -> anyhow::Result<Option<Vec<String>>> {
+        if !Self::is_collection_url(url) {
+            return Ok(None);
+        }
+        let ytdlp_path = ytdlp::ensure_ytdlp().await?;
+        // yt-dlp walks the album pages itself.
+        let (_title, entries) = match ytdlp::get_playlist_info(&ytdlp_path, url, &[]).await {
+            Ok(found) => found,
+            Err(e) if e.to_string().to_lowercase().contains("password") => {
+                return Err(anyhow::Error::new(DownloadError::Private)
+                    .context("This Vimeo album is password-protected"));
             }
+            Err(e) => return Err(e),
+        };
+        if entries.is_empty() {
+            return Err(DownloadError::NotFound.into());
         }
-        false
+        Ok(Some(entries.into_iter().map(|e| e.url).collect()))
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
@@ -147,19 +217,22 @@ impl PlatformDownloader for VimeoDownloader {
 
         let ytdlp_path = ytdlp::ensure_ytdlp().await?;
 
-        let first = info
-            .available_qualities
-            .first()
-            .ok_or_else(|| anyhow!("No quality available"))?;
+        if opts.quality_selection.is_multi() {
+            let qualities = opts.quality_selection.select(&info.available_qualities);
+            return ytdlp::download_qualities(
+                &ytdlp_path,
+                &qualities,
+                opts,
+                opts.referer.as_deref(),
+                false,
+                progress,
+            )
+            .await;
+        }
 
-        let selected = if let Some(ref wanted) = opts.quality {
-            info.available_qualities
-                .iter()
-                .find(|q| q.label == *wanted)
-                .unwrap_or(first)
-        } else {
-            first
-        };
+        let selected = opts
+            .pick_quality(&info.available_qualities)
+            .ok_or_else(|| anyhow!("No quality available"))?;
 
         let quality_height = Self::extract_quality_height(&selected.label);
         let video_url = &selected.url;
@@ -180,7 +253,33 @@ impl PlatformDownloader for VimeoDownloader {
             false,
             &[],
             opts.audio_format.as_deref(),
+            &opts.quality_preference,
+            &opts.extra_ytdlp_args,
         )
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_collection_urls() {
+        assert!(VimeoDownloader::is_collection_url(
+            "https://vimeo.com/showcase/1234567"
+        ));
+        assert!(VimeoDownloader::is_collection_url(
+            "https://vimeo.com/album/1234567/"
+        ));
+        assert!(VimeoDownloader::is_collection_url(
+            "https://vimeo.com/channels/staffpicks"
+        ));
+        assert!(!VimeoDownloader::is_collection_url(
+            "https://vimeo.com/channels/staffpicks/76979871"
+        ));
+        assert!(!VimeoDownloader::is_collection_url(
+            "https://vimeo.com/76979871"
+        ));
+    }
+}
@@ -1,5 +1,5 @@
 use omniget_core::models::progress::ProgressUpdate;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::anyhow;
 use async_trait::async_trait;
@@ -9,7 +9,12 @@ use crate::core::ytdlp;
 use crate::models::media::{
     DownloadOptions, DownloadResult, MediaInfo, MediaType, VideoQuality as MediaVideoQuality,
 };
-use crate::platforms::traits::PlatformDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
+
+/// Label of the best format that already has video and audio in one
+/// stream, which yt-dlp saves without ffmpeg.
+const PROGRESSIVE_LABEL: &str = "progressive";
 
 pub struct YouTubeDownloader;
 
@@ -41,7 +46,9 @@ impl YouTubeDownloader {
         if host.contains("youtube.com") || host.contains("youtube-nocookie.com") {
             let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
 
-            if segments.first() == Some(&"shorts") {
+            // `/clip/<id>` carries the clip's own ID; yt-dlp resolves it to
+            // the source video.
+            if matches!(segments.first(), Some(&"shorts" | &"live" | &"clip")) {
                 return segments.get(1).map(|s| s.to_string());
             }
 
@@ -54,6 +61,13 @@ impl YouTubeDownloader {
         None
     }
 
+    /// The full-size `maxresdefault.jpg` of a video. Older and low-res
+    /// uploads don't have one, so callers need a fallback.
+    pub fn max_res_thumbnail(url: &str) -> Option<String> {
+        Self::extract_video_id(url)
+            .map(|id| format!("https://i.ytimg.com/vi/{}/maxresdefault.jpg", id))
+    }
+
     pub fn is_playlist_url(url: &str) -> bool {
         if let Ok(parsed) = url::Url::parse(url) {
             if parsed.path().starts_with("/playlist") {
@@ -96,6 +110,7 @@ impl YouTubeDownloader {
                     height: 0,
                     url: entry.url,
                     format: "ytdlp_playlist".to_string(),
+                    ..Default::default()
                 })
                 .collect();
 
@@ -107,7 +122,7 @@ impl YouTubeDownloader {
                 thumbnail_url: None,
                 available_qualities: qualities,
                 media_type: MediaType::Playlist,
-                file_size_bytes: None,
+                ..Default::default()
             });
         }
 
@@ -115,7 +130,8 @@ impl YouTubeDownloader {
             .ok_or_else(|| anyhow!("Could not extract YouTube video ID"))?;
 
         let json = ytdlp::get_video_info(ytdlp_path, url, &[]).await?;
-        Self::parse_video_info(&json)
+        let can_mux = crate::core::ffmpeg::is_ffmpeg_available().await;
+        Self::parse_video_info(&json, can_mux)
     }
 
     fn extract_quality_height(quality_str: &str) -> Option<u32> {
@@ -123,10 +139,20 @@ impl YouTubeDownloader {
         if s == "best" || s == "highest" {
             return None;
         }
-        s.trim_end_matches('p').parse::<u32>().ok()
+        s.split_whitespace()
+            .next()?
+            .trim_end_matches('p')
+            .parse::<u32>()
+            .ok()
     }
 
-    pub fn parse_video_info(json: &serde_json::Value) -> anyhow::Result<MediaInfo> {
+    /// Video qualities of a yt-dlp info dict, highest first. Heights that
+    /// only exist as a video-only stream need ffmpeg to mux in the audio;
+    /// without it (`can_mux` false) they're left out, so picking one can't
+    /// quietly fall back to a lower single-stream format. The best
+    /// single-stream format is also listed last, labelled `progressive` so
+    /// it can be requested by name.
+    pub fn parse_video_info(json: &serde_json::Value, can_mux: bool) -> anyhow::Result<MediaInfo> {
         let video_id = json
             .get("id")
             .and_then(|v| v.as_str())
@@ -153,17 +179,10 @@ impl YouTubeDownloader {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
-        let is_live = json
-            .get("is_live")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-
-        if is_live {
-            return Err(anyhow!("Livestreams not supported"));
-        }
-
         let mut qualities: Vec<MediaVideoQuality> = Vec::new();
         let mut seen_heights: HashSet<u32> = HashSet::new();
+        let mut best_tbr: HashMap<u32, f64> = HashMap::new();
+        let mut progressive: Option<MediaVideoQuality> = None;
 
         if let Some(formats) = json.get("formats").and_then(|v| v.as_array()) {
             for f in formats {
@@ -178,25 +197,52 @@ impl YouTubeDownloader {
 
                 let has_audio = acodec != "none";
 
-                if seen_heights.insert(height) {
-                    let label = if has_audio {
-                        format!("{}p", height)
-                    } else {
-                        format!("{}p (HD)", height)
-                    };
+                if has_audio && progressive.as_ref().is_none_or(|p| height >= p.height) {
+                    if let Some(format_id) = f.get("format_id").and_then(|v| v.as_str()) {
+                        let mut best = MediaVideoQuality {
+                            id: Some(format_id.to_string()),
+                            label: PROGRESSIVE_LABEL.to_string(),
+                            width,
+                            height,
+                            url: format!("https://www.youtube.com/watch?v={}", video_id),
+                            format: "ytdlp".to_string(),
+                            progressive: true,
+                            ..Default::default()
+                        };
+                        ytdlp::apply_format_details(&mut best, f);
+                        progressive = Some(best);
+                    }
+                }
+
+                if !has_audio && !can_mux {
+                    continue;
+                }
 
+                if seen_heights.insert(height) {
                     qualities.push(MediaVideoQuality {
-                        label,
+                        label: format!("{}p", height),
                         width,
                         height,
                         url: format!("https://www.youtube.com/watch?v={}", video_id),
                         format: "ytdlp".to_string(),
+                        needs_mux: !has_audio,
+                        ..Default::default()
                     });
                 }
+                // The details are those of the format a download of this
+                // height gets, not a mix of every format that has it.
+                let tbr = ytdlp::format_tbr(f);
+                if best_tbr.get(&height).is_none_or(|&best| tbr > best) {
+                    best_tbr.insert(height, tbr);
+                    if let Some(quality) = qualities.iter_mut().find(|q| q.height == height) {
+                        ytdlp::apply_format_details(quality, f);
+                    }
+                }
             }
         }
 
         qualities.sort_by(|a, b| b.height.cmp(&a.height));
+        qualities.extend(progressive);
 
         if qualities.is_empty() {
             qualities.push(MediaVideoQuality {
@@ -205,10 +251,12 @@ impl YouTubeDownloader {
                 height: 0,
                 url: format!("https://www.youtube.com/watch?v={}", video_id),
                 format: "ytdlp".to_string(),
+                ..Default::default()
             });
         }
 
         Ok(MediaInfo {
+            id: Some(video_id.clone()),
             title,
             author,
             platform: "youtube".to_string(),
@@ -216,7 +264,8 @@ impl YouTubeDownloader {
             thumbnail_url: thumbnail,
             available_qualities: qualities,
             media_type: MediaType::Video,
-            file_size_bytes: None,
+            is_live: ytdlp::is_live_info(json),
+            ..Default::default()
         })
     }
 }
@@ -239,6 +288,128 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn max_res_thumbnail_uses_the_video_id() {
+        assert_eq!(
+            YouTubeDownloader::max_res_thumbnail("https://youtu.be/dQw4w9WgXcQ").as_deref(),
+            Some("https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg")
+        );
+        assert_eq!(
+            YouTubeDownloader::max_res_thumbnail("https://www.youtube.com/playlist?list=PLx"),
+            None
+        );
+    }
+
+    #[test]
+    fn video_id_from_every_url_shape() {
+        let cases = [
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ", "dQw4w9WgXcQ"),
+            ("https://m.youtube.com/watch?v=dQw4w9WgXcQ", "dQw4w9WgXcQ"),
+            (
+                "https://music.youtube.com/watch?v=dQw4w9WgXcQ",
+                "dQw4w9WgXcQ",
+            ),
+            ("https://youtu.be/dQw4w9WgXcQ?t=10", "dQw4w9WgXcQ"),
+            ("https://www.youtube.com/embed/dQw4w9WgXcQ", "dQw4w9WgXcQ"),
+            ("https://www.youtube.com/shorts/dQw4w9WgXcQ", "dQw4w9WgXcQ"),
+            (
+                "https://www.youtube.com/live/dQw4w9WgXcQ?si=x",
+                "dQw4w9WgXcQ",
+            ),
+            (
+                "https://www.youtube.com/clip/UgkxU2HSeGL_NvmDJ-nQJrlLwllwMDBdGZFs",
+                "UgkxU2HSeGL_NvmDJ-nQJrlLwllwMDBdGZFs",
+            ),
+        ];
+        for (url, id) in cases {
+            assert_eq!(
+                YouTubeDownloader::extract_video_id(url).as_deref(),
+                Some(id),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(
+            YouTubeDownloader::extract_video_id("https://www.youtube.com/live/"),
+            None
+        );
+    }
+
+    #[test]
+    fn mux_qualities_need_ffmpeg_and_progressive_is_listed() {
+        let json = serde_json::json!({
+            "id": "abc",
+            "formats": [
+                { "format_id": "18", "height": 360, "width": 640, "vcodec": "avc1", "acodec": "mp4a" },
+                { "format_id": "22", "height": 720, "width": 1280, "vcodec": "avc1", "acodec": "mp4a" },
+                { "format_id": "137", "height": 1080, "width": 1920, "vcodec": "avc1", "acodec": "none" }
+            ]
+        });
+        let labels = |can_mux| {
+            YouTubeDownloader::parse_video_info(&json, can_mux)
+                .unwrap()
+                .available_qualities
+                .into_iter()
+                .map(|q| (q.label, q.id, q.needs_mux, q.progressive))
+                .collect::<Vec<_>>()
+        };
+        let progressive = (
+            "progressive".to_string(),
+            Some("22".to_string()),
+            false,
+            true,
+        );
+        assert_eq!(
+            labels(true),
+            [
+                ("1080p".to_string(), None, true, false),
+                ("720p".to_string(), None, false, false),
+                ("360p".to_string(), None, false, false),
+                progressive.clone(),
+            ]
+        );
+        assert_eq!(
+            labels(false),
+            [
+                ("720p".to_string(), None, false, false),
+                ("360p".to_string(), None, false, false),
+                progressive
+            ]
+        );
+    }
+
+    #[test]
+    fn quality_details_come_from_the_highest_bitrate_format() {
+        let json = serde_json::json!({
+            "id": "abc",
+            "formats": [
+                { "format_id": "137", "height": 1080, "vcodec": "avc1", "acodec": "none", "fps": 30.0, "tbr": 4000.0, "filesize": 400 },
+                { "format_id": "248", "height": 1080, "vcodec": "vp9", "acodec": "none", "tbr": 2500.0 },
+                { "format_id": "299", "height": 1080, "vcodec": "avc1.64002a", "acodec": "none", "fps": 60.0, "tbr": 6000.0 }
+            ]
+        });
+        let info = YouTubeDownloader::parse_video_info(&json, true).unwrap();
+        let quality = &info.available_qualities[0];
+        assert_eq!(quality.vcodec.as_deref(), Some("avc1.64002a"));
+        assert_eq!(quality.fps, Some(60.0));
+        assert_eq!(quality.bitrate, Some(6_000_000));
+        assert_eq!(quality.filesize, None);
+    }
+
+    #[test]
+    fn quality_height_ignores_the_label_suffix() {
+        assert_eq!(
+            YouTubeDownloader::extract_quality_height("1080p (HD, needs ffmpeg)"),
+            Some(1080)
+        );
+        assert_eq!(YouTubeDownloader::extract_quality_height("720p"), Some(720));
+        assert_eq!(YouTubeDownloader::extract_quality_height("best"), None);
+        assert_eq!(
+            YouTubeDownloader::extract_quality_height("progressive"),
+            None
+        );
+    }
+
     #[test]
     fn list_only_watch_url_is_playlist() {
         assert!(YouTubeDownloader::is_playlist_url(
@@ -254,17 +425,27 @@ impl PlatformDownloader for YouTubeDownloader {
     }
 
     fn can_handle(&self, url: &str) -> bool {
-        if let Ok(parsed) = url::Url::parse(url) {
-            if let Some(host) = parsed.host_str() {
-                let host = host.to_lowercase();
-                return host == "youtube.com"
-                    || host.ends_with(".youtube.com")
-                    || host == "youtu.be"
-                    || host == "youtube-nocookie.com"
-                    || host.ends_with(".youtube-nocookie.com");
-            }
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        &[
+            "youtube.com",
+            "music.youtube.com",
+            "youtu.be",
+            "youtube-nocookie.com",
+        ]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_quality_selection: true,
+            supports_audio_only: true,
+            supports_subtitles: true,
+            // Playlists come back from `get_media_info` as a playlist item.
+            supports_expansion: true,
+            ..Capabilities::default()
         }
-        false
     }
 
     async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
@@ -291,6 +472,7 @@ impl PlatformDownloader for YouTubeDownloader {
                     height: 0,
                     url: entry.url,
                     format: "ytdlp_playlist".to_string(),
+                    ..Default::default()
                 })
                 .collect();
 
@@ -302,7 +484,7 @@ impl PlatformDownloader for YouTubeDownloader {
                 thumbnail_url: None,
                 available_qualities: qualities,
                 media_type: MediaType::Playlist,
-                file_size_bytes: None,
+                ..Default::default()
             });
         }
 
@@ -310,7 +492,8 @@ impl PlatformDownloader for YouTubeDownloader {
             .ok_or_else(|| anyhow!("Could not extract YouTube video ID"))?;
 
         let json = ytdlp::get_video_info(&ytdlp_path, url, &[]).await?;
-        Self::parse_video_info(&json)
+        let can_mux = crate::core::ffmpeg::is_ffmpeg_available().await;
+        Self::parse_video_info(&json, can_mux)
     }
 
     async fn download(
@@ -338,6 +521,19 @@ impl PlatformDownloader for YouTubeDownloader {
                 .await;
         }
 
+        if opts.quality_selection.is_multi() {
+            let qualities = opts.quality_selection.select(&info.available_qualities);
+            return ytdlp::download_qualities(
+                &ytdlp_path,
+                &qualities,
+                opts,
+                opts.referer.as_deref().or(Some("https://www.youtube.com/")),
+                opts.download_subtitles,
+                progress,
+            )
+            .await;
+        }
+
         let first = info
             .available_qualities
             .first()
@@ -353,6 +549,18 @@ impl PlatformDownloader for YouTubeDownloader {
             None => first,
         };
         let video_url = &selected.url;
+        let section = ytdlp::section_args(opts.section_start, opts.section_end).await;
+        // The progressive entry is a specific yt-dlp format, not a height.
+        let progressive = MediaVideoQuality::find_requested(
+            &info.available_qualities,
+            opts.format_id.as_deref(),
+            opts.quality.as_deref(),
+        )
+        .filter(|q| q.label == PROGRESSIVE_LABEL);
+        let format_id = match progressive {
+            Some(q) => q.id.as_deref(),
+            None => opts.format_id.as_deref(),
+        };
 
         ytdlp::download_video(
             &ytdlp_path,
@@ -361,15 +569,17 @@ impl PlatformDownloader for YouTubeDownloader {
             quality_height,
             progress,
             opts.download_mode.as_deref(),
-            opts.format_id.as_deref(),
+            format_id,
             opts.filename_template.as_deref(),
             opts.referer.as_deref().or(Some("https://www.youtube.com/")),
             opts.cancel_token.clone(),
             None,
             opts.concurrent_fragments,
             opts.download_subtitles,
-            &[],
+            &section,
             opts.audio_format.as_deref(),
+            &opts.quality_preference,
+            &opts.extra_ytdlp_args,
         )
         .await
     }
@@ -389,13 +599,35 @@ impl YouTubeDownloader {
             .join(sanitize_filename::sanitize(&info.title));
         tokio::fs::create_dir_all(&playlist_dir).await?;
 
-        let total = info.available_qualities.len();
+        // In "only new" mode yt-dlp records each video in the archive
+        // itself; entries it already lists aren't handed to it at all.
+        let mut extra_args = opts.extra_ytdlp_args.clone();
+        let entries: Vec<&MediaVideoQuality> = match opts.download_archive.as_deref() {
+            Some(archive) => {
+                let seen = crate::core::download_archive::load(archive);
+                if let Some(parent) = archive.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                extra_args.push("--download-archive".to_string());
+                extra_args.push(archive.to_string_lossy().to_string());
+                info.available_qualities
+                    .iter()
+                    .filter(|e| !seen.contains(&crate::core::download_archive::entry_for(&e.url)))
+                    .collect()
+            }
+            None => info.available_qualities.iter().collect(),
+        };
+        if entries.is_empty() {
+            return Err(anyhow!("No new videos in this playlist"));
+        }
+
+        let total = entries.len();
         let mut total_bytes = 0u64;
         let mut last_path = playlist_dir.clone();
         let mut success_count = 0usize;
         let mut last_err: Option<anyhow::Error> = None;
 
-        for (i, entry) in info.available_qualities.iter().enumerate() {
+        for (i, entry) in entries.into_iter().enumerate() {
             if opts.cancel_token.is_cancelled() {
                 anyhow::bail!("Download cancelado");
             }
@@ -438,6 +670,8 @@ impl YouTubeDownloader {
                 opts.download_subtitles,
                 &[],
                 opts.audio_format.as_deref(),
+                &opts.quality_preference,
+                &extra_args,
             )
             .await
             {
@@ -466,11 +700,6 @@ impl YouTubeDownloader {
 
         let _ = progress.send(ProgressUpdate::percent(100.0)).await;
 
-        Ok(DownloadResult {
-            file_path: last_path,
-            file_size_bytes: total_bytes,
-            duration_seconds: 0.0,
-            torrent_id: None,
-        })
+        Ok(DownloadResult::new(last_path, total_bytes))
     }
 }
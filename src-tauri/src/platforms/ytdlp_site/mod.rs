@@ -0,0 +1,91 @@
+use omniget_core::models::progress::ProgressUpdate;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::models::media::{DownloadOptions, DownloadResult, MediaInfo};
+use crate::platforms::generic_ytdlp::GenericYtdlpDownloader;
+use crate::platforms::traits::{Capabilities, PlatformDownloader};
+use crate::platforms::url_matches_hosts;
+
+/// A site yt-dlp already extracts well, promoted to a named platform. The
+/// generic yt-dlp downloader does the work; this claims the site's links
+/// ahead of it and reports the platform by name, so the UI shows it as
+/// recognized. Promoting another site takes one more constructor here, a
+/// `Platform` variant and a `register` call.
+pub struct YtdlpSiteDownloader {
+    name: &'static str,
+    hosts: &'static [&'static str],
+    inner: GenericYtdlpDownloader,
+}
+
+impl YtdlpSiteDownloader {
+    pub fn new(name: &'static str, hosts: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            hosts,
+            inner: GenericYtdlpDownloader::new(),
+        }
+    }
+
+    /// Clips, past broadcasts and live channels.
+    pub fn kick() -> Self {
+        Self::new("kick", &["kick.com"])
+    }
+
+    pub fn dailymotion() -> Self {
+        Self::new("dailymotion", &["dailymotion.com", "dai.ly"])
+    }
+}
+
+#[async_trait]
+impl PlatformDownloader for YtdlpSiteDownloader {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn can_handle(&self, url: &str) -> bool {
+        url_matches_hosts(url, self.supported_hosts())
+    }
+
+    fn supported_hosts(&self) -> &[&'static str] {
+        self.hosts
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn get_media_info(&self, url: &str) -> anyhow::Result<MediaInfo> {
+        let mut info = self.inner.get_media_info(url).await?;
+        info.platform = self.name.to_string();
+        Ok(info)
+    }
+
+    async fn download(
+        &self,
+        info: &MediaInfo,
+        opts: &DownloadOptions,
+        progress: mpsc::Sender<ProgressUpdate>,
+    ) -> anyhow::Result<DownloadResult> {
+        self.inner.download(info, opts, progress).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_only_its_own_hosts() {
+        let kick = YtdlpSiteDownloader::kick();
+        assert_eq!(kick.name(), "kick");
+        assert!(kick.can_handle("https://kick.com/xqc/clips/clip_01HABC"));
+        assert!(!kick.can_handle("https://www.dailymotion.com/video/x8abcd"));
+
+        let dailymotion = YtdlpSiteDownloader::dailymotion();
+        assert!(dailymotion.can_handle("https://www.dailymotion.com/video/x8abcd"));
+        assert!(dailymotion.can_handle("https://dai.ly/x8abcd"));
+        assert!(!dailymotion.can_handle("https://kick.com/xqc"));
+    }
+}
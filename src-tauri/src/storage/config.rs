@@ -1,21 +1,41 @@
+use std::sync::{Mutex, MutexGuard};
+
 use tauri::AppHandle;
 use tauri_plugin_store::StoreExt;
 
-use crate::models::settings::AppSettings;
+use crate::models::settings::{self, AppSettings};
 
 const STORE_PATH: &str = "settings.json";
 const STORE_KEY: &str = "app_settings";
 
+/// Serializes load-modify-save cycles so writers running at the same time,
+/// like `update_settings` and the queue recording a recent folder, don't
+/// drop each other's changes.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Hold this from `load_settings` until `save_settings` when changing a
+/// field of the loaded settings.
+pub fn write_lock() -> MutexGuard<'static, ()> {
+    WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 pub fn load_settings(app: &AppHandle) -> AppSettings {
     let store = match app.store(STORE_PATH) {
         Ok(s) => s,
         Err(_) => return AppSettings::default(),
     };
 
-    match store.get(STORE_KEY) {
-        Some(val) => serde_json::from_value::<AppSettings>(val.clone()).unwrap_or_default(),
-        None => AppSettings::default(),
+    let Some(val) = store.get(STORE_KEY) else {
+        return AppSettings::default();
+    };
+    let settings = settings::migrate(val.clone());
+    // Persist the upgraded shape so older fields are only migrated once.
+    if serde_json::to_value(&settings).is_ok_and(|upgraded| upgraded != val) {
+        if let Err(e) = save_settings(app, &settings) {
+            tracing::warn!("[settings] failed to save migrated settings: {}", e);
+        }
     }
+    settings
 }
 
 pub fn load_settings_standalone() -> AppSettings {
@@ -36,7 +56,7 @@ pub fn load_settings_standalone() -> AppSettings {
     };
 
     match json.get(STORE_KEY) {
-        Some(val) => serde_json::from_value::<AppSettings>(val.clone()).unwrap_or_default(),
+        Some(val) => settings::migrate(val.clone()),
         None => AppSettings::default(),
     }
 }
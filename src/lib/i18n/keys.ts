@@ -101,13 +101,19 @@ export type TranslationKeys =
 
   | 'convert.audio_bitrate'
   | 'convert.audio_codec'
+  | 'convert.audio_track'
   | 'convert.auto'
+  | 'convert.burn_subtitles'
+  | 'convert.burn_subtitles_desc'
   | 'convert.cancel'
   | 'convert.clear_all'
   | 'convert.conversion_failed'
   | 'convert.convert_btn'
   | 'convert.converting'
   | 'convert.empty'
+  | 'convert.encoder'
+  | 'convert.encoder_desc'
+  | 'convert.encoder_software'
   | 'convert.file_list'
   | 'convert.hwaccel_detected'
   | 'convert.hwaccel_none'
@@ -127,9 +133,14 @@ export type TranslationKeys =
   | 'convert.status_idle'
   | 'convert.status_probing'
   | 'convert.status_ready'
+  | 'convert.subtitle_track'
+  | 'convert.subtitles_none'
+  | 'convert.subtitles_none_available'
   | 'convert.title'
   | 'convert.toast_complete'
   | 'convert.toast_error'
+  | 'convert.track_default'
+  | 'convert.track_n'
   | 'convert.trim_end'
   | 'convert.trim_start'
   | 'convert.video_bitrate'
@@ -154,6 +165,7 @@ export type TranslationKeys =
   | 'courses.verify_code'
 
   | 'debug.clear'
+  | 'debug.copy_logs'
   | 'debug.copy_report'
   | 'debug.enable'
   | 'debug.enable_desc'
@@ -164,6 +176,7 @@ export type TranslationKeys =
   | 'debug.filter_errors'
   | 'debug.filter_network'
   | 'debug.filter_system'
+  | 'debug.logs_copied'
   | 'debug.no_entries'
   | 'debug.open_panel'
   | 'debug.open_panel_desc'
@@ -171,12 +184,15 @@ export type TranslationKeys =
   | 'debug.title'
 
   | 'downloads.cancel'
+  | 'downloads.cancel_all'
+  | 'downloads.cancel_all_confirm'
   | 'downloads.clear_confirm'
   | 'downloads.clear_finished'
   | 'downloads.confirm_remove'
   | 'downloads.delete_file'
   | 'downloads.delete_file_confirm'
   | 'downloads.empty'
+  | 'downloads.extra_files'
   | 'downloads.filter.active'
   | 'downloads.filter.all'
   | 'downloads.filter.completed'
@@ -186,9 +202,12 @@ export type TranslationKeys =
   | 'downloads.history_clear'
   | 'downloads.history_clear_confirm'
   | 'downloads.history_empty'
+  | 'downloads.history_load_more'
   | 'downloads.history_loading'
+  | 'downloads.history_no_matches'
   | 'downloads.history_redownload'
   | 'downloads.history_requeued'
+  | 'downloads.history_search_placeholder'
   | 'downloads.history_toggle'
   | 'downloads.history_toggle_hint'
   | 'downloads.history_view_link'
@@ -201,21 +220,33 @@ export type TranslationKeys =
   | 'downloads.module_progress'
   | 'downloads.open_folder'
   | 'downloads.open_in_study'
+  | 'downloads.outside_hours'
   | 'downloads.page_progress'
   | 'downloads.pause'
   | 'downloads.pause_all'
   | 'downloads.phase_connecting'
+  | 'downloads.phase_converting'
   | 'downloads.phase_fetching_info'
+  | 'downloads.phase_muxing'
   | 'downloads.phase_preparing'
+  | 'downloads.phase_resolving'
   | 'downloads.phase_starting'
   | 'downloads.quality_hint'
+  | 'downloads.rate_limited'
+  | 'downloads.rate_limited_toast'
   | 'downloads.reorder_hint'
+  | 'downloads.repair'
+  | 'downloads.repair_done'
+  | 'downloads.repair_still_broken'
   | 'downloads.resume'
   | 'downloads.resume_all'
   | 'downloads.retry'
+  | 'downloads.retry_attempt'
+  | 'downloads.scheduled_for'
   | 'downloads.section_finished'
   | 'downloads.section_queued'
   | 'downloads.show_more'
+  | 'downloads.start_now'
   | 'downloads.stats_line'
   | 'downloads.status.complete'
   | 'downloads.status.downloading'
@@ -224,6 +255,8 @@ export type TranslationKeys =
   | 'downloads.status.queued'
   | 'downloads.status.seeding'
   | 'downloads.stop'
+  | 'downloads.suspect'
+  | 'downloads.suspect_hint'
   | 'downloads.sw.autofix'
   | 'downloads.sw.autofix_none'
   | 'downloads.sw.autofixed'
@@ -265,8 +298,14 @@ export type TranslationKeys =
   | 'downloads.sw.twopoint'
   | 'downloads.title'
   | 'downloads.vop.action_label'
+  | 'downloads.vop.contact_sheet'
   | 'downloads.vop.done'
   | 'downloads.vop.extract_audio'
+  | 'downloads.vop.gif_fps'
+  | 'downloads.vop.gif_generate'
+  | 'downloads.vop.gif_loop'
+  | 'downloads.vop.gif_to_mp4'
+  | 'downloads.vop.gif_width'
   | 'downloads.vop.mute'
   | 'downloads.vop.nl_label'
   | 'downloads.vop.nl_placeholder'
@@ -277,6 +316,10 @@ export type TranslationKeys =
   | 'downloads.vop.review_hint'
   | 'downloads.vop.review_title'
   | 'downloads.vop.run'
+  | 'downloads.vop.sheet_columns'
+  | 'downloads.vop.sheet_generate'
+  | 'downloads.vop.sheet_rows'
+  | 'downloads.vop.sheet_width'
   | 'downloads.vop.title'
   | 'downloads.vop.to_gif'
   | 'downloads.vop.to_mp4'
@@ -319,6 +362,18 @@ export type TranslationKeys =
   | 'errors.video_private'
   | 'errors.video_unavailable'
 
+  | 'extract_audio.action_label'
+  | 'extract_audio.extracting'
+  | 'extract_audio.format_legend'
+  | 'extract_audio.preset_flac_desc'
+  | 'extract_audio.preset_m4a_256_desc'
+  | 'extract_audio.preset_mp3_320_desc'
+  | 'extract_audio.preset_mp3_v0_desc'
+  | 'extract_audio.preset_opus_192_desc'
+  | 'extract_audio.start'
+  | 'extract_audio.title'
+  | 'extract_audio.toast_done'
+
   | 'hints.clipboard'
   | 'hints.convert'
   | 'hints.downloads_empty'
@@ -482,6 +537,7 @@ export type TranslationKeys =
   | 'omnibox.adv.url_placeholder'
   | 'omnibox.adv.url_required'
   | 'omnibox.advanced'
+  | 'omnibox.already_queued'
   | 'omnibox.batch_detected'
   | 'omnibox.batch_download_all'
   | 'omnibox.batch_file_empty'
@@ -508,6 +564,8 @@ export type TranslationKeys =
   | 'omnibox.error'
   | 'omnibox.external_url_ready'
   | 'omnibox.fmt_audio'
+  | 'omnibox.fmt_needs_ffmpeg'
+  | 'omnibox.fmt_progressive'
   | 'omnibox.fmt_quality'
   | 'omnibox.fmt_size'
   | 'omnibox.fmt_speed'
@@ -526,6 +584,8 @@ export type TranslationKeys =
   | 'omnibox.mode_mute'
   | 'omnibox.mode_normal'
   | 'omnibox.no_formats'
+  | 'omnibox.only_new'
+  | 'omnibox.only_new_hint'
   | 'omnibox.options'
   | 'omnibox.placeholder'
   | 'omnibox.playlist_all'
@@ -714,22 +774,55 @@ export type TranslationKeys =
   | 'services.title_hide'
   | 'services.title_show'
 
+  | 'settings.advanced.allowed_hours'
+  | 'settings.advanced.allowed_hours_desc'
+  | 'settings.advanced.allowed_hours_from'
+  | 'settings.advanced.allowed_hours_to'
   | 'settings.advanced.concurrent_fragments'
   | 'settings.advanced.concurrent_fragments_desc'
   | 'settings.advanced.cookies_from_browser'
   | 'settings.advanced.cookies_from_browser_desc'
   | 'settings.advanced.cookies_from_browser_placeholder'
+  | 'settings.advanced.download_timeout'
+  | 'settings.advanced.download_timeout_desc'
+  | 'settings.advanced.extra_ytdlp_flags'
+  | 'settings.advanced.extra_ytdlp_flags_desc'
+  | 'settings.advanced.extra_ytdlp_flags_unsafe'
+  | 'settings.advanced.extra_ytdlp_flags_unsafe_desc'
+  | 'settings.advanced.header_profiles'
+  | 'settings.advanced.header_profiles_desc'
+  | 'settings.advanced.header_profiles_edit'
+  | 'settings.advanced.header_profiles_headers'
+  | 'settings.advanced.header_profiles_hide'
+  | 'settings.advanced.header_profiles_user_agents'
+  | 'settings.advanced.header_profiles_user_agents_placeholder'
+  | 'settings.advanced.http_connect_timeout'
+  | 'settings.advanced.http_connect_timeout_desc'
+  | 'settings.advanced.http_max_retries'
+  | 'settings.advanced.http_max_retries_desc'
+  | 'settings.advanced.http_timeout'
+  | 'settings.advanced.http_timeout_desc'
   | 'settings.advanced.max_concurrent_downloads'
   | 'settings.advanced.max_concurrent_downloads_desc'
   | 'settings.advanced.max_concurrent_segments'
   | 'settings.advanced.max_concurrent_segments_desc'
+  | 'settings.advanced.max_downloads_per_host'
+  | 'settings.advanced.max_downloads_per_host_desc'
   | 'settings.advanced.max_retries'
   | 'settings.advanced.max_retries_desc'
+  | 'settings.advanced.native_aria2c'
+  | 'settings.advanced.native_aria2c_desc'
+  | 'settings.advanced.platform_backend'
+  | 'settings.advanced.platform_backend_auto'
+  | 'settings.advanced.platform_backend_desc'
+  | 'settings.advanced.platform_backend_native'
   | 'settings.advanced.prevent_sleep'
   | 'settings.advanced.prevent_sleep_desc'
   | 'settings.advanced.reset'
   | 'settings.advanced.reset_confirm'
   | 'settings.advanced.reset_desc'
+  | 'settings.advanced.retry_base_delay'
+  | 'settings.advanced.retry_base_delay_desc'
   | 'settings.advanced.section_debug'
   | 'settings.advanced.section_debug_desc'
   | 'settings.advanced.section_expert'
@@ -959,6 +1052,12 @@ export type TranslationKeys =
   | 'settings.dependencies.col_name'
   | 'settings.dependencies.col_status'
   | 'settings.dependencies.col_version'
+  | 'settings.dependencies.ffmpeg_missing_dismiss'
+  | 'settings.dependencies.ffmpeg_missing_kept_audio_body'
+  | 'settings.dependencies.ffmpeg_missing_kept_video_body'
+  | 'settings.dependencies.ffmpeg_missing_separate_tracks_body'
+  | 'settings.dependencies.ffmpeg_missing_single_stream_body'
+  | 'settings.dependencies.ffmpeg_missing_title'
   | 'settings.dependencies.filter_all_files'
   | 'settings.dependencies.filter_pdfium'
   | 'settings.dependencies.install'
@@ -1016,8 +1115,12 @@ export type TranslationKeys =
   | 'settings.download.choose_folder'
   | 'settings.download.clipboard_detection'
   | 'settings.download.clipboard_detection_desc'
+  | 'settings.download.clipboard_watch'
+  | 'settings.download.clipboard_watch_desc'
   | 'settings.download.continuous_lecture_numbers'
   | 'settings.download.continuous_lecture_numbers_desc'
+  | 'settings.download.convert_keep_original'
+  | 'settings.download.convert_keep_original_desc'
   | 'settings.download.copy_to_clipboard_on_hotkey'
   | 'settings.download.copy_to_clipboard_on_hotkey_desc'
   | 'settings.download.courses_section'
@@ -1030,6 +1133,16 @@ export type TranslationKeys =
   | 'settings.download.embed_metadata_desc'
   | 'settings.download.embed_thumbnail'
   | 'settings.download.embed_thumbnail_desc'
+  | 'settings.download.ffmpeg_missing_policy'
+  | 'settings.download.ffmpeg_missing_policy_desc'
+  | 'settings.download.ffmpeg_missing_separate_tracks'
+  | 'settings.download.ffmpeg_missing_single_stream'
+  | 'settings.download.filename_style'
+  | 'settings.download.filename_style_custom'
+  | 'settings.download.filename_style_desc'
+  | 'settings.download.filename_style_id_only'
+  | 'settings.download.filename_style_title'
+  | 'settings.download.filename_style_title_with_id'
   | 'settings.download.filename_template'
   | 'settings.download.filename_template_desc'
   | 'settings.download.filename_template_preview'
@@ -1045,6 +1158,8 @@ export type TranslationKeys =
   | 'settings.download.keep_vtt_desc'
   | 'settings.download.live_from_start'
   | 'settings.download.live_from_start_desc'
+  | 'settings.download.max_filesize'
+  | 'settings.download.max_filesize_desc'
   | 'settings.download.music_audio_format'
   | 'settings.download.music_audio_format_desc'
   | 'settings.download.music_hotkey_binding'
@@ -1053,6 +1168,11 @@ export type TranslationKeys =
   | 'settings.download.organize_by_platform'
   | 'settings.download.organize_by_platform_desc'
   | 'settings.download.path_too_long'
+  | 'settings.download.platform_default_global'
+  | 'settings.download.platform_default_mode'
+  | 'settings.download.platform_default_video'
+  | 'settings.download.platform_defaults'
+  | 'settings.download.platform_defaults_desc'
   | 'settings.download.preset_compact'
   | 'settings.download.preset_compact_desc'
   | 'settings.download.preset_fast'
@@ -1063,6 +1183,10 @@ export type TranslationKeys =
   | 'settings.download.preset_quality_desc'
   | 'settings.download.presets'
   | 'settings.download.presets_desc'
+  | 'settings.download.record_live'
+  | 'settings.download.record_live_desc'
+  | 'settings.download.save_metadata_json'
+  | 'settings.download.save_metadata_json_desc'
   | 'settings.download.sb_cat_filler'
   | 'settings.download.sb_cat_interaction'
   | 'settings.download.sb_cat_intro'
@@ -1095,8 +1219,17 @@ export type TranslationKeys =
   | 'settings.download.split_by_chapters'
   | 'settings.download.split_by_chapters_desc'
   | 'settings.download.telegram_plugin_section'
+  | 'settings.download.temp_dir'
+  | 'settings.download.temp_dir_clear'
+  | 'settings.download.temp_dir_default'
+  | 'settings.download.temp_dir_desc'
   | 'settings.download.translate_metadata'
   | 'settings.download.translate_metadata_desc'
+  | 'settings.download.twitter_full_thread'
+  | 'settings.download.twitter_full_thread_desc'
+  | 'settings.download.twitter_section'
+  | 'settings.download.verify_after_download'
+  | 'settings.download.verify_after_download_desc'
   | 'settings.download.video_quality'
   | 'settings.download.video_quality_desc'
   | 'settings.download.what_to_also_save'
@@ -3245,6 +3378,7 @@ export type TranslationKeys =
   | 'toast.file_copied_to_clipboard'
   | 'toast.generic_download_complete'
 
+  | 'toast.no_audio_kept_video'
   | 'tools.cc_chapters'
   | 'tools.cc_comments'
   | 'tools.cc_desc'
@@ -3279,6 +3413,8 @@ export type TranslationKeys =
   | 'tools.subs_title'
   | 'tools.tab'
   | 'tools.thumbnails_action'
+  | 'tools.thumbnails_best'
+  | 'tools.thumbnails_best_desc'
   | 'tools.thumbnails_default'
   | 'tools.thumbnails_desc'
   | 'tools.thumbnails_none'
@@ -9,6 +9,7 @@ export const PLATFORM_DISPLAY_NAMES: Record<string, string> = {
   youku: "Youku (优酷)",
   youtube: "YouTube",
   instagram: "Instagram",
+  facebook: "Facebook",
   tiktok: "TikTok",
   twitter: "Twitter / X",
   reddit: "Reddit",
@@ -17,6 +18,9 @@ export const PLATFORM_DISPLAY_NAMES: Record<string, string> = {
   bluesky: "Bluesky",
   telegram: "Telegram",
   vimeo: "Vimeo",
+  kick: "Kick",
+  dailymotion: "Dailymotion",
+  soundcloud: "SoundCloud",
   hotmart: "Hotmart",
   udemy: "Udemy",
   magnet: "BitTorrent",
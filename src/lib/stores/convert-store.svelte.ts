@@ -12,6 +12,8 @@ export type StreamInfo = {
   sample_rate?: number;
   channels?: number;
   duration_seconds?: number;
+  language?: string | null;
+  title?: string | null;
 };
 
 export type ProbeInfo = {
@@ -39,6 +41,7 @@ export type ConvertFile = {
 export type ConvertOptions = {
   outputFormat: string;
   videoCodec: string;
+  encoder: string;
   audioCodec: string;
   resolution: string;
   preset: string;
@@ -46,6 +49,9 @@ export type ConvertOptions = {
   audioBitrate: string;
   trimStart: string;
   trimEnd: string;
+  audioTrack: string;
+  subtitleTrack: string;
+  burnSubtitles: boolean;
 };
 
 export type HwAccelInfo = {
@@ -58,6 +64,7 @@ export type HwAccelInfo = {
 const defaultOptions: ConvertOptions = {
   outputFormat: "mp4",
   videoCodec: "auto",
+  encoder: "auto",
   audioCodec: "auto",
   resolution: "original",
   preset: "medium",
@@ -65,6 +72,9 @@ const defaultOptions: ConvertOptions = {
   audioBitrate: "",
   trimStart: "",
   trimEnd: "",
+  audioTrack: "",
+  subtitleTrack: "",
+  burnSubtitles: false,
 };
 
 let nextId = 1;
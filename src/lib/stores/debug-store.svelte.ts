@@ -78,12 +78,26 @@ export function clearLogs() {
   logs = [];
 }
 
-type DependencyStatus = {
-  name: string;
-  installed: boolean;
-  version: string | null;
+type DiagnosticsReport = {
+  app_version: string;
+  os: string;
+  arch: string;
+  tools: { name: string; path: string | null; version: string | null }[];
+  download_dir: { path: string; writable: boolean; error: string | null };
+  network: {
+    name: string;
+    url: string;
+    status: number | null;
+    latency_ms: number | null;
+    error: string | null;
+  }[];
+  rate_limit_429_count: number;
 };
 
+export async function getRecentLogs(lines?: number): Promise<string> {
+  return invoke<string>("get_recent_logs", { lines: lines ?? null });
+}
+
 export async function exportDiagnostics(): Promise<string> {
   const lines: string[] = [];
   lines.push("--- OmniGet Diagnostic Report ---");
@@ -100,12 +114,25 @@ export async function exportDiagnostics(): Promise<string> {
   lines.push(`Timestamp: ${new Date().toISOString()}`);
 
   try {
-    const deps = await invoke<DependencyStatus[]>("check_dependencies");
-    for (const dep of deps) {
-      lines.push(`${dep.name}: ${dep.installed ? dep.version ?? "installed" : "not found"}`);
+    const report = await invoke<DiagnosticsReport>("run_diagnostics");
+    lines.push(`Target: ${report.os}/${report.arch}`);
+    for (const tool of report.tools) {
+      const found = tool.path ? tool.version ?? "found" : "not found";
+      lines.push(`${tool.name}: ${found}${tool.path ? ` (${tool.path})` : ""}`);
+    }
+    const dir = report.download_dir;
+    const writable = dir.writable ? "writable" : `NOT writable: ${dir.error}`;
+    lines.push(`Download folder: ${dir.path} ${writable}`);
+    for (const site of report.network) {
+      const result =
+        site.status !== null
+          ? `HTTP ${site.status} in ${site.latency_ms}ms`
+          : `unreachable: ${site.error}`;
+      lines.push(`${site.name}: ${result}`);
     }
+    lines.push(`Recent YouTube 429s: ${report.rate_limit_429_count}`);
   } catch {
-    lines.push("Dependencies: check failed");
+    lines.push("Diagnostics: check failed");
   }
 
   lines.push("");
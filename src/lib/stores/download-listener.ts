@@ -7,6 +7,7 @@ import {
   syncQueueState,
   upsertGenericProgress,
   getDownloads,
+  setRateLimited,
 } from "./download-store.svelte";
 import { showToast } from "./toast-store.svelte";
 import {
@@ -264,7 +265,7 @@ export async function initDownloadListener(): Promise<() => void> {
       const payload = event.payload;
       if (!queueStateInitialized) {
         for (const item of payload) {
-          if (item.status.type === "Complete" || item.status.type === "Error") {
+          if (item.status.type === "Complete" || item.status.type === "Error" || item.status.type === "GeoBlocked") {
             loggedQueueTerminal.add(item.id);
           } else {
             queueToastEligibleIds.add(item.id);
@@ -276,7 +277,7 @@ export async function initDownloadListener(): Promise<() => void> {
       }
 
       for (const item of payload) {
-        if (item.status.type !== "Complete" && item.status.type !== "Error") {
+        if (item.status.type !== "Complete" && item.status.type !== "Error" && item.status.type !== "GeoBlocked") {
           queueToastEligibleIds.add(item.id);
           continue;
         }
@@ -285,7 +286,7 @@ export async function initDownloadListener(): Promise<() => void> {
           loggedQueueTerminal.add(item.id);
           continue;
         }
-        if (item.status.type === "Error") {
+        if (item.status.type === "Error" || item.status.type === "GeoBlocked") {
           loggedQueueTerminal.add(item.id);
           queueToastEligibleIds.delete(item.id);
           const errMsg = typeof item.status.data === "string"
@@ -364,6 +365,19 @@ export async function initDownloadListener(): Promise<() => void> {
     },
   );
 
+  const unlistenRateLimited = await listen<{ active: boolean }>(
+    "queue-rate-limited",
+    (event) => {
+      setRateLimited(event.payload.active);
+      if (event.payload.active) {
+        const tr = get(t);
+        showToast("info", tr("downloads.rate_limited_toast"));
+      } else {
+        addLog("info", "download", "No 429s for a while, queue back to normal");
+      }
+    },
+  );
+
   const unlistenFileCopied = await listen<{ path: string }>(
     "file-copied-to-clipboard",
     () => {
@@ -407,6 +421,7 @@ export async function initDownloadListener(): Promise<() => void> {
     unlistenBatchFileStatus();
     unlistenConvertProgress();
     unlistenConvertComplete();
+    unlistenRateLimited();
     unlistenFileCopied();
     unlistenMediaPreview();
     clearInterval(cookieCheckInterval);
@@ -48,6 +48,12 @@ export type GenericDownloadItem = BaseItem & {
   thumbnail_url?: string | null;
   quality?: string | null;
   downloadMode?: string | null;
+  retryCount?: number;
+  maxRetries?: number;
+  tags?: string[];
+  suspect?: boolean;
+  extraFiles?: string[];
+  scheduledAt?: number | null;
 };
 
 export type DownloadItem = CourseDownloadItem | GenericDownloadItem;
@@ -58,6 +64,7 @@ const SPEED_SMOOTHING = 0.3;
 const SPEED_HISTORY_MAX = 60;
 
 let downloads = $state(new Map<number, DownloadItem>());
+let rateLimited = $state(false);
 const speedHistory = new Map<number, SpeedPoint[]>();
 let flushScheduled = false;
 
@@ -138,6 +145,15 @@ export function getPausedCount(): number {
   return getCounts().paused;
 }
 
+/** Whether the queue is slowed down after repeated 429 responses. */
+export function isRateLimited(): boolean {
+  return rateLimited;
+}
+
+export function setRateLimited(active: boolean) {
+  rateLimited = active;
+}
+
 export function upsertProgress(
   courseId: number,
   courseName: string,
@@ -246,8 +262,29 @@ type QueueItemInfo = {
   eta_seconds?: number | null;
   quality?: string | null;
   download_mode?: string | null;
+  priority?: number;
+  retry_count?: number;
+  max_retries?: number;
+  retry_at_ms?: number;
+  tags?: string[];
+  suspect?: boolean;
+  extra_files?: string[];
+  scheduled_at_ms?: number;
+  phase?: string;
 };
 
+// Steps only the queue state reports; byte progress events can't tell them.
+const QUEUE_ONLY_PHASES = new Set(["resolving", "muxing", "converting"]);
+
+function resolvePhase(queuePhase: string | undefined, current: string | undefined): string {
+  if (queuePhase && QUEUE_ONLY_PHASES.has(queuePhase)) return queuePhase;
+  if (current && QUEUE_ONLY_PHASES.has(current)) {
+    if (queuePhase === "extracting") return "fetching_info";
+    if (queuePhase === "downloading") return "downloading";
+  }
+  return current ?? "queued";
+}
+
 function queueStatusToDownloadStatus(status: { type: string; data?: unknown }): DownloadStatus {
   switch (status.type) {
     case "Queued": return "queued";
@@ -255,13 +292,15 @@ function queueStatusToDownloadStatus(status: { type: string; data?: unknown }):
     case "Paused": return "paused";
     case "Seeding": return "seeding";
     case "Complete": return "complete";
+    case "DuplicateSkipped": return "complete";
     case "Error": return "error";
+    case "GeoBlocked": return "error";
     default: return "queued";
   }
 }
 
 function extractError(status: { type: string; data?: unknown }): string | undefined {
-  if (status.type === "Error" && status.data && typeof status.data === "object" && "message" in (status.data as Record<string, unknown>)) {
+  if ((status.type === "Error" || status.type === "GeoBlocked") && status.data && typeof status.data === "object" && "message" in (status.data as Record<string, unknown>)) {
     return (status.data as { message: string }).message;
   }
   if (status.type === "Error" && typeof status.data === "string") {
@@ -301,7 +340,7 @@ export function syncQueueState(items: QueueItemInfo[]) {
       speed: effectiveSpeed,
       downloadedBytes: qi.downloaded_bytes,
       totalBytes: qi.total_bytes,
-      phase: (existing?.kind === "generic" ? existing.phase : undefined) ?? "queued",
+      phase: resolvePhase(qi.phase, existing?.kind === "generic" ? existing.phase : undefined),
       etaSeconds: qi.eta_seconds ?? null,
       status: dlStatus,
       error: extractError(qi.status),
@@ -314,6 +353,12 @@ export function syncQueueState(items: QueueItemInfo[]) {
       external: qi.external,
       quality: qi.quality ?? null,
       downloadMode: qi.download_mode ?? null,
+      retryCount: qi.retry_count ?? 0,
+      maxRetries: qi.max_retries ?? 0,
+      tags: qi.tags ?? [],
+      suspect: qi.suspect ?? false,
+      extraFiles: qi.extra_files ?? [],
+      scheduledAt: qi.scheduled_at_ms ?? null,
     });
 
     if (dlStatus === "downloading" || dlStatus === "seeding") {
@@ -17,8 +17,12 @@ export type AppSettings = {
     embed_thumbnail: boolean;
     clipboard_detection: boolean;
     auto_download_on_paste: boolean;
+    clipboard_watch: boolean;
+    filename_style?: FilenameStyle;
+    twitter_full_thread?: boolean;
     filename_template: string;
     organize_by_platform: boolean;
+    output_subfolder_template?: string | null;
     download_subtitles: boolean;
     include_auto_subtitles: boolean;
     caption_locale: string;
@@ -30,7 +34,18 @@ export type AppSettings = {
     sponsorblock_categories: string[];
     split_by_chapters: boolean;
     live_from_start: boolean;
+    record_live: boolean;
     speed_limit: string;
+    max_download_speed_kbps?: number | null;
+    max_filesize_mb?: number;
+    temp_dir?: string | null;
+    verify_after_download?: boolean;
+    convert_keep_original?: boolean;
+    save_metadata_json?: boolean;
+    http_timeout_secs?: number;
+    http_connect_timeout_secs?: number;
+    http_max_retries?: number;
+    download_timeout_mins?: number;
     hotkey_enabled: boolean;
     hotkey_binding: string;
     clip_hotkey_enabled?: boolean;
@@ -40,6 +55,10 @@ export type AppSettings = {
     music_audio_format: string;
     copy_to_clipboard_on_hotkey: boolean;
     extra_ytdlp_flags?: string[];
+    extra_ytdlp_flags_unsafe?: boolean;
+    platform_backend?: Record<string, "auto" | "native" | "ytdlp">;
+    platform_defaults?: Record<string, PlatformDefault>;
+    ffmpeg_missing_policy?: FfmpegMissingPolicy;
     cookie_file: string;
     always_use_managed_cookies: boolean;
     bilibili_danmaku_enabled: boolean;
@@ -71,15 +90,20 @@ export type AppSettings = {
     max_concurrent_segments: number;
     max_retries: number;
     max_concurrent_downloads: number;
+    max_downloads_per_host: number;
+    allowed_hours?: { start_hour: number; end_hour: number } | null;
     concurrent_fragments: number;
     stagger_delay_ms: number;
+    retry_base_delay_secs: number;
     torrent_listen_port: number;
     torrent_auto_trackers: boolean;
     torrent_upnp: boolean;
+    native_aria2c: boolean;
     prevent_sleep: boolean;
     cookies_from_browser: string;
     twitter_manual_cookie: string;
     user_agent: string;
+    header_profiles?: Record<string, { user_agents: string[]; headers: string[] }>;
   };
   telegram: {
     concurrent_downloads: number;
@@ -98,9 +122,20 @@ export type AppSettings = {
     mode?: "auto" | "audio" | "mute" | null;
     quality?: string | null;
   };
+  recent_output_dirs?: string[];
   typography?: TypographySettings;
 };
 
+export type FilenameStyle = "title" | "title_with_id" | "id_only" | "custom";
+
+export type FfmpegMissingPolicy = "single_stream" | "separate_tracks";
+
+export type PlatformDefault = {
+  quality?: string | null;
+  download_mode?: "audio" | "mute" | null;
+  audio_format?: string | null;
+};
+
 export type TypographySettings = {
   font_display: string;
   font_body: string;
@@ -13,7 +13,9 @@ function proxyUrl(): string | undefined {
   const proxy = getSettings()?.proxy;
   if (!proxy?.enabled || !proxy.host || !proxy.port) return undefined;
   const scheme =
-    proxy.proxy_type === "socks5" || proxy.proxy_type === "https"
+    proxy.proxy_type === "socks5" ||
+    proxy.proxy_type === "socks5h" ||
+    proxy.proxy_type === "https"
       ? proxy.proxy_type
       : "http";
   const auth = proxy.username